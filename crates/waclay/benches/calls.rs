@@ -0,0 +1,233 @@
+//! Measures the cost of calling guest functions through the canonical ABI.
+//!
+//! Run with `cargo bench -p waclay --bench calls`. Each case reports the fastest average time per
+//! call over several samples, so results from two revisions may be compared directly.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use waclay::*;
+
+// The bytes of the component, which exports `test:bench/calls`.
+const WASM: &[u8] = include_bytes!("calls/component.wasm");
+
+/// The number of calls made per sample.
+const ITERATIONS: u32 = 20_000;
+
+/// The number of samples taken for each case.
+const SAMPLES: u32 = 30;
+
+/// A point, which converts itself directly to and from core values in typed calls.
+#[derive(Copy, Clone, Debug)]
+struct Point {
+    /// The horizontal coordinate.
+    x: f32,
+    /// The vertical coordinate.
+    y: f32,
+}
+
+impl ComponentType for Point {
+    fn ty() -> ValueType {
+        ValueType::Record(
+            RecordType::new(None, [("x", ValueType::F32), ("y", ValueType::F32)]).unwrap(),
+        )
+    }
+
+    fn from_value(value: &Value) -> anyhow::Result<Self> {
+        match value {
+            Value::Record(record) => match (record.field("x"), record.field("y")) {
+                (Some(Value::F32(x)), Some(Value::F32(y))) => Ok(Self { x, y }),
+                _ => anyhow::bail!("Invalid point fields."),
+            },
+            _ => anyhow::bail!("Expected a record."),
+        }
+    }
+
+    fn into_value(self) -> anyhow::Result<Value> {
+        Ok(Value::Record(Record::new(
+            RecordType::new(None, [("x", ValueType::F32), ("y", ValueType::F32)])?,
+            [("x", Value::F32(self.x)), ("y", Value::F32(self.y))],
+        )?))
+    }
+
+    const LAYOUT: Option<FlatLayout> = FlatLayout::record(&[f32::LAYOUT, f32::LAYOUT]);
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> anyhow::Result<()> {
+        self.x.lower_flat(cx)?;
+        self.y.lower_flat(cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> anyhow::Result<()> {
+        let mut fields = FlatFields::new(offset);
+        self.x.store_flat(cx, fields.field::<f32>()?)?;
+        self.y.store_flat(cx, fields.field::<f32>()?)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> anyhow::Result<Self> {
+        Ok(Self {
+            x: f32::lift_flat(cx)?,
+            y: f32::lift_flat(cx)?,
+        })
+    }
+
+    fn load_flat<C: AsContextMut>(
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> anyhow::Result<Self> {
+        let mut fields = FlatFields::new(offset);
+        Ok(Self {
+            x: f32::load_flat(cx, fields.field::<f32>()?)?,
+            y: f32::load_flat(cx, fields.field::<f32>()?)?,
+        })
+    }
+}
+
+impl UnaryComponentType for Point {}
+
+/// Runs the given call repeatedly and prints the fastest average time per call.
+fn bench(name: &str, mut call: impl FnMut()) {
+    for _ in 0..ITERATIONS {
+        call();
+    }
+
+    let mut best = Duration::MAX;
+    for _ in 0..SAMPLES {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            call();
+        }
+        best = best.min(start.elapsed());
+    }
+
+    println!(
+        "{name:<24} {:>10.1} ns/call",
+        best.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, WASM).unwrap();
+
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:bench/host".try_into().unwrap())
+        .unwrap()
+        .func_wrap(&mut store, "add", |_, (a, b): (u32, u32)| Ok(a + b))
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:bench/calls".try_into().unwrap())
+        .unwrap();
+
+    let add = exports.func("add").unwrap();
+    let typed_add = add.typed::<(u32, u32), u32>().unwrap();
+    let echo = exports.func("echo").unwrap();
+    let typed_echo = echo.typed::<String, String>().unwrap();
+    let sum = exports.func("sum").unwrap();
+    let typed_sum = sum.typed::<Vec<u32>, u32>().unwrap();
+    let translate = exports.func("translate").unwrap();
+    let typed_translate = translate.typed::<(Point, f32), Point>().unwrap();
+    let relay = exports.func("relay").unwrap();
+    let typed_relay = relay.typed::<(u32, u32), u32>().unwrap();
+    let prefix = exports.func("prefix").unwrap();
+    let typed_prefix = prefix.typed::<(u32, String), Vec<u8>>().unwrap();
+
+    let mut results = [Value::U32(0)];
+    bench("add (Func)", || {
+        add.call(&mut store, &[Value::U32(1), Value::U32(2)], &mut results)
+            .unwrap();
+        black_box(&results);
+    });
+
+    bench("add (TypedFunc)", || {
+        black_box(typed_add.call(&mut store, (1, 2)).unwrap());
+    });
+
+    let text = "The quick brown fox jumps over the lazy dog.".to_string();
+    let mut results = [Value::Bool(false)];
+    bench("echo (Func)", || {
+        echo.call(
+            &mut store,
+            &[Value::String(text.as_str().into())],
+            &mut results,
+        )
+        .unwrap();
+        black_box(&results);
+    });
+
+    bench("echo (TypedFunc)", || {
+        black_box(typed_echo.call(&mut store, text.clone()).unwrap());
+    });
+
+    let values = (0..64).collect::<Vec<u32>>();
+    let mut results = [Value::U32(0)];
+    bench("sum (Func)", || {
+        sum.call(
+            &mut store,
+            &[Value::List(List::from(values.as_slice()))],
+            &mut results,
+        )
+        .unwrap();
+        black_box(&results);
+    });
+
+    bench("sum (TypedFunc)", || {
+        black_box(typed_sum.call(&mut store, values.clone()).unwrap());
+    });
+
+    let point_ty = match &translate.ty().params()[0] {
+        ValueType::Record(x) => x.clone(),
+        _ => unreachable!(),
+    };
+    let point = Value::Record(
+        Record::new(point_ty, [("x", Value::F32(1.0)), ("y", Value::F32(2.0))]).unwrap(),
+    );
+    let mut results = [Value::Bool(false)];
+    bench("translate (Func)", || {
+        translate
+            .call(&mut store, &[point.clone(), Value::F32(0.5)], &mut results)
+            .unwrap();
+        black_box(&results);
+    });
+
+    let point = Point { x: 1.0, y: 2.0 };
+    bench("translate (TypedFunc)", || {
+        black_box(typed_translate.call(&mut store, (point, 0.5)).unwrap());
+    });
+
+    let mut results = [Value::Bool(false)];
+    bench("prefix (Func)", || {
+        prefix
+            .call(
+                &mut store,
+                &[Value::U32(16), Value::String(text.as_str().into())],
+                &mut results,
+            )
+            .unwrap();
+        black_box(&results);
+    });
+
+    bench("prefix (TypedFunc)", || {
+        black_box(typed_prefix.call(&mut store, (16, text.clone())).unwrap());
+    });
+
+    let mut results = [Value::U32(0)];
+    bench("relay (Func)", || {
+        relay
+            .call(&mut store, &[Value::U32(1), Value::U32(2)], &mut results)
+            .unwrap();
+        black_box(&results);
+    });
+
+    bench("relay (TypedFunc)", || {
+        black_box(typed_relay.call(&mut store, (1, 2)).unwrap());
+    });
+}
@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# Build the guest component used by the call benchmarks
+wasm-tools component embed wit guest.wat --world guest -o guest.core.wasm
+wasm-tools component new guest.core.wasm -o component.wasm
+
+rm guest.core.wasm
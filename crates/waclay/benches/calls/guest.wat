@@ -0,0 +1,118 @@
+(module
+  (import "test:bench/host" "add" (func $host_add (param i32 i32) (result i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address. Addresses below 1024 are reserved for return values.
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; A bump allocator which wraps around when memory is exhausted, since values
+  ;; never outlive the call that allocated them.
+  (func (export "cabi_realloc") (param $old i32) (param $old_size i32) (param $align i32) (param $new_size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.set $ptr
+    local.get $ptr
+    local.get $new_size
+    i32.add
+    i32.const 65536
+    i32.gt_u
+    if
+      i32.const 1024
+      local.set $ptr
+    end
+    local.get $ptr
+    local.get $new_size
+    i32.add
+    global.set $heap
+    local.get $old
+    if
+      local.get $ptr
+      local.get $old
+      local.get $old_size
+      local.get $new_size
+      local.get $old_size
+      local.get $new_size
+      i32.lt_u
+      select
+      memory.copy
+    end
+    local.get $ptr)
+
+  (func (export "test:bench/calls#add") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.add)
+
+  (func (export "test:bench/calls#echo") (param i32 i32) (result i32)
+    i32.const 16
+    local.get 0
+    i32.store
+    i32.const 20
+    local.get 1
+    i32.store
+    i32.const 16)
+
+  (func (export "test:bench/calls#sum") (param $ptr i32) (param $len i32) (result i32)
+    (local $total i32)
+    block $done
+      loop $next
+        local.get $len
+        i32.eqz
+        br_if $done
+        local.get $total
+        local.get $ptr
+        i32.load
+        i32.add
+        local.set $total
+        local.get $ptr
+        i32.const 4
+        i32.add
+        local.set $ptr
+        local.get $len
+        i32.const 1
+        i32.sub
+        local.set $len
+        br $next
+      end
+    end
+    local.get $total)
+
+  (func (export "test:bench/calls#translate") (param f32 f32 f32) (result i32)
+    i32.const 16
+    local.get 0
+    local.get 2
+    f32.add
+    f32.store
+    i32.const 20
+    local.get 1
+    f32.store
+    i32.const 16)
+
+  (func (export "test:bench/calls#relay") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    call $host_add)
+
+  (func (export "test:bench/calls#prefix") (param $n i32) (param $ptr i32) (param $len i32) (result i32)
+    i32.const 16
+    local.get $ptr
+    i32.store
+    i32.const 20
+    local.get $n
+    local.get $len
+    local.get $n
+    local.get $len
+    i32.lt_u
+    select
+    i32.store
+    i32.const 16)
+)
@@ -0,0 +1,137 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use waclay::*;
+
+// The bytes of the component, which imports asynchronous `lookup` and `log` functions through the
+// async ABI, and exports an async `process-message` function that waits for them.
+const WASM: &[u8] = include_bytes!("async_host/component.wasm");
+
+// The bytes of the `string_host_guest` component, which imports its `host-log` function synchronously.
+const SYNC_WASM: &[u8] = include_bytes!("string_host_guest/component.wasm");
+
+/// The data of the store, which the host functions read and write.
+#[derive(Default)]
+struct Database {
+    /// The values which the guest may look up.
+    values: HashMap<String, String>,
+    /// The messages which the guest logged.
+    log: Vec<String>,
+}
+
+// Guest code never blocks the thread which awaits it, so a single-threaded executor suffices.
+#[tokio::main(flavor = "current_thread")]
+pub async fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store whose data holds the values that the guest looks up, and the messages it logs.
+    let mut database = Database::default();
+    database
+        .values
+        .insert("greeting".to_string(), "hello world".to_string());
+    let mut store = Store::new(&engine, database);
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Define the host functions with closures that return futures. The futures hold the
+    // store context across an await, as though they were querying a database.
+    let mut linker = Linker::default();
+    let host = linker
+        .define_instance("test:guest/host".try_into().unwrap())
+        .unwrap();
+    host.func_wrap_async(&mut store, "lookup", |ctx, key: String| {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(ctx.data().values.get(&key).cloned().unwrap_or_default())
+        })
+    })
+    .unwrap();
+    host.func_wrap_async(&mut store, "log", |mut ctx, message: String| {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            ctx.data_mut().log.push(message);
+            Ok(())
+        })
+    })
+    .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let process_message = instance
+        .exports()
+        .instance(&"test:guest/message".try_into().unwrap())
+        .unwrap()
+        .func("process-message")
+        .unwrap()
+        .typed::<String, u32>()
+        .unwrap();
+
+    // The guest waits while its imports complete, and the executor runs other tasks meanwhile.
+    let ticks = Cell::new(0);
+    let ticker = async {
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            ticks.set(ticks.get() + 1);
+        }
+    };
+    let (result, ()) = tokio::join!(
+        process_message.call_async(&mut store, "greeting".to_string()),
+        ticker
+    );
+
+    // Prints "[Host] Guest returned 11 after logging ["hello world"], while the executor ticked 3 times"
+    println!(
+        "[Host] Guest returned {} after logging {:?}, while the executor ticked {} times",
+        result.unwrap(),
+        store.data().log,
+        ticks.get()
+    );
+
+    // Asynchronous host functions which are called from the host are awaited directly.
+    let count = Func::wrap_async(&mut store, |ctx, (): ()| {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(ctx.data().log.len() as u32)
+        })
+    })
+    .typed::<(), u32>()
+    .unwrap();
+
+    // Prints '[Host] 1 message(s) were logged'
+    let logged = count.call_async(&mut store, ()).await.unwrap();
+    println!("[Host] {logged} message(s) were logged");
+
+    // Guest code which imports an asynchronous host function synchronously cannot be suspended,
+    // so its call fails rather than block the executor while the future is pending.
+    let component = Component::new(&engine, SYNC_WASM).unwrap();
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:guest/host-logger".try_into().unwrap())
+        .unwrap()
+        .func_wrap_async(&mut store, "host-log", |mut ctx, message: String| {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                ctx.data_mut().log.push(message);
+                Ok(())
+            })
+        })
+        .unwrap();
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let process_message = instance
+        .exports()
+        .instance(&"test:guest/message".try_into().unwrap())
+        .unwrap()
+        .func("process-message")
+        .unwrap()
+        .typed::<String, String>()
+        .unwrap();
+
+    // Prints "[Host] A synchronous import of an asynchronous host function failed: ..."
+    let error = process_message
+        .call_async(&mut store, "hello".to_string())
+        .await
+        .unwrap_err();
+    println!("[Host] A synchronous import of an asynchronous host function failed: {error}");
+}
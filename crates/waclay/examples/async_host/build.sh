@@ -0,0 +1,8 @@
+#!/bin/bash
+
+# Build the guest component, which exports an async function that calls asynchronous imports
+# and waits on a waitable set until they return
+wasm-tools component embed --all-features wit guest.wat --world guest -o guest.core.wasm
+wasm-tools component new --all-features guest.core.wasm -o component.wasm
+
+rm guest.core.wasm
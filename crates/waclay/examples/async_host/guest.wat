@@ -0,0 +1,99 @@
+(module
+  (import "test:guest/host" "[async-lower][async]lookup" (func $lookup (param i32 i32 i32) (result i32)))
+  (import "test:guest/host" "[async-lower][async]log" (func $log (param i32 i32) (result i32)))
+  (import "[export]test:guest/message" "[task-return][async]process-message" (func $process-message-return (param i32)))
+  (import "$root" "[subtask-drop]" (func $subtask-drop (param i32)))
+  (import "$root" "[waitable-set-new]" (func $waitable-set-new (result i32)))
+  (import "$root" "[waitable-join]" (func $waitable-join (param i32 i32)))
+  (import "$root" "[waitable-set-drop]" (func $waitable-set-drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address for `cabi_realloc`.
+  (global $heap (mut i32) (i32.const 4096))
+  ;; The waitable set on which `process-message` waits for its subtasks.
+  (global $set (mut i32) (i32.const 0))
+  ;; Whether `process-message` is logging the value, rather than looking it up.
+  (global $logging (mut i32) (i32.const 0))
+
+  ;; A bump allocator, which never frees memory.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get 2
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get 2
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get 3
+    i32.add
+    global.set $heap
+    local.get $ptr)
+
+  ;; Starts looking up the key. The value is written to address 1024.
+  (func (export "[async-lift]test:guest/message#[async]process-message") (param $ptr i32) (param $len i32) (result i32)
+    call $waitable-set-new
+    global.set $set
+    i32.const 0
+    global.set $logging
+    local.get $ptr
+    local.get $len
+    i32.const 1024
+    call $lookup
+    call $step)
+
+  ;; Drops each subtask once it returns, and moves on to the next step.
+  (func (export "[callback][async-lift]test:guest/message#[async]process-message") (param $event i32) (param $subtask i32) (param $state i32) (result i32)
+    local.get $subtask
+    call $subtask-drop
+    ;; The subtask returned
+    i32.const 2
+    call $step)
+
+  ;; Moves on once a subtask with the given state returned, or waits for it to return.
+  (func $step (param $state i32) (result i32)
+    local.get $state
+    i32.const 2
+    i32.ne
+    if
+      ;; The subtask started, so wait until it returns
+      local.get $state
+      i32.const 4
+      i32.shr_u
+      global.get $set
+      call $waitable-join
+      global.get $set
+      i32.const 4
+      i32.shl
+      i32.const 2
+      i32.or
+      return
+    end
+
+    global.get $logging
+    i32.eqz
+    if
+      ;; The value was looked up, so log it
+      i32.const 1
+      global.set $logging
+      i32.const 1024
+      i32.load
+      i32.const 1028
+      i32.load
+      call $log
+      call $step
+      return
+    end
+
+    ;; The value was logged, so return its length
+    i32.const 1028
+    i32.load
+    call $process-message-return
+    global.get $set
+    call $waitable-set-drop
+    i32.const 0)
+)
@@ -0,0 +1,98 @@
+use waclay::*;
+
+// The bytes of the component, which passes generated images to `test:images/host`.
+const WASM: &[u8] = include_bytes!("borrowed_host/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    let bytes = ValueType::List(ListType::new(ValueType::U8));
+
+    // Borrowed host functions read strings and byte lists as slices, rather than as `Value`s,
+    // and write byte and string results straight into memory allocated by the guest. The backend
+    // only exposes guest memory through copies, so each parameter is copied once per call.
+    let invert = Func::new_borrowed(
+        &mut store,
+        FuncType::new([bytes.clone()], [bytes.clone()]),
+        |call| {
+            let params = call.params()?;
+            let inverted = params.bytes(0)?.iter().map(|x| !x).collect::<Vec<_>>();
+            call.set_result_bytes(&inverted)
+        },
+    );
+
+    let describe = Func::new_borrowed(
+        &mut store,
+        FuncType::new([ValueType::String, bytes], [ValueType::String]),
+        |call| {
+            let params = call.params()?;
+            let (label, pixels) = (params.str(0)?, params.bytes(1)?);
+            let checksum = pixels.iter().map(|&x| x as u64).sum::<u64>();
+            call.set_result_str(&format!(
+                "{label}: {} bytes, checksum {checksum}",
+                pixels.len()
+            ))
+        },
+    );
+
+    // Create a linker that will be used to resolve the component's imports.
+    let mut linker = Linker::default();
+    let host = linker
+        .define_instance("test:images/host".try_into().unwrap())
+        .unwrap();
+    host.define_func("invert", invert.clone()).unwrap();
+    host.define_func("describe", describe).unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:images/filters".try_into().unwrap())
+        .unwrap();
+
+    // The guest passes a four megabyte image to the host, which inverts it and writes the result
+    // into guest memory.
+    let run = exports
+        .func("run")
+        .unwrap()
+        .typed::<u32, Vec<u8>>()
+        .unwrap();
+    let image = run.call(&mut store, 4 * 1024 * 1024).unwrap();
+    assert!(image.iter().enumerate().all(|(i, &x)| x == !(i as u8)));
+    println!(
+        "run(4 MiB) returned {} bytes, starting with {:?}",
+        image.len(),
+        &image[..4]
+    );
+
+    // Prints 'summary(1000) = "thumbnail: 1000 bytes, checksum 124716"'
+    let summary = exports
+        .func("summary")
+        .unwrap()
+        .typed::<u32, String>()
+        .unwrap();
+    println!(
+        "summary(1000) = {:?}",
+        summary.call(&mut store, 1000).unwrap()
+    );
+
+    // When called from the host, the function borrows from the argument values instead.
+    let mut results = [Value::Bool(false)];
+    invert
+        .call(
+            &mut store,
+            &[Value::List(List::from(&[0u8, 1, 2][..]))],
+            &mut results,
+        )
+        .unwrap();
+    println!(
+        "invert([0, 1, 2]) = {:?}",
+        Vec::<u8>::from_value(&results[0]).unwrap()
+    );
+}
@@ -0,0 +1,97 @@
+(module
+  (import "test:images/host" "invert" (func $invert (param i32 i32 i32)))
+  (import "test:images/host" "describe" (func $describe (param i32 i32 i32 i32 i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The label passed to `describe`.
+  (data (i32.const 0) "thumbnail")
+
+  ;; The next free address, after the return area at 1024.
+  (global $heap (mut i32) (i32.const 2048))
+
+  ;; A bump allocator, which grows memory as needed and never frees.
+  (func $realloc (export "cabi_realloc") (param $old i32) (param $old_size i32) (param $align i32) (param $size i32) (result i32)
+    (local $ptr i32)
+    (local $end i32)
+    global.get $heap
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get $size
+    i32.add
+    local.tee $end
+    global.set $heap
+
+    ;; Grow memory by the number of pages that the allocation is missing
+    local.get $end
+    i32.const 65535
+    i32.add
+    i32.const 16
+    i32.shr_u
+    memory.size
+    i32.sub
+    local.tee $end
+    i32.const 0
+    i32.gt_s
+    (if
+      (then
+        local.get $end
+        memory.grow
+        i32.const -1
+        i32.eq
+        (if (then unreachable))))
+    local.get $ptr)
+
+  ;; Allocates an image of the given size, filling each byte with its index.
+  (func $image (param $size i32) (result i32)
+    (local $ptr i32)
+    (local $i i32)
+    i32.const 0
+    i32.const 0
+    i32.const 1
+    local.get $size
+    call $realloc
+    local.set $ptr
+    (block $done
+      (loop $fill
+        local.get $i
+        local.get $size
+        i32.ge_u
+        br_if $done
+        local.get $ptr
+        local.get $i
+        i32.add
+        local.get $i
+        i32.store8
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $fill))
+    local.get $ptr)
+
+  (func (export "test:images/filters#run") (param $size i32) (result i32)
+    local.get $size
+    call $image
+    local.get $size
+    i32.const 1024
+    call $invert
+    i32.const 1024)
+
+  (func (export "test:images/filters#summary") (param $size i32) (result i32)
+    i32.const 0
+    i32.const 9
+    local.get $size
+    call $image
+    local.get $size
+    i32.const 1024
+    call $describe
+    i32.const 1024)
+)
@@ -0,0 +1,81 @@
+use anyhow::*;
+use waclay::*;
+
+// The bytes of the component, which logs messages through the host.
+const WASM: &[u8] = include_bytes!("caller_identity/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // The store remembers a name for each plugin instance.
+    let mut store = Store::new(&engine, Vec::<(Instance, &str)>::new());
+
+    // A single host function is shared by every plugin, and asks the store which one called it.
+    let log = Func::wrap(&mut store, |ctx, msg: String| {
+        let caller = ctx.caller().context("Log was not called by a guest.")?;
+        let plugin = ctx
+            .data()
+            .iter()
+            .find(|(instance, _)| instance == caller.instance())
+            .map_or("unknown", |(_, name)| name);
+
+        // The caller's memory is also available, for imports that use one.
+        let mut raw = [0; 6];
+        caller.read_memory(&ctx, 16, &mut raw)?;
+
+        println!(
+            "[{plugin}] {msg} (via {}#{} from package {}, memory starts with {:?})",
+            caller.interface().unwrap(),
+            caller.name(),
+            caller.package(),
+            String::from_utf8_lossy(&raw)
+        );
+        Ok(())
+    });
+
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:plugins/logging".try_into().unwrap())
+        .unwrap()
+        .define_func("log", log.clone())
+        .unwrap();
+
+    // Instantiate the same component twice, and record the name of each instance.
+    let component = Component::new(&engine, WASM).unwrap();
+    let mut runs = Vec::new();
+    for name in ["first", "second"] {
+        let instance = linker.instantiate(&mut store, &component).unwrap();
+        let run = instance
+            .exports()
+            .instance(&"test:plugins/plugin".try_into().unwrap())
+            .unwrap()
+            .func("run")
+            .unwrap()
+            .typed::<(), ()>()
+            .unwrap();
+        store.data_mut().push((instance, name));
+        runs.push(run);
+    }
+
+    // Prints '[second] plugin is running (via test:plugins/logging#log ...)', and so on.
+    for run in runs.iter().rev() {
+        run.call(&mut store, ()).unwrap();
+    }
+
+    // Host functions that the host calls directly have no caller either.
+    let error = log
+        .call(
+            &mut store,
+            &[Value::String("from the host".into())],
+            &mut [],
+        )
+        .unwrap_err();
+    println!("Calling log from the host: {error}");
+
+    // Outside of a host function, there is no caller.
+    println!(
+        "Caller outside of a call: {:?}",
+        store.as_context().caller()
+    );
+}
@@ -0,0 +1,15 @@
+(module
+  (import "test:plugins/logging" "log" (func $log (param i32 i32)))
+
+  (memory (export "memory") 1)
+  (data (i32.const 16) "plugin is running")
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (func (export "test:plugins/plugin#run")
+    i32.const 16
+    i32.const 17
+    call $log)
+)
@@ -0,0 +1,37 @@
+use waclay::*;
+
+// The bytes of a component which nests a provider of `test:composed/greeter` and a consumer
+// of that interface. The two inner components use different string encodings.
+const WASM: &[u8] = include_bytes!("composed/composed.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // The nested components are linked to each other internally, so nothing
+    // needs to be provided by the host.
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+
+    let start = instance
+        .exports()
+        .instance(&"test:composed/run".try_into().unwrap())
+        .unwrap()
+        .func("start")
+        .unwrap()
+        .typed::<(), String>()
+        .unwrap();
+
+    // Prints 'Composed component returned wäclay!'
+    println!(
+        "Composed component returned {}",
+        start.call(&mut store, ()).unwrap()
+    );
+}
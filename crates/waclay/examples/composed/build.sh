@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Build the provider component, which exports `test:composed/greeter` using UTF-16 strings
+wasm-tools component embed wit provider.wat --world provider --encoding utf16 -o provider.core.wasm
+wasm-tools component new provider.core.wasm -o provider.wasm
+
+# Build the consumer component, which imports `test:composed/greeter` using UTF-8 strings
+wasm-tools component embed wit consumer.wat --world consumer -o consumer.core.wasm
+wasm-tools component new consumer.core.wasm -o consumer.wasm
+
+# Plug the provider into the consumer, creating a single component with both nested inside
+wac plug consumer.wasm --plug provider.wasm -o composed.wasm
+
+rm provider.core.wasm consumer.core.wasm provider.wasm consumer.wasm
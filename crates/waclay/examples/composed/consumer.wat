@@ -0,0 +1,81 @@
+(module
+  (import "test:composed/greeter" "[constructor]name-tag" (func $name_tag_new (param i32 i32) (result i32)))
+  (import "test:composed/greeter" "greet" (func $greet (param i32 i32 i32 i32)))
+  (import "test:composed/greeter" "adopt" (func $adopt (param i32) (result i32)))
+  (import "test:composed/greeter" "dropped" (func $dropped (result i32)))
+  (import "test:composed/greeter" "[resource-drop]name-tag" (func $name_tag_drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address of the bump allocator.
+  (global $heap (mut i32) (i32.const 1024))
+
+  (data (i32.const 100) "w\c3\a4clay")
+  (data (i32.const 120) "!")
+  (data (i32.const 130) "x")
+
+  ;; A bump allocator which never frees memory.
+  (func (export "cabi_realloc") (param $old_ptr i32) (param $old_size i32) (param $align i32) (param $new_size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get $new_size
+    i32.add
+    global.set $heap
+    local.get $old_ptr
+    if
+      local.get $ptr
+      local.get $old_ptr
+      local.get $old_size
+      local.get $new_size
+      local.get $old_size
+      local.get $new_size
+      i32.lt_u
+      select
+      memory.copy
+    end
+    local.get $ptr)
+
+  (func (export "test:composed/run#start") (result i32)
+    (local $tag i32)
+    ;; Greet a tag named "wäclay", writing the result to the return area.
+    i32.const 100
+    i32.const 7
+    call $name_tag_new
+    local.tee $tag
+    i32.const 120
+    i32.const 1
+    i32.const 200
+    call $greet
+
+    ;; Give away ownership of a second tag.
+    i32.const 130
+    i32.const 1
+    call $name_tag_new
+    call $adopt
+    i32.const 1
+    i32.ne
+    if
+      unreachable
+    end
+
+    ;; Both tags should be destroyed by the provider.
+    local.get $tag
+    call $name_tag_drop
+    call $dropped
+    i32.const 2
+    i32.ne
+    if
+      unreachable
+    end
+
+    i32.const 200)
+)
@@ -0,0 +1,127 @@
+(module
+  (import "[export]test:composed/greeter" "[resource-new]name-tag" (func $resource_new (param i32) (result i32)))
+  (import "[export]test:composed/greeter" "[resource-rep]name-tag" (func $resource_rep (param i32) (result i32)))
+  (import "[export]test:composed/greeter" "[resource-drop]name-tag" (func $resource_drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address of the bump allocator.
+  (global $heap (mut i32) (i32.const 1024))
+  ;; The number of name tags that have been destroyed.
+  (global $dropped (mut i32) (i32.const 0))
+
+  ;; A bump allocator which never frees memory.
+  (func $cabi_realloc (export "cabi_realloc") (param $old_ptr i32) (param $old_size i32) (param $align i32) (param $new_size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get $new_size
+    i32.add
+    global.set $heap
+    local.get $old_ptr
+    if
+      local.get $ptr
+      local.get $old_ptr
+      local.get $old_size
+      local.get $new_size
+      local.get $old_size
+      local.get $new_size
+      i32.lt_u
+      select
+      memory.copy
+    end
+    local.get $ptr)
+
+  ;; Name tags are stored as a pointer to UTF-16 code units, followed by their length.
+  (func (export "test:composed/greeter#[constructor]name-tag") (param $ptr i32) (param $len i32) (result i32)
+    (local $rep i32)
+    i32.const 0
+    i32.const 0
+    i32.const 4
+    i32.const 8
+    call $cabi_realloc
+    local.tee $rep
+    local.get $ptr
+    i32.store
+    local.get $rep
+    local.get $len
+    i32.store offset=4
+    local.get $rep
+    call $resource_new)
+
+  (func (export "test:composed/greeter#[method]name-tag.describe") (param $self i32) (result i32)
+    i32.const 16
+    local.get $self
+    i64.load
+    i64.store
+    i32.const 16)
+
+  ;; Concatenates the name of the tag with the punctuation.
+  (func (export "test:composed/greeter#greet") (param $self i32) (param $ptr i32) (param $len i32) (result i32)
+    (local $name_len i32) (local $total i32) (local $dst i32)
+    local.get $self
+    i32.load offset=4
+    local.tee $name_len
+    local.get $len
+    i32.add
+    local.set $total
+    i32.const 0
+    i32.const 0
+    i32.const 2
+    local.get $total
+    i32.const 1
+    i32.shl
+    call $cabi_realloc
+    local.tee $dst
+    local.get $self
+    i32.load
+    local.get $name_len
+    i32.const 1
+    i32.shl
+    memory.copy
+    local.get $dst
+    local.get $name_len
+    i32.const 1
+    i32.shl
+    i32.add
+    local.get $ptr
+    local.get $len
+    i32.const 1
+    i32.shl
+    memory.copy
+    i32.const 16
+    local.get $dst
+    i32.store
+    i32.const 16
+    local.get $total
+    i32.store offset=4
+    i32.const 16)
+
+  ;; Takes ownership of the tag, and returns the length of its name.
+  (func (export "test:composed/greeter#adopt") (param $handle i32) (result i32)
+    (local $len i32)
+    local.get $handle
+    call $resource_rep
+    i32.load offset=4
+    local.set $len
+    local.get $handle
+    call $resource_drop
+    local.get $len)
+
+  (func (export "test:composed/greeter#dropped") (result i32)
+    global.get $dropped)
+
+  (func (export "test:composed/greeter#[dtor]name-tag") (param $rep i32)
+    global.get $dropped
+    i32.const 1
+    i32.add
+    global.set $dropped)
+)
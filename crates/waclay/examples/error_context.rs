@@ -0,0 +1,47 @@
+use waclay::*;
+
+// The bytes of the component, which exports `test:diagnostics/parser`.
+const WASM: &[u8] = include_bytes!("error_context/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:diagnostics/parser".try_into().unwrap())
+        .unwrap();
+
+    // Error contexts returned by the guest carry a debug message that the host may read.
+    let parse = exports
+        .func("parse")
+        .unwrap()
+        .typed::<String, Result<u32, ErrorContext>>()
+        .unwrap();
+    for text in ["1234", "12x4"] {
+        match parse.call(&mut store, text.to_string()).unwrap() {
+            Ok(value) => println!("parse({text:?}) == {value}"),
+            Err(error) => println!("parse({text:?}) failed: {}", error.debug_message()),
+        }
+    }
+
+    // The host may also create error contexts and pass them to the guest.
+    let describe = exports
+        .func("describe")
+        .unwrap()
+        .typed::<ErrorContext, String>()
+        .unwrap();
+    let error = ErrorContext::new("connection reset by host");
+    println!(
+        "describe(error) == {:?}",
+        describe.call(&mut store, error).unwrap()
+    );
+}
@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# Build the guest component, which creates and reads error contexts
+wasm-tools component embed --all-features wit guest.wat --world guest -o guest.core.wasm
+wasm-tools component new --all-features guest.core.wasm -o component.wasm
+
+rm guest.core.wasm
@@ -0,0 +1,107 @@
+(module
+  (import "$root" "[error-context-new-utf8]" (func $error-new (param i32 i32) (result i32)))
+  (import "$root" "[error-context-debug-message-utf8]" (func $error-debug-message (param i32 i32)))
+  (import "$root" "[error-context-drop]" (func $error-drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The message of the error context created by `parse`.
+  (data (i32.const 0) "invalid digit")
+
+  ;; The error context returned by the last call to `parse`, if any.
+  (global $last-error (mut i32) (i32.const 0))
+
+  ;; The next free address for `cabi_realloc`.
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; A bump allocator, which never frees memory.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get 2
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get 2
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get 3
+    i32.add
+    global.set $heap
+    local.get $ptr)
+
+  ;; Writes the result to address 16, as a discriminant followed by a number or error context.
+  ;; The host keeps its own copy of a returned error context, so the guest drops its handle
+  ;; once it is no longer needed.
+  (func (export "test:diagnostics/parser#parse") (param $ptr i32) (param $len i32) (result i32)
+    (local $value i32)
+    (local $digit i32)
+    global.get $last-error
+    if
+      global.get $last-error
+      call $error-drop
+      i32.const 0
+      global.set $last-error
+    end
+    block $done
+      loop $next
+        local.get $len
+        i32.eqz
+        br_if $done
+        local.get $ptr
+        i32.load8_u
+        i32.const 48
+        i32.sub
+        local.tee $digit
+        i32.const 9
+        i32.gt_u
+        if
+          i32.const 16
+          i32.const 1
+          i32.store
+          i32.const 0
+          i32.const 13
+          call $error-new
+          global.set $last-error
+          i32.const 20
+          global.get $last-error
+          i32.store
+          i32.const 16
+          return
+        end
+        local.get $value
+        i32.const 10
+        i32.mul
+        local.get $digit
+        i32.add
+        local.set $value
+        local.get $ptr
+        i32.const 1
+        i32.add
+        local.set $ptr
+        local.get $len
+        i32.const 1
+        i32.sub
+        local.set $len
+        br $next
+      end
+    end
+    i32.const 16
+    i32.const 0
+    i32.store
+    i32.const 20
+    local.get $value
+    i32.store
+    i32.const 16)
+
+  ;; Writes the debug message's pointer and length to address 32.
+  (func (export "test:diagnostics/parser#describe") (param $error i32) (result i32)
+    local.get $error
+    i32.const 32
+    call $error-debug-message
+    local.get $error
+    call $error-drop
+    i32.const 32)
+)
@@ -0,0 +1,119 @@
+use waclay::*;
+
+// The bytes of the component, which exports `divide: func(a: u32, b: u32) -> u32`.
+const WASM: &[u8] = include_bytes!("error_handling/component.wasm");
+
+/// Describes an error raised by this crate, after recovering the structured [`waclay::Error`].
+fn describe(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<waclay::Error>() {
+        Some(waclay::Error::Trap(trap)) => format!("trap: {trap}"),
+        Some(waclay::Error::TypeMismatch { expected, actual }) => {
+            format!("type mismatch: expected {expected}, got {actual}")
+        }
+        Some(waclay::Error::StoreMismatch) => "store mismatch".to_string(),
+        Some(waclay::Error::InstanceDropped) => "instance dropped".to_string(),
+        Some(waclay::Error::ResourceError(message)) => format!("resource error: {message}"),
+        Some(waclay::Error::LinkError(report)) => format!(
+            "link error: {} missing function(s)",
+            report.missing_functions().len()
+        ),
+        Some(waclay::Error::DecodeError(_)) => "decode error".to_string(),
+        _ => format!("other error: {error}"),
+    }
+}
+
+struct Counter(u32);
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Prints 'decode error'
+    let error = Component::new(&engine, b"not a component").unwrap_err();
+    println!("{}", describe(&error));
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Prints 'link error: 1 missing function(s)'
+    let error = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap_err();
+    println!("{}", describe(&error));
+
+    // Create a linker that will be used to resolve the component's imports.
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:errors/host".try_into().unwrap())
+        .unwrap()
+        .func_wrap(&mut store, "report", |_, value: u32| {
+            println!("[Host] quotient is {value}");
+            Ok(())
+        })
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let divide = instance
+        .exports()
+        .instance(&"test:errors/calculator".try_into().unwrap())
+        .unwrap()
+        .func("divide")
+        .unwrap();
+
+    // Prints '[Host] quotient is 4'
+    let mut results = [Value::U32(0)];
+    divide
+        .call(&mut store, &[Value::U32(8), Value::U32(2)], &mut results)
+        .unwrap();
+
+    // Prints 'trap: in test:errors/calculator.divide: integer divide by zero'
+    let error = divide
+        .call(&mut store, &[Value::U32(1), Value::U32(0)], &mut results)
+        .unwrap_err();
+    println!("{}", describe(&error));
+
+    // Prints 'type mismatch: expected u32, got string'
+    let error = divide
+        .call(
+            &mut store,
+            &[Value::U32(1), Value::String("2".into())],
+            &mut results,
+        )
+        .unwrap_err();
+    println!("{}", describe(&error));
+
+    // Prints 'store mismatch'
+    let mut other_store = Store::new(&engine, ());
+    let error = divide
+        .call(
+            &mut other_store,
+            &[Value::U32(1), Value::U32(1)],
+            &mut results,
+        )
+        .unwrap_err();
+    println!("{}", describe(&error));
+
+    // Taking the value out of a resource consumes it, so it cannot be dropped afterward.
+    let counter =
+        ResourceOwn::new(&mut store, Counter(0), ResourceType::new::<Counter>(None)).unwrap();
+
+    // Prints '[Host] counter starts at 0'
+    println!(
+        "[Host] counter starts at {}",
+        counter.take::<Counter>(&mut store).unwrap().0
+    );
+
+    // Prints 'resource error: Resource had remaining borrows or was already dropped.'
+    let error = counter.drop(&mut store).unwrap_err();
+    println!("{}", describe(&error));
+
+    // Prints 'instance dropped'
+    instance.drop(&mut store).unwrap();
+    let error = divide
+        .call(&mut store, &[Value::U32(1), Value::U32(1)], &mut results)
+        .unwrap_err();
+    println!("{}", describe(&error));
+}
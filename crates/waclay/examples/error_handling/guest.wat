@@ -0,0 +1,19 @@
+(module
+  (import "test:errors/host" "report" (func $report (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  ;; The division traps when the divisor is zero.
+  (func (export "test:errors/calculator#divide") (param i32 i32) (result i32)
+    (local $quotient i32)
+    local.get 0
+    local.get 1
+    i32.div_u
+    local.tee $quotient
+    call $report
+    local.get $quotient)
+)
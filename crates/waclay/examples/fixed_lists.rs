@@ -0,0 +1,41 @@
+use waclay::*;
+
+// The bytes of a component which exports `sum: func(v: list<f32, 4>) -> f32`.
+const WASM: &[u8] = include_bytes!("fixed_lists/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Arrays correspond to fixed-size lists, so this function has type
+    // `func(list<f32, 4>, f32) -> list<f32, 4>`.
+    let scale = Func::wrap(&mut store, |_, (vector, factor): ([f32; 4], f32)| {
+        Ok(vector.map(|x| x * factor))
+    });
+    println!("scale has type {}", scale.ty());
+
+    // Prints 'scale([1, 2, 3, 4], 2) == [2.0, 4.0, 6.0, 8.0]'
+    let result = scale
+        .typed::<([f32; 4], f32), [f32; 4]>()
+        .unwrap()
+        .call(&mut store, ([1.0, 2.0, 3.0, 4.0], 2.0))
+        .unwrap();
+    println!("scale([1, 2, 3, 4], 2) == {result:?}");
+
+    // Fixed-size lists may also be built dynamically, and must have exactly the right length.
+    let ty = FixedListType::new(ValueType::U8, 3);
+    let rgb = FixedList::new(ty.clone(), [255, 128, 0].map(Value::U8)).unwrap();
+    println!("{} value: {:?}", ValueType::FixedList(ty.clone()), &*rgb);
+
+    let error = FixedList::new(ty, [Value::U8(255)]).unwrap_err();
+    println!("Could not create list: {error}");
+
+    // Guest components cannot use fixed-size lists yet, because the `wasmtime-environ`
+    // translator rejects them. Until it accepts them, fixed-size lists may only be passed
+    // between the host and host functions.
+    let error = Component::new(&engine, WASM).unwrap_err();
+    println!("Could not load guest component: {error:#}");
+}
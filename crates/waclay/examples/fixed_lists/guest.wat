@@ -0,0 +1,11 @@
+(module
+  ;; Sums the four flattened components of the vector.
+  (func (export "test:vectors/math#sum") (param f32 f32 f32 f32) (result f32)
+    local.get 0
+    local.get 1
+    f32.add
+    local.get 2
+    f32.add
+    local.get 3
+    f32.add)
+)
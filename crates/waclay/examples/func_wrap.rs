@@ -0,0 +1,62 @@
+use waclay::*;
+
+// The bytes of the component, which imports a `host-log: func(message: string)` function.
+const WASM: &[u8] = include_bytes!("string_host_guest/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Define the host function from a closure. Its type, `func(message: string)`,
+    // is derived from the closure's parameter and result types.
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:guest/host-logger".try_into().unwrap())
+        .unwrap()
+        .func_wrap(&mut store, "host-log", |_, message: String| {
+            println!("[Host] Guest logged '{message}'");
+            Ok(())
+        })
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let process_message = instance
+        .exports()
+        .instance(&"test:guest/message".try_into().unwrap())
+        .unwrap()
+        .func("process-message")
+        .unwrap()
+        .typed::<String, String>()
+        .unwrap();
+
+    // Prints "[Host] Guest logged 'world'" followed by "[Host] Guest returned 'hello world'"
+    let result = process_message
+        .call(&mut store, "hello".to_string())
+        .unwrap();
+    println!("[Host] Guest returned '{result}'");
+
+    // Functions with multiple parameters take them as a tuple.
+    let repeat = Func::wrap(&mut store, |_, (text, count): (String, u32)| {
+        Ok(text.repeat(count as usize).into_bytes())
+    });
+
+    // Prints 'repeat has type func(string, u32) -> list<u8>'
+    println!("repeat has type {}", repeat.ty());
+
+    // Prints 'repeat("ab", 3) == "ababab"'
+    let bytes = repeat
+        .typed::<(String, u32), Vec<u8>>()
+        .unwrap()
+        .call(&mut store, ("ab".to_string(), 3))
+        .unwrap();
+    println!(
+        "repeat(\"ab\", 3) == {:?}",
+        String::from_utf8(bytes).unwrap()
+    );
+}
@@ -93,4 +93,15 @@ pub fn main() {
         }
         _ => panic!("Unexpected result type"),
     }
+
+    // Untyped handles may also appear in typed signatures, which allows calling guest resources directly.
+    let constructor = resource_constructor.typed::<i32, ResourceOwn>().unwrap();
+    let value = method_bar_value.typed::<ResourceBorrow, i32>().unwrap();
+
+    let resource = constructor.call(&mut store, 7).unwrap();
+    let borrow = resource.borrow(&mut store).unwrap();
+    println!(
+        " └ typed bar.value() = {}",
+        value.call(&mut store, borrow).unwrap()
+    );
 }
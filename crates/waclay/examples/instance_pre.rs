@@ -0,0 +1,91 @@
+use waclay::*;
+
+// The bytes of the component.
+const WASM: &[u8] = include_bytes!("string_host_guest/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Every missing import is reported before any instance is created.
+    // Prints 'missing function test:guest/host-logger.host-log'
+    let mut linker = Linker::default();
+    let report = linker.check(&component).unwrap_err();
+    for name in report.missing_functions() {
+        println!("missing function {name}");
+    }
+
+    // Functions with the wrong signature are reported alongside both types.
+    // Prints 'function host-log had type func(u32), but expected func(string)'
+    let mut wrong_linker = Linker::default();
+    wrong_linker
+        .define_instance("test:guest/host-logger".try_into().unwrap())
+        .unwrap()
+        .define_func(
+            "host-log",
+            Func::new(
+                &mut store,
+                FuncType::new([ValueType::U32], []),
+                |_, _, _| Ok(()),
+            ),
+        )
+        .unwrap();
+    for mismatch in wrong_linker
+        .check(&component)
+        .unwrap_err()
+        .mismatched_functions()
+    {
+        println!(
+            "function {} had type {}, but expected {}",
+            mismatch.import().name(),
+            mismatch.actual(),
+            mismatch.expected()
+        );
+    }
+
+    // Define the host function that the guest calls.
+    linker
+        .define_instance("test:guest/host-logger".try_into().unwrap())
+        .unwrap()
+        .define_func(
+            "host-log",
+            Func::new(
+                &mut store,
+                FuncType::new([ValueType::String], []),
+                move |_, params, _results| {
+                    println!("[Host] Guest logged {:?}", params[0]);
+                    Ok(())
+                },
+            ),
+        )
+        .unwrap();
+
+    // Resolve and type-check the imports once.
+    let pre = linker.instantiate_pre(&component).unwrap();
+
+    // Create a fresh instance for each request, without repeating import resolution.
+    for request in ["first", "second", "third"] {
+        let instance = pre.instantiate(&mut store).unwrap();
+        let process_message = instance
+            .exports()
+            .instance(&"test:guest/message".try_into().unwrap())
+            .unwrap()
+            .func("process-message")
+            .unwrap()
+            .typed::<String, String>()
+            .unwrap();
+
+        println!(
+            "[Host] Instance returned {:?}",
+            process_message
+                .call(&mut store, request.to_string())
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use waclay::*;
+
+// The bytes of the component, whose exports return pointers and lengths of the guest's choosing.
+const WASM: &[u8] = include_bytes!("lift_limits/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Limit the size of values that the guest may pass to the host.
+    store.set_lift_limits(
+        LiftLimits::default()
+            .with_max_string_bytes(32)
+            .with_max_list_elements(1024)
+            .with_max_bytes_per_call(1024),
+    );
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Create a linker that will be used to resolve the component's imports.
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:limits/host".try_into().unwrap())
+        .unwrap()
+        .func_wrap(&mut store, "log", |_, message: String| {
+            println!("[Host] guest says {message:?}");
+            Ok(())
+        })
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:limits/source".try_into().unwrap())
+        .unwrap();
+
+    let numbers = exports
+        .func("numbers")
+        .unwrap()
+        .typed::<u32, Vec<u32>>()
+        .unwrap();
+    let text = exports
+        .func("text")
+        .unwrap()
+        .typed::<u32, String>()
+        .unwrap();
+    let misaligned = exports
+        .func("misaligned")
+        .unwrap()
+        .typed::<(), Vec<u32>>()
+        .unwrap();
+    let dangling = exports
+        .func("dangling")
+        .unwrap()
+        .typed::<(), String>()
+        .unwrap();
+    let letter = exports
+        .func("letter")
+        .unwrap()
+        .typed::<u32, char>()
+        .unwrap();
+    let relay = exports
+        .func("relay")
+        .unwrap()
+        .typed::<(u32, u32), ()>()
+        .unwrap();
+    let pick = exports.func("pick").unwrap();
+
+    // Prints 'numbers(4) = [1, 2, 3, 4]'
+    println!("numbers(4) = {:?}", numbers.call(&mut store, 4).unwrap());
+
+    // Prints 'text(9) = "The quick"'
+    println!("text(9) = {:?}", text.call(&mut store, 9).unwrap());
+
+    // Prints '[Host] guest says "brown fox"'
+    relay.call(&mut store, (10, 9)).unwrap();
+
+    // Values which break the canonical ABI's rules, or exceed the store's limits, are rejected
+    // before the host allocates memory for them.
+    let errors = [
+        (
+            "numbers(2000)",
+            numbers.call(&mut store, 2000).map(|x| format!("{x:?}")),
+        ),
+        (
+            "numbers(1000)",
+            numbers.call(&mut store, 1000).map(|x| format!("{x:?}")),
+        ),
+        (
+            "text(40)",
+            text.call(&mut store, 40).map(|x| format!("{x:?}")),
+        ),
+        (
+            "misaligned()",
+            misaligned.call(&mut store, ()).map(|x| format!("{x:?}")),
+        ),
+        (
+            "dangling()",
+            dangling.call(&mut store, ()).map(|x| format!("{x:?}")),
+        ),
+        (
+            "letter(0xd800)",
+            letter.call(&mut store, 0xd800).map(|x| format!("{x:?}")),
+        ),
+        (
+            "relay(65530, 16)",
+            relay
+                .call(&mut store, (65530, 16))
+                .map(|x| format!("{x:?}")),
+        ),
+    ];
+
+    for (call, result) in errors {
+        println!("{call} failed: {}", result.unwrap_err());
+    }
+
+    // Enum discriminants are validated against the number of cases.
+    let mut results = [Value::Bool(false)];
+    let error = pick
+        .call(&mut store, &[Value::U32(3)], &mut results)
+        .unwrap_err();
+    println!("pick(3) failed: {error}");
+}
@@ -0,0 +1,52 @@
+(module
+  (import "test:limits/host" "log" (func $log (param i32 i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The text returned by `text`, followed by the numbers returned by `numbers`.
+  (data (i32.const 0) "The quick brown fox jumps over the lazy dog.")
+  (data (i32.const 64) "\01\00\00\00\02\00\00\00\03\00\00\00\04\00\00\00")
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  ;; Writes a pointer and length to the return area at 1024, and returns its address.
+  (func $return_pair (param $ptr i32) (param $len i32) (result i32)
+    i32.const 1024
+    local.get $ptr
+    i32.store
+    i32.const 1028
+    local.get $len
+    i32.store
+    i32.const 1024)
+
+  (func (export "test:limits/source#numbers") (param i32) (result i32)
+    i32.const 64
+    local.get 0
+    call $return_pair)
+
+  (func (export "test:limits/source#text") (param i32) (result i32)
+    i32.const 0
+    local.get 0
+    call $return_pair)
+
+  (func (export "test:limits/source#misaligned") (result i32)
+    i32.const 66
+    i32.const 1
+    call $return_pair)
+
+  (func (export "test:limits/source#dangling") (result i32)
+    i32.const 0x20000)
+
+  (func (export "test:limits/source#letter") (param i32) (result i32)
+    local.get 0)
+
+  (func (export "test:limits/source#pick") (param i32) (result i32)
+    local.get 0)
+
+  (func (export "test:limits/source#relay") (param i32 i32)
+    local.get 0
+    local.get 1
+    call $log)
+)
@@ -0,0 +1,67 @@
+use waclay::*;
+
+// The bytes of the component which exports `test:guest/counter`.
+const PROVIDER_WASM: &[u8] = include_bytes!("linked_instances/provider.wasm");
+// The bytes of the component which imports `test:guest/counter`.
+const CONSUMER_WASM: &[u8] = include_bytes!("linked_instances/consumer.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load their imports and exports.
+    let provider = Component::new(&engine, PROVIDER_WASM).unwrap();
+    let consumer = Component::new(&engine, CONSUMER_WASM).unwrap();
+
+    // The provider has no imports, so it may be instantiated with an empty linker.
+    let provider_instance = Linker::default()
+        .instantiate(&mut store, &provider)
+        .unwrap();
+
+    // Satisfy the consumer's import of `test:guest/counter` with the provider's export
+    // of the same interface. Both the functions and the `tally` resource are linked.
+    let counter_interface = "test:guest/counter".try_into().unwrap();
+    let mut linker = Linker::default();
+    linker
+        .define_instance_from_exports(
+            "test:guest/counter".try_into().unwrap(),
+            &provider_instance,
+            &counter_interface,
+        )
+        .unwrap();
+
+    // Create an instance of the consumer, whose calls are forwarded to the provider.
+    let consumer_instance = linker.instantiate(&mut store, &consumer).unwrap();
+
+    let start = consumer_instance
+        .exports()
+        .instance(&"test:guest/run".try_into().unwrap())
+        .unwrap()
+        .func("start")
+        .unwrap()
+        .typed::<(), u32>()
+        .unwrap();
+
+    // Prints 'Consumer returned 115'
+    println!("Consumer returned {}", start.call(&mut store, ()).unwrap());
+
+    let dropped = provider_instance
+        .exports()
+        .instance(&counter_interface)
+        .unwrap()
+        .func("dropped")
+        .unwrap()
+        .typed::<(), u32>()
+        .unwrap();
+
+    // Both tallies were destroyed by the provider: one consumed by the provider itself,
+    // and one dropped by the consumer through the provider's destructor.
+    // Prints 'Provider destroyed 2 tallies'
+    println!(
+        "Provider destroyed {} tallies",
+        dropped.call(&mut store, ()).unwrap()
+    );
+}
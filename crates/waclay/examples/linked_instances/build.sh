@@ -0,0 +1,11 @@
+#!/bin/bash
+
+# Build the provider component, which exports `test:guest/counter`
+wasm-tools component embed wit provider.wat --world provider -o provider.core.wasm
+wasm-tools component new provider.core.wasm -o provider.wasm
+
+# Build the consumer component, which imports `test:guest/counter`
+wasm-tools component embed wit consumer.wat --world consumer -o consumer.core.wasm
+wasm-tools component new consumer.core.wasm -o consumer.wasm
+
+rm provider.core.wasm consumer.core.wasm
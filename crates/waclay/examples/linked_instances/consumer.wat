@@ -0,0 +1,31 @@
+(module
+  (import "test:guest/counter" "[constructor]tally" (func $tally_new (param i32) (result i32)))
+  (import "test:guest/counter" "[method]tally.add" (func $tally_add (param i32 i32)))
+  (import "test:guest/counter" "[method]tally.get" (func $tally_get (param i32) (result i32)))
+  (import "test:guest/counter" "consume" (func $consume (param i32) (result i32)))
+  (import "test:guest/counter" "[resource-drop]tally" (func $tally_drop (param i32)))
+
+  ;; Creates a tally of 10 and adds 5 to it, then hands a second tally of 1 to
+  ;; `consume`. Returns `first + 100 * second`, which is 115.
+  (func (export "test:guest/run#start") (result i32)
+    (local $tally i32)
+    (local $value i32)
+    i32.const 10
+    call $tally_new
+    local.set $tally
+    local.get $tally
+    i32.const 5
+    call $tally_add
+    local.get $tally
+    call $tally_get
+    local.set $value
+    i32.const 1
+    call $tally_new
+    call $consume
+    i32.const 100
+    i32.mul
+    local.get $value
+    i32.add
+    local.get $tally
+    call $tally_drop)
+)
@@ -0,0 +1,57 @@
+(module
+  (import "[export]test:guest/counter" "[resource-new]tally" (func $resource_new (param i32) (result i32)))
+  (import "[export]test:guest/counter" "[resource-rep]tally" (func $resource_rep (param i32) (result i32)))
+  (import "[export]test:guest/counter" "[resource-drop]tally" (func $resource_drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The address of the next tally to allocate.
+  (global $next (mut i32) (i32.const 16))
+  ;; The number of tallies that have been destroyed.
+  (global $dropped (mut i32) (i32.const 0))
+
+  (func (export "test:guest/counter#[constructor]tally") (param $start i32) (result i32)
+    (local $rep i32)
+    global.get $next
+    local.set $rep
+    global.get $next
+    i32.const 4
+    i32.add
+    global.set $next
+    local.get $rep
+    local.get $start
+    i32.store
+    local.get $rep
+    call $resource_new)
+
+  (func (export "test:guest/counter#[method]tally.add") (param $self i32) (param $n i32)
+    local.get $self
+    local.get $self
+    i32.load
+    local.get $n
+    i32.add
+    i32.store)
+
+  (func (export "test:guest/counter#[method]tally.get") (param $self i32) (result i32)
+    local.get $self
+    i32.load)
+
+  (func (export "test:guest/counter#consume") (param $handle i32) (result i32)
+    (local $value i32)
+    local.get $handle
+    call $resource_rep
+    i32.load
+    local.set $value
+    local.get $handle
+    call $resource_drop
+    local.get $value)
+
+  (func (export "test:guest/counter#dropped") (result i32)
+    global.get $dropped)
+
+  (func (export "test:guest/counter#[dtor]tally") (param $rep i32)
+    global.get $dropped
+    i32.const 1
+    i32.add
+    global.set $dropped)
+)
@@ -0,0 +1,79 @@
+use waclay::*;
+
+// The bytes of the component, which opens host sessions that it never closes.
+const WASM: &[u8] = include_bytes!("plugin_reload/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a single long-lived store, which is reused for every version of the plugin.
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, WASM).unwrap();
+
+    for generation in 0..3 {
+        // Each reload defines fresh host functions in a new linker.
+        let new_session = Func::wrap(&mut store, move |ctx, ()| {
+            Resource::new(ctx, Session(generation))
+        });
+        let base = Func::wrap(&mut store, move |_, ()| Ok(100 * generation));
+
+        let mut linker = Linker::default();
+        let host = linker
+            .define_instance("test:plugin/host".try_into().unwrap())
+            .unwrap();
+        host.define_resource("session", Resource::<Session>::resource_type())
+            .unwrap();
+        host.define_func("[constructor]session", new_session)
+            .unwrap();
+        host.define_func("base", base).unwrap();
+
+        let instance = linker.instantiate(&mut store, &component).unwrap();
+        let run = instance
+            .exports()
+            .instance(&"test:plugin/plugin".try_into().unwrap())
+            .unwrap()
+            .func("run")
+            .unwrap()
+            .typed::<(), u32>()
+            .unwrap();
+
+        // Prints 'Plugin returned 1', 'Plugin returned 101', and so on.
+        println!("Plugin returned {}", run.call(&mut store, ()).unwrap());
+        println!(
+            "While loaded: {} host functions, {} sessions",
+            store.host_function_count(),
+            session_count(&store)
+        );
+
+        // Dropping the instance releases its sessions and the host functions that it imported.
+        drop(linker);
+        let report = instance.drop_with_leak_report(&mut store).unwrap();
+        println!("{report}");
+        println!(
+            "After unloading: {} host functions, {} sessions",
+            store.host_function_count(),
+            session_count(&store)
+        );
+
+        // The backend store cannot remove core instances or trampolines, so they grow with each reload.
+        println!(
+            "Backend store: {} core instances, {} trampolines",
+            store.backend_core_instance_count(),
+            store.backend_trampoline_count()
+        );
+    }
+}
+
+/// Counts the sessions that are alive in the store.
+fn session_count(store: &Store<(), impl wasm_runtime_layer::backend::WasmEngine>) -> usize {
+    store
+        .host_resource_stats()
+        .iter()
+        .filter(|x| x.ty() == &Resource::<Session>::resource_type())
+        .map(ResourceStats::total)
+        .sum()
+}
+
+#[derive(Debug)]
+pub struct Session(pub u32);
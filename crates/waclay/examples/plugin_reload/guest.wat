@@ -0,0 +1,18 @@
+(module
+  (import "test:plugin/host" "[constructor]session" (func $new-session (result i32)))
+  (import "test:plugin/host" "base" (func $base (result i32)))
+
+  (memory (export "memory") 1)
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  ;; Opens a session, which is leaked, and returns one more than the host's base value.
+  (func (export "test:plugin/plugin#run") (result i32)
+    call $new-session
+    drop
+    call $base
+    i32.const 1
+    i32.add)
+)
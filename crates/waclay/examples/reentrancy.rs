@@ -0,0 +1,56 @@
+use waclay::*;
+
+// The bytes of the component, which calls `test:reentry/host` from its exports.
+const WASM: &[u8] = include_bytes!("reentrancy/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store, which holds the guest's `run` export once it is instantiated.
+    let mut store = Store::new(&engine, None::<TypedFunc<u32, ()>>);
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Create a linker that will be used to resolve the component's imports.
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:reentry/host".try_into().unwrap())
+        .unwrap()
+        .func_wrap(&mut store, "notify", |mut ctx, depth: u32| {
+            println!("[Host] notified at depth {depth}");
+            if depth > 0 {
+                // The guest is still on the call stack, so calling back into it traps
+                let run = ctx.data().clone().unwrap();
+                let error = run.call(&mut ctx, depth - 1).unwrap_err();
+                println!("[Host] could not call back into the guest: {error}");
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:reentry/runner".try_into().unwrap())
+        .unwrap();
+
+    let run = exports.func("run").unwrap().typed::<u32, ()>().unwrap();
+    *store.data_mut() = Some(run.clone());
+
+    // The host's notification succeeds, even though its attempt to reenter the guest fails.
+    run.call(&mut store, 1).unwrap();
+
+    // The guest's post-return function may not call imports, so the call fails.
+    let greet = exports
+        .func("greet")
+        .unwrap()
+        .typed::<(), String>()
+        .unwrap();
+    let error = greet.call(&mut store, ()).unwrap_err();
+    println!("greet() failed: {error}");
+
+    // Once a call has completed, the instance may be entered again.
+    run.call(&mut store, 0).unwrap();
+}
@@ -0,0 +1,25 @@
+(module
+  (import "test:reentry/host" "notify" (func $notify (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The greeting returned by `greet`, followed by its return area.
+  (data (i32.const 0) "hello")
+  (data (i32.const 8) "\00\00\00\00\05\00\00\00")
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (func (export "test:reentry/runner#run") (param i32)
+    local.get 0
+    call $notify)
+
+  (func (export "test:reentry/runner#greet") (result i32)
+    i32.const 8)
+
+  ;; Imports may not be called while the host is running a post-return function.
+  (func (export "cabi_post_test:reentry/runner#greet") (param i32)
+    i32.const 0
+    call $notify)
+)
@@ -0,0 +1,138 @@
+use waclay::*;
+
+// The bytes of the component which exports `test:pipeline/feeds`.
+const PRODUCER_WASM: &[u8] = include_bytes!("resource_pipeline/producer.wasm");
+// The bytes of the component which imports `test:pipeline/feeds`.
+const CONSUMER_WASM: &[u8] = include_bytes!("resource_pipeline/consumer.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // The producer defines the `feed` resource, and the consumer imports it.
+    let producer = Component::new(&engine, PRODUCER_WASM).unwrap();
+    let consumer = Component::new(&engine, CONSUMER_WASM).unwrap();
+
+    let producer_instance = Linker::default()
+        .instantiate(&mut store, &producer)
+        .unwrap();
+
+    let feeds_interface = "test:pipeline/feeds".try_into().unwrap();
+    let mut linker = Linker::default();
+    linker
+        .define_instance_from_exports(
+            "test:pipeline/feeds".try_into().unwrap(),
+            &producer_instance,
+            &feeds_interface,
+        )
+        .unwrap();
+    let consumer_instance = linker.instantiate(&mut store, &consumer).unwrap();
+
+    let feeds = producer_instance
+        .exports()
+        .instance(&feeds_interface)
+        .unwrap();
+    let new_feed = feeds.func("[constructor]feed").unwrap();
+    let dropped = feeds.func("dropped").unwrap().typed::<(), u32>().unwrap();
+
+    let sink = consumer_instance
+        .exports()
+        .instance(&"test:pipeline/sink".try_into().unwrap())
+        .unwrap();
+    let sum = sink.func("sum").unwrap();
+    let keep = sink.func("keep").unwrap();
+    let peek = sink.func("peek").unwrap().typed::<(), u32>().unwrap();
+    let give = sink.func("give").unwrap();
+    let release = sink.func("release").unwrap().typed::<(), ()>().unwrap();
+
+    // Create a feed in the producer. The host now owns the handle.
+    let Value::Own(feed) = new_feed_with(&mut store, &new_feed, 1) else {
+        panic!("Unexpected result type");
+    };
+
+    // Lend the feed to the consumer, which reads from it through the producer.
+    // Prints 'sum(feed, 3) == 6'
+    let borrow = feed.borrow(&mut store).unwrap();
+    let mut results = [Value::U32(0)];
+    sum.call(
+        &mut store,
+        &[Value::Borrow(borrow.clone()), Value::U32(3)],
+        &mut results,
+    )
+    .unwrap();
+    println!("sum(feed, 3) == {:?}", results[0]);
+
+    // Ownership cannot be transferred while the feed is still lent.
+    let error = keep
+        .call(&mut store, &[Value::Own(feed.clone())], &mut [])
+        .unwrap_err();
+    println!("Could not move borrowed feed: {error}");
+    borrow.drop(&mut store).unwrap();
+
+    // Move the feed into the consumer's handle table.
+    keep.call(&mut store, &[Value::Own(feed)], &mut []).unwrap();
+    print_stats("Consumer", &consumer_instance);
+    println!("peek() == {}", peek.call(&mut store, ()).unwrap());
+
+    // Take the feed back from the consumer, then destroy it through the producer's destructor.
+    let mut results = [Value::Bool(false)];
+    give.call(&mut store, &[], &mut results).unwrap();
+    print_stats("Consumer", &consumer_instance);
+    let Value::Own(feed) = &results[0] else {
+        panic!("Unexpected result type");
+    };
+    feed.drop(&mut store).unwrap();
+    println!(
+        "Producer destroyed {} feed(s)",
+        dropped.call(&mut store, ()).unwrap()
+    );
+
+    // The consumer may also drop feeds itself.
+    let feed = new_feed_with(&mut store, &new_feed, 5);
+    keep.call(&mut store, &[feed], &mut []).unwrap();
+    release.call(&mut store, ()).unwrap();
+    println!(
+        "Producer destroyed {} feed(s)",
+        dropped.call(&mut store, ()).unwrap()
+    );
+
+    // Feeds that the consumer still holds are destroyed when it is dropped.
+    let feed = new_feed_with(&mut store, &new_feed, 10);
+    keep.call(&mut store, &[feed], &mut []).unwrap();
+    println!(
+        "{}",
+        consumer_instance.drop_with_leak_report(&mut store).unwrap()
+    );
+    println!(
+        "Producer destroyed {} feed(s)",
+        dropped.call(&mut store, ()).unwrap()
+    );
+}
+
+/// Creates a feed in the producer which starts at the given number.
+fn new_feed_with(
+    store: &mut Store<(), wasmi_runtime_layer::Engine>,
+    new_feed: &Func,
+    start: u32,
+) -> Value {
+    let mut results = [Value::Bool(false)];
+    new_feed
+        .call(store, &[Value::U32(start)], &mut results)
+        .unwrap();
+    results[0].clone()
+}
+
+/// Prints the number of handles that an instance holds.
+fn print_stats(name: &str, instance: &Instance) {
+    for stat in instance.resource_stats().unwrap() {
+        println!(
+            "{name} holds {} owned and {} borrowed {}",
+            stat.own(),
+            stat.borrowed(),
+            ValueType::Own(stat.ty().clone())
+        );
+    }
+}
@@ -0,0 +1,11 @@
+#!/bin/bash
+
+# Build the producer component, which exports `test:pipeline/feeds`
+wasm-tools component embed wit producer.wat --world producer -o producer.core.wasm
+wasm-tools component new producer.core.wasm -o producer.wasm
+
+# Build the consumer component, which imports `test:pipeline/feeds`
+wasm-tools component embed wit consumer.wat --world consumer -o consumer.core.wasm
+wasm-tools component new consumer.core.wasm -o consumer.wasm
+
+rm producer.core.wasm consumer.core.wasm
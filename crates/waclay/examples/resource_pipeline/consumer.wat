@@ -0,0 +1,43 @@
+(module
+  (import "test:pipeline/feeds" "[method]feed.next" (func $feed_next (param i32) (result i32)))
+  (import "test:pipeline/feeds" "[resource-drop]feed" (func $feed_drop (param i32)))
+
+  ;; The handle of the kept feed.
+  (global $kept (mut i32) (i32.const 0))
+
+  (func (export "test:pipeline/sink#sum") (param $feed i32) (param $n i32) (result i32)
+    (local $total i32)
+    block $done
+      loop $read
+        local.get $n
+        i32.eqz
+        br_if $done
+        local.get $feed
+        call $feed_next
+        local.get $total
+        i32.add
+        local.set $total
+        local.get $n
+        i32.const 1
+        i32.sub
+        local.set $n
+        br $read
+      end
+    end
+    local.get $total)
+
+  (func (export "test:pipeline/sink#keep") (param $feed i32)
+    local.get $feed
+    global.set $kept)
+
+  (func (export "test:pipeline/sink#peek") (result i32)
+    global.get $kept
+    call $feed_next)
+
+  (func (export "test:pipeline/sink#give") (result i32)
+    global.get $kept)
+
+  (func (export "test:pipeline/sink#release")
+    global.get $kept
+    call $feed_drop)
+)
@@ -0,0 +1,45 @@
+(module
+  (import "[export]test:pipeline/feeds" "[resource-new]feed" (func $resource_new (param i32) (result i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The address of the next feed to allocate.
+  (global $next (mut i32) (i32.const 16))
+  ;; The number of feeds that have been destroyed.
+  (global $dropped (mut i32) (i32.const 0))
+
+  (func (export "test:pipeline/feeds#[constructor]feed") (param $start i32) (result i32)
+    (local $rep i32)
+    global.get $next
+    local.set $rep
+    global.get $next
+    i32.const 4
+    i32.add
+    global.set $next
+    local.get $rep
+    local.get $start
+    i32.store
+    local.get $rep
+    call $resource_new)
+
+  (func (export "test:pipeline/feeds#[method]feed.next") (param $self i32) (result i32)
+    (local $value i32)
+    local.get $self
+    i32.load
+    local.set $value
+    local.get $self
+    local.get $value
+    i32.const 1
+    i32.add
+    i32.store
+    local.get $value)
+
+  (func (export "test:pipeline/feeds#dropped") (result i32)
+    global.get $dropped)
+
+  (func (export "test:pipeline/feeds#[dtor]feed") (param $rep i32)
+    global.get $dropped
+    i32.const 1
+    i32.add
+    global.set $dropped)
+)
@@ -0,0 +1,115 @@
+use anyhow::*;
+use waclay::*;
+
+// The bytes of the component, which keeps a pool of host-owned connections.
+const WASM: &[u8] = include_bytes!("resource_stats/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Create a named resource type, so that statistics and leak reports can identify it.
+    let interface_id: InterfaceIdentifier = "test:pool/connections".try_into().unwrap();
+    let connection_ty = ResourceType::with_destructor(
+        &mut store,
+        Some(TypeIdentifier::new(
+            "connection",
+            Some(interface_id.clone()),
+        )),
+        |_, connection: Connection| {
+            println!("Closed connection {}", connection.0);
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    let connection_ty_clone = connection_ty.clone();
+    let new_connection = Func::new(
+        &mut store,
+        FuncType::new([ValueType::U32], [ValueType::Own(connection_ty.clone())]),
+        move |ctx, args, results| {
+            let Value::U32(id) = args[0] else {
+                bail!("Incorrect input type.")
+            };
+            results[0] = Value::Own(ResourceOwn::new(
+                ctx,
+                Connection(id),
+                connection_ty_clone.clone(),
+            )?);
+            Ok(())
+        },
+    );
+
+    // Define the host interface and instantiate the component.
+    let mut linker = Linker::default();
+    let connections = linker.define_instance(interface_id).unwrap();
+    connections
+        .define_resource("connection", connection_ty.clone())
+        .unwrap();
+    connections
+        .define_func("[constructor]connection", new_connection)
+        .unwrap();
+
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let client = instance
+        .exports()
+        .instance(&"test:pool/client".try_into().unwrap())
+        .unwrap();
+
+    let open = client.func("open").unwrap().typed::<u32, ()>().unwrap();
+    let close = client.func("close").unwrap().typed::<(), ()>().unwrap();
+    let adopt = client.func("adopt").unwrap();
+
+    // The guest opens two connections, but only closes one of them.
+    open.call(&mut store, 1).unwrap();
+    open.call(&mut store, 2).unwrap();
+    close.call(&mut store, ()).unwrap();
+    print_stats("Guest handles", &instance.resource_stats().unwrap());
+
+    // Host borrows count as lent until they are dropped.
+    let connection = ResourceOwn::new(&mut store, Connection(3), connection_ty).unwrap();
+    let borrow = connection.borrow(&mut store).unwrap();
+    print_stats("Host resources", &store.host_resource_stats());
+    borrow.drop(&mut store).unwrap();
+
+    // Each borrow is released exactly once, even through its clones.
+    println!(
+        "Dropping the borrow again: {}",
+        borrow.clone().drop(&mut store).unwrap_err()
+    );
+
+    // Passing the connection to the guest moves the owned handle into its table.
+    adopt
+        .call(&mut store, &[Value::Own(connection)], &mut [])
+        .unwrap();
+    print_stats("Guest handles", &instance.resource_stats().unwrap());
+
+    // Dropping the instance reports the handles that the guest never closed.
+    let report = instance.drop_with_leak_report(&mut store).unwrap();
+    println!("{report}");
+    print_stats("Host resources", &store.host_resource_stats());
+}
+
+/// Prints the number of live handles of each resource type.
+fn print_stats(title: &str, stats: &[ResourceStats]) {
+    println!("{title}:");
+    if stats.iter().all(|x| x.total() == 0) {
+        println!("  none");
+    }
+    for stat in stats.iter().filter(|x| x.total() > 0) {
+        println!(
+            "  {}: {} owned, {} borrowed, {} lent",
+            stat.ty().name().unwrap(),
+            stat.own(),
+            stat.borrowed(),
+            stat.lent()
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct Connection(pub u32);
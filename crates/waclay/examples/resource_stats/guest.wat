@@ -0,0 +1,45 @@
+(module
+  (import "test:pool/connections" "[constructor]connection" (func $new-connection (param i32) (result i32)))
+  (import "test:pool/connections" "[resource-drop]connection" (func $drop-connection (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The number of connection handles stored at the start of memory.
+  (global $count (mut i32) (i32.const 0))
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  ;; Appends a handle to the pool.
+  (func $push (param $handle i32)
+    global.get $count
+    i32.const 4
+    i32.mul
+    local.get $handle
+    i32.store
+    global.get $count
+    i32.const 1
+    i32.add
+    global.set $count)
+
+  (func (export "test:pool/client#open") (param $id i32)
+    local.get $id
+    call $new-connection
+    call $push)
+
+  (func (export "test:pool/client#close")
+    global.get $count
+    i32.const 1
+    i32.sub
+    global.set $count
+    global.get $count
+    i32.const 4
+    i32.mul
+    i32.load
+    call $drop-connection)
+
+  (func (export "test:pool/client#adopt") (param $handle i32)
+    local.get $handle
+    call $push)
+)
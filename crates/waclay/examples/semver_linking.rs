@@ -0,0 +1,67 @@
+use waclay::*;
+
+// The bytes of the component, which imports and exports interfaces at version 0.2.3.
+const WASM: &[u8] = include_bytes!("semver_linking/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Define older, but semver-compatible, versions of the imported logger.
+    let mut linker = Linker::default();
+    for version in ["0.2.0", "0.2.1"] {
+        let logger = format!("test:versioned/logger@{version}");
+        linker
+            .define_instance(logger.as_str().try_into().unwrap())
+            .unwrap()
+            .define_func(
+                "log",
+                Func::new(
+                    &mut store,
+                    FuncType::new([ValueType::String], []),
+                    move |_, params, _results| {
+                        println!("[Host] {logger} received {:?}", params[0]);
+                        Ok(())
+                    },
+                ),
+            )
+            .unwrap();
+    }
+
+    // The import of @0.2.3 is satisfied by the newest compatible version, @0.2.1.
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+
+    // Exports may also be looked up by any compatible version.
+    let start = instance
+        .exports()
+        .instance(&"test:versioned/run@0.2.0".try_into().unwrap())
+        .unwrap()
+        .func("start")
+        .unwrap()
+        .typed::<(), u32>()
+        .unwrap();
+
+    // Prints '[Host] test:versioned/logger@0.2.1 received String("hello from 0.2.3")'
+    start.call(&mut store, ()).unwrap();
+
+    // Exact lookups only match the version that the component exports.
+    // Prints 'Exact lookup of run@0.2.0 found an instance: false'
+    println!(
+        "Exact lookup of run@0.2.0 found an instance: {}",
+        instance
+            .exports()
+            .instance_exact(&"test:versioned/run@0.2.0".try_into().unwrap())
+            .is_some()
+    );
+
+    // Requiring exact versions causes the import to go unsatisfied.
+    linker.set_exact_versions(true);
+    // Prints 'Could not link component:' followed by the missing `log` function.
+    println!("{}", linker.check(&component).unwrap_err());
+}
@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# Build the guest component, which imports and exports interfaces at version 0.2.3
+wasm-tools component embed wit guest.wat --world guest -o guest.core.wasm
+wasm-tools component new guest.core.wasm -o component.wasm
+
+rm guest.core.wasm
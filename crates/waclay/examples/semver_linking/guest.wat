@@ -0,0 +1,17 @@
+(module
+  (import "test:versioned/logger@0.2.3" "log" (func $log (param i32 i32)))
+
+  (memory (export "memory") 1)
+
+  (data (i32.const 16) "hello from 0.2.3")
+
+  ;; No allocations are required, since the guest never receives strings.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (func (export "test:versioned/run@0.2.3#start") (result i32)
+    i32.const 16
+    i32.const 16
+    call $log
+    i32.const 16)
+)
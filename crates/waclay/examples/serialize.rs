@@ -0,0 +1,49 @@
+use waclay::*;
+
+// The bytes of the component.
+const WASM: &[u8] = include_bytes!("single_component/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Parse the component bytes once, and serialize the result. These bytes could be
+    // written to a cache on disk and reused across runs.
+    let serialized = Component::new(&engine, WASM).unwrap().serialize().unwrap();
+
+    // Reload the component without decoding or translating the original binary again.
+    let component = Component::deserialize(&engine, &serialized).unwrap();
+
+    // The reloaded component may be instantiated like any other.
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+
+    let select_nth = instance
+        .exports()
+        .instance(&"test:guest/foo".try_into().unwrap())
+        .unwrap()
+        .func("select-nth")
+        .unwrap()
+        .typed::<(Vec<String>, u32), String>()
+        .unwrap();
+
+    // Prints 'Reloaded component returned c'
+    println!(
+        "Reloaded component returned {}",
+        select_nth
+            .call(&mut store, (vec!["a".into(), "b".into(), "c".into()], 2))
+            .unwrap()
+    );
+
+    // Corrupted or stale caches are rejected rather than loaded.
+    let mut corrupted = serialized;
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0xff;
+    // Prints 'Corrupted cache rejected: Serialized component checksum did not match.'
+    println!(
+        "Corrupted cache rejected: {}",
+        Component::deserialize(&engine, &corrupted).unwrap_err()
+    );
+}
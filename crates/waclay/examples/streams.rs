@@ -0,0 +1,71 @@
+use std::task::Poll;
+
+use waclay::*;
+
+// The bytes of the component, which exports `test:streams/numbers`.
+const WASM: &[u8] = include_bytes!("streams/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:streams/numbers".try_into().unwrap())
+        .unwrap();
+
+    // The guest writes every square to the stream before returning its readable end.
+    let squares = exports.func("squares").unwrap();
+    let mut results = [Value::Bool(false)];
+    squares
+        .call(&mut store, &[Value::U32(5)], &mut results)
+        .unwrap();
+    let Value::Stream(reader) = &results[0] else {
+        panic!("Expected a stream.");
+    };
+    while let Some(values) = reader.read(&mut store, 2).unwrap() {
+        println!("Read squares: {values:?}");
+    }
+
+    // The host writes values to a stream, which the guest's async export reads a few at a time.
+    // The guest's reads complete immediately, since the values are already buffered.
+    let (reader, writer) =
+        StreamReader::new(&mut store, StreamType::new(Some(ValueType::U32))).unwrap();
+    writer.write(&mut store, (1..=10).map(Value::U32)).unwrap();
+    writer.close(&mut store).unwrap();
+
+    let sum = exports.func("sum").unwrap();
+    let mut results = [Value::U32(0)];
+    sum.call(&mut store, &[Value::Stream(reader)], &mut results)
+        .unwrap();
+    println!("Sum of 1 to 10: {:?}", results[0]);
+
+    // The guest resolves a future before returning it.
+    let echo = exports.func("echo").unwrap();
+    let mut results = [Value::Bool(false)];
+    echo.call(&mut store, &[Value::String("hello".into())], &mut results)
+        .unwrap();
+    let Value::Future(reader) = &results[0] else {
+        panic!("Expected a future.");
+    };
+    if let Poll::Ready(Some(message)) = reader.read(&mut store).unwrap() {
+        println!("Future resolved to: {message:?}");
+    }
+
+    // While the writer stays open without writing, the guest's read blocks and it waits for the
+    // read on a waitable set. No other task may write while the guest waits, so the call fails.
+    let (reader, _writer) =
+        StreamReader::new(&mut store, StreamType::new(Some(ValueType::U32))).unwrap();
+    let error = sum
+        .call(&mut store, &[Value::Stream(reader)], &mut results)
+        .unwrap_err();
+    println!("Sum of an open stream failed: {error}");
+}
@@ -0,0 +1,210 @@
+(module
+  (import "[export]test:streams/numbers" "[stream-new-0]squares" (func $squares-new (result i64)))
+  (import "[export]test:streams/numbers" "[stream-write-0]squares" (func $squares-write (param i32 i32 i32) (result i32)))
+  (import "[export]test:streams/numbers" "[stream-drop-writable-0]squares" (func $squares-drop (param i32)))
+  (import "[export]test:streams/numbers" "[async-lower][stream-read-0][async]sum" (func $sum-read (param i32 i32 i32) (result i32)))
+  (import "[export]test:streams/numbers" "[stream-drop-readable-0][async]sum" (func $sum-drop (param i32)))
+  (import "[export]test:streams/numbers" "[task-return][async]sum" (func $sum-return (param i32)))
+  (import "$root" "[waitable-set-new]" (func $waitable-set-new (result i32)))
+  (import "$root" "[waitable-join]" (func $waitable-join (param i32 i32)))
+  (import "$root" "[waitable-set-drop]" (func $waitable-set-drop (param i32)))
+  (import "[export]test:streams/numbers" "[future-new-0]echo" (func $echo-new (result i64)))
+  (import "[export]test:streams/numbers" "[future-write-0]echo" (func $echo-write (param i32 i32) (result i32)))
+  (import "[export]test:streams/numbers" "[future-drop-writable-0]echo" (func $echo-drop (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address for `cabi_realloc`.
+  (global $heap (mut i32) (i32.const 4096))
+  ;; The stream which `sum` is reading, the total so far, and the waitable set which the stream joined.
+  (global $sum-handle (mut i32) (i32.const 0))
+  (global $sum-total (mut i32) (i32.const 0))
+  (global $sum-set (mut i32) (i32.const 0))
+
+  ;; A bump allocator, which never frees memory.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get 2
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get 2
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get 3
+    i32.add
+    global.set $heap
+    local.get $ptr)
+
+  ;; Writes the squares to address 1024, then sends them all through a new stream.
+  (func (export "test:streams/numbers#squares") (param $n i32) (result i32)
+    (local $i i32)
+    (local $handles i64)
+    block $done
+      loop $next
+        local.get $i
+        local.get $n
+        i32.ge_u
+        br_if $done
+        local.get $i
+        i32.const 4
+        i32.mul
+        i32.const 1024
+        i32.add
+        local.get $i
+        local.get $i
+        i32.mul
+        i32.store
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $next
+      end
+    end
+    call $squares-new
+    local.set $handles
+    local.get $handles
+    i64.const 32
+    i64.shr_u
+    i32.wrap_i64
+    i32.const 1024
+    local.get $n
+    call $squares-write
+    drop
+    local.get $handles
+    i64.const 32
+    i64.shr_u
+    i32.wrap_i64
+    call $squares-drop
+    local.get $handles
+    i32.wrap_i64)
+
+  ;; Adds the values which a read copied to address 2048, given the status of the read.
+  ;; Once the writer has dropped the stream, returns the total and exits; otherwise, yields.
+  (func $sum-result (param $status i32) (result i32)
+    local.get $status
+    i32.const 4
+    i32.shr_u
+    i32.const 0
+    i32.gt_u
+    if
+      global.get $sum-total
+      i32.const 2048
+      i32.load
+      i32.add
+      global.set $sum-total
+    end
+    local.get $status
+    i32.const 4
+    i32.shr_u
+    i32.const 1
+    i32.gt_u
+    if
+      global.get $sum-total
+      i32.const 2052
+      i32.load
+      i32.add
+      global.set $sum-total
+    end
+    ;; A status of 1 means that the writer dropped the stream.
+    local.get $status
+    i32.const 15
+    i32.and
+    i32.const 1
+    i32.eq
+    if (result i32)
+      global.get $sum-total
+      call $sum-return
+      global.get $sum-handle
+      i32.const 0
+      call $waitable-join
+      global.get $sum-handle
+      call $sum-drop
+      global.get $sum-set
+      call $waitable-set-drop
+      ;; EXIT
+      i32.const 0
+    else
+      ;; YIELD
+      i32.const 1
+    end)
+
+  ;; Reads up to two values into address 2048. If no values are available yet, waits for
+  ;; the read to complete on the waitable set which the stream joined.
+  (func $sum-step (result i32)
+    (local $status i32)
+    global.get $sum-handle
+    i32.const 2048
+    i32.const 2
+    call $sum-read
+    local.tee $status
+    ;; BLOCKED
+    i32.const -1
+    i32.eq
+    if
+      ;; WAIT on the set
+      global.get $sum-set
+      i32.const 4
+      i32.shl
+      i32.const 2
+      i32.or
+      return
+    end
+    local.get $status
+    call $sum-result)
+
+  (func (export "[async-lift]test:streams/numbers#[async]sum") (param i32) (result i32)
+    local.get 0
+    global.set $sum-handle
+    i32.const 0
+    global.set $sum-total
+    call $waitable-set-new
+    global.set $sum-set
+    global.get $sum-handle
+    global.get $sum-set
+    call $waitable-join
+    call $sum-step)
+
+  ;; A stream read event delivers the status of a read which blocked; any other event
+  ;; follows a yield.
+  (func (export "[callback][async-lift]test:streams/numbers#[async]sum") (param $event i32) (param i32) (param $status i32) (result i32)
+    local.get $event
+    i32.const 2
+    i32.eq
+    if (result i32)
+      local.get $status
+      call $sum-result
+    else
+      call $sum-step
+    end)
+
+  ;; Stores the message's pointer and length at address 3072, then writes them to a new future.
+  (func (export "test:streams/numbers#echo") (param $ptr i32) (param $len i32) (result i32)
+    (local $handles i64)
+    i32.const 3072
+    local.get $ptr
+    i32.store
+    i32.const 3076
+    local.get $len
+    i32.store
+    call $echo-new
+    local.set $handles
+    local.get $handles
+    i64.const 32
+    i64.shr_u
+    i32.wrap_i64
+    i32.const 3072
+    call $echo-write
+    drop
+    local.get $handles
+    i64.const 32
+    i64.shr_u
+    i32.wrap_i64
+    call $echo-drop
+    local.get $handles
+    i32.wrap_i64)
+)
@@ -0,0 +1,77 @@
+use waclay::*;
+
+// The same guest, built with each string encoding. It exports `roundtrip: func(s: string) -> string`,
+// which passes the string to the host's `transform` function without decoding it.
+const COMPONENTS: [(&str, &[u8]); 3] = [
+    (
+        "utf8",
+        include_bytes!("string_encoding/component-utf8.wasm"),
+    ),
+    (
+        "utf16",
+        include_bytes!("string_encoding/component-utf16.wasm"),
+    ),
+    (
+        "latin1+utf16",
+        include_bytes!("string_encoding/component-compact-utf16.wasm"),
+    ),
+];
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    for (encoding, bytes) in COMPONENTS {
+        println!("== Guest using {encoding} strings");
+
+        // Create a store for managing WASM data and any custom user-defined state.
+        let mut store = Store::new(&engine, ());
+
+        // Parse the component bytes and load its imports and exports.
+        let component = Component::new(&engine, bytes).unwrap();
+
+        // Create a linker that will be used to resolve the component's imports.
+        let mut linker = Linker::default();
+        let host = linker
+            .define_instance("test:strings/host".try_into().unwrap())
+            .unwrap();
+        host.func_wrap(&mut store, "transform", |_, s: String| {
+            println!("[Host] transform received {s:?}");
+            Ok(s.to_uppercase())
+        })
+        .unwrap();
+
+        let instance = linker.instantiate(&mut store, &component).unwrap();
+        let exports = instance
+            .exports()
+            .instance(&"test:strings/relay".try_into().unwrap())
+            .unwrap();
+        let roundtrip = exports
+            .func("roundtrip")
+            .unwrap()
+            .typed::<String, String>()
+            .unwrap();
+
+        // The guest logs each allocation, showing how strings are reserved and then resized.
+        let reallocs = exports
+            .func("reallocs")
+            .unwrap()
+            .typed::<(), Vec<u32>>()
+            .unwrap();
+
+        // ASCII and Latin-1 strings are stored in one byte per character by latin1+utf16 guests,
+        // while other strings fall back to UTF-16.
+        for text in ["hello", "café", "naïve ☕"] {
+            let result = roundtrip.call(&mut store, text.to_string()).unwrap();
+            for call in reallocs.call(&mut store, ()).unwrap().chunks(4) {
+                let [old_ptr, old_size, align, new_size] = call else {
+                    unreachable!()
+                };
+                println!("[Guest] realloc(ptr: {old_ptr}, size: {old_size}, align: {align}, new size: {new_size})");
+            }
+            println!("Guest returned {result:?}");
+            assert_eq!(result, text.to_uppercase());
+        }
+        println!();
+    }
+}
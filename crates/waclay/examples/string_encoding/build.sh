@@ -0,0 +1,9 @@
+#!/bin/bash
+
+# Build the same guest once for each string encoding, where compact-utf16 is latin1+utf16
+for encoding in utf8 utf16 compact-utf16; do
+    wasm-tools component embed wit guest.wat --world guest --encoding $encoding -o guest.core.wasm
+    wasm-tools component new guest.core.wasm -o component-$encoding.wasm
+done
+
+rm guest.core.wasm
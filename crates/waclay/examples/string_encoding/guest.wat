@@ -0,0 +1,95 @@
+(module
+  (import "test:strings/host" "transform" (func $transform (param i32 i32 i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address of the bump allocator. The first kilobyte holds the return area,
+  ;; followed at 256 by the log of allocator calls.
+  (global $next (mut i32) (i32.const 1024))
+
+  ;; The number of allocator calls in the log, which has room for 48.
+  (global $logged (mut i32) (i32.const 0))
+
+  ;; Allocations shrink in place, and grow by copying the old contents to a new allocation.
+  (func (export "cabi_realloc") (param $old_ptr i32) (param $old_size i32) (param $align i32) (param $new_size i32) (result i32)
+    (local $ptr i32)
+    (local $entry i32)
+
+    ;; Log the call, since the allocator may not call imports to report it.
+    global.get $logged
+    i32.const 16
+    i32.mul
+    i32.const 256
+    i32.add
+    local.tee $entry
+    local.get $old_ptr
+    i32.store
+    local.get $entry
+    local.get $old_size
+    i32.store offset=4
+    local.get $entry
+    local.get $align
+    i32.store offset=8
+    local.get $entry
+    local.get $new_size
+    i32.store offset=12
+    global.get $logged
+    i32.const 1
+    i32.add
+    global.set $logged
+
+    local.get $old_ptr
+    i32.const 0
+    i32.ne
+    local.get $new_size
+    local.get $old_size
+    i32.le_u
+    i32.and
+    if
+      local.get $old_ptr
+      return
+    end
+
+    ;; Round the next free address up to the alignment.
+    global.get $next
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get $new_size
+    i32.add
+    global.set $next
+
+    local.get $ptr
+    local.get $old_ptr
+    local.get $old_size
+    memory.copy
+    local.get $ptr)
+
+  ;; The string is forwarded in the encoding that the guest was built with, whichever that is.
+  (func (export "test:strings/relay#roundtrip") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.const 8
+    call $transform
+    i32.const 8)
+
+  ;; Returns the log of allocator calls, and clears it.
+  (func (export "test:strings/relay#reallocs") (result i32)
+    i32.const 8
+    i32.const 256
+    i32.store
+    i32.const 12
+    global.get $logged
+    i32.const 4
+    i32.mul
+    i32.store
+    i32.const 0
+    global.set $logged
+    i32.const 8)
+)
@@ -0,0 +1,105 @@
+use waclay::*;
+
+// The bytes of the component, which imports `locate: func(p: point) -> side`.
+const WASM: &[u8] = include_bytes!("subtyping/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // The host only cares about the `x` field, and never returns the `center` case.
+    let horizontal = RecordType::new(None, [("x", ValueType::S32)]).unwrap();
+    let side = EnumType::new(None, ["left", "right"]).unwrap();
+    let host_side = side.clone();
+
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:subtyping/geometry".try_into().unwrap())
+        .unwrap()
+        .define_func(
+            "locate",
+            Func::new(
+                &mut store,
+                FuncType::new(
+                    [ValueType::Record(horizontal)],
+                    [ValueType::Enum(host_side.clone())],
+                ),
+                move |_, params, results| {
+                    let Value::Record(point) = &params[0] else {
+                        unreachable!()
+                    };
+                    // The guest's `y` field is discarded before the host receives the point.
+                    let fields = point.fields().map(|(name, _)| name).collect::<Vec<_>>();
+                    println!("[Host] locate received fields {fields:?}");
+
+                    let Some(Value::S32(x)) = point.field("x") else {
+                        unreachable!()
+                    };
+                    results[0] = Value::Enum(Enum::new(host_side.clone(), (x >= 0) as usize)?);
+                    Ok(())
+                },
+            ),
+        )
+        .unwrap();
+
+    // The host function accepts any point and returns a subset of the sides, so it satisfies the import.
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+
+    let classify = instance
+        .exports()
+        .instance(&"test:subtyping/classifier".try_into().unwrap())
+        .unwrap()
+        .func("classify")
+        .unwrap();
+
+    // Points with extra fields may be passed where the guest expects a `point`.
+    let labelled = Record::from_fields(
+        None,
+        [
+            ("x", Value::S32(-4)),
+            ("y", Value::S32(2)),
+            ("label", Value::String("a".into())),
+        ],
+    )
+    .unwrap();
+
+    // Prints '[Host] locate received fields ["x"]'
+    let mut results = [Value::Bool(false)];
+    classify
+        .call(&mut store, &[Value::Record(labelled)], &mut results)
+        .unwrap();
+
+    // Prints 'Guest classified point as left'
+    let Value::Enum(result) = &results[0] else {
+        unreachable!()
+    };
+    println!(
+        "Guest classified point as {}",
+        result.ty().cases().nth(result.discriminant()).unwrap()
+    );
+
+    // A host function which requires a field that the guest does not provide is rejected.
+    let depth = RecordType::new(None, [("x", ValueType::S32), ("z", ValueType::S32)]).unwrap();
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:subtyping/geometry".try_into().unwrap())
+        .unwrap()
+        .define_func(
+            "locate",
+            Func::new(
+                &mut store,
+                FuncType::new([ValueType::Record(depth)], [ValueType::Enum(side)]),
+                |_, _, _| unreachable!(),
+            ),
+        )
+        .unwrap();
+
+    // Prints 'Could not link component:' followed by the mismatched `locate` function.
+    println!("{}", linker.check(&component).unwrap_err());
+}
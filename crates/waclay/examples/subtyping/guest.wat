@@ -0,0 +1,14 @@
+(module
+  (import "test:subtyping/geometry" "locate" (func $locate (param i32 i32) (result i32)))
+
+  (memory (export "memory") 1)
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (func (export "test:subtyping/classifier#classify") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    call $locate)
+)
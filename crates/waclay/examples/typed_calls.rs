@@ -0,0 +1,141 @@
+use anyhow::*;
+use waclay::*;
+
+// The bytes of the component, which exports `test:shapes/geometry`.
+const WASM: &[u8] = include_bytes!("typed_calls/component.wasm");
+
+/// A point, which converts itself directly to and from core values. Typed calls whose
+/// parameters and results all provide a `LAYOUT` skip the intermediate `Value`s entirely.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+impl ComponentType for Point {
+    fn ty() -> ValueType {
+        ValueType::Record(
+            RecordType::new(None, [("x", ValueType::F32), ("y", ValueType::F32)]).unwrap(),
+        )
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        if let Value::Record(record) = value {
+            match (record.field("x"), record.field("y")) {
+                (Some(Value::F32(x)), Some(Value::F32(y))) => Ok(Point { x, y }),
+                _ => bail!("Expected 'x' and 'y' fields of type F32"),
+            }
+        } else {
+            bail!("Expected Record value")
+        }
+    }
+
+    fn into_value(self) -> Result<Value> {
+        let record = Record::new(
+            RecordType::new(None, [("x", ValueType::F32), ("y", ValueType::F32)]).unwrap(),
+            [("x", Value::F32(self.x)), ("y", Value::F32(self.y))],
+        )?;
+        Ok(Value::Record(record))
+    }
+
+    // A record is laid out like its fields in order, and each conversion visits them in turn.
+    const LAYOUT: Option<FlatLayout> = FlatLayout::record(&[f32::LAYOUT, f32::LAYOUT]);
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        self.x.lower_flat(cx)?;
+        self.y.lower_flat(cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        let mut fields = FlatFields::new(offset);
+        self.x.store_flat(cx, fields.field::<f32>()?)?;
+        self.y.store_flat(cx, fields.field::<f32>()?)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        Ok(Point {
+            x: f32::lift_flat(cx)?,
+            y: f32::lift_flat(cx)?,
+        })
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        let mut fields = FlatFields::new(offset);
+        Ok(Point {
+            x: f32::load_flat(cx, fields.field::<f32>()?)?,
+            y: f32::load_flat(cx, fields.field::<f32>()?)?,
+        })
+    }
+}
+
+impl UnaryComponentType for Point {}
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // Create a linker that will be used to resolve the component's imports, if any.
+    let linker = Linker::default();
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let exports = instance
+        .exports()
+        .instance(&"test:shapes/geometry".try_into().unwrap())
+        .unwrap();
+
+    // Records with a layout are lowered as flat parameters, and lifted from the returned pointer.
+    let translate = exports
+        .func("translate")
+        .unwrap()
+        .typed::<(Point, f32, f32), Point>()
+        .unwrap();
+    let moved = translate
+        .call(&mut store, (Point { x: 1.0, y: 2.0 }, 0.5, -1.0))
+        .unwrap();
+    println!("translate((1, 2), 0.5, -1) = {moved:?}");
+
+    // Lists of records are stored directly in guest memory.
+    let centroid = exports
+        .func("centroid")
+        .unwrap()
+        .typed::<Vec<Point>, Point>()
+        .unwrap();
+    let points = vec![
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 4.0, y: 0.0 },
+        Point { x: 2.0, y: 6.0 },
+    ];
+    println!(
+        "centroid({points:?}) = {:?}",
+        centroid.call(&mut store, points.clone()).unwrap()
+    );
+
+    // Strings and byte lists are copied straight between Rust buffers and guest memory.
+    let prefix = exports.func("prefix").unwrap();
+    let typed_prefix = prefix.typed::<(u32, String), Vec<u8>>().unwrap();
+    let bytes = typed_prefix
+        .call(&mut store, (5, "hello world".to_string()))
+        .unwrap();
+    println!("prefix(5, \"hello world\") = {bytes:?}");
+
+    // The untyped function converts through `Value`s, and produces the same result.
+    let mut results = [Value::Bool(false)];
+    prefix
+        .call(
+            &mut store,
+            &[Value::U32(5), Value::String("hello world".into())],
+            &mut results,
+        )
+        .unwrap();
+    assert_eq!(Vec::<u8>::from_value(&results[0]).unwrap(), bytes);
+    println!("Untyped call returned the same bytes");
+}
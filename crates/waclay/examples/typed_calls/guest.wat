@@ -0,0 +1,119 @@
+(module
+  (memory (export "memory") 1)
+
+  ;; The next free address. Addresses below 1024 are reserved for return values.
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; A bump allocator which wraps around when memory is exhausted, since values
+  ;; never outlive the call that allocated them.
+  (func (export "cabi_realloc") (param $old i32) (param $old_size i32) (param $align i32) (param $new_size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get $align
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get $align
+    i32.sub
+    i32.and
+    local.set $ptr
+    local.get $ptr
+    local.get $new_size
+    i32.add
+    i32.const 65536
+    i32.gt_u
+    if
+      i32.const 1024
+      local.set $ptr
+    end
+    local.get $ptr
+    local.get $new_size
+    i32.add
+    global.set $heap
+    local.get $old
+    if
+      local.get $ptr
+      local.get $old
+      local.get $old_size
+      local.get $new_size
+      local.get $old_size
+      local.get $new_size
+      i32.lt_u
+      select
+      memory.copy
+    end
+    local.get $ptr)
+
+  (func (export "test:shapes/geometry#translate") (param $x f32) (param $y f32) (param $dx f32) (param $dy f32) (result i32)
+    i32.const 16
+    local.get $x
+    local.get $dx
+    f32.add
+    f32.store
+    i32.const 20
+    local.get $y
+    local.get $dy
+    f32.add
+    f32.store
+    i32.const 16)
+
+  (func (export "test:shapes/geometry#centroid") (param $ptr i32) (param $len i32) (result i32)
+    (local $i i32)
+    (local $x f32)
+    (local $y f32)
+    block $done
+      loop $next
+        local.get $i
+        local.get $len
+        i32.ge_u
+        br_if $done
+        local.get $x
+        local.get $ptr
+        f32.load
+        f32.add
+        local.set $x
+        local.get $y
+        local.get $ptr
+        f32.load offset=4
+        f32.add
+        local.set $y
+        local.get $ptr
+        i32.const 8
+        i32.add
+        local.set $ptr
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $next
+      end
+    end
+    i32.const 16
+    local.get $x
+    local.get $len
+    f32.convert_i32_u
+    f32.div
+    f32.store
+    i32.const 20
+    local.get $y
+    local.get $len
+    f32.convert_i32_u
+    f32.div
+    f32.store
+    i32.const 16)
+
+  (func (export "test:shapes/geometry#prefix") (param $n i32) (param $ptr i32) (param $len i32) (result i32)
+    i32.const 16
+    local.get $ptr
+    i32.store
+    i32.const 20
+    local.get $n
+    local.get $len
+    local.get $n
+    local.get $len
+    i32.lt_u
+    select
+    i32.store
+    i32.const 16)
+)
@@ -0,0 +1,79 @@
+use waclay::*;
+
+// The bytes of the component, which exports `test:db/queries`.
+const WASM: &[u8] = include_bytes!("typed_resources/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Typed resources may have their own resource type, as long as its representation matches.
+    let statement_ty = ResourceType::new::<Statement>(None);
+
+    // Typed resources may be used directly in the signatures of host functions.
+    let prepare_ty = statement_ty.clone();
+    let prepare = Func::wrap(
+        &mut store,
+        move |ctx, (conn, sql): (ResourceRef<Connection>, String)| {
+            let text = format!("[{}] {sql}", conn.rep(&ctx.as_context())?.0);
+            Resource::with_type(ctx, Statement(text), prepare_ty.clone())
+        },
+    );
+
+    // Each typed resource has a default resource type, which may be used to define the resource.
+    let mut linker = Linker::default();
+    let host = linker
+        .define_instance("test:db/host".try_into().unwrap())
+        .unwrap();
+    host.define_resource("connection", Resource::<Connection>::resource_type())
+        .unwrap();
+    host.define_resource("statement", statement_ty.clone())
+        .unwrap();
+    host.define_func("prepare", prepare).unwrap();
+
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let queries = instance
+        .exports()
+        .instance(&"test:db/queries".try_into().unwrap())
+        .unwrap();
+
+    // The representation types of the parameters and results are checked against the signature.
+    let prepare_once = queries.func("prepare-once").unwrap();
+    let error = prepare_once
+        .typed::<(Resource<Statement>, String), Resource<Statement>>()
+        .unwrap_err();
+    println!("Could not use wrong resource type: {error}");
+
+    let prepare_once = prepare_once
+        .typed::<(Resource<Connection>, String), Resource<Statement>>()
+        .unwrap();
+
+    // Prints 'Prepared statement: [main] SELECT * FROM users'
+    let conn = Resource::new(&mut store, Connection("main".to_string())).unwrap();
+    let statement = prepare_once
+        .call(&mut store, (conn, "SELECT * FROM users".to_string()))
+        .unwrap();
+    println!(
+        "Prepared statement: {} (custom type: {})",
+        statement.rep(&store.as_context()).unwrap().0,
+        statement.as_inner().ty() == statement_ty
+    );
+
+    // Untyped resources are checked when they are converted.
+    let untyped = statement.into_inner();
+    let error = Resource::<Connection>::try_from(untyped.clone()).unwrap_err();
+    println!("Could not convert statement: {error}");
+
+    let statement = Resource::<Statement>::try_from(untyped).unwrap();
+    println!("Took statement: {:?}", statement.take(&mut store).unwrap());
+}
+
+#[derive(Debug)]
+pub struct Connection(pub String);
+
+#[derive(Debug)]
+pub struct Statement(pub String);
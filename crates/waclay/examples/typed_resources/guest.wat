@@ -0,0 +1,38 @@
+(module
+  (import "test:db/host" "prepare" (func $prepare (param i32 i32 i32) (result i32)))
+  (import "test:db/host" "[resource-drop]connection" (func $drop-connection (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; The next free address for `cabi_realloc`.
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; A bump allocator, which never frees memory.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.get 2
+    i32.add
+    i32.const 1
+    i32.sub
+    i32.const 0
+    local.get 2
+    i32.sub
+    i32.and
+    local.tee $ptr
+    local.get 3
+    i32.add
+    global.set $heap
+    local.get $ptr)
+
+  (func (export "test:db/queries#prepare-once") (param $conn i32) (param $ptr i32) (param $len i32) (result i32)
+    (local $statement i32)
+    local.get $conn
+    local.get $ptr
+    local.get $len
+    call $prepare
+    local.set $statement
+    local.get $conn
+    call $drop-connection
+    local.get $statement)
+)
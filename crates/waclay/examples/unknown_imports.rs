@@ -0,0 +1,63 @@
+use waclay::*;
+
+// The bytes of the component, which imports a catalog interface but only calls its `count` function.
+const WASM: &[u8] = include_bytes!("unknown_imports/component.wasm");
+
+pub fn main() {
+    // Create a new engine for instantiating a component.
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+
+    // Create a store for managing WASM data and any custom user-defined state.
+    let mut store = Store::new(&engine, ());
+
+    // Parse the component bytes and load its imports and exports.
+    let component = Component::new(&engine, WASM).unwrap();
+
+    // The host defines nothing, so the component cannot be linked.
+    let mut linker = Linker::default();
+    // Prints 'Could not link component:' followed by the missing `count` function and `entry` resource.
+    println!("{}", linker.check(&component).unwrap_err());
+
+    // Fill in every missing import with a function that fails when called.
+    linker
+        .define_unknown_imports_as_traps(&mut store, &component)
+        .unwrap();
+    let total = instantiate_total(&mut store, &linker, &component);
+
+    // Prints 'Calling total failed: Called unknown import test:probe/catalog.count.'
+    println!(
+        "Calling total failed: {}",
+        total.call(&mut store, ()).unwrap_err().root_cause()
+    );
+
+    // Alternatively, fill in every missing import with a function that returns default values.
+    let mut linker = Linker::default();
+    linker
+        .define_unknown_imports_as_default_values(&mut store, &component)
+        .unwrap();
+    let total = instantiate_total(&mut store, &linker, &component);
+
+    // Prints 'Calling total returned 0'
+    println!(
+        "Calling total returned {}",
+        total.call(&mut store, ()).unwrap()
+    );
+}
+
+/// Instantiates the component and gets its exported `total` function.
+fn instantiate_total(
+    store: &mut Store<(), wasmi_runtime_layer::Engine>,
+    linker: &Linker,
+    component: &Component,
+) -> TypedFunc<(), u32> {
+    linker
+        .instantiate(store, component)
+        .unwrap()
+        .exports()
+        .instance(&"test:probe/probe".try_into().unwrap())
+        .unwrap()
+        .func("total")
+        .unwrap()
+        .typed::<(), u32>()
+        .unwrap()
+}
@@ -0,0 +1,13 @@
+(module
+  (import "test:probe/catalog" "count" (func $count (result i32)))
+  (import "test:probe/catalog" "[resource-drop]entry" (func $drop-entry (param i32)))
+
+  (memory (export "memory") 1)
+
+  ;; No allocations are required, since the guest never receives strings or lists.
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (func (export "test:probe/probe#total") (result i32)
+    call $count)
+)
@@ -6,6 +6,10 @@ use anyhow::*;
 pub use wit_parser::abi::{AbiVariant, WasmSignature, WasmType};
 use wit_parser::*;
 
+/// The maximum number of core parameters that a function may take before they are passed
+/// indirectly through guest memory.
+pub const MAX_FLAT_PARAMS: usize = 16;
+
 /// Joins two WASM types.
 fn join(a: WasmType, b: WasmType) -> WasmType {
     use WasmType::*;
@@ -28,6 +32,115 @@ fn align_to(val: usize, align: usize) -> usize {
     (val + align - 1) & !(align - 1)
 }
 
+/// The sizes and alignments of every type in a resolve, as calculated by [`SizeAlign`].
+/// Unlike [`SizeAlign`], the table may be serialized, so that reloading a component does
+/// not require recalculating it.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SizeAlignTable {
+    /// The size in bytes and pointers of each type, followed by its alignment in bytes,
+    /// or zero if the type is pointer-aligned.
+    types: Vec<(usize, usize, usize)>,
+}
+
+impl SizeAlignTable {
+    /// Calculates the size and alignment of every type in the resolve.
+    pub fn new(resolve: &Resolve) -> Self {
+        let mut sizes = SizeAlign::default();
+        sizes.fill(resolve);
+
+        Self {
+            types: resolve
+                .types
+                .iter()
+                .map(|(id, def)| {
+                    if def.kind == TypeDefKind::Resource {
+                        return (0, 0, 1);
+                    }
+
+                    let size = sizes.size(&Type::Id(id));
+                    let align = match sizes.align(&Type::Id(id)) {
+                        Alignment::Pointer => 0,
+                        Alignment::Bytes(x) => x.get(),
+                    };
+                    (size.bytes, size.pointers, align)
+                })
+                .collect(),
+        }
+    }
+
+    /// Gets the size of a type.
+    pub fn size(&self, ty: &Type) -> ArchitectureSize {
+        match ty {
+            Type::Id(id) => {
+                let (bytes, pointers, _) = self.types[id.index()];
+                ArchitectureSize::new(bytes, pointers)
+            }
+            _ => SizeAlign::default().size(ty),
+        }
+    }
+
+    /// Gets the alignment of a type.
+    pub fn align(&self, ty: &Type) -> Alignment {
+        match ty {
+            Type::Id(id) => match std::num::NonZeroUsize::new(self.types[id.index()].2) {
+                Some(x) => Alignment::Bytes(x),
+                None => Alignment::Pointer,
+            },
+            _ => SizeAlign::default().align(ty),
+        }
+    }
+
+    /// Gets the offset of each field in a record with the given types.
+    pub fn field_offsets<'a>(
+        &self,
+        types: impl IntoIterator<Item = &'a Type>,
+    ) -> Vec<(ArchitectureSize, &'a Type)> {
+        let mut cur = ArchitectureSize::default();
+        types
+            .into_iter()
+            .map(|ty| {
+                let ret = align_to_arch(cur, self.align(ty));
+                cur = ret + self.size(ty);
+                (ret, ty)
+            })
+            .collect()
+    }
+
+    /// Gets the offset of the payload in a variant with the given tag and cases.
+    pub fn payload_offset<'a>(
+        &self,
+        tag: Int,
+        cases: impl IntoIterator<Item = Option<&'a Type>>,
+    ) -> ArchitectureSize {
+        let max_align = cases
+            .into_iter()
+            .flatten()
+            .fold(Alignment::default(), |acc, ty| acc.max(self.align(ty)));
+        let tag_size = match tag {
+            Int::U8 => 1,
+            Int::U16 => 2,
+            Int::U32 => 4,
+            Int::U64 => 8,
+        };
+        align_to_arch(ArchitectureSize::new(tag_size, 0), max_align)
+    }
+
+    /// Gets the size and alignment of a record with the given types.
+    pub fn record<'a>(&self, types: impl IntoIterator<Item = &'a Type>) -> ElementInfo {
+        let mut size = ArchitectureSize::default();
+        let mut align = Alignment::default();
+        for ty in types {
+            size = align_to_arch(size, self.align(ty)) + self.size(ty);
+            align = align.max(self.align(ty));
+        }
+        ElementInfo {
+            size: align_to_arch(size, align),
+            align,
+        }
+    }
+}
+
 /// Helper macro for defining instructions without having to have tons of
 /// exhaustive `match` statements to update
 macro_rules! def_instruction {
@@ -280,12 +393,12 @@ def_instruction! {
         /// for cleaning up any temporary allocation it created, if any.
         ListCanonLower {
             element: &'a Type,
-            realloc: Option<&'a str>,
+            realloc: Option<&'static str>,
         } : [1] => [2],
 
         /// Pops a string from the stack, lowers it into guest memory, and pushes a pointer and length onto the stack.
         StringLower {
-            realloc: Option<&'a str>,
+            realloc: Option<&'static str>,
         } : [1] => [2],
 
         /// Lowers a list where the element's layout in the native language is
@@ -304,7 +417,7 @@ def_instruction! {
         /// the receiver to own.
         ListLower {
             element: &'a Type,
-            realloc: Option<&'a str>,
+            realloc: Option<&'static str>,
             len: Cell<i32>
         } : [1] => [(len.get() as usize) + 3],
 
@@ -338,6 +451,21 @@ def_instruction! {
         /// Converts a 32-bit integer from a stack value, and stores it in the instruction.
         ReadI32 { value: Cell<i32> } : [1] => [0],
 
+        /// Pops a pointer and length off of the stack, ensures that a list of `element` with
+        /// that length lies within guest memory, and stores the length in the instruction.
+        /// Pushes the pointer back onto the stack.
+        ValidateList {
+            element: &'a Type,
+            len: Cell<i32>,
+        } : [2] => [1],
+
+        /// Pops a pointer off of the stack, ensures that it is aligned and that `size` bytes
+        /// after it lie within guest memory, and pushes it back onto the stack.
+        ValidatePointer {
+            size: usize,
+            align: usize,
+        } : [1] => [1],
+
         // records and tuples
 
         /// Pops a record value off the stack, decomposes the record to all of
@@ -366,6 +494,24 @@ def_instruction! {
             ty: TypeId,
         } : [1] => [1],
 
+        /// Create an `i32` from the readable end of a future.
+        FutureLower { ty: TypeId } : [1] => [1],
+
+        /// Create the readable end of a future from an `i32`.
+        FutureLift { ty: TypeId } : [1] => [1],
+
+        /// Create an `i32` from the readable end of a stream.
+        StreamLower { ty: TypeId } : [1] => [1],
+
+        /// Create the readable end of a stream from an `i32`.
+        StreamLift { ty: TypeId } : [1] => [1],
+
+        /// Create an `i32` from an error context.
+        ErrorContextLower : [1] => [1],
+
+        /// Create an error context from an `i32`.
+        ErrorContextLift : [1] => [1],
+
         /// Pops a tuple value off the stack, decomposes the tuple to all of
         /// its fields, and then pushes the fields onto the stack.
         TupleLower {
@@ -380,6 +526,20 @@ def_instruction! {
             ty: TypeId,
         } : [tuple.types.len()] => [1],
 
+        /// Pops a fixed-size list off the stack and pushes all of its elements
+        /// onto the stack.
+        FixedListLower {
+            size: u32,
+            ty: TypeId,
+        } : [1] => [*size as usize],
+
+        /// Pops all elements of a fixed-size list off the stack and then
+        /// composes them into a list.
+        FixedListLift {
+            size: u32,
+            ty: TypeId,
+        } : [*size as usize] => [1],
+
         /// Converts a language-specific record-of-bools to a list of `i32`.
         FlagsLower {
             flags: &'a Flags,
@@ -456,6 +616,14 @@ def_instruction! {
             sig: &'a WasmSignature,
         } : [sig.params.len()] => [sig.results.len()],
 
+        /// Same as `CallWasm`, except that the function is lifted with the async ABI.
+        /// Pushes nothing, since the function passes its results to `task.return`.
+        /// This is always the last instruction.
+        AsyncCallWasm {
+            name: &'a str,
+            sig: &'a WasmSignature,
+        } : [sig.params.len()] => [0],
+
         /// Same as `CallWasm`, except the dual where an interface is being
         /// called rather than a raw wasm function.
         ///
@@ -556,7 +724,7 @@ pub trait Bindgen {
     ) -> Result<()>;
 
     /// Returns size information that was previously calculated for all types.
-    fn sizes(&self) -> &SizeAlign;
+    fn sizes(&self) -> &SizeAlignTable;
 
     /// Determines whether a list's language-specific representation matches the canonical representation.
     fn is_list_canonical(&self, element: &Type) -> bool;
@@ -631,7 +799,9 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                         AbiVariant::GuestImport => unimplemented!(),
                         // When calling a wasm module from the outside, though,
                         // malloc needs to be called.
-                        AbiVariant::GuestExport => {
+                        AbiVariant::GuestExport
+                        | AbiVariant::GuestExportAsync
+                        | AbiVariant::GuestExportAsyncStackful => {
                             self.emit(&Instruction::Malloc {
                                 realloc: "cabi_realloc",
                                 size,
@@ -639,10 +809,8 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                             })?;
                             self.stack.pop().unwrap()
                         }
-                        // Async variants - not yet supported
-                        AbiVariant::GuestImportAsync
-                        | AbiVariant::GuestExportAsync
-                        | AbiVariant::GuestExportAsyncStackful => {
+                        // Imports lowered with the async ABI are not supported
+                        AbiVariant::GuestImportAsync => {
                             unimplemented!("Async ABI variants are not yet supported")
                         }
                     };
@@ -660,28 +828,41 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                 // Now that all the wasm args are prepared we can call the
                 // actual wasm function.
                 assert_eq!(self.stack.len(), sig.params.len());
-                self.emit(&Instruction::CallWasm {
-                    name: &func.name,
-                    sig: &sig,
-                })?;
 
-                if !sig.retptr {
-                    // With no return pointer in use we can simply lift the
-                    // result(s) of the function from the result of the core
-                    // wasm function.
-                    if let Some(ty) = &func.result {
-                        self.lift(ty)?
-                    }
+                if matches!(
+                    self.variant,
+                    AbiVariant::GuestExportAsync | AbiVariant::GuestExportAsyncStackful
+                ) {
+                    // Functions lifted with the async ABI pass their results to
+                    // `task.return`, which lifts them, so the call ends once the
+                    // core function has started the task.
+                    self.emit(&Instruction::AsyncCallWasm {
+                        name: &func.name,
+                        sig: &sig,
+                    })?;
                 } else {
-                    let ptr = self.stack.pop().unwrap();
+                    self.emit(&Instruction::CallWasm {
+                        name: &func.name,
+                        sig: &sig,
+                    })?;
+
+                    if !sig.retptr {
+                        // With no return pointer in use we can simply lift the
+                        // result(s) of the function from the result of the core
+                        // wasm function.
+                        if let Some(ty) = &func.result {
+                            self.lift(ty)?
+                        }
+                    } else {
+                        let ptr = self.validate_pointer(func.result.iter())?;
+                        self.read_results_from_memory(&func.result, ptr, 0)?;
+                    }
 
-                    self.read_results_from_memory(&func.result, ptr, 0)?;
+                    self.emit(&Instruction::Return {
+                        func,
+                        amt: if func.result.is_some() { 1 } else { 0 },
+                    })?;
                 }
-
-                self.emit(&Instruction::Return {
-                    func,
-                    amt: if func.result.is_some() { 1 } else { 0 },
-                })?;
             }
             LiftLower::LiftArgsLowerResults => {
                 if !sig.indirect_params {
@@ -705,7 +886,7 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     // to the function.
                     let mut offset = 0usize;
                     self.emit(&Instruction::GetArg { nth: 0 })?;
-                    let ptr = self.stack.pop().unwrap();
+                    let ptr = self.validate_pointer(func.params.iter().map(|(_, ty)| ty))?;
                     for (_, ty) in func.params.iter() {
                         offset = align_to(offset, self.bindgen.sizes().align(ty).align_wasm32());
                         self.read_from_memory(ty, ptr.clone(), offset as i32)?;
@@ -736,7 +917,7 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                             self.emit(&Instruction::GetArg {
                                 nth: sig.params.len() - 1,
                             })?;
-                            let ptr = self.stack.pop().unwrap();
+                            let ptr = self.validate_pointer(func.result.iter())?;
                             if let Some(ty) = &func.result {
                                 self.write_to_memory(ty, ptr, 0)?;
                             }
@@ -776,6 +957,39 @@ impl<'a, B: Bindgen> Generator<'a, B> {
         Ok(())
     }
 
+    /// Reads a value of the given type from guest memory at `ptr`, returning the lifted operand.
+    pub fn read_value(&mut self, ty: &Type, ptr: B::Operand) -> Result<B::Operand> {
+        self.read_from_memory(ty, ptr, 0)?;
+        Ok(self.stack.pop().expect("No value was read from memory."))
+    }
+
+    /// Lifts a value of the given type from its flattened core operands.
+    pub fn lift_flat(&mut self, ty: &Type, operands: Vec<B::Operand>) -> Result<B::Operand> {
+        self.stack.extend(operands);
+        self.lift(ty)?;
+        Ok(self.stack.pop().expect("No value was lifted."))
+    }
+
+    /// Lowers `value`, which has the given type, and writes it to guest memory at `ptr`.
+    pub fn write_value(&mut self, ty: &Type, ptr: B::Operand, value: B::Operand) -> Result<()> {
+        self.stack.push(value);
+        self.write_to_memory(ty, ptr, 0)
+    }
+
+    /// Pops a pointer off of the stack and ensures that it may hold a record of the given types,
+    /// returning the pointer.
+    fn validate_pointer<'b>(
+        &mut self,
+        tys: impl IntoIterator<Item = &'b Type>,
+    ) -> Result<B::Operand> {
+        let info = self.bindgen.sizes().record(tys);
+        self.emit(&Instruction::ValidatePointer {
+            size: info.size.size_wasm32(),
+            align: info.align.align_wasm32(),
+        })?;
+        Ok(self.stack.pop().unwrap())
+    }
+
     /// Emits the provided instruction.
     fn emit(&mut self, inst: &Instruction<'_>) -> Result<()> {
         self.operands.clear();
@@ -836,9 +1050,7 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                 let realloc = self.list_realloc();
                 self.emit(&StringLower { realloc })
             }
-            Type::ErrorContext => {
-                bail!("ErrorContext type is not yet supported")
-            }
+            Type::ErrorContext => self.emit(&ErrorContextLower),
             Type::Id(id) => match &self.resolve.types[id].kind {
                 TypeDefKind::Type(t) => self.lower(t),
                 TypeDefKind::List(element) => {
@@ -907,10 +1119,24 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                 TypeDefKind::Result(r) => {
                     self.lower_variant_arm(ty, [r.ok.as_ref(), r.err.as_ref()])
                 }
-                TypeDefKind::Future(_) => todo!("lower future"),
-                TypeDefKind::Stream(_) => todo!("lower stream"),
+                TypeDefKind::Future(_) => self.emit(&FutureLower { ty: id }),
+                TypeDefKind::Stream(_) => self.emit(&StreamLower { ty: id }),
                 TypeDefKind::Unknown => unreachable!(),
-                TypeDefKind::FixedSizeList(_, _) => bail!("FixedSizeList not yet supported"),
+                TypeDefKind::FixedSizeList(element, size) => {
+                    self.emit(&FixedListLower {
+                        size: *size,
+                        ty: id,
+                    })?;
+                    let values = self
+                        .stack
+                        .drain(self.stack.len() - *size as usize..)
+                        .collect::<Vec<_>>();
+                    for value in values {
+                        self.stack.push(value);
+                        self.lower(element)?;
+                    }
+                    Ok(())
+                }
             },
         }
     }
@@ -1019,22 +1245,21 @@ impl<'a, B: Bindgen> Generator<'a, B> {
             Type::F32 => self.emit(&Float32FromF32),
             Type::F64 => self.emit(&Float64FromF64),
             Type::String => self.emit(&StringLift),
-            Type::ErrorContext => {
-                bail!("ErrorContext type is not yet supported")
-            }
+            Type::ErrorContext => self.emit(&ErrorContextLift),
             Type::Id(id) => match &self.resolve.types[id].kind {
                 TypeDefKind::Type(t) => self.lift(t),
                 TypeDefKind::List(element) => {
                     if self.bindgen.is_list_canonical(element) {
                         self.emit(&ListCanonLift { element, ty: id })
                     } else {
-                        let len = ReadI32 {
-                            value: Cell::default(),
+                        let validate = ValidateList {
+                            element,
+                            len: Cell::default(),
                         };
-                        self.emit(&len)?;
+                        self.emit(&validate)?;
 
-                        let len = match len {
-                            ReadI32 { value } => value.get(),
+                        let len = match validate {
+                            ValidateList { len, .. } => len.get(),
                             _ => unreachable!(),
                         };
 
@@ -1042,7 +1267,10 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                         let stride = self.bindgen.sizes().size(element).size_wasm32() as i32;
 
                         for i in 0..len {
-                            self.read_from_memory(element, addr.clone(), stride * i)?;
+                            let offset = stride
+                                .checked_mul(i)
+                                .context("List was too large to address.")?;
+                            self.read_from_memory(element, addr.clone(), offset)?;
                         }
 
                         self.emit(&ListLift {
@@ -1136,10 +1364,25 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     })
                 }
 
-                TypeDefKind::Future(_) => todo!("lift future"),
-                TypeDefKind::Stream(_) => todo!("lift stream"),
+                TypeDefKind::Future(_) => self.emit(&FutureLift { ty: id }),
+                TypeDefKind::Stream(_) => self.emit(&StreamLift { ty: id }),
                 TypeDefKind::Unknown => unreachable!(),
-                TypeDefKind::FixedSizeList(_, _) => bail!("FixedSizeList not yet supported"),
+                TypeDefKind::FixedSizeList(element, size) => {
+                    let mut temp = Vec::new();
+                    push_wasm(self.resolve, self.variant, element, &mut temp);
+                    let mut args = self
+                        .stack
+                        .drain(self.stack.len() - temp.len() * *size as usize..)
+                        .collect::<Vec<_>>();
+                    for _ in 0..*size {
+                        self.stack.extend(args.drain(..temp.len()));
+                        self.lift(element)?;
+                    }
+                    self.emit(&FixedListLift {
+                        size: *size,
+                        ty: id,
+                    })
+                }
             },
         }
     }
@@ -1170,8 +1413,8 @@ impl<'a, B: Bindgen> Generator<'a, B> {
 
             let has_value = if let Some(ty) = cases
                 .into_iter()
-                .nth(discriminant as usize)
-                .ok_or_else(|| Error::msg("Invalid discriminant value."))?
+                .nth(discriminant as u32 as usize)
+                .ok_or_else(|| anyhow!("Invalid discriminant value {discriminant}."))?
             {
                 // Push only the values we need for this variant onto
                 // the stack.
@@ -1221,9 +1464,7 @@ impl<'a, B: Bindgen> Generator<'a, B> {
             Type::F32 => self.lower_and_emit(ty, addr, &F32Store { offset }),
             Type::F64 => self.lower_and_emit(ty, addr, &F64Store { offset }),
             Type::String => self.write_list_to_memory(ty, addr, offset),
-            Type::ErrorContext => {
-                bail!("ErrorContext type is not yet supported")
-            }
+            Type::ErrorContext => self.lower_and_emit(ty, addr, &I32Store { offset }),
 
             Type::Id(id) => match &self.resolve.types[id].kind {
                 TypeDefKind::Type(t) => self.write_to_memory(t, addr, offset),
@@ -1297,10 +1538,21 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     self.store_intrepr(offset, e.tag())
                 }
 
-                TypeDefKind::Future(_) => todo!("write future to memory"),
-                TypeDefKind::Stream(_) => todo!("write stream to memory"),
+                TypeDefKind::Future(_) | TypeDefKind::Stream(_) => {
+                    self.lower_and_emit(ty, addr, &I32Store { offset })
+                }
                 TypeDefKind::Unknown => unreachable!(),
-                TypeDefKind::FixedSizeList(_, _) => bail!("FixedSizeList not yet supported"),
+                TypeDefKind::FixedSizeList(element, size) => {
+                    self.emit(&FixedListLower {
+                        size: *size,
+                        ty: id,
+                    })?;
+                    self.write_fields_to_memory(
+                        std::iter::repeat_n(element, *size as usize),
+                        addr,
+                        offset,
+                    )
+                }
             },
         }
     }
@@ -1425,7 +1677,7 @@ impl<'a, B: Bindgen> Generator<'a, B> {
             Type::F32 => self.emit_and_lift(ty, addr, &F32Load { offset }),
             Type::F64 => self.emit_and_lift(ty, addr, &F64Load { offset }),
             Type::String => self.read_list_from_memory(ty, addr, offset),
-            Type::ErrorContext => bail!("ErrorContext not yet supported"),
+            Type::ErrorContext => self.emit_and_lift(ty, addr, &I32Load { offset }),
 
             Type::Id(id) => match &self.resolve.types[id].kind {
                 TypeDefKind::Type(t) => self.read_from_memory(t, addr, offset),
@@ -1528,10 +1780,21 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     self.lift(ty)
                 }
 
-                TypeDefKind::Future(_) => todo!("read future from memory"),
-                TypeDefKind::Stream(_) => todo!("read stream from memory"),
+                TypeDefKind::Future(_) | TypeDefKind::Stream(_) => {
+                    self.emit_and_lift(ty, addr, &I32Load { offset })
+                }
                 TypeDefKind::Unknown => unreachable!(),
-                TypeDefKind::FixedSizeList(_, _) => bail!("FixedSizeList not yet supported"),
+                TypeDefKind::FixedSizeList(element, size) => {
+                    self.read_fields_from_memory(
+                        std::iter::repeat_n(element, *size as usize),
+                        addr,
+                        offset,
+                    )?;
+                    self.emit(&FixedListLift {
+                        size: *size,
+                        ty: id,
+                    })
+                }
             },
         }
     }
@@ -1575,8 +1838,8 @@ impl<'a, B: Bindgen> Generator<'a, B> {
             let disc = value.get();
             let has_value = if let Some(ty) = cases
                 .into_iter()
-                .nth(disc as usize)
-                .ok_or_else(|| Error::msg("Invalid discriminant value."))?
+                .nth(disc as u32 as usize)
+                .ok_or_else(|| anyhow!("Invalid discriminant value {disc}."))?
             {
                 self.read_from_memory(ty, addr, payload_offset)?;
                 true
@@ -1675,7 +1938,12 @@ fn cast(from: WasmType, to: WasmType) -> Bitcast {
 }
 
 /// Pushes the WASM types used to describe the given type into the result vector.
-fn push_wasm(resolve: &Resolve, variant: AbiVariant, ty: &Type, result: &mut Vec<WasmType>) {
+pub(crate) fn push_wasm(
+    resolve: &Resolve,
+    variant: AbiVariant,
+    ty: &Type,
+    result: &mut Vec<WasmType>,
+) {
     match ty {
         Type::Bool
         | Type::S8
@@ -1758,9 +2026,10 @@ fn push_wasm(resolve: &Resolve, variant: AbiVariant, ty: &Type, result: &mut Vec
             }
 
             TypeDefKind::Unknown => unreachable!(),
-            TypeDefKind::FixedSizeList(_, _) => {
-                // Fixed size lists not yet supported - treat as error
-                result.push(WasmType::I32);
+            TypeDefKind::FixedSizeList(element, size) => {
+                for _ in 0..*size {
+                    push_wasm(resolve, variant, element, result);
+                }
             }
         },
     }
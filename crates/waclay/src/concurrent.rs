@@ -0,0 +1,1851 @@
+use std::collections::*;
+use std::sync::*;
+use std::task::Poll;
+
+use anyhow::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use slab::*;
+use wasm_runtime_layer::backend;
+use wit_parser::Type;
+
+use crate::abi::{push_wasm, AbiVariant, MAX_FLAT_PARAMS};
+use crate::func::{run_blocking, within_async_call, AsyncImportCall, CopyOptions};
+use crate::require_matches::require_matches;
+use crate::types::*;
+use crate::values::Value;
+use crate::{AsContextMut, StateTable, StoreContextMut};
+
+/// The result of a copy which cannot complete until another task runs.
+const BLOCKED: u32 = 0xffff_ffff;
+/// The result of a copy which completed.
+const COMPLETED: u32 = 0;
+/// The result of a copy whose other end was dropped.
+const DROPPED: u32 = 1;
+/// The result of a copy which was cancelled.
+const CANCELLED: u32 = 2;
+
+/// The callback code with which an async export signals that it has finished.
+const CALLBACK_EXIT: i32 = 0;
+/// The callback code with which an async export asks to be called again immediately.
+const CALLBACK_YIELD: i32 = 1;
+/// The callback code with which an async export waits for an event from a waitable set.
+const CALLBACK_WAIT: i32 = 2;
+/// The callback code with which an async export polls a waitable set for events.
+const CALLBACK_POLL: i32 = 3;
+/// The event code which is delivered to a callback when no event occurred.
+const EVENT_NONE: i32 = 0;
+/// The event code which is delivered when a subtask returns.
+const EVENT_SUBTASK: i32 = 1;
+/// The event code which is delivered when a read from a stream completes.
+const EVENT_STREAM_READ: i32 = 2;
+/// The event code which is delivered when a write to a stream completes.
+const EVENT_STREAM_WRITE: i32 = 3;
+/// The event code which is delivered when a read from a future completes.
+const EVENT_FUTURE_READ: i32 = 4;
+/// The event code which is delivered when a write to a future completes.
+const EVENT_FUTURE_WRITE: i32 = 5;
+/// The state of a subtask whose call has started, but not yet returned.
+const SUBTASK_STARTED: u32 = 1;
+/// The state of a subtask whose call returned.
+pub(crate) const SUBTASK_RETURNED: u32 = 2;
+/// The state of a subtask which was cancelled after it started, but before it returned.
+const SUBTASK_CANCELLED_BEFORE_RETURNED: u32 = 4;
+/// The number of times in a row that an async export may yield without making progress while it
+/// waits for a copy, before the host reports that the export is stuck.
+const MAX_IDLE_YIELDS: u32 = 1000;
+
+/// Distinguishes futures from streams.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ChannelKind {
+    /// A future, which carries a single value.
+    Future,
+    /// A stream, which carries a sequence of values.
+    Stream,
+}
+
+impl ChannelKind {
+    /// The name of this kind of channel, as used by the canonical built-ins.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Future => "future",
+            Self::Stream => "stream",
+        }
+    }
+}
+
+/// Identifies the table of a future or stream type, as referenced by the canonical built-ins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ChannelTable {
+    /// The table of a future type.
+    Future(wasmtime_environ::component::TypeFutureTableIndex),
+    /// The table of a stream type.
+    Stream(wasmtime_environ::component::TypeStreamTableIndex),
+}
+
+impl ChannelTable {
+    /// Gets whether this table holds futures or streams.
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            Self::Future(_) => ChannelKind::Future,
+            Self::Stream(_) => ChannelKind::Stream,
+        }
+    }
+}
+
+/// Describes who holds one end of a channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ChannelEnd {
+    /// The end is held by the host.
+    Host,
+    /// The end is held by a guest instance.
+    Guest,
+    /// The end has been dropped.
+    Dropped,
+}
+
+/// The shared state of a future or stream, which is stored in the [`crate::Store`].
+#[derive(Debug)]
+pub(crate) struct Channel {
+    /// The unique ID of this channel, which distinguishes it from channels that later reuse its slot.
+    id: u64,
+    /// Whether this is a future or stream.
+    kind: ChannelKind,
+    /// The type of the values carried by this channel, if any.
+    payload: Option<ValueType>,
+    /// The values which have been written, but not yet read.
+    buffer: VecDeque<Option<Value>>,
+    /// Whether a value was written to this channel, if it is a future.
+    written: bool,
+    /// Whether the value of this channel was read, if it is a future.
+    read: bool,
+    /// The holder of the readable end.
+    reader: ChannelEnd,
+    /// The holder of the writable end.
+    writer: ChannelEnd,
+}
+
+/// Refers to a channel in a store.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct ChannelRef {
+    /// The slot of the channel.
+    index: usize,
+    /// The unique ID of the channel.
+    id: u64,
+    /// The store in which the channel resides.
+    store_id: u64,
+}
+
+impl ChannelRef {
+    /// Creates a new channel in the store, with both ends held by the given party.
+    fn new(
+        mut ctx: impl AsContextMut,
+        kind: ChannelKind,
+        payload: Option<ValueType>,
+        holder: ChannelEnd,
+    ) -> Self {
+        /// A counter that uniquely identifies channels.
+        static ID_COUNTER: atomic::AtomicU64 = atomic::AtomicU64::new(0);
+
+        let id = ID_COUNTER.fetch_add(1, atomic::Ordering::AcqRel);
+        let mut ctx = ctx.as_context_mut();
+        let data = ctx.inner.data_mut();
+        let index = data.channels.insert(Channel {
+            id,
+            kind,
+            payload,
+            buffer: VecDeque::new(),
+            written: false,
+            read: false,
+            reader: holder,
+            writer: holder,
+        });
+
+        Self {
+            index,
+            id,
+            store_id: data.id,
+        }
+    }
+
+    /// Gets the channel to which a guest handle refers.
+    fn from_handle(mut ctx: impl AsContextMut, handle: &ChannelHandle) -> Self {
+        Self {
+            index: handle.index,
+            id: handle.id,
+            store_id: ctx.as_context_mut().inner.data().id,
+        }
+    }
+
+    /// Accesses the channel, failing if it belongs to another store or no longer exists.
+    fn with<C: AsContextMut, R>(
+        &self,
+        mut ctx: C,
+        f: impl FnOnce(&mut Channel) -> Result<R>,
+    ) -> Result<R> {
+        let mut ctx = ctx.as_context_mut();
+        let data = ctx.inner.data_mut();
+        ensure!(data.id == self.store_id, crate::Error::StoreMismatch);
+        let channel = data
+            .channels
+            .get_mut(self.index)
+            .filter(|x| x.id == self.id)
+            .context("Channel was already dropped.")?;
+        let result = f(channel);
+        Self::collect(&mut data.channels, self.index);
+        result
+    }
+
+    /// Removes the channel in the given slot if both of its ends were dropped.
+    fn collect(channels: &mut Slab<Channel>, index: usize) {
+        if channels
+            .get(index)
+            .is_some_and(|x| x.reader == ChannelEnd::Dropped && x.writer == ChannelEnd::Dropped)
+        {
+            channels.remove(index);
+        }
+    }
+}
+
+/// The readable end of a stream held by the host. Values of this type may be passed to
+/// guests as [`Value::Stream`], and guests may return the readable ends of streams that they created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamReader {
+    /// The underlying channel.
+    channel: ChannelRef,
+    /// The type of the stream.
+    ty: StreamType,
+}
+
+impl StreamReader {
+    /// Creates a new stream of the given type, returning its readable and writable ends.
+    pub fn new(ctx: impl AsContextMut, ty: StreamType) -> Result<(Self, StreamWriter)> {
+        ensure!(
+            ty.payload_ty().is_some(),
+            "Streams without an element type are not supported by the host."
+        );
+        let channel = ChannelRef::new(ctx, ChannelKind::Stream, ty.payload_ty(), ChannelEnd::Host);
+        Ok((
+            Self {
+                channel,
+                ty: ty.clone(),
+            },
+            StreamWriter { channel, ty },
+        ))
+    }
+
+    /// Gets the type of this stream.
+    pub fn ty(&self) -> StreamType {
+        self.ty.clone()
+    }
+
+    /// Reads at most `max` values which have been written to the stream. Returns an empty list if
+    /// no values are available yet, or `None` once the writable end was dropped and all values were read.
+    pub fn read(&self, ctx: impl AsContextMut, max: usize) -> Result<Option<Vec<Value>>> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.reader == ChannelEnd::Host,
+                "Stream was transferred to a guest."
+            );
+            if channel.buffer.is_empty() && channel.writer == ChannelEnd::Dropped {
+                return Ok(None);
+            }
+
+            let count = max.min(channel.buffer.len());
+            channel
+                .buffer
+                .drain(..count)
+                .map(|x| x.context("Stream had no element type."))
+                .collect::<Result<_>>()
+                .map(Some)
+        })
+    }
+
+    /// Drops the readable end of the stream, discarding any unread values.
+    pub fn close(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.reader == ChannelEnd::Host,
+                "Stream was transferred to a guest."
+            );
+            channel.reader = ChannelEnd::Dropped;
+            channel.buffer.clear();
+            Ok(())
+        })
+    }
+
+    /// Moves the readable end into a guest, returning its channel.
+    pub(crate) fn lower(&self, ctx: impl AsContextMut) -> Result<(usize, u64)> {
+        lower_reader(ctx, &self.channel, ChannelKind::Stream)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for StreamReader {
+    fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::*;
+        std::result::Result::Err(S::Error::custom("Cannot serialize streams."))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> Deserialize<'a> for StreamReader {
+    fn deserialize<D: Deserializer<'a>>(_: D) -> Result<Self, D::Error> {
+        use serde::de::*;
+        std::result::Result::Err(D::Error::custom("Cannot deserialize streams."))
+    }
+}
+
+/// The writable end of a stream held by the host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamWriter {
+    /// The underlying channel.
+    channel: ChannelRef,
+    /// The type of the stream.
+    ty: StreamType,
+}
+
+impl StreamWriter {
+    /// Gets the type of this stream.
+    pub fn ty(&self) -> StreamType {
+        self.ty.clone()
+    }
+
+    /// Writes values to the stream, which are buffered until they are read. Returns `false`
+    /// if the readable end was dropped, in which case the values are discarded.
+    pub fn write(
+        &self,
+        ctx: impl AsContextMut,
+        values: impl IntoIterator<Item = Value>,
+    ) -> Result<bool> {
+        let element = self
+            .ty
+            .payload_ty()
+            .context("Stream had no element type.")?;
+        let values = values
+            .into_iter()
+            .map(|x| {
+                ensure!(
+                    x.ty() == element,
+                    crate::Error::TypeMismatch {
+                        expected: element.clone(),
+                        actual: x.ty(),
+                    }
+                );
+                Ok(Some(x))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.writer == ChannelEnd::Host,
+                "Stream was already closed."
+            );
+            if channel.reader == ChannelEnd::Dropped {
+                return Ok(false);
+            }
+
+            channel.buffer.extend(values);
+            Ok(true)
+        })
+    }
+
+    /// Drops the writable end of the stream, signalling to the reader that no more values will arrive.
+    pub fn close(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.writer == ChannelEnd::Host,
+                "Stream was already closed."
+            );
+            channel.writer = ChannelEnd::Dropped;
+            Ok(())
+        })
+    }
+}
+
+/// The readable end of a future held by the host. Values of this type may be passed to
+/// guests as [`Value::Future`], and guests may return the readable ends of futures that they created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FutureReader {
+    /// The underlying channel.
+    channel: ChannelRef,
+    /// The type of the future.
+    ty: FutureType,
+}
+
+impl FutureReader {
+    /// Creates a new future of the given type, returning its readable and writable ends.
+    pub fn new(ctx: impl AsContextMut, ty: FutureType) -> Result<(Self, FutureWriter)> {
+        ensure!(
+            ty.payload_ty().is_some(),
+            "Futures without a value type are not supported by the host."
+        );
+        let channel = ChannelRef::new(ctx, ChannelKind::Future, ty.payload_ty(), ChannelEnd::Host);
+        Ok((
+            Self {
+                channel,
+                ty: ty.clone(),
+            },
+            FutureWriter { channel, ty },
+        ))
+    }
+
+    /// Gets the type of this future.
+    pub fn ty(&self) -> FutureType {
+        self.ty.clone()
+    }
+
+    /// Takes the value of the future, if it was resolved. Returns [`Poll::Ready`] with `None`
+    /// if the writable end was dropped without resolving the future. The value may only be taken once.
+    pub fn read(&self, ctx: impl AsContextMut) -> Result<Poll<Option<Value>>> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.reader == ChannelEnd::Host,
+                "Future was transferred to a guest."
+            );
+            ensure!(!channel.read, "Future was already read.");
+            match channel.buffer.pop_front() {
+                Some(value) => {
+                    channel.read = true;
+                    Ok(Poll::Ready(Some(
+                        value.context("Future had no value type.")?,
+                    )))
+                }
+                None if channel.writer == ChannelEnd::Dropped => Ok(Poll::Ready(None)),
+                None => Ok(Poll::Pending),
+            }
+        })
+    }
+
+    /// Drops the readable end of the future, discarding its value.
+    pub fn close(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.reader == ChannelEnd::Host,
+                "Future was transferred to a guest."
+            );
+            channel.reader = ChannelEnd::Dropped;
+            channel.buffer.clear();
+            Ok(())
+        })
+    }
+
+    /// Moves the readable end into a guest, returning its channel.
+    pub(crate) fn lower(&self, ctx: impl AsContextMut) -> Result<(usize, u64)> {
+        lower_reader(ctx, &self.channel, ChannelKind::Future)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FutureReader {
+    fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::*;
+        std::result::Result::Err(S::Error::custom("Cannot serialize futures."))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> Deserialize<'a> for FutureReader {
+    fn deserialize<D: Deserializer<'a>>(_: D) -> Result<Self, D::Error> {
+        use serde::de::*;
+        std::result::Result::Err(D::Error::custom("Cannot deserialize futures."))
+    }
+}
+
+/// The writable end of a future held by the host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FutureWriter {
+    /// The underlying channel.
+    channel: ChannelRef,
+    /// The type of the future.
+    ty: FutureType,
+}
+
+impl FutureWriter {
+    /// Gets the type of this future.
+    pub fn ty(&self) -> FutureType {
+        self.ty.clone()
+    }
+
+    /// Resolves the future with the given value, and drops the writable end. Returns `false`
+    /// if the readable end was dropped, in which case the value is discarded.
+    pub fn resolve(&self, ctx: impl AsContextMut, value: Value) -> Result<bool> {
+        let expected = self.ty.payload_ty().context("Future had no value type.")?;
+        ensure!(
+            value.ty() == expected,
+            crate::Error::TypeMismatch {
+                expected,
+                actual: value.ty(),
+            }
+        );
+
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.writer == ChannelEnd::Host,
+                "Future was already resolved or closed."
+            );
+            channel.writer = ChannelEnd::Dropped;
+            channel.written = true;
+            if channel.reader == ChannelEnd::Dropped {
+                return Ok(false);
+            }
+
+            channel.buffer.push_back(Some(value));
+            Ok(true)
+        })
+    }
+
+    /// Drops the writable end of the future without resolving it.
+    pub fn close(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.channel.with(ctx, |channel| {
+            ensure!(
+                channel.writer == ChannelEnd::Host,
+                "Future was already resolved or closed."
+            );
+            channel.writer = ChannelEnd::Dropped;
+            Ok(())
+        })
+    }
+}
+
+/// Marks a host-held readable end as transferred to a guest.
+fn lower_reader(
+    ctx: impl AsContextMut,
+    channel: &ChannelRef,
+    kind: ChannelKind,
+) -> Result<(usize, u64)> {
+    channel.with(ctx, |x| {
+        ensure!(x.kind == kind, "Channel kind did not match.");
+        ensure!(
+            x.reader == ChannelEnd::Host,
+            "Readable end was already transferred or closed."
+        );
+        x.reader = ChannelEnd::Guest;
+        Ok((channel.index, channel.id))
+    })
+}
+
+/// A guest instance's handle to one end of a channel.
+#[derive(Clone, Debug)]
+pub(crate) struct ChannelHandle {
+    /// The slot of the channel in the store.
+    index: usize,
+    /// The unique ID of the channel.
+    id: u64,
+    /// Whether the channel is a future or stream.
+    kind: ChannelKind,
+    /// Whether this is the writable end.
+    writable: bool,
+    /// The copy using this handle which returned `BLOCKED`, and was neither completed nor cancelled.
+    pending: Option<PendingCopy>,
+    /// The result of a copy which completed after returning `BLOCKED`, until it is delivered as an event.
+    completed: Option<u32>,
+    /// The waitable set which this handle joined, if any.
+    set: Option<i32>,
+}
+
+impl ChannelHandle {
+    /// Creates a handle to one end of the given channel.
+    fn new((index, id): (usize, u64), kind: ChannelKind, writable: bool) -> Self {
+        Self {
+            index,
+            id,
+            kind,
+            writable,
+            pending: None,
+            completed: None,
+            set: None,
+        }
+    }
+
+    /// Gets the code of the event which is delivered when a copy using this handle completes.
+    fn event_code(&self) -> i32 {
+        match (self.kind, self.writable) {
+            (ChannelKind::Future, false) => EVENT_FUTURE_READ,
+            (ChannelKind::Future, true) => EVENT_FUTURE_WRITE,
+            (ChannelKind::Stream, false) => EVENT_STREAM_READ,
+            (ChannelKind::Stream, true) => EVENT_STREAM_WRITE,
+        }
+    }
+}
+
+/// A copy which returned `BLOCKED`, and which the host completes once values are available to
+/// read, or the buffer of the channel has room for values to write.
+#[derive(Clone, Debug)]
+struct PendingCopy {
+    /// The address in guest memory to which values are read, or from which they are written.
+    ptr: u32,
+    /// The maximum number of values to copy.
+    max: usize,
+    /// The built-in which started the copy, and which lowers or lifts the values.
+    builtin: Weak<ChannelBuiltin>,
+}
+
+/// An event which is delivered to a task that waits on a waitable set.
+#[derive(Copy, Clone, Debug)]
+struct Event {
+    /// The kind of event.
+    code: i32,
+    /// The handle of the waitable to which the event occurred.
+    index: i32,
+    /// The result which the event carries, such as the status of a copy.
+    payload: u32,
+}
+
+impl Event {
+    /// The event which is delivered when nothing occurred.
+    const NONE: Self = Self {
+        code: EVENT_NONE,
+        index: 0,
+        payload: 0,
+    };
+
+    /// Passes this event to a callback, as its core arguments.
+    fn arguments(&self) -> [wasm_runtime_layer::Value; 3] {
+        [
+            wasm_runtime_layer::Value::I32(self.code),
+            wasm_runtime_layer::Value::I32(self.index),
+            wasm_runtime_layer::Value::I32(self.payload as i32),
+        ]
+    }
+}
+
+/// A call which a guest made to an asynchronous host function through an import lowered with
+/// the async ABI.
+#[derive(Debug)]
+struct Subtask {
+    /// The call, until the host starts running it.
+    call: Option<AsyncImportCall>,
+    /// Whether the call returned or was cancelled, so that the subtask may be dropped.
+    resolved: bool,
+    /// The state which has not yet been delivered to the guest as an event.
+    completed: Option<u32>,
+    /// The waitable set which this subtask joined, if any.
+    set: Option<i32>,
+}
+
+/// An entry in the table of waitables held by a guest instance.
+#[derive(Debug)]
+enum Waitable {
+    /// One end of a future or stream.
+    Channel(ChannelHandle),
+    /// A call to an asynchronous host function.
+    Subtask(Subtask),
+    /// A waitable set, which collects the events of the waitables that joined it.
+    Set,
+}
+
+impl Waitable {
+    /// Gets the waitable set which this waitable joined, if any.
+    fn set_mut(&mut self) -> Option<&mut Option<i32>> {
+        match self {
+            Self::Channel(handle) => Some(&mut handle.set),
+            Self::Subtask(subtask) => Some(&mut subtask.set),
+            Self::Set => None,
+        }
+    }
+
+    /// Takes the event which occurred to this waitable, if it is in the given set.
+    fn take_event(&mut self, set: i32) -> Option<(i32, u32)> {
+        match self {
+            Self::Channel(handle) if handle.set == Some(set) => {
+                Some((handle.event_code(), handle.completed.take()?))
+            }
+            Self::Subtask(subtask) if subtask.set == Some(set) => {
+                Some((EVENT_SUBTASK, subtask.completed.take()?))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The table of future and stream handles and waitable sets held by a guest instance. Handle
+/// indices begin at one, since zero is not a valid handle.
+#[derive(Debug, Default)]
+pub(crate) struct Waitables {
+    /// The entries of the table.
+    entries: Slab<Waitable>,
+    /// Counts the copies and events which show that the instance's tasks are making progress.
+    progress: u64,
+    /// Counts the copies which returned `BLOCKED`.
+    blocked: u64,
+}
+
+impl Waitables {
+    /// Inserts a handle, returning its index.
+    fn add(&mut self, handle: ChannelHandle) -> i32 {
+        (self.entries.insert(Waitable::Channel(handle)) + 1) as i32
+    }
+
+    /// Gets the entry at the given index.
+    fn entry_mut(&mut self, idx: i32) -> Option<&mut Waitable> {
+        (idx as u32 as usize)
+            .checked_sub(1)
+            .and_then(|x| self.entries.get_mut(x))
+    }
+
+    /// Gets the handle at the given index, ensuring that it refers to the expected end.
+    fn get_mut(
+        &mut self,
+        idx: i32,
+        kind: ChannelKind,
+        writable: bool,
+    ) -> Result<&mut ChannelHandle> {
+        let Some(Waitable::Channel(handle)) = self.entry_mut(idx) else {
+            bail!("Invalid future or stream handle.");
+        };
+        ensure!(
+            handle.kind == kind && handle.writable == writable,
+            "Handle referred to the wrong kind of future or stream end."
+        );
+        Ok(handle)
+    }
+
+    /// Removes the handle at the given index, ensuring that it refers to the expected end.
+    fn remove(&mut self, idx: i32, kind: ChannelKind, writable: bool) -> Result<ChannelHandle> {
+        let handle = self.get_mut(idx, kind, writable)?;
+        ensure!(
+            handle.pending.is_none(),
+            "Cannot drop a future or stream end while a copy is in progress."
+        );
+        let handle = handle.clone();
+        self.entries.remove(idx as usize - 1);
+        Ok(handle)
+    }
+
+    /// Inserts a handle to the readable end of a channel that was transferred from the host.
+    pub fn lower(&mut self, channel: (usize, u64), kind: ChannelKind) -> i32 {
+        self.add(ChannelHandle::new(channel, kind, false))
+    }
+
+    /// Removes a handle to a readable end so that it may be passed to the host.
+    fn lift_handle(&mut self, idx: i32, kind: ChannelKind) -> Result<ChannelHandle> {
+        self.remove(idx, kind, false)
+    }
+
+    /// Creates a new waitable set, returning its index.
+    fn new_set(&mut self) -> i32 {
+        (self.entries.insert(Waitable::Set) + 1) as i32
+    }
+
+    /// Ensures that the given index refers to a waitable set.
+    fn check_set(&mut self, set: i32) -> Result<()> {
+        ensure!(
+            matches!(self.entry_mut(set), Some(Waitable::Set)),
+            "Invalid waitable set handle."
+        );
+        Ok(())
+    }
+
+    /// Adds a waitable to a set, or removes it from its set if `set` is zero.
+    fn join(&mut self, idx: i32, set: i32) -> Result<()> {
+        if set != 0 {
+            self.check_set(set)?;
+        }
+        let joined = self
+            .entry_mut(idx)
+            .and_then(Waitable::set_mut)
+            .context("Invalid waitable handle.")?;
+        *joined = (set != 0).then_some(set);
+        Ok(())
+    }
+
+    /// Removes a waitable set, which no waitable may still be joined to.
+    fn drop_set(&mut self, set: i32) -> Result<()> {
+        self.check_set(set)?;
+        ensure!(
+            !self
+                .entries
+                .iter_mut()
+                .any(|(_, x)| x.set_mut().is_some_and(|x| *x == Some(set))),
+            "Cannot drop a waitable set which waitables have joined."
+        );
+        self.entries.remove(set as usize - 1);
+        Ok(())
+    }
+
+    /// Takes the next event which occurred to a waitable in the given set, if any.
+    fn take_event(&mut self, set: i32) -> Result<Option<Event>> {
+        self.check_set(set)?;
+        let event = self.entries.iter_mut().find_map(|(i, x)| {
+            let (code, payload) = x.take_event(set)?;
+            Some(Event {
+                code,
+                index: i as i32 + 1,
+                payload,
+            })
+        });
+        if event.is_some() {
+            self.progress += 1;
+        }
+        Ok(event)
+    }
+
+    /// Inserts a subtask for a call to an asynchronous host function, returning its index.
+    fn add_subtask(&mut self, call: AsyncImportCall) -> i32 {
+        let subtask = Subtask {
+            call: Some(call),
+            resolved: false,
+            completed: None,
+            set: None,
+        };
+        (self.entries.insert(Waitable::Subtask(subtask)) + 1) as i32
+    }
+
+    /// Gets the subtask at the given index.
+    fn subtask_mut(&mut self, idx: i32) -> Result<&mut Subtask> {
+        let Some(Waitable::Subtask(subtask)) = self.entry_mut(idx) else {
+            bail!("Invalid subtask handle.");
+        };
+        Ok(subtask)
+    }
+
+    /// Takes the call of a subtask which has not yet started, along with the subtask's index.
+    fn take_call(&mut self) -> Option<(i32, AsyncImportCall)> {
+        self.entries.iter_mut().find_map(|(i, x)| match x {
+            Waitable::Subtask(subtask) => Some((i as i32 + 1, subtask.call.take()?)),
+            _ => None,
+        })
+    }
+
+    /// Records that the call of a subtask returned.
+    fn finish_subtask(&mut self, idx: i32) -> Result<()> {
+        let subtask = self.subtask_mut(idx)?;
+        subtask.resolved = true;
+        subtask.completed = Some(SUBTASK_RETURNED);
+        self.progress += 1;
+        Ok(())
+    }
+
+    /// Removes a subtask, which must have returned or been cancelled.
+    fn drop_subtask(&mut self, idx: i32) -> Result<()> {
+        ensure!(
+            self.subtask_mut(idx)?.resolved,
+            "Cannot drop a subtask which has not returned."
+        );
+        self.entries.remove(idx as usize - 1);
+        Ok(())
+    }
+
+    /// Cancels a subtask whose call has not yet run, returning the subtask's new state.
+    /// If the call already returned but the guest has not yet received the event, the
+    /// returned state is taken instead.
+    fn cancel_subtask(&mut self, idx: i32) -> Result<u32> {
+        let subtask = self.subtask_mut(idx)?;
+        if subtask.resolved {
+            return subtask
+                .completed
+                .take()
+                .context("Cannot cancel a subtask which already returned.");
+        }
+        ensure!(
+            subtask.call.take().is_some(),
+            "Cannot cancel a subtask while its call is running."
+        );
+        subtask.resolved = true;
+        self.progress += 1;
+        Ok(SUBTASK_CANCELLED_BEFORE_RETURNED)
+    }
+
+    /// Resolves every subtask which has not yet returned, discarding the calls which have not yet
+    /// run, because the task which started them was abandoned.
+    fn abandon_subtasks(&mut self) {
+        for (_, entry) in &mut self.entries {
+            if let Waitable::Subtask(subtask) = entry {
+                subtask.call = None;
+                subtask.resolved = true;
+            }
+        }
+    }
+
+    /// Iterates over the future and stream handles, along with their indices.
+    fn channel_handles(&self) -> impl Iterator<Item = (i32, &ChannelHandle)> {
+        self.entries.iter().filter_map(|(i, x)| match x {
+            Waitable::Channel(handle) => Some((i as i32 + 1, handle)),
+            Waitable::Subtask(_) | Waitable::Set => None,
+        })
+    }
+
+    /// Gets the copies which returned `BLOCKED`, along with the indices of their handles.
+    fn pending_copies(&self) -> Vec<(i32, ChannelHandle, PendingCopy)> {
+        self.channel_handles()
+            .filter_map(|(i, x)| Some((i, x.clone(), x.pending.clone()?)))
+            .collect()
+    }
+
+    /// Marks the ends held by this table as dropped, and removes all entries.
+    pub fn drop_all(&mut self, channels: &mut Slab<Channel>) {
+        for entry in self.entries.drain() {
+            let Waitable::Channel(handle) = entry else {
+                continue;
+            };
+            if let Some(channel) = channels.get_mut(handle.index).filter(|x| x.id == handle.id) {
+                if handle.writable {
+                    channel.writer = ChannelEnd::Dropped;
+                } else {
+                    channel.reader = ChannelEnd::Dropped;
+                }
+            }
+            ChannelRef::collect(channels, handle.index);
+        }
+    }
+}
+
+/// Lifts the readable end of a stream from a guest handle.
+pub(crate) fn lift_stream(
+    mut ctx: impl AsContextMut,
+    handles: &Mutex<Waitables>,
+    idx: i32,
+    ty: StreamType,
+) -> Result<StreamReader> {
+    let channel = lift_reader(&mut ctx, handles, idx, ChannelKind::Stream, ty.payload_ty())?;
+    Ok(StreamReader { channel, ty })
+}
+
+/// Lifts the readable end of a future from a guest handle.
+pub(crate) fn lift_future(
+    mut ctx: impl AsContextMut,
+    handles: &Mutex<Waitables>,
+    idx: i32,
+    ty: FutureType,
+) -> Result<FutureReader> {
+    let channel = lift_reader(&mut ctx, handles, idx, ChannelKind::Future, ty.payload_ty())?;
+    Ok(FutureReader { channel, ty })
+}
+
+/// Removes the readable end of a channel from a guest's table, and gives it to the host.
+fn lift_reader(
+    mut ctx: impl AsContextMut,
+    handles: &Mutex<Waitables>,
+    idx: i32,
+    kind: ChannelKind,
+    payload: Option<ValueType>,
+) -> Result<ChannelRef> {
+    let handle = lock(handles)?.lift_handle(idx, kind)?;
+    let channel = ChannelRef::from_handle(&mut ctx, &handle);
+    channel.with(&mut ctx, |x| {
+        x.reader = ChannelEnd::Host;
+        ensure!(
+            x.payload == payload,
+            "Future or stream handle was lifted with the wrong type."
+        );
+        Ok(())
+    })?;
+    Ok(channel)
+}
+
+/// Acquires the table of future and stream handles.
+fn lock(handles: &Mutex<Waitables>) -> Result<MutexGuard<'_, Waitables>> {
+    handles
+        .try_lock()
+        .map_err(|_| crate::Error::resource("Could not lock future and stream handles.").into())
+}
+
+/// The state of the async export which an instance is running.
+#[derive(Debug, Default)]
+pub(crate) enum TaskState {
+    /// No async export is running.
+    #[default]
+    Idle,
+    /// An async export is running, and has not yet returned. Holds the type of its result,
+    /// and the options with which `task.return` lifts it.
+    Running(Option<Type>, CopyOptions),
+    /// An async export passed its results to `task.return`.
+    Returned(Vec<Value>),
+}
+
+/// Acquires the task state.
+fn lock_task(task: &Mutex<TaskState>) -> Result<MutexGuard<'_, TaskState>> {
+    task.try_lock()
+        .map_err(|_| crate::Error::resource("Could not lock task state.").into())
+}
+
+/// Records that an async export with the given result type is running.
+pub(crate) fn start_task(
+    task: &Mutex<TaskState>,
+    result: Option<Type>,
+    copy: CopyOptions,
+) -> Result<()> {
+    let mut state = lock_task(task)?;
+    ensure!(
+        matches!(*state, TaskState::Idle),
+        "Cannot call an async export while another is running in the same instance."
+    );
+    *state = TaskState::Running(result, copy);
+    Ok(())
+}
+
+/// Records that the running async export ended, returning the results that it passed to
+/// `task.return`, if any.
+pub(crate) fn end_task(task: &Mutex<TaskState>) -> Result<Option<Vec<Value>>> {
+    match std::mem::take(&mut *lock_task(task)?) {
+        TaskState::Returned(results) => Ok(Some(results)),
+        _ => Ok(None),
+    }
+}
+
+/// Lifts and records the results that an async export passed to `task.return`. They are passed
+/// as core values, or as a pointer into guest memory if there are too many.
+pub(crate) fn task_return(
+    ctx: impl AsContextMut,
+    task: &Mutex<TaskState>,
+    args: &[wasm_runtime_layer::Value],
+) -> Result<()> {
+    let TaskState::Running(result, copy) = std::mem::take(&mut *lock_task(task)?) else {
+        bail!("Called `task.return` outside of an async export, or more than once.");
+    };
+
+    let results = match &result {
+        Some(ty) => {
+            let mut flat = Vec::new();
+            push_wasm(
+                &copy.component.resolve,
+                AbiVariant::GuestExportAsync,
+                ty,
+                &mut flat,
+            );
+            let args = args
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<Vec<Value>>>()?;
+            if flat.len() > MAX_FLAT_PARAMS {
+                let ptr = require_matches!(args.first(), Some(Value::S32(x)), *x);
+                copy.lift(ctx, ty, ptr as u32, 1)?
+            } else {
+                vec![copy.lift_flat(ctx, ty, args)?]
+            }
+        }
+        None => Vec::new(),
+    };
+
+    *lock_task(task)? = TaskState::Returned(results);
+    Ok(())
+}
+
+/// Starts a subtask for a call to an asynchronous host function, returning the state and handle
+/// which are passed to the guest.
+pub(crate) fn start_subtask(waitables: &Mutex<Waitables>, call: AsyncImportCall) -> Result<u32> {
+    let idx = lock(waitables)?.add_subtask(call);
+    Ok(((idx as u32) << 4) | SUBTASK_STARTED)
+}
+
+/// Abandons the calls to asynchronous host functions which an instance made, because the future
+/// that drove its task was dropped.
+pub(crate) fn abandon_subtasks(waitables: &Mutex<Waitables>) -> Result<()> {
+    lock(waitables)?.abandon_subtasks();
+    Ok(())
+}
+
+/// Calls the callback of an async export until it exits, delivering the event that each callback
+/// code asks for. While the export waits or yields, the host runs the calls that it made to
+/// asynchronous host functions.
+pub(crate) async fn drive_task<T: 'static, E: backend::WasmEngine>(
+    mut ctx: StoreContextMut<'_, T, E>,
+    callback: &wasm_runtime_layer::Func,
+    state_table: &StateTable,
+    mut code: i32,
+) -> Result<()> {
+    let waitables = &state_table.waitables;
+    let mut idle = IdleYields::default();
+    loop {
+        let set = (code as u32 >> 4) as i32;
+        let event = match code & 0xf {
+            CALLBACK_EXIT => return Ok(()),
+            CALLBACK_YIELD => {
+                if !run_subtask(ctx.as_context_mut(), waitables).await? {
+                    idle.record(waitables)?;
+                }
+                Event::NONE
+            }
+            CALLBACK_WAIT => wait_event(ctx.as_context_mut(), waitables, set).await?,
+            CALLBACK_POLL => poll_event(ctx.as_context_mut(), waitables, set, &mut idle).await?,
+            x => bail!("Async export returned unknown callback code {x}."),
+        };
+        let mut results = [wasm_runtime_layer::Value::I32(CALLBACK_EXIT)];
+        callback.call(&mut ctx.inner, &event.arguments(), &mut results)?;
+        code = require_matches!(results[0], wasm_runtime_layer::Value::I32(x), x);
+    }
+}
+
+/// Waits for the next event in a waitable set, running the calls that the instance made to
+/// asynchronous host functions until one occurs.
+async fn wait_event<T: 'static, E: backend::WasmEngine>(
+    mut ctx: StoreContextMut<'_, T, E>,
+    waitables: &Mutex<Waitables>,
+    set: i32,
+) -> Result<Event> {
+    loop {
+        if let Some(event) = next_event(ctx.as_context_mut(), waitables, set)? {
+            return Ok(event);
+        }
+        ensure!(
+            run_subtask(ctx.as_context_mut(), waitables).await?,
+            "Guest waited on a waitable set, but no event can occur because no other task may run."
+        );
+    }
+}
+
+/// Checks for an event in a waitable set. If none has occurred, runs one call that the instance
+/// made to an asynchronous host function, if any, and checks again.
+async fn poll_event<T: 'static, E: backend::WasmEngine>(
+    mut ctx: StoreContextMut<'_, T, E>,
+    waitables: &Mutex<Waitables>,
+    set: i32,
+    idle: &mut IdleYields,
+) -> Result<Event> {
+    if let Some(event) = next_event(ctx.as_context_mut(), waitables, set)? {
+        return Ok(event);
+    }
+    if !run_subtask(ctx.as_context_mut(), waitables).await? {
+        idle.record(waitables)?;
+        return Ok(Event::NONE);
+    }
+    Ok(next_event(ctx, waitables, set)?.unwrap_or(Event::NONE))
+}
+
+/// Runs the oldest call that the instance made to an asynchronous host function and which has not
+/// yet started, returning whether there was one.
+async fn run_subtask<T: 'static, E: backend::WasmEngine>(
+    ctx: StoreContextMut<'_, T, E>,
+    waitables: &Mutex<Waitables>,
+) -> Result<bool> {
+    let Some((idx, call)) = lock(waitables)?.take_call() else {
+        return Ok(false);
+    };
+    call.run(ctx).await?;
+    lock(waitables)?.finish_subtask(idx)?;
+    Ok(true)
+}
+
+/// Counts the times in a row that an async export yields without making progress while it
+/// waits for a copy, so that an export which could only spin forever fails instead.
+#[derive(Debug, Default)]
+struct IdleYields {
+    /// The number of times in a row that the export yielded without making progress.
+    count: u32,
+    /// The progress of the instance when the export last yielded.
+    progress: u64,
+    /// The number of blocked copies when the export last yielded.
+    blocked: u64,
+}
+
+impl IdleYields {
+    /// Records that the export yielded without receiving an event. Fails if the export waits for
+    /// a copy, and has yielded too many times in a row without copying values or receiving events.
+    fn record(&mut self, waitables: &Mutex<Waitables>) -> Result<()> {
+        let table = lock(waitables)?;
+        let waiting = table.blocked != self.blocked
+            || table.channel_handles().any(|(_, x)| x.pending.is_some());
+        if waiting && table.progress == self.progress {
+            self.count += 1;
+            ensure!(
+                self.count < MAX_IDLE_YIELDS,
+                "Async export yielded {MAX_IDLE_YIELDS} times without making progress while it waited for a copy that no other task may complete."
+            );
+        } else {
+            self.count = 0;
+        }
+
+        self.progress = table.progress;
+        self.blocked = table.blocked;
+        Ok(())
+    }
+}
+
+/// Takes the next event which occurred to a waitable in the given set, after completing
+/// any blocked reads for which values are now available, and any blocked writes for which
+/// buffers now have room.
+fn next_event(
+    mut ctx: impl AsContextMut,
+    waitables: &Mutex<Waitables>,
+    set: i32,
+) -> Result<Option<Event>> {
+    for (idx, handle, copy) in lock(waitables)?.pending_copies() {
+        let builtin = copy
+            .builtin
+            .upgrade()
+            .ok_or(crate::Error::InstanceDropped)?;
+        let status = if handle.writable {
+            builtin.put_values(&mut ctx, &handle, copy.ptr, copy.max)?
+        } else {
+            builtin
+                .take_values(&mut ctx, &handle, copy.max)?
+                .map(|(values, status)| builtin.deliver(&mut ctx, copy.ptr, values, status))
+                .transpose()?
+        };
+        let Some(status) = status else {
+            continue;
+        };
+
+        let mut table = lock(waitables)?;
+        let handle = table.get_mut(idx, handle.kind, handle.writable)?;
+        handle.pending = None;
+        handle.completed = Some(status);
+    }
+
+    lock(waitables)?.take_event(set)
+}
+
+/// A canonical built-in which operates upon futures or streams of one type.
+#[derive(Clone, Debug)]
+pub(crate) enum ChannelOp {
+    /// Creates a new future or stream.
+    New,
+    /// Reads values into guest memory, with the given options.
+    Read(wasmtime_environ::component::CanonicalOptions),
+    /// Writes values from guest memory, with the given options.
+    Write(wasmtime_environ::component::CanonicalOptions),
+    /// Cancels a read which returned `BLOCKED`.
+    CancelRead,
+    /// Cancels a write which returned `BLOCKED`.
+    CancelWrite,
+    /// Drops a readable end.
+    DropReadable,
+    /// Drops a writable end.
+    DropWritable,
+}
+
+impl ChannelOp {
+    /// The name of this operation, as used by the canonical built-ins.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Read(_) => "read",
+            Self::Write(_) => "write",
+            Self::CancelRead => "cancel-read",
+            Self::CancelWrite => "cancel-write",
+            Self::DropReadable => "drop-readable",
+            Self::DropWritable => "drop-writable",
+        }
+    }
+}
+
+/// Implements a canonical built-in which creates, copies, or drops futures or streams of one type.
+pub(crate) struct ChannelBuiltin {
+    /// The operation to perform.
+    pub op: ChannelOp,
+    /// Whether the built-in operates on futures or streams.
+    pub kind: ChannelKind,
+    /// The type of the values carried by the channel, if any.
+    pub payload: Option<Type>,
+    /// The value type of the values carried by the channel, if any.
+    pub payload_ty: Option<ValueType>,
+    /// The state of the calling instance.
+    pub state_table: Arc<StateTable>,
+    /// The options with which values are copied to and from guest memory, if the built-in copies values.
+    pub copy: Option<CopyOptions>,
+}
+
+impl ChannelBuiltin {
+    /// Performs the built-in's operation. The arguments and results follow the signatures
+    /// that the canonical ABI defines for each built-in.
+    pub fn call(
+        self: &Arc<Self>,
+        ctx: impl AsContextMut,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        match &self.op {
+            ChannelOp::New => self.new_channel(ctx, results),
+            ChannelOp::Read(options) => self.read(ctx, options.async_, args, results),
+            ChannelOp::Write(options) => self.write(ctx, options.async_, args, results),
+            ChannelOp::CancelRead => self.cancel(args, results, false),
+            ChannelOp::CancelWrite => self.cancel(args, results, true),
+            ChannelOp::DropReadable => self.drop_end(ctx, args, false),
+            ChannelOp::DropWritable => self.drop_end(ctx, args, true),
+        }
+    }
+
+    /// Creates a new channel, returning the handles of its writable and readable ends.
+    fn new_channel(
+        &self,
+        mut ctx: impl AsContextMut,
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        let channel = ChannelRef::new(
+            &mut ctx,
+            self.kind,
+            self.payload_ty.clone(),
+            ChannelEnd::Guest,
+        );
+        let mut handles = lock(&self.state_table.waitables)?;
+        let read = handles.add(ChannelHandle::new(
+            (channel.index, channel.id),
+            self.kind,
+            false,
+        ));
+        let write = handles.add(ChannelHandle::new(
+            (channel.index, channel.id),
+            self.kind,
+            true,
+        ));
+        handles.progress += 1;
+        results[0] = wasm_runtime_layer::Value::I64(((write as i64) << 32) | read as i64);
+        Ok(())
+    }
+
+    /// Reads values from a channel into guest memory. If no values are available, the read
+    /// returns `BLOCKED`, and the host completes it once values are written or the writer is dropped.
+    fn read(
+        self: &Arc<Self>,
+        mut ctx: impl AsContextMut,
+        async_: bool,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        self.copy()?.ensure_may_leave(&mut ctx)?;
+        let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+        let ptr = require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x) as u32;
+        let max = match self.kind {
+            ChannelKind::Future => 1,
+            ChannelKind::Stream => {
+                require_matches!(args[2], wasm_runtime_layer::Value::I32(x), x) as u32 as usize
+            }
+        };
+
+        let handle = self.begin_copy(idx, false)?;
+        let status = match self.take_values(&mut ctx, &handle, max)? {
+            Some((values, status)) => self.deliver(&mut ctx, ptr, values, status)?,
+            None => self.block(idx, false, async_, ptr, max)?,
+        };
+        results[0] = wasm_runtime_layer::Value::I32(status as i32);
+        Ok(())
+    }
+
+    /// Takes at most `max` values from the channel to which a readable handle refers, along with the
+    /// status of the copy. Returns `None` if no values are available, but the writer may still write some.
+    fn take_values(
+        &self,
+        mut ctx: impl AsContextMut,
+        handle: &ChannelHandle,
+        max: usize,
+    ) -> Result<Option<(Vec<Option<Value>>, u32)>> {
+        ChannelRef::from_handle(&mut ctx, handle).with(&mut ctx, |channel| {
+            self.check_payload(channel)?;
+            ensure!(
+                self.kind == ChannelKind::Stream || !channel.read,
+                "Future was already read."
+            );
+            if channel.buffer.is_empty() {
+                return Ok((channel.writer == ChannelEnd::Dropped).then(|| (Vec::new(), DROPPED)));
+            }
+
+            channel.read = true;
+            let count = max.min(channel.buffer.len());
+            Ok(Some((channel.buffer.drain(..count).collect(), COMPLETED)))
+        })
+    }
+
+    /// Lowers the values which a read took into guest memory at `ptr`, returning the result of the read.
+    fn deliver(
+        &self,
+        mut ctx: impl AsContextMut,
+        ptr: u32,
+        values: Vec<Option<Value>>,
+        status: u32,
+    ) -> Result<u32> {
+        let count = values.len();
+        if let Some(ty) = &self.payload {
+            let values = values
+                .into_iter()
+                .map(|x| x.context("Channel value was missing."))
+                .collect::<Result<Vec<_>>>()?;
+            self.copy()?.lower(&mut ctx, ty, ptr, values)?;
+        }
+        lock(&self.state_table.waitables)?.progress += 1;
+        Ok(self.status(count, status))
+    }
+
+    /// Writes values from guest memory to a channel, which buffers them until they are read. If the
+    /// buffer is full, the write returns `BLOCKED`, and the host completes it once values are read
+    /// or the reader is dropped.
+    fn write(
+        self: &Arc<Self>,
+        mut ctx: impl AsContextMut,
+        async_: bool,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        self.copy()?.ensure_may_leave(&mut ctx)?;
+        let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+        let ptr = require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x) as u32;
+        let count = match self.kind {
+            ChannelKind::Future => 1,
+            ChannelKind::Stream => {
+                require_matches!(args[2], wasm_runtime_layer::Value::I32(x), x) as u32 as usize
+            }
+        };
+
+        let handle = self.begin_copy(idx, true)?;
+        ChannelRef::from_handle(&mut ctx, &handle).with(&mut ctx, |x| {
+            self.check_payload(x)?;
+            ensure!(
+                self.kind == ChannelKind::Stream || !x.written,
+                "Future was already written."
+            );
+            x.written = true;
+            Ok(())
+        })?;
+
+        let status = match self.put_values(&mut ctx, &handle, ptr, count)? {
+            Some(status) => status,
+            None => self.block(idx, true, async_, ptr, count)?,
+        };
+        results[0] = wasm_runtime_layer::Value::I32(status as i32);
+        Ok(())
+    }
+
+    /// Lifts at most `max` values from guest memory at `ptr`, and buffers as many as fit in the
+    /// channel to which a writable handle refers, returning the result of the write. Returns `None`
+    /// if the buffer is full, but the reader may still take values from it.
+    fn put_values(
+        &self,
+        mut ctx: impl AsContextMut,
+        handle: &ChannelHandle,
+        ptr: u32,
+        max: usize,
+    ) -> Result<Option<u32>> {
+        let limit = ctx
+            .as_context()
+            .inner
+            .data()
+            .lift_limits
+            .max_buffered_values()
+            .max(1);
+        let channel = ChannelRef::from_handle(&mut ctx, handle);
+        let room = channel.with(&mut ctx, |x| {
+            Ok((x.reader != ChannelEnd::Dropped).then(|| limit.saturating_sub(x.buffer.len())))
+        })?;
+        let count = match room {
+            None => return Ok(Some(self.status(0, DROPPED))),
+            Some(0) => return Ok(None),
+            Some(room) => max.min(room),
+        };
+
+        let values = match &self.payload {
+            Some(ty) => self
+                .copy()?
+                .lift(&mut ctx, ty, ptr, count)?
+                .into_iter()
+                .map(Some)
+                .collect(),
+            None => vec![None; count],
+        };
+        channel.with(&mut ctx, |x| {
+            x.buffer.extend(values);
+            Ok(())
+        })?;
+        lock(&self.state_table.waitables)?.progress += 1;
+        Ok(Some(self.status(count, COMPLETED)))
+    }
+
+    /// Cancels a copy which previously returned `BLOCKED`. If the copy already completed, but its
+    /// event was not yet delivered, returns the result of the copy instead.
+    fn cancel(
+        &self,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+        writable: bool,
+    ) -> Result<()> {
+        let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+        let mut handles = lock(&self.state_table.waitables)?;
+        let handle = handles.get_mut(idx, self.kind, writable)?;
+        let status = match handle.completed.take() {
+            Some(status) => status,
+            None => {
+                ensure!(
+                    handle.pending.take().is_some(),
+                    "No copy was in progress to cancel."
+                );
+                self.status(0, CANCELLED)
+            }
+        };
+        results[0] = wasm_runtime_layer::Value::I32(status as i32);
+        Ok(())
+    }
+
+    /// Drops one end of a channel.
+    fn drop_end(
+        &self,
+        mut ctx: impl AsContextMut,
+        args: &[wasm_runtime_layer::Value],
+        writable: bool,
+    ) -> Result<()> {
+        let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+        let handle = {
+            let mut handles = lock(&self.state_table.waitables)?;
+            handles.progress += 1;
+            handles.remove(idx, self.kind, writable)?
+        };
+        ChannelRef::from_handle(&mut ctx, &handle).with(&mut ctx, |channel| {
+            if writable {
+                channel.writer = ChannelEnd::Dropped;
+            } else {
+                channel.reader = ChannelEnd::Dropped;
+                channel.buffer.clear();
+            }
+            Ok(())
+        })
+    }
+
+    /// Ensures that a copy may begin with the given handle, returning the handle.
+    fn begin_copy(&self, idx: i32, writable: bool) -> Result<ChannelHandle> {
+        let mut handles = lock(&self.state_table.waitables)?;
+        let handle = handles.get_mut(idx, self.kind, writable)?;
+        ensure!(
+            handle.pending.is_none() && handle.completed.is_none(),
+            "Cannot begin a copy while another is in progress."
+        );
+        Ok(handle.clone())
+    }
+
+    /// Records that a copy cannot complete yet, so that the host completes it once values are
+    /// available to read, or there is room to write them. Fails if the built-in may not return `BLOCKED`.
+    fn block(
+        self: &Arc<Self>,
+        idx: i32,
+        writable: bool,
+        async_: bool,
+        ptr: u32,
+        max: usize,
+    ) -> Result<u32> {
+        ensure!(
+            async_,
+            "Copy would block, but no other task may run to complete it."
+        );
+        let mut handles = lock(&self.state_table.waitables)?;
+        handles.get_mut(idx, self.kind, writable)?.pending = Some(PendingCopy {
+            ptr,
+            max,
+            builtin: Arc::downgrade(self),
+        });
+        handles.blocked += 1;
+        Ok(BLOCKED)
+    }
+
+    /// Gets the options with which values are copied.
+    fn copy(&self) -> Result<&CopyOptions> {
+        self.copy
+            .as_ref()
+            .context("Future or stream built-in had no canonical options.")
+    }
+
+    /// Ensures that a channel carries the type of values that this built-in expects.
+    fn check_payload(&self, channel: &Channel) -> Result<()> {
+        ensure!(
+            channel.payload == self.payload_ty,
+            "Future or stream handle was used with the wrong type."
+        );
+        Ok(())
+    }
+
+    /// Encodes the result of a copy, which includes the number of values copied for streams.
+    fn status(&self, count: usize, status: u32) -> u32 {
+        match self.kind {
+            ChannelKind::Future => status,
+            ChannelKind::Stream => ((count as u32) << 4) | status,
+        }
+    }
+}
+
+/// A canonical built-in which operates upon waitable sets or subtasks, or yields to other tasks.
+#[derive(Clone, Debug)]
+pub(crate) enum WaitableOp {
+    /// Creates a new waitable set.
+    SetNew,
+    /// Waits for an event in a waitable set, and writes it to guest memory with the given options.
+    SetWait(wasmtime_environ::component::CanonicalOptions),
+    /// Checks for an event in a waitable set, and writes it to guest memory with the given options.
+    SetPoll(wasmtime_environ::component::CanonicalOptions),
+    /// Drops a waitable set.
+    SetDrop,
+    /// Adds a waitable to a waitable set, or removes it from its set.
+    Join,
+    /// Allows other tasks to run.
+    Yield,
+    /// Drops a subtask which returned.
+    SubtaskDrop,
+    /// Cancels a subtask.
+    SubtaskCancel,
+}
+
+impl WaitableOp {
+    /// The name of this operation, as used by the canonical built-ins.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SetNew => "waitable-set-new",
+            Self::SetWait(_) => "waitable-set-wait",
+            Self::SetPoll(_) => "waitable-set-poll",
+            Self::SetDrop => "waitable-set-drop",
+            Self::Join => "waitable-join",
+            Self::Yield => "thread-yield",
+            Self::SubtaskDrop => "subtask-drop",
+            Self::SubtaskCancel => "subtask-cancel",
+        }
+    }
+}
+
+/// Implements a canonical built-in which operates upon waitable sets or subtasks, or yields to
+/// other tasks.
+pub(crate) struct WaitableBuiltin {
+    /// The operation to perform.
+    pub op: WaitableOp,
+    /// The state of the calling instance.
+    pub state_table: Arc<StateTable>,
+    /// The options with which events are written to guest memory, if the built-in writes events.
+    pub copy: Option<CopyOptions>,
+}
+
+impl WaitableBuiltin {
+    /// Performs the built-in's operation. The arguments and results follow the signatures
+    /// that the canonical ABI defines for each built-in.
+    pub fn call(
+        &self,
+        mut ctx: impl AsContextMut,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        let arg = |i: usize| -> Result<i32> {
+            Ok(require_matches!(
+                args[i],
+                wasm_runtime_layer::Value::I32(x),
+                x
+            ))
+        };
+        let waitables = &self.state_table.waitables;
+        match self.op {
+            WaitableOp::SetNew => {
+                results[0] = wasm_runtime_layer::Value::I32(lock(waitables)?.new_set());
+            }
+            WaitableOp::SetWait(_) => {
+                let within_async = within_async_call(&ctx);
+                let event = run_blocking(
+                    within_async,
+                    wait_event(ctx.as_context_mut(), waitables, arg(0)?),
+                )?;
+                self.write_event(ctx, event, arg(1)?, results)?;
+            }
+            WaitableOp::SetPoll(_) => {
+                let within_async = within_async_call(&ctx);
+                let event = run_blocking(
+                    within_async,
+                    poll_event(
+                        ctx.as_context_mut(),
+                        waitables,
+                        arg(0)?,
+                        &mut IdleYields::default(),
+                    ),
+                )?;
+                self.write_event(ctx, event, arg(1)?, results)?;
+            }
+            WaitableOp::SetDrop => lock(waitables)?.drop_set(arg(0)?)?,
+            WaitableOp::Join => lock(waitables)?.join(arg(0)?, arg(1)?)?,
+            WaitableOp::Yield => {
+                let within_async = within_async_call(&ctx);
+                run_blocking(within_async, run_subtask(ctx.as_context_mut(), waitables))?;
+                // The caller was not cancelled.
+                results[0] = wasm_runtime_layer::Value::I32(0);
+            }
+            WaitableOp::SubtaskDrop => lock(waitables)?.drop_subtask(arg(0)?)?,
+            WaitableOp::SubtaskCancel => {
+                let state = lock(waitables)?.cancel_subtask(arg(0)?)?;
+                results[0] = wasm_runtime_layer::Value::I32(state as i32);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the handle and payload of an event to guest memory at `ptr`, and returns its code.
+    fn write_event(
+        &self,
+        mut ctx: impl AsContextMut,
+        event: Event,
+        ptr: i32,
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        let copy = self
+            .copy
+            .as_ref()
+            .context("Waitable set built-in had no canonical options.")?;
+        copy.ensure_may_leave(&mut ctx)?;
+        copy.lower(
+            ctx,
+            &Type::U32,
+            ptr as u32,
+            vec![Value::U32(event.index as u32), Value::U32(event.payload)],
+        )?;
+        results[0] = wasm_runtime_layer::Value::I32(event.code);
+        Ok(())
+    }
+}
+
+/// A diagnostic value which a component may attach to an error, such as the error case of a
+/// `result`. Error contexts are immutable and cheaply cloned, and their debug messages may be
+/// read by the host. The host may also create error contexts to pass to guests.
+#[derive(Clone, Debug)]
+pub struct ErrorContext(Arc<str>);
+
+impl ErrorContext {
+    /// Creates a new error context with the given debug message.
+    pub fn new(debug_message: impl Into<Arc<str>>) -> Self {
+        Self(debug_message.into())
+    }
+
+    /// Gets the debug message of this error context.
+    pub fn debug_message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for ErrorContext {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ErrorContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> Deserialize<'a> for ErrorContext {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        Box::<str>::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// The error contexts held by an instance, indexed by the handles that its guests use.
+#[derive(Debug, Default)]
+pub(crate) struct ErrorContextTable(Slab<ErrorContext>);
+
+impl ErrorContextTable {
+    /// Adds an error context to the table, returning its handle.
+    pub fn add(&mut self, context: ErrorContext) -> i32 {
+        self.0.insert(context) as i32 + 1
+    }
+
+    /// Gets the error context with the given handle.
+    pub fn get(&self, idx: i32) -> Result<&ErrorContext> {
+        (idx as usize)
+            .checked_sub(1)
+            .and_then(|x| self.0.get(x))
+            .context("Invalid error context handle.")
+    }
+
+    /// Removes the error context with the given handle.
+    pub fn remove(&mut self, idx: i32) -> Result<ErrorContext> {
+        self.get(idx)?;
+        Ok(self.0.remove(idx as usize - 1))
+    }
+
+    /// Removes all error contexts from the table.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// A canonical built-in which operates upon error contexts.
+#[derive(Clone, Debug)]
+pub(crate) enum ErrorContextOp {
+    /// Creates an error context from a string in guest memory, with the given options.
+    New(wasmtime_environ::component::CanonicalOptions),
+    /// Writes the debug message of an error context to guest memory, with the given options.
+    DebugMessage(wasmtime_environ::component::CanonicalOptions),
+    /// Drops an error context handle.
+    Drop,
+}
+
+impl ErrorContextOp {
+    /// The name of this operation, as used by the canonical built-ins.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::New(_) => "error-context-new",
+            Self::DebugMessage(_) => "error-context-debug-message",
+            Self::Drop => "error-context-drop",
+        }
+    }
+}
+
+/// Implements a canonical built-in which creates, reads, or drops error contexts.
+pub(crate) struct ErrorContextBuiltin {
+    /// The operation to perform.
+    pub op: ErrorContextOp,
+    /// The state of the calling instance.
+    pub state_table: Arc<StateTable>,
+    /// The options with which strings are copied to and from guest memory, if the built-in copies strings.
+    pub copy: Option<CopyOptions>,
+}
+
+impl ErrorContextBuiltin {
+    /// Performs the built-in's operation. The arguments and results follow the signatures
+    /// that the canonical ABI defines for each built-in.
+    pub fn call(
+        &self,
+        mut ctx: impl AsContextMut,
+        args: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        match self.op {
+            ErrorContextOp::New(_) => {
+                let copy = self.copy()?;
+                copy.ensure_may_leave(&mut ctx)?;
+                let ptr = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+                let len = require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x);
+                let message = require_matches!(
+                    copy.lift_flat(
+                        &mut ctx,
+                        &Type::String,
+                        vec![Value::S32(ptr), Value::S32(len)]
+                    )?,
+                    Value::String(x),
+                    x
+                );
+                results[0] =
+                    wasm_runtime_layer::Value::I32(self.table()?.add(ErrorContext(message)));
+            }
+            ErrorContextOp::DebugMessage(_) => {
+                let copy = self.copy()?;
+                copy.ensure_may_leave(&mut ctx)?;
+                let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+                let ptr = require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x);
+                let message = self.table()?.get(idx)?.0.clone();
+                copy.lower(ctx, &Type::String, ptr as u32, vec![Value::String(message)])?;
+            }
+            ErrorContextOp::Drop => {
+                let idx = require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+                self.table()?.remove(idx)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the options with which strings are copied.
+    fn copy(&self) -> Result<&CopyOptions> {
+        self.copy
+            .as_ref()
+            .context("Error context built-in had no canonical options.")
+    }
+
+    /// Acquires the error context table of the calling instance.
+    fn table(&self) -> Result<MutexGuard<'_, ErrorContextTable>> {
+        lock_error_contexts(&self.state_table.error_contexts)
+    }
+}
+
+/// Acquires a table of error contexts.
+pub(crate) fn lock_error_contexts(
+    table: &Mutex<ErrorContextTable>,
+) -> Result<MutexGuard<'_, ErrorContextTable>> {
+    table
+        .try_lock()
+        .map_err(|_| crate::Error::resource("Could not lock error context table.").into())
+}
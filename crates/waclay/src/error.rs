@@ -0,0 +1,83 @@
+use crate::types::ValueType;
+use crate::LinkReport;
+
+/// Describes why an operation on a component, instance, function, or resource failed.
+///
+/// Fallible methods of this crate return [`anyhow::Error`], so that host functions may propagate
+/// errors of any kind. The failures below are raised as a root [`Error`], which may be recovered
+/// with [`anyhow::Error::downcast_ref`] and matched upon:
+///
+/// ```
+/// fn describe(err: &anyhow::Error) {
+///     match err.downcast_ref::<waclay::Error>() {
+///         Some(waclay::Error::Trap(trap)) => println!("Guest trapped: {trap}"),
+///         Some(waclay::Error::StoreMismatch) => println!("Wrong store"),
+///         _ => println!("Other error: {err}"),
+///     }
+/// }
+/// ```
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A guest function failed while executing, either because it trapped or because a function
+    /// that it imported returned an error. The inner error holds the [`crate::FuncError`] for the
+    /// guest function, whose source is the trap reported by the backend runtime or the host error.
+    Trap(anyhow::Error),
+    /// A value passed to or returned from a function was not a subtype of the expected type.
+    TypeMismatch {
+        /// The type that the function signature required.
+        expected: ValueType,
+        /// The type of the value that was provided.
+        actual: ValueType,
+    },
+    /// A function, instance, or resource was used with a store other than the one that created it.
+    StoreMismatch,
+    /// A function was called after its instance had been dropped.
+    InstanceDropped,
+    /// A resource handle was used incorrectly, such as after it was dropped, while it was borrowed,
+    /// or as a host value of the wrong type.
+    ResourceError(String),
+    /// A component could not be instantiated, because the linker was missing some of its imports
+    /// or defined them with incompatible types.
+    LinkError(LinkReport),
+    /// The bytes provided did not describe a valid component.
+    DecodeError(anyhow::Error),
+}
+
+impl Error {
+    /// Creates a resource error with the given message.
+    pub(crate) fn resource(message: impl Into<String>) -> Self {
+        Self::ResourceError(message.into())
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Trap(error) | Self::DecodeError(error) => std::fmt::Display::fmt(error, f),
+            Self::TypeMismatch { expected, actual } => {
+                write!(f, "Expected value of type {expected} but got {actual}.")
+            }
+            Self::StoreMismatch => f.write_str("Incorrect store."),
+            Self::InstanceDropped => f.write_str("Instance had been dropped."),
+            Self::ResourceError(message) => f.write_str(message),
+            Self::LinkError(report) => std::fmt::Display::fmt(report, f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Trap(error) | Self::DecodeError(error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<LinkReport> for Error {
+    fn from(value: LinkReport) -> Self {
+        Self::LinkError(value)
+    }
+}
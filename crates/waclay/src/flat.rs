@@ -0,0 +1,570 @@
+use std::mem::*;
+
+use anyhow::*;
+use bytemuck::*;
+use wasm_runtime_layer::Memory;
+
+use crate::func::InstanceFlags;
+use crate::require_matches::require_matches;
+use crate::{AsContextMut, ComponentType, LiftLimits};
+
+/// Describes how values of a type are represented by the canonical ABI, both as a sequence
+/// of core WebAssembly values and in guest memory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlatLayout {
+    /// The number of core values in the flattened representation.
+    flat_count: usize,
+    /// The size of the type in guest memory, in bytes.
+    size: usize,
+    /// The alignment of the type in guest memory, in bytes.
+    align: usize,
+}
+
+impl FlatLayout {
+    /// Creates a new layout from its number of core values, size, and alignment.
+    pub const fn new(flat_count: usize, size: usize, align: usize) -> Self {
+        Self {
+            flat_count,
+            size,
+            align,
+        }
+    }
+
+    /// Computes the layout of a record with the given fields, or `None` if any of the fields
+    /// cannot be converted directly.
+    pub const fn record(fields: &[Option<FlatLayout>]) -> Option<FlatLayout> {
+        let mut result = Self::new(0, 0, 1);
+        let mut i = 0;
+        while i < fields.len() {
+            let Some(field) = fields[i] else {
+                return None;
+            };
+
+            result.flat_count += field.flat_count;
+            result.size = align_to(result.size, field.align) + field.size;
+            if field.align > result.align {
+                result.align = field.align;
+            }
+            i += 1;
+        }
+
+        result.size = align_to(result.size, result.align);
+        Some(result)
+    }
+
+    /// Computes the layout of a fixed-size list with `len` elements of the given layout, or
+    /// `None` if the elements cannot be converted directly.
+    pub const fn array(element: Option<FlatLayout>, len: usize) -> Option<FlatLayout> {
+        let Some(element) = element else {
+            return None;
+        };
+
+        Some(Self::new(
+            element.flat_count * len,
+            element.size * len,
+            element.align,
+        ))
+    }
+
+    /// The number of core values in the flattened representation.
+    pub const fn flat_count(&self) -> usize {
+        self.flat_count
+    }
+
+    /// The size of the type in guest memory, in bytes.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The alignment of the type in guest memory, in bytes.
+    pub const fn align(&self) -> usize {
+        self.align
+    }
+}
+
+/// Rounds an offset up to the next multiple of the given alignment.
+const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// Gets the layout of a type, failing if it cannot be converted directly.
+pub(crate) fn layout_of<T: ComponentType>() -> Result<FlatLayout> {
+    T::LAYOUT.context("Type cannot be converted directly to core values.")
+}
+
+/// Computes the offsets of the consecutive fields of a record in guest memory.
+#[derive(Copy, Clone, Debug)]
+pub struct FlatFields {
+    /// The offset at which the next field may begin.
+    offset: usize,
+}
+
+impl FlatFields {
+    /// Begins laying out a record at the given offset, which must be aligned for the record.
+    pub fn new(offset: usize) -> Self {
+        Self { offset }
+    }
+
+    /// Gets the offset of the next field, which is of type `T`.
+    pub fn field<T: ComponentType>(&mut self) -> Result<usize> {
+        Ok(self.next(layout_of::<T>()?))
+    }
+
+    /// Gets the offset of the next field, which has the given layout.
+    pub(crate) fn next(&mut self, layout: FlatLayout) -> usize {
+        let field = align_to(self.offset, layout.align);
+        self.offset = field + layout.size;
+        field
+    }
+}
+
+/// Converts Rust values directly to and from their canonical ABI representation when calling
+/// a guest function, providing access to guest memory and to the core values of the call.
+pub struct FlatContext<'a, C: AsContextMut> {
+    /// The store context.
+    ctx: C,
+    /// The guest's memory.
+    memory: Option<&'a Memory>,
+    /// The guest's reallocation function.
+    realloc: Option<&'a wasm_runtime_layer::Func>,
+    /// The flags of the guest's component instance.
+    flags: &'a InstanceFlags,
+    /// The limits on values lifted from guest memory.
+    lift_limits: LiftLimits,
+    /// The number of bytes lifted from guest memory so far.
+    lifted_bytes: usize,
+    /// The flattened core values that are being lowered or lifted.
+    values: Vec<wasm_runtime_layer::Value>,
+    /// The index of the next core value to lift.
+    next: usize,
+}
+
+impl<'a, C: AsContextMut> FlatContext<'a, C> {
+    /// Creates a new context, which lifts from the given core values and lowers after them.
+    pub(crate) fn new(
+        ctx: C,
+        memory: Option<&'a Memory>,
+        realloc: Option<&'a wasm_runtime_layer::Func>,
+        flags: &'a InstanceFlags,
+        values: Vec<wasm_runtime_layer::Value>,
+    ) -> Self {
+        let lift_limits = ctx.as_context().inner.data().lift_limits;
+        Self {
+            ctx,
+            memory,
+            realloc,
+            flags,
+            lift_limits,
+            lifted_bytes: 0,
+            values,
+            next: 0,
+        }
+    }
+
+    /// Gets the store context.
+    pub(crate) fn ctx_mut(&mut self) -> &mut C {
+        &mut self.ctx
+    }
+
+    /// Restarts lifting from the first core value.
+    pub(crate) fn rewind(&mut self) {
+        self.next = 0;
+    }
+
+    /// Lowers core values with the given function and moves them into `results`, leaving the
+    /// values that are being lifted untouched.
+    pub(crate) fn lower_into(
+        &mut self,
+        results: &mut [wasm_runtime_layer::Value],
+        lower: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<()> {
+        let values = replace(&mut self.values, Vec::with_capacity(results.len()));
+        let lowered = lower(self);
+        let lowered_values = replace(&mut self.values, values);
+        lowered?;
+
+        ensure!(
+            lowered_values.len() == results.len(),
+            "Incorrect number of core values."
+        );
+        for (result, value) in results.iter_mut().zip(lowered_values) {
+            *result = value;
+        }
+        Ok(())
+    }
+
+    /// Calls a core function with the lowered values, replacing them with its results so
+    /// that they may be lifted.
+    pub(crate) fn call(&mut self, callee: &wasm_runtime_layer::Func, results: usize) -> Result<()> {
+        let mut flat_results = vec![wasm_runtime_layer::Value::I32(0); results];
+        callee.call(
+            &mut self.ctx.as_context_mut().inner,
+            &self.values,
+            &mut flat_results,
+        )?;
+        self.values = flat_results;
+        self.next = 0;
+        Ok(())
+    }
+
+    /// Calls a post-return function with the core values returned by the callee.
+    pub(crate) fn post_return(&mut self, post_return: &wasm_runtime_layer::Func) -> Result<()> {
+        self.flags
+            .call_without_leave(&mut self.ctx, post_return, &self.values, &mut [])
+    }
+
+    /// Appends a lowered core value.
+    fn push(&mut self, value: wasm_runtime_layer::Value) {
+        self.values.push(value);
+    }
+
+    /// Takes the next core value to lift.
+    fn pop(&mut self) -> Result<&wasm_runtime_layer::Value> {
+        let value = self
+            .values
+            .get(self.next)
+            .context("Incorrect number of core values.")?;
+        self.next += 1;
+        Ok(value)
+    }
+
+    /// Takes the next core value to lift as a guest pointer or length.
+    pub(crate) fn pop_u32(&mut self) -> Result<usize> {
+        Ok(require_matches!(self.pop()?, wasm_runtime_layer::Value::I32(x), *x) as u32 as usize)
+    }
+
+    /// Gets the guest's memory.
+    fn memory(&self) -> Result<&'a Memory> {
+        self.memory.context("Guest did not export a memory.")
+    }
+
+    /// Reads bytes from guest memory at the given offset.
+    fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<()> {
+        self.memory()?
+            .read(self.ctx.as_context().inner, offset, buffer)
+    }
+
+    /// Writes bytes to guest memory at the given offset.
+    fn write(&mut self, offset: usize, buffer: &[u8]) -> Result<()> {
+        let memory = self.memory()?;
+        memory.write(self.ctx.as_context_mut().inner, offset, buffer)
+    }
+
+    /// Allocates `size` bytes of guest memory with the given alignment.
+    fn allocate(&mut self, align: usize, size: usize) -> Result<usize> {
+        ensure!(
+            size <= u32::MAX as usize,
+            "Allocation of {size} bytes was too large for guest memory."
+        );
+
+        let args = [
+            wasm_runtime_layer::Value::I32(0),
+            wasm_runtime_layer::Value::I32(0),
+            wasm_runtime_layer::Value::I32(align as i32),
+            wasm_runtime_layer::Value::I32(size as i32),
+        ];
+        let mut res = [wasm_runtime_layer::Value::I32(0)];
+        let realloc = self
+            .realloc
+            .context("Guest did not export a reallocation function.")?;
+        self.flags
+            .call_without_leave(&mut self.ctx, realloc, &args, &mut res)?;
+
+        let ptr = require_matches!(res[0], wasm_runtime_layer::Value::I32(x), x) as u32 as usize;
+        ensure!(
+            ptr.is_multiple_of(align),
+            "Reallocated pointer was not aligned."
+        );
+        Ok(ptr)
+    }
+
+    /// Ensures that a range of `size` bytes at `ptr` is aligned and lies within guest memory.
+    pub(crate) fn validate_range(&self, ptr: usize, size: usize, align: usize) -> Result<()> {
+        crate::func::validate_range(&self.ctx, self.memory()?, ptr, size, align)
+    }
+
+    /// Lowers a UTF-8 string into newly-allocated guest memory.
+    pub(crate) fn lower_string(&mut self, value: &str) -> Result<()> {
+        ensure!(
+            value.len() <= crate::transcode::MAX_STRING_BYTE_LENGTH,
+            "String was too long to lower."
+        );
+
+        let ptr = self.allocate(1, value.len())?;
+        self.write(ptr, value.as_bytes())?;
+        self.push(wasm_runtime_layer::Value::I32(ptr as i32));
+        self.push(wasm_runtime_layer::Value::I32(value.len() as i32));
+        Ok(())
+    }
+
+    /// Stores a UTF-8 string in newly-allocated guest memory, writing its pointer and length at `offset`.
+    pub(crate) fn store_string(&mut self, value: &str, offset: usize) -> Result<()> {
+        ensure!(
+            value.len() <= crate::transcode::MAX_STRING_BYTE_LENGTH,
+            "String was too long to lower."
+        );
+
+        let ptr = self.allocate(1, value.len())?;
+        self.write(ptr, value.as_bytes())?;
+        self.write(offset, &(ptr as u32).to_le_bytes())?;
+        self.write(offset + 4, &(value.len() as u32).to_le_bytes())
+    }
+
+    /// Lifts a UTF-8 string from guest memory, after validating it against the lift limits.
+    fn lift_string(&mut self, ptr: usize, len: usize) -> Result<String> {
+        self.lift_limits.check_string(len)?;
+        self.validate_range(ptr, len, 1)?;
+        self.lift_limits.account(&mut self.lifted_bytes, len)?;
+
+        let mut bytes = vec![0; len];
+        self.read(ptr, &mut bytes)?;
+        String::from_utf8(bytes).context("Invalid UTF-8 string.")
+    }
+
+    /// Lowers a list into newly-allocated guest memory.
+    pub(crate) fn lower_list<T: ComponentType>(&mut self, values: &[T]) -> Result<()> {
+        let ptr = self.allocate_list(values)?;
+        self.push(wasm_runtime_layer::Value::I32(ptr as i32));
+        self.push(wasm_runtime_layer::Value::I32(values.len() as i32));
+        Ok(())
+    }
+
+    /// Stores a list in newly-allocated guest memory, writing its pointer and length at `offset`.
+    pub(crate) fn store_list<T: ComponentType>(
+        &mut self,
+        values: &[T],
+        offset: usize,
+    ) -> Result<()> {
+        let ptr = self.allocate_list(values)?;
+        self.write(offset, &(ptr as u32).to_le_bytes())?;
+        self.write(offset + 4, &(values.len() as u32).to_le_bytes())
+    }
+
+    /// Allocates guest memory for a list and stores its elements, returning the list's pointer.
+    fn allocate_list<T: ComponentType>(&mut self, values: &[T]) -> Result<usize> {
+        let layout = layout_of::<T>()?;
+        let size = values
+            .len()
+            .checked_mul(layout.size)
+            .context("List was too large to lower.")?;
+        let ptr = self.allocate(layout.align, size)?;
+        T::store_flat_list(values, self, ptr)?;
+        Ok(ptr)
+    }
+
+    /// Lifts a list from guest memory, after validating it against the lift limits.
+    pub(crate) fn lift_list<T: ComponentType>(&mut self, ptr: usize, len: usize) -> Result<Vec<T>> {
+        let layout = layout_of::<T>()?;
+        self.lift_limits.check_list(len)?;
+        let byte_len = len
+            .checked_mul(layout.size)
+            .context("List byte length overflowed.")?;
+        self.validate_range(ptr, byte_len, layout.align)?;
+        self.lift_limits.account(&mut self.lifted_bytes, byte_len)?;
+        T::load_flat_list(self, ptr, len)
+    }
+
+    /// Reads a pointer and length pair from guest memory at the given offset.
+    pub(crate) fn load_pointer_pair(&self, offset: usize) -> Result<(usize, usize)> {
+        let mut bytes = [0; 8];
+        self.read(offset, &mut bytes)?;
+        let [ptr, len] = cast::<_, [[u8; 4]; 2]>(bytes).map(u32::from_le_bytes);
+        std::result::Result::Ok((ptr as usize, len as usize))
+    }
+}
+
+/// A primitive type whose values occupy exactly one core value.
+pub(crate) trait FlatPrimitive: ComponentType + Copy {
+    /// The little-endian representation of this type in guest memory.
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
+
+    /// Converts this value to a core value.
+    fn to_raw(self) -> wasm_runtime_layer::Value;
+
+    /// Converts a core value to this type.
+    fn from_raw(value: &wasm_runtime_layer::Value) -> Result<Self>;
+
+    /// Converts this value to its representation in guest memory.
+    fn to_bytes(self) -> Self::Bytes;
+
+    /// Converts a representation from guest memory to this type.
+    fn from_bytes(bytes: Self::Bytes) -> Result<Self>;
+
+    /// Appends this value as a core value.
+    fn lower<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        cx.push(self.to_raw());
+        Ok(())
+    }
+
+    /// Stores this value in guest memory at the given offset.
+    fn store<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {
+        cx.write(offset, self.to_bytes().as_ref())
+    }
+
+    /// Lifts a value from the next core value.
+    fn lift<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        Self::from_raw(cx.pop()?)
+    }
+
+    /// Loads a value from guest memory at the given offset.
+    fn load<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        let mut bytes = Self::Bytes::default();
+        cx.read(offset, bytes.as_mut())?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Stores consecutive values in guest memory at the given offset.
+    fn store_list<C: AsContextMut>(
+        values: &[Self],
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        for (i, value) in values.iter().enumerate() {
+            value.store(cx, offset + i * size_of::<Self>())?;
+        }
+        Ok(())
+    }
+
+    /// Loads consecutive values from guest memory at the given offset.
+    fn load_list<C: AsContextMut>(
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<Self>> {
+        (0..len)
+            .map(|i| Self::load(cx, offset + i * size_of::<Self>()))
+            .collect()
+    }
+}
+
+impl FlatPrimitive for bool {
+    type Bytes = [u8; 1];
+
+    fn to_raw(self) -> wasm_runtime_layer::Value {
+        wasm_runtime_layer::Value::I32(self as i32)
+    }
+
+    fn from_raw(value: &wasm_runtime_layer::Value) -> Result<Self> {
+        Ok(require_matches!(
+            value,
+            wasm_runtime_layer::Value::I32(x),
+            *x != 0
+        ))
+    }
+
+    fn to_bytes(self) -> Self::Bytes {
+        [self as u8]
+    }
+
+    fn from_bytes(bytes: Self::Bytes) -> Result<Self> {
+        Ok(bytes[0] != 0)
+    }
+}
+
+impl FlatPrimitive for char {
+    type Bytes = [u8; 4];
+
+    fn to_raw(self) -> wasm_runtime_layer::Value {
+        wasm_runtime_layer::Value::I32(self as i32)
+    }
+
+    fn from_raw(value: &wasm_runtime_layer::Value) -> Result<Self> {
+        let x = require_matches!(value, wasm_runtime_layer::Value::I32(x), *x);
+        char::from_u32(x as u32)
+            .with_context(|| format!("Value {:#x} was not a valid Unicode scalar value.", x))
+    }
+
+    fn to_bytes(self) -> Self::Bytes {
+        (self as u32).to_le_bytes()
+    }
+
+    fn from_bytes(bytes: Self::Bytes) -> Result<Self> {
+        Self::from_raw(&wasm_runtime_layer::Value::I32(i32::from_le_bytes(bytes)))
+    }
+}
+
+/// Implements `FlatPrimitive` for numeric types, which may be copied to and from guest memory
+/// in bulk on little-endian hosts.
+macro_rules! impl_flat_number {
+    ($(($type_name: ident, $raw: ident, $raw_type: ident))*) => {
+        $(
+            #[allow(clippy::unnecessary_cast)]
+            impl FlatPrimitive for $type_name {
+                type Bytes = [u8; size_of::<$type_name>()];
+
+                fn to_raw(self) -> wasm_runtime_layer::Value {
+                    wasm_runtime_layer::Value::$raw(self as $raw_type)
+                }
+
+                fn from_raw(value: &wasm_runtime_layer::Value) -> Result<Self> {
+                    Ok(require_matches!(value, wasm_runtime_layer::Value::$raw(x), *x as $type_name))
+                }
+
+                fn to_bytes(self) -> Self::Bytes {
+                    self.to_le_bytes()
+                }
+
+                fn from_bytes(bytes: Self::Bytes) -> Result<Self> {
+                    Ok(Self::from_le_bytes(bytes))
+                }
+
+                fn store_list<C: AsContextMut>(values: &[Self], cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {
+                    if cfg!(target_endian = "little") {
+                        cx.write(offset, cast_slice(values))
+                    } else {
+                        for (i, value) in values.iter().enumerate() {
+                            value.store(cx, offset + i * size_of::<Self>())?;
+                        }
+                        Ok(())
+                    }
+                }
+
+                fn load_list<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize, len: usize) -> Result<Vec<Self>> {
+                    if cfg!(target_endian = "little") {
+                        let mut values = vec![0 as $type_name; len];
+                        cx.read(offset, cast_slice_mut(&mut values))?;
+                        Ok(values)
+                    } else {
+                        (0..len).map(|i| Self::load(cx, offset + i * size_of::<Self>())).collect()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_flat_number!((i8, I32, i32)(u8, I32, i32)(i16, I32, i32)(u16, I32, i32)(
+    i32, I32, i32
+)(u32, I32, i32)(i64, I64, i64)(u64, I64, i64)(f32, F32, f32)(
+    f64, F64, f64
+));
+
+/// Implements the flat conversions of `ComponentType` for a string type.
+pub(crate) trait FlatString: ComponentType + AsRef<str> + From<String> {
+    /// Lowers this string into newly-allocated guest memory.
+    fn lower<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        cx.lower_string(self.as_ref())
+    }
+
+    /// Stores this string in newly-allocated guest memory, writing its pointer and length at `offset`.
+    fn store<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {
+        cx.store_string(self.as_ref(), offset)
+    }
+
+    /// Lifts a string from the next core values.
+    fn lift<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        let ptr = cx.pop_u32()?;
+        let len = cx.pop_u32()?;
+        cx.lift_string(ptr, len).map(Self::from)
+    }
+
+    /// Loads a string whose pointer and length are at the given offset in guest memory.
+    fn load<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        let (ptr, len) = cx.load_pointer_pair(offset)?;
+        cx.lift_string(ptr, len).map(Self::from)
+    }
+}
+
+impl FlatString for String {}
+impl FlatString for Box<str> {}
+impl FlatString for std::sync::Arc<str> {}
@@ -1,8 +1,12 @@
+use std::future::*;
 use std::marker::*;
 use std::mem::*;
+use std::pin::*;
 use std::sync::atomic::*;
 use std::sync::*;
+use std::task::{Poll, Wake, Waker};
 
+use anyhow::Error;
 use bytemuck::*;
 use wasm_runtime_layer::*;
 #[allow(unused_imports)]
@@ -14,6 +18,9 @@ use crate::types::{FuncType, ValueType};
 use crate::values::Value;
 use crate::{AsContext, AsContextMut, StoreContextMut, *};
 
+/// The size of a page of guest memory, in bytes.
+const WASM_PAGE_SIZE: usize = 65536;
+
 /// Stores the backing implementation for a function.
 #[derive(Clone, Debug)]
 pub(crate) enum FuncImpl {
@@ -48,6 +55,221 @@ pub(crate) struct GuestFunc {
     pub instance_id: u64,
     /// The ID of the interface associated with this function.
     pub interface_id: Option<InterfaceIdentifier>,
+    /// The compiled instructions for calling this function.
+    pub plan: CachedPlan,
+    /// The flags of the component instance which lifted this function.
+    pub flags: InstanceFlags,
+    /// The callback which drives this function, if it is lifted with the async ABI.
+    pub callback: Option<wasm_runtime_layer::Func>,
+    /// Whether this function is lifted with the async ABI.
+    pub is_async: bool,
+}
+
+impl GuestFunc {
+    /// Gets the ABI variant with which this function is lifted.
+    fn variant(&self) -> AbiVariant {
+        match (self.is_async, self.callback.is_some()) {
+            (false, _) => AbiVariant::GuestExport,
+            (true, true) => AbiVariant::GuestExportAsync,
+            (true, false) => AbiVariant::GuestExportAsyncStackful,
+        }
+    }
+
+    /// Creates the options with which `task.return` lifts the results of this function, if it
+    /// is lifted with the async ABI.
+    fn copy_options(&self, store_id: u64) -> CopyOptions {
+        CopyOptions {
+            component: self.component.clone(),
+            encoding: self.encoding,
+            memory: self.memory.clone(),
+            realloc: self.realloc.clone(),
+            state_table: self.state_table.clone(),
+            types: self.types.clone(),
+            instance_id: self.instance_id,
+            store_id,
+            flags: self.flags.clone(),
+        }
+    }
+
+    /// Reports an error raised while calling this function as a trap, unless it
+    /// describes misuse of the API.
+    fn call_error(&self, instance: &crate::Instance, error: Error) -> Error {
+        match error.downcast_ref::<crate::Error>() {
+            // Errors that describe misuse of the API are reported directly, rather than as traps
+            Some(x) if !matches!(x, crate::Error::Trap(_)) => error,
+            _ => crate::Error::Trap(
+                FuncError {
+                    name: self.function.name.clone(),
+                    interface: self.interface_id.clone(),
+                    instance: instance.clone(),
+                    error,
+                }
+                .into(),
+            )
+            .into(),
+        }
+    }
+
+    /// Lowers the parameters, calls the core function, and lifts its results, converting
+    /// them directly to and from core values.
+    fn call_flat<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        flat: FlatCall,
+        params: &P,
+    ) -> Result<R> {
+        params.lower_flats(cx)?;
+        cx.call(&self.callee, flat.results)?;
+
+        let results = if flat.retptr {
+            let ptr = cx.pop_u32()?;
+            let layout =
+                R::RECORD_LAYOUT.context("Results could not be loaded from guest memory.")?;
+            cx.validate_range(ptr, layout.size(), layout.align())?;
+            R::load_flats(cx, ptr)?
+        } else {
+            R::lift_flats(cx)?
+        };
+
+        if let Some(post) = &self.post_return {
+            cx.post_return(post)?;
+        }
+
+        Ok(results)
+    }
+}
+
+/// A call to a guest function lifted with the async ABI, whose core function returned.
+struct StartedTask {
+    /// The callback code which the core function returned, or `None` if it has no callback.
+    code: Option<i32>,
+    /// The handles to drop once the task ends.
+    handles_to_drop: Vec<(u32, i32)>,
+    /// The handles to require dropped once the task ends.
+    required_dropped: Vec<(bool, u32, i32, Arc<AtomicBool>)>,
+}
+
+impl StartedTask {
+    /// Calls the callback of the task until it exits.
+    async fn drive<T: 'static, E: backend::WasmEngine>(
+        &self,
+        ctx: StoreContextMut<'_, T, E>,
+        guest: &GuestFunc,
+    ) -> Result<()> {
+        let (Some(code), Some(callback)) = (self.code, &guest.callback) else {
+            return Ok(());
+        };
+        crate::concurrent::drive_task(ctx, callback, &guest.state_table, code).await
+    }
+
+    /// Ends the task after its callback exited with the given result, releasing the handles
+    /// that the call lent, and writes the results that the task passed to `task.return`.
+    fn finish(
+        self,
+        mut ctx: impl AsContextMut,
+        guest: &GuestFunc,
+        result: Result<()>,
+        results: &mut [Value],
+    ) -> Result<()> {
+        let returned = crate::concurrent::end_task(&guest.state_table.task);
+        let result = result.and_then(|()| {
+            release_handles(
+                &guest.state_table.resource_tables,
+                &self.handles_to_drop,
+                &self.required_dropped,
+            )?;
+            let values = returned?.context("Async export exited without calling `task.return`.")?;
+            ensure!(
+                values.len() == results.len(),
+                "Unexpected number of output arguments."
+            );
+            for (result, value) in results.iter_mut().zip(values) {
+                *result = value;
+            }
+            Ok(())
+        });
+        guest.flags.exit(&mut ctx).and(result)
+    }
+
+    /// Abandons the task because the future which drove it was dropped, so that the instance
+    /// may be entered again. Errors are ignored, since no caller remains to receive them.
+    fn abandon(self, mut ctx: impl AsContextMut, guest: &GuestFunc) {
+        let _ = crate::concurrent::end_task(&guest.state_table.task);
+        let _ = crate::concurrent::abandon_subtasks(&guest.state_table.waitables);
+        let _ = release_handles(
+            &guest.state_table.resource_tables,
+            &self.handles_to_drop,
+            &self.required_dropped,
+        );
+        let _ = guest.flags.exit(&mut ctx);
+    }
+}
+
+/// Records a call which the host started from [`Func::call_async`] in the store, and restores
+/// the store once the call completes or its future is dropped.
+struct AsyncCallGuard<'a, T: 'static, E: backend::WasmEngine> {
+    /// The store in which the call runs.
+    ctx: StoreContextMut<'a, T, E>,
+    /// The number of callers which were recorded before the call started.
+    depth: usize,
+    /// The guest task which the call started, until it is finished.
+    task: Option<(Arc<GuestFunc>, StartedTask)>,
+}
+
+impl<'a, T: 'static, E: backend::WasmEngine> AsyncCallGuard<'a, T, E> {
+    /// Records that the host started a call from [`Func::call_async`].
+    fn new(mut ctx: StoreContextMut<'a, T, E>) -> Self {
+        let data = ctx.inner.data_mut();
+        let depth = data.callers.len();
+        data.callers.push(None);
+        data.async_calls += 1;
+        Self {
+            ctx,
+            depth,
+            task: None,
+        }
+    }
+
+    /// Calls the callback of the started task until it exits.
+    async fn drive_task(&mut self) -> Result<()> {
+        match &self.task {
+            Some((guest, task)) => task.drive(self.ctx.as_context_mut(), guest).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes the started task after its callback exited with the given result.
+    fn finish_task(&mut self, result: Result<()>, results: &mut [Value]) -> Result<()> {
+        match self.task.take() {
+            Some((guest, task)) => task.finish(&mut self.ctx, &guest, result, results),
+            None => result,
+        }
+    }
+}
+
+impl<T: 'static, E: backend::WasmEngine> Drop for AsyncCallGuard<'_, T, E> {
+    fn drop(&mut self) {
+        if let Some((guest, task)) = self.task.take() {
+            task.abandon(&mut self.ctx, &guest);
+        }
+
+        // Host functions which were suspended when the future was dropped never removed their callers
+        let data = self.ctx.inner.data_mut();
+        data.callers.truncate(self.depth);
+        data.async_calls -= 1;
+    }
+}
+
+/// Describes how a [`TypedFunc`] or borrowing host function passes its parameters and
+/// results directly to and from the core function on the other side of the call.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct FlatCall {
+    /// The number of core values returned by the core function.
+    results: usize,
+    /// Whether the results are returned through a pointer into guest memory.
+    retptr: bool,
+    /// Whether the parameters are passed through a pointer into guest memory.
+    indirect_params: bool,
 }
 
 /// A component model function that may be invoked to interact with an `Instance`.
@@ -73,7 +295,7 @@ impl Func {
     ) -> Self {
         let mut ctx_mut = ctx.as_context_mut();
         let data = ctx_mut.inner.data_mut();
-        let idx = data.host_functions.push(f);
+        let idx = data.host_functions.push(HostFunction::Sync(Arc::new(f)));
 
         Self {
             store_id: data.id,
@@ -82,22 +304,159 @@ impl Func {
         }
     }
 
+    /// Creates a new function from a closure whose parameters and results are Rust types.
+    /// The component model type of the function is derived from `P` and `R`, and values
+    /// are converted to and from them automatically.
+    pub fn wrap<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        ctx: C,
+        f: impl 'static + Send + Sync + Fn(StoreContextMut<C::UserState, C::Engine>, P) -> Result<R>,
+    ) -> Self {
+        TypedFunc::new(ctx, f).inner
+    }
+
+    /// Creates a new function with the provided type, whose implementation returns a future.
+    /// The future may hold onto the store context until it completes.
+    ///
+    /// When the function is called with [`Func::call_async`], the future is awaited. A guest that
+    /// imports the function with the async ABI receives a subtask, and the future is awaited when
+    /// the guest's async export waits or yields within [`Func::call_async`].
+    ///
+    /// Only guests that import the function with the async ABI can be suspended. Backends cannot
+    /// suspend synchronous guest code, so when a guest imports the function synchronously, or the
+    /// host calls it with [`Func::call`]:
+    ///
+    /// - Within [`Func::call_async`], the call fails unless the future is ready when first polled.
+    /// - Otherwise, the calling thread is parked until the future completes. This deadlocks if the
+    ///   future needs the parked thread to make progress, as on a single-threaded Tokio runtime.
+    pub fn new_async<C: AsContextMut>(
+        mut ctx: C,
+        ty: FuncType,
+        f: impl 'static
+            + Send
+            + Sync
+            + for<'a> Fn(
+                StoreContextMut<'a, C::UserState, C::Engine>,
+                &'a [Value],
+                &'a mut [Value],
+            ) -> HostFuture<'a, ()>,
+    ) -> Self {
+        let mut ctx_mut = ctx.as_context_mut();
+        let data = ctx_mut.inner.data_mut();
+        let idx = data.host_functions.push(HostFunction::Async(Arc::new(f)));
+
+        Self {
+            store_id: data.id,
+            ty,
+            backing: FuncImpl::HostFunc(idx),
+        }
+    }
+
+    /// Creates a new function with the provided type, whose implementation reads its parameters
+    /// and writes its results through a [`HostCall`].
+    ///
+    /// When a guest calls the function and all of its parameters and results are primitives,
+    /// strings, or `list<u8>`, strings and byte lists are copied once per call from guest memory
+    /// into buffers that the implementation borrows, and byte and string results are written into
+    /// memory allocated with the guest's `realloc`, without building intermediate [`Value`]s.
+    /// Parameters cannot borrow guest memory itself, because the runtime backend only exposes
+    /// guest memory through copies.
+    /// Otherwise, including when the function is called from the host, [`HostParams`] borrows
+    /// from the argument [`Value`]s directly.
+    pub fn new_borrowed<C: AsContextMut>(
+        mut ctx: C,
+        ty: FuncType,
+        f: impl 'static + Send + Sync + Fn(&mut HostCall<'_, C::UserState, C::Engine>) -> Result<()>,
+    ) -> Self {
+        let mut ctx_mut = ctx.as_context_mut();
+        let data = ctx_mut.inner.data_mut();
+        let idx = data
+            .host_functions
+            .push(HostFunction::Borrowed(Arc::new(f)));
+
+        Self {
+            store_id: data.id,
+            ty,
+            backing: FuncImpl::HostFunc(idx),
+        }
+    }
+
+    /// Creates a new asynchronous function from a closure whose parameters and results are Rust types.
+    /// The component model type of the function is derived from `P` and `R`, as with [`Func::wrap`].
+    /// See [`Func::new_async`] for details about how the returned future is driven.
+    pub fn wrap_async<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        ctx: C,
+        f: impl 'static
+            + Send
+            + Sync
+            + for<'a> Fn(StoreContextMut<'a, C::UserState, C::Engine>, P) -> HostFuture<'a, R>,
+    ) -> Self {
+        let mut params_results = vec![ValueType::Bool; P::LEN + R::LEN];
+        P::into_tys(&mut params_results[..P::LEN]);
+        R::into_tys(&mut params_results[P::LEN..]);
+
+        Self::new_async(
+            ctx,
+            FuncType::new(
+                params_results[..P::LEN].iter().cloned(),
+                params_results[P::LEN..].iter().cloned(),
+            ),
+            move |ctx, args, res| {
+                let future = P::from_values(args).map(|p| f(ctx, p));
+                Box::pin(async move { future?.await?.into_values(res) })
+            },
+        )
+    }
+
     /// Calls this function, returning an error if:
     ///
-    /// - The store did not match the original.
-    /// - The arguments or results did not match the signature.
-    /// - A trap occurred.
+    /// - The store did not match the original, with [`crate::Error::StoreMismatch`].
+    /// - The arguments or results did not match the signature, with [`crate::Error::TypeMismatch`].
+    /// - The instance of a guest function was dropped, with [`crate::Error::InstanceDropped`].
+    /// - A trap occurred in a guest function, with [`crate::Error::Trap`].
     pub fn call<C: AsContextMut>(
         &self,
         mut ctx: C,
         arguments: &[Value],
         results: &mut [Value],
     ) -> Result<()> {
-        if ctx.as_context().inner.data().id != self.store_id {
-            panic!("Attempted to call function with incorrect store.");
-        }
+        Self::called_by_host(&mut ctx, |ctx| self.call_inner(ctx, arguments, results))
+    }
 
-        self.ty.match_params(arguments)?;
+    /// Runs `f` as a call that the host started, so that host functions which it reaches
+    /// directly report no [`Caller`](crate::Caller).
+    fn called_by_host<C: AsContextMut, R>(
+        ctx: &mut C,
+        f: impl FnOnce(&mut C) -> Result<R>,
+    ) -> Result<R> {
+        ctx.as_context_mut().inner.data_mut().callers.push(None);
+        let result = f(ctx);
+        ctx.as_context_mut().inner.data_mut().callers.pop();
+        result
+    }
+
+    /// Runs `f` as a call that the host started from [`Func::call_async`], so that asynchronous
+    /// host functions which it reaches fail rather than park the thread that runs the executor.
+    fn called_by_executor<T: 'static, E: backend::WasmEngine, R>(
+        ctx: StoreContextMut<'_, T, E>,
+        f: impl FnOnce(StoreContextMut<'_, T, E>) -> Result<R>,
+    ) -> Result<R> {
+        let mut guard = AsyncCallGuard::new(ctx);
+        f(guard.ctx.as_context_mut())
+    }
+
+    /// Calls this function on behalf of the innermost caller that is recorded in the store.
+    fn call_inner<C: AsContextMut>(
+        &self,
+        mut ctx: C,
+        arguments: &[Value],
+        results: &mut [Value],
+    ) -> Result<()> {
+        ensure!(
+            ctx.as_context().inner.data().id == self.store_id,
+            crate::Error::StoreMismatch
+        );
+
+        let arguments = self.ty.match_params(arguments)?;
 
         if self.ty.results().len() != results.len() {
             bail!("Incorrect result length.");
@@ -105,64 +464,193 @@ impl Func {
 
         match &self.backing {
             FuncImpl::GuestFunc(i, x) => {
-                let GuestFunc {
-                    callee,
-                    component,
-                    encoding,
-                    function,
-                    memory,
-                    realloc,
-                    state_table,
-                    post_return,
-                    types,
-                    instance_id,
-                    interface_id,
-                } = &**x;
+                let instance = i.as_ref().ok_or(crate::Error::InstanceDropped)?;
+                let Some(task) = self
+                    .start_guest(&mut ctx, x, &arguments, results)
+                    .map_err(|error| x.call_error(instance, error))?
+                else {
+                    return Ok(());
+                };
+
+                let within_async = within_async_call(&ctx);
+                let result = run_blocking(within_async, task.drive(ctx.as_context_mut(), x));
+                task.finish(&mut ctx, x, result, results)
+                    .map_err(|error| x.call_error(instance, error))
+            }
+            FuncImpl::HostFunc(idx) => {
+                let callee = ctx.as_context().inner.data().host_functions.get(idx);
+                callee.call_blocking(ctx.as_context_mut(), &self.ty, &arguments, results)?;
+                self.ty.match_results(results)
+            }
+        }
+    }
+
+    /// Lowers the arguments of a guest function and calls its core function. For functions
+    /// lifted with the async ABI, returns the task which the call started, whose callback must
+    /// be driven until it exits before the task is finished.
+    fn start_guest<C: AsContextMut>(
+        &self,
+        mut ctx: C,
+        guest: &GuestFunc,
+        arguments: &[Value],
+        results: &mut [Value],
+    ) -> Result<Option<StartedTask>> {
+        let GuestFunc {
+            callee,
+            component,
+            encoding,
+            function,
+            memory,
+            realloc,
+            state_table,
+            post_return,
+            types,
+            instance_id,
+            interface_id: _,
+            plan,
+            flags,
+            callback: _,
+            is_async,
+        } = guest;
+
+        ensure!(
+            !state_table.dropped.load(Ordering::Acquire),
+            crate::Error::InstanceDropped
+        );
+
+        let lift_limits = ctx.as_context().inner.data().lift_limits;
+        flags.enter(&mut ctx)?;
+        if *is_async {
+            let copy = guest.copy_options(self.store_id);
+            if let Err(error) =
+                crate::concurrent::start_task(&state_table.task, function.result, copy)
+            {
+                return flags.exit(&mut ctx).and(Err(error));
+            }
+        }
+
+        let mut bindgen = FuncBindgen {
+            ctx: &mut ctx,
+            flat_results: Vec::default(),
+            arguments,
+            results,
+            callee_interface: None,
+            callee_wasm: Some(callee),
+            component,
+            encoding,
+            memory,
+            realloc,
+            resource_tables: &state_table.resource_tables,
+            state_table,
+            post_return,
+            types,
+            handles_to_drop: Vec::new(),
+            required_dropped: Vec::new(),
+            instance_id: *instance_id,
+            store_id: self.store_id,
+            lift_limits,
+            lifted_bytes: 0,
+            flags,
+        };
+
+        let result = plan.call(
+            component,
+            guest.variant(),
+            LiftLower::LowerArgsLiftResults,
+            function,
+            &mut bindgen,
+        );
+        let task = StartedTask {
+            code: bindgen.flat_results.first().and_then(|x| match x {
+                wasm_runtime_layer::Value::I32(x) => Some(*x),
+                _ => None,
+            }),
+            handles_to_drop: take(&mut bindgen.handles_to_drop),
+            required_dropped: take(&mut bindgen.required_dropped),
+        };
+
+        match result {
+            std::result::Result::Ok(()) if *is_async => Ok(Some(task)),
+            result => {
+                if *is_async {
+                    crate::concurrent::end_task(&state_table.task)?;
+                }
+                flags.exit(&mut ctx).and(result).map(|()| None)
+            }
+        }
+    }
+
+    /// Calls this function asynchronously, returning an error under the same conditions as [`Func::call`].
+    ///
+    /// The futures of asynchronous host functions are awaited. Guest functions lifted with the
+    /// async ABI are suspended whenever they wait or yield, so that the asynchronous host
+    /// functions which they import through the async ABI are awaited as well. Guest code itself
+    /// never blocks the calling thread: if it calls an asynchronous host function which it imports
+    /// synchronously, and the function's future is not ready when first polled, the call fails
+    /// instead. See [`Func::new_async`] for details.
+    ///
+    /// If the returned future is dropped before it completes, the guest task which it started is
+    /// abandoned: calls to asynchronous host functions which the task has not yet run are discarded,
+    /// and the instance may be entered again.
+    pub async fn call_async<C: AsContextMut>(
+        &self,
+        mut ctx: C,
+        arguments: &[Value],
+        results: &mut [Value],
+    ) -> Result<()> {
+        ensure!(
+            ctx.as_context().inner.data().id == self.store_id,
+            crate::Error::StoreMismatch
+        );
+
+        let mut guard = AsyncCallGuard::new(ctx.as_context_mut());
+        self.call_async_inner(&mut guard, arguments, results).await
+    }
 
+    /// Calls this function asynchronously on behalf of the innermost caller that is recorded in the store.
+    async fn call_async_inner<T: 'static, E: backend::WasmEngine>(
+        &self,
+        guard: &mut AsyncCallGuard<'_, T, E>,
+        arguments: &[Value],
+        results: &mut [Value],
+    ) -> Result<()> {
+        match &self.backing {
+            FuncImpl::GuestFunc(i, x) if x.is_async => {
+                let arguments = self.ty.match_params(arguments)?;
                 ensure!(
-                    !state_table.dropped.load(Ordering::Acquire),
-                    "Instance had been dropped."
+                    self.ty.results().len() == results.len(),
+                    "Incorrect result length."
                 );
-
-                let mut bindgen = FuncBindgen {
-                    ctx,
-                    flat_results: Vec::default(),
-                    arguments,
-                    results,
-                    callee_interface: None,
-                    callee_wasm: Some(callee),
-                    component,
-                    encoding,
-                    memory,
-                    realloc,
-                    resource_tables: &state_table.resource_tables,
-                    post_return,
-                    types,
-                    handles_to_drop: Vec::new(),
-                    required_dropped: Vec::new(),
-                    instance_id: *instance_id,
-                    store_id: self.store_id,
+                let instance = i.as_ref().ok_or(crate::Error::InstanceDropped)?;
+                let Some(task) = self
+                    .start_guest(&mut guard.ctx, x, &arguments, results)
+                    .map_err(|error| x.call_error(instance, error))?
+                else {
+                    return Ok(());
                 };
 
-                Ok(Generator::new(
-                    &component.resolve,
-                    AbiVariant::GuestExport,
-                    LiftLower::LowerArgsLiftResults,
-                    &mut bindgen,
-                )
-                .call(function)
-                .map_err(|error| FuncError {
-                    name: function.name.clone(),
-                    interface: interface_id.clone(),
-                    instance: i.as_ref().expect("No instance available.").clone(),
-                    error,
-                })?)
+                guard.task = Some((x.clone(), task));
+                let result = guard.drive_task().await;
+                guard
+                    .finish_task(result, results)
+                    .map_err(|error| x.call_error(instance, error))
             }
             FuncImpl::HostFunc(idx) => {
-                let callee = ctx.as_context().inner.data().host_functions.get(idx);
-                (callee)(ctx.as_context_mut(), arguments, results)?;
+                let HostFunction::Async(callee) =
+                    guard.ctx.as_context().inner.data().host_functions.get(idx)
+                else {
+                    return self.call_inner(&mut guard.ctx, arguments, results);
+                };
+
+                let arguments = self.ty.match_params(arguments)?;
+                ensure!(
+                    self.ty.results().len() == results.len(),
+                    "Incorrect result length."
+                );
+                callee(guard.ctx.as_context_mut(), &arguments, results).await?;
                 self.ty.match_results(results)
             }
+            FuncImpl::GuestFunc(..) => self.call_inner(&mut guard.ctx, arguments, results),
         }
     }
 
@@ -176,56 +664,229 @@ impl Func {
         let mut params_results = vec![ValueType::Bool; P::LEN + R::LEN];
         P::into_tys(&mut params_results[..P::LEN]);
         R::into_tys(&mut params_results[P::LEN..]);
+        let describes = |declared: &[ValueType], actual: &[ValueType]| {
+            declared.len() == actual.len()
+                && declared.iter().zip(actual).all(|(x, y)| x.describes(y))
+        };
         ensure!(
-            &params_results[..P::LEN] == self.ty.params(),
+            describes(&params_results[..P::LEN], self.ty.params()),
             "Parameters did not match function signature. Expected {:?} but got {:?}",
             self.ty.params(),
             &params_results[..P::LEN]
         );
         ensure!(
-            &params_results[P::LEN..] == self.ty.results(),
+            describes(&params_results[P::LEN..], self.ty.results()),
             "Results did not match function signature. Expected {:?} but got {:?}",
             self.ty.results(),
             &params_results[P::LEN..]
         );
         Ok(TypedFunc {
             inner: self.clone(),
+            flat: self.flat_call::<P, R>(),
             data: PhantomData,
         })
     }
 
-    /// Ties the given instance to this function.
+    /// Determines whether this function may be called by converting the given parameter and
+    /// result types directly to and from core values, rather than through [`Value`]s.
+    fn flat_call<P: ComponentList, R: ComponentList>(&self) -> Option<FlatCall> {
+        let FuncImpl::GuestFunc(_, guest) = &self.backing else {
+            return None;
+        };
+        if guest.is_async {
+            return None;
+        }
+        let (params, results) = (P::RECORD_LAYOUT?, R::RECORD_LAYOUT?);
+        let sig = guest
+            .component
+            .resolve
+            .wasm_signature(AbiVariant::GuestExport, &guest.function);
+
+        let supported = guest.encoding == StringEncoding::Utf8
+            && !sig.indirect_params
+            && sig.params.len() == params.flat_count()
+            && (sig.retptr || sig.results.len() == results.flat_count());
+        supported.then_some(FlatCall {
+            results: sig.results.len(),
+            retptr: sig.retptr,
+            indirect_params: false,
+        })
+    }
+
+    /// Calls this guest function, converting the parameters and results directly to and
+    /// from core values. Errors are reported in the same way as [`Func::call`].
+    fn call_flat<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        &self,
+        ctx: C,
+        flat: FlatCall,
+        params: &P,
+    ) -> Result<R> {
+        ensure!(
+            ctx.as_context().inner.data().id == self.store_id,
+            crate::Error::StoreMismatch
+        );
+
+        let FuncImpl::GuestFunc(i, guest) = &self.backing else {
+            bail!("Function was not a guest function.");
+        };
+        ensure!(
+            !guest.state_table.dropped.load(Ordering::Acquire),
+            crate::Error::InstanceDropped
+        );
+        let instance = i.as_ref().ok_or(crate::Error::InstanceDropped)?;
+
+        let mut cx = FlatContext::new(
+            ctx,
+            guest.memory.as_ref(),
+            guest.realloc.as_ref(),
+            &guest.flags,
+            Vec::with_capacity(P::RECORD_LAYOUT.map_or(0, |x| x.flat_count())),
+        );
+        guest
+            .flags
+            .enter(cx.ctx_mut())
+            .and_then(|()| {
+                let result = guest.call_flat(&mut cx, flat, params);
+                guest.flags.exit(cx.ctx_mut()).and(result)
+            })
+            .map_err(|error| guest.call_error(instance, error))
+    }
+
+    /// Ties the given instance to this function. Functions which are already tied to an instance,
+    /// or which are implemented by the host, are returned unchanged.
     pub(crate) fn instantiate(&self, inst: crate::Instance) -> Self {
-        if let FuncImpl::GuestFunc(None, y) = &self.backing {
-            Self {
+        match &self.backing {
+            FuncImpl::GuestFunc(None, y) => Self {
                 store_id: self.store_id,
                 backing: FuncImpl::GuestFunc(Some(inst), y.clone()),
                 ty: self.ty.clone(),
-            }
-        } else {
-            panic!("Function was not an uninitialized guest function.");
+            },
+            _ => self.clone(),
         }
     }
 
     /// Calls this function from a guest context.
     pub(crate) fn call_from_guest<C: AsContextMut>(
         &self,
-        ctx: C,
+        mut ctx: C,
         options: &GuestInvokeOptions,
         arguments: &[wasm_runtime_layer::Value],
         results: &mut [wasm_runtime_layer::Value],
     ) -> Result<()> {
         ensure!(
             self.store_id == options.store_id,
-            "Function stores did not match."
+            crate::Error::StoreMismatch
+        );
+        ensure!(
+            options.flags.may_leave(&mut ctx)?,
+            "Cannot call import `{}` while the component instance is lowering values or running a post-return function.",
+            options.function.name
         );
 
+        ctx.as_context_mut()
+            .inner
+            .data_mut()
+            .callers
+            .push(Some(options.site.clone()));
+        let result = self.call_import(ctx.as_context_mut(), options, arguments, results);
+        ctx.as_context_mut().inner.data_mut().callers.pop();
+        result
+    }
+
+    /// Calls this function from a guest which lowered it with the async ABI. An asynchronous host
+    /// function starts a subtask, whose handle the guest receives, and which runs once the guest
+    /// waits or yields. Other functions run to completion immediately.
+    pub(crate) fn start_from_guest<C: AsContextMut>(
+        &self,
+        mut ctx: C,
+        import: &Weak<(Func, GuestInvokeOptions)>,
+        options: &GuestInvokeOptions,
+        arguments: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        ensure!(
+            self.store_id == options.store_id,
+            crate::Error::StoreMismatch
+        );
+        ensure!(
+            options.flags.may_leave(&mut ctx)?,
+            "Cannot call import `{}` while the component instance is lowering values or running a post-return function.",
+            options.function.name
+        );
+
+        let (arguments, retptr) = match options.function.result {
+            Some(_) => {
+                let (ptr, arguments) = arguments
+                    .split_last()
+                    .context("Async import had no return pointer.")?;
+                let ptr = require_matches!(ptr, wasm_runtime_layer::Value::I32(x), *x);
+                (arguments, Some(ptr as u32))
+            }
+            None => (arguments, None),
+        };
+        let params = options.lift_async_params(&mut ctx, arguments)?;
+        let params = self.ty.match_params(&params)?;
+
+        if let FuncImpl::HostFunc(idx) = &self.backing {
+            let callee = ctx.as_context().inner.data().host_functions.get(idx);
+            if let HostFunction::Async(_) = callee {
+                let call = AsyncImportCall {
+                    import: import.clone(),
+                    arguments: params.into_owned(),
+                    retptr,
+                };
+                let state = crate::concurrent::start_subtask(&options.state_table.waitables, call)?;
+                results[0] = wasm_runtime_layer::Value::I32(state as i32);
+                return Ok(());
+            }
+        }
+
+        let mut res = vec![Value::Bool(false); self.ty.results().len()];
+        ctx.as_context_mut()
+            .inner
+            .data_mut()
+            .callers
+            .push(Some(options.site.clone()));
+        let result = self.call_inner(ctx.as_context_mut(), &params, &mut res);
+        ctx.as_context_mut().inner.data_mut().callers.pop();
+        result?;
+
+        options.lower_async_result(ctx, retptr, res)?;
+        results[0] = wasm_runtime_layer::Value::I32(crate::concurrent::SUBTASK_RETURNED as i32);
+        Ok(())
+    }
+
+    /// Calls this function on behalf of a guest, after the guest has been recorded as the caller.
+    fn call_import<C: AsContextMut>(
+        &self,
+        mut ctx: C,
+        options: &GuestInvokeOptions,
+        arguments: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        if let FuncImpl::HostFunc(idx) = &self.backing {
+            let callee = ctx.as_context().inner.data().host_functions.get(idx);
+            if let (HostFunction::Borrowed(f), Some(flat)) = (callee, options.flat_import(&self.ty))
+            {
+                return HostCall::call_flat(
+                    ctx.as_context_mut(),
+                    &self.ty,
+                    options,
+                    flat,
+                    arguments,
+                    results,
+                    &*f,
+                );
+            }
+        }
+
         let args = arguments
             .iter()
             .map(TryFrom::try_from)
             .collect::<Result<Vec<_>>>()?;
         let mut res = vec![Value::Bool(false); results.len()];
 
+        let lift_limits = ctx.as_context().inner.data().lift_limits;
         let mut bindgen = FuncBindgen {
             ctx,
             flat_results: Vec::default(),
@@ -238,21 +899,25 @@ impl Func {
             memory: &options.memory,
             realloc: &options.realloc,
             resource_tables: &options.state_table.resource_tables,
+            state_table: &options.state_table,
             post_return: &options.post_return,
             types: &options.types,
             handles_to_drop: Vec::new(),
             required_dropped: Vec::new(),
             instance_id: options.instance_id,
             store_id: self.store_id,
+            lift_limits,
+            lifted_bytes: 0,
+            flags: &options.flags,
         };
 
-        Generator::new(
-            &options.component.resolve,
+        options.plan.call(
+            &options.component,
             AbiVariant::GuestImport,
             LiftLower::LiftArgsLowerResults,
+            &options.function,
             &mut bindgen,
-        )
-        .call(&options.function)?;
+        )?;
 
         for (idx, val) in res.into_iter().enumerate() {
             results[idx] = (&val).try_into()?;
@@ -264,6 +929,8 @@ impl Func {
 
 /// Describes options to invoke an imported function from a guest.
 pub(crate) struct GuestInvokeOptions {
+    /// Identifies the instance and import through which the function is called.
+    pub site: Arc<ImportSite>,
     /// The component to use.
     pub component: Arc<ComponentInner>,
     /// The string encoding to use.
@@ -284,6 +951,314 @@ pub(crate) struct GuestInvokeOptions {
     pub instance_id: u64,
     /// The store ID to use.
     pub store_id: u64,
+    /// The compiled instructions for calling this function.
+    pub plan: CachedPlan,
+    /// How to pass the parameters and results of a borrowing host function directly, if possible.
+    pub flat: OnceLock<Option<FlatCall>>,
+    /// The flags of the component instance which lowered this function.
+    pub flags: InstanceFlags,
+    /// Whether this function is lowered with the async ABI.
+    pub async_: bool,
+}
+
+impl GuestInvokeOptions {
+    /// Creates the options with which the parameters and results of an import lowered with the
+    /// async ABI are copied to and from guest memory.
+    fn copy_options(&self) -> CopyOptions {
+        CopyOptions {
+            component: self.component.clone(),
+            encoding: self.encoding,
+            memory: self.memory.clone(),
+            realloc: self.realloc.clone(),
+            state_table: self.state_table.clone(),
+            types: self.types.clone(),
+            instance_id: self.instance_id,
+            store_id: self.store_id,
+            flags: self.flags.clone(),
+        }
+    }
+
+    /// Lifts the parameters of an import lowered with the async ABI. They are passed as core
+    /// values, or as a pointer into guest memory if there are too many.
+    fn lift_async_params(
+        &self,
+        mut ctx: impl AsContextMut,
+        arguments: &[wasm_runtime_layer::Value],
+    ) -> Result<Vec<Value>> {
+        let copy = self.copy_options();
+        let resolve = &self.component.resolve;
+        let params = self.function.params.iter().map(|(_, ty)| ty);
+        if resolve
+            .wasm_signature(AbiVariant::GuestImportAsync, &self.function)
+            .indirect_params
+        {
+            let ptr = require_matches!(
+                arguments.first(),
+                Some(wasm_runtime_layer::Value::I32(x)),
+                *x as u32
+            );
+            self.component
+                .size_align
+                .field_offsets(params)
+                .into_iter()
+                .map(|(offset, ty)| {
+                    let offset = offset.size_wasm32() as u32;
+                    Ok(copy
+                        .lift(&mut ctx, ty, ptr.wrapping_add(offset), 1)?
+                        .remove(0))
+                })
+                .collect()
+        } else {
+            let mut arguments = arguments.iter();
+            params
+                .map(|ty| {
+                    let mut flat = Vec::new();
+                    push_wasm(resolve, AbiVariant::GuestImportAsync, ty, &mut flat);
+                    let operands = arguments
+                        .by_ref()
+                        .take(flat.len())
+                        .map(TryFrom::try_from)
+                        .collect::<Result<Vec<_>>>()?;
+                    copy.lift_flat(&mut ctx, ty, operands)
+                })
+                .collect()
+        }
+    }
+
+    /// Lowers the result of an import lowered with the async ABI into guest memory at `retptr`.
+    fn lower_async_result(
+        &self,
+        ctx: impl AsContextMut,
+        retptr: Option<u32>,
+        results: Vec<Value>,
+    ) -> Result<()> {
+        if let (Some(ty), Some(ptr)) = (&self.function.result, retptr) {
+            self.copy_options().lower(ctx, ty, ptr, results)?;
+        }
+        Ok(())
+    }
+
+    /// Determines whether a borrowing host function of the given type may read its parameters
+    /// and write its results directly to and from core values and guest memory.
+    fn flat_import(&self, ty: &FuncType) -> Option<FlatCall> {
+        *self.flat.get_or_init(|| {
+            let params = self
+                .function
+                .params
+                .iter()
+                .map(|(_, x)| value_type(&self.types, x))
+                .collect::<Result<Vec<_>>>()
+                .ok()?;
+            let results = self
+                .function
+                .result
+                .iter()
+                .map(|x| value_type(&self.types, x))
+                .collect::<Result<Vec<_>>>()
+                .ok()?;
+            if params != ty.params() || results != ty.results() {
+                return None;
+            }
+
+            let params =
+                FlatLayout::record(&params.iter().map(borrowed_layout).collect::<Vec<_>>())?;
+            let results =
+                FlatLayout::record(&results.iter().map(borrowed_layout).collect::<Vec<_>>())?;
+            let sig = self
+                .component
+                .resolve
+                .wasm_signature(AbiVariant::GuestImport, &self.function);
+            let flat_params = if sig.indirect_params {
+                1
+            } else {
+                params.flat_count()
+            };
+
+            let supported = self.encoding == StringEncoding::Utf8
+                && sig.params.len() == flat_params + usize::from(sig.retptr)
+                && (sig.retptr || sig.results.len() == results.flat_count());
+            supported.then_some(FlatCall {
+                results: sig.results.len(),
+                retptr: sig.retptr,
+                indirect_params: sig.indirect_params,
+            })
+        })
+    }
+}
+
+/// A call to an asynchronous host function that a guest made through an import lowered with the
+/// async ABI, which the host runs while the guest waits.
+#[derive(Debug)]
+pub(crate) struct AsyncImportCall {
+    /// The imported function, along with the options with which the guest lowered it.
+    import: Weak<(Func, GuestInvokeOptions)>,
+    /// The lifted arguments.
+    arguments: Vec<Value>,
+    /// The address in guest memory to which the result is written, if the function has one.
+    retptr: Option<u32>,
+}
+
+impl AsyncImportCall {
+    /// Runs the call, and lowers its result into guest memory.
+    pub(crate) async fn run<T: 'static, E: backend::WasmEngine>(
+        self,
+        mut ctx: StoreContextMut<'_, T, E>,
+    ) -> Result<()> {
+        let import = self.import.upgrade().ok_or(crate::Error::InstanceDropped)?;
+        let (func, options) = &*import;
+        let FuncImpl::HostFunc(idx) = &func.backing else {
+            bail!("Subtask did not call an asynchronous host function.");
+        };
+        let HostFunction::Async(callee) = ctx.as_context().inner.data().host_functions.get(idx)
+        else {
+            bail!("Subtask did not call an asynchronous host function.");
+        };
+
+        let mut results = vec![Value::Bool(false); func.ty.results().len()];
+        ctx.inner
+            .data_mut()
+            .callers
+            .push(Some(options.site.clone()));
+        let result = callee(ctx.as_context_mut(), &self.arguments, &mut results).await;
+        ctx.inner.data_mut().callers.pop();
+        result?;
+        func.ty.match_results(&mut results)?;
+
+        options.lower_async_result(ctx, self.retptr, results)
+    }
+}
+
+/// Describes how the canonical built-ins for futures and streams, and the async ABI, copy values
+/// to and from guest memory.
+#[derive(Debug)]
+pub(crate) struct CopyOptions {
+    /// The component to use.
+    pub component: Arc<ComponentInner>,
+    /// The string encoding to use.
+    pub encoding: StringEncoding,
+    /// The memory to use.
+    pub memory: Option<Memory>,
+    /// The reallocation function to use.
+    pub realloc: Option<wasm_runtime_layer::Func>,
+    /// The state table to use.
+    pub state_table: Arc<StateTable>,
+    /// The types to use.
+    pub types: Arc<[crate::types::ValueType]>,
+    /// The instance ID to use.
+    pub instance_id: u64,
+    /// The store ID to use.
+    pub store_id: u64,
+    /// The flags of the component instance which called the built-in.
+    pub flags: InstanceFlags,
+}
+
+impl CopyOptions {
+    /// Ensures that the instance may call built-ins, rather than lowering values or running
+    /// a post-return function.
+    pub fn ensure_may_leave(&self, mut ctx: impl AsContextMut) -> Result<()> {
+        ensure!(
+            self.flags.may_leave(&mut ctx)?,
+            "Cannot copy values while the component instance is lowering values or running a post-return function."
+        );
+        Ok(())
+    }
+
+    /// Lifts `count` consecutive values of the given type from guest memory at `ptr`.
+    pub fn lift<C: AsContextMut>(
+        &self,
+        ctx: C,
+        ty: &Type,
+        ptr: u32,
+        count: usize,
+    ) -> Result<Vec<Value>> {
+        let mut bindgen = self.bindgen(ctx);
+        let (ptr, count) = bindgen.validate_list(ptr as i32, count as i32, ty)?;
+        let size = self.component.size_align.size(ty).size_wasm32();
+
+        let mut generator = Generator::new(
+            &self.component.resolve,
+            AbiVariant::GuestImport,
+            LiftLower::LiftArgsLowerResults,
+            &mut bindgen,
+        );
+        (0..count)
+            .map(|i| generator.read_value(ty, Value::S32((ptr + i * size) as i32)))
+            .collect()
+    }
+
+    /// Lifts a value of the given type from its flattened core values, reading any data that they point to.
+    pub fn lift_flat<C: AsContextMut>(
+        &self,
+        ctx: C,
+        ty: &Type,
+        operands: Vec<Value>,
+    ) -> Result<Value> {
+        let mut bindgen = self.bindgen(ctx);
+        let mut generator = Generator::new(
+            &self.component.resolve,
+            AbiVariant::GuestImport,
+            LiftLower::LiftArgsLowerResults,
+            &mut bindgen,
+        );
+        generator.lift_flat(ty, operands)
+    }
+
+    /// Lowers the given values of the given type into consecutive locations of guest memory at `ptr`.
+    pub fn lower<C: AsContextMut>(
+        &self,
+        ctx: C,
+        ty: &Type,
+        ptr: u32,
+        values: Vec<Value>,
+    ) -> Result<()> {
+        let mut bindgen = self.bindgen(ctx);
+        let size = self.component.size_align.size(ty).size_wasm32();
+        let align = self.component.size_align.align(ty).align_wasm32();
+        let byte_len = values
+            .len()
+            .checked_mul(size)
+            .context("Copy byte length overflowed.")?;
+        bindgen.validate_range(ptr as usize, byte_len, align)?;
+
+        let mut generator = Generator::new(
+            &self.component.resolve,
+            AbiVariant::GuestImport,
+            LiftLower::LiftArgsLowerResults,
+            &mut bindgen,
+        );
+        for (i, value) in values.into_iter().enumerate() {
+            generator.write_value(ty, Value::S32((ptr as usize + i * size) as i32), value)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a bindgen which converts values using these options.
+    fn bindgen<C: AsContextMut>(&self, ctx: C) -> FuncBindgen<'_, C> {
+        let lift_limits = ctx.as_context().inner.data().lift_limits;
+        FuncBindgen {
+            ctx,
+            flat_results: Vec::default(),
+            arguments: &[],
+            results: &mut [],
+            callee_interface: None,
+            callee_wasm: None,
+            component: &self.component,
+            encoding: &self.encoding,
+            memory: &self.memory,
+            realloc: &self.realloc,
+            resource_tables: &self.state_table.resource_tables,
+            state_table: &self.state_table,
+            post_return: &None,
+            types: &self.types,
+            handles_to_drop: Vec::new(),
+            required_dropped: Vec::new(),
+            instance_id: self.instance_id,
+            store_id: self.store_id,
+            lift_limits,
+            lifted_bytes: 0,
+            flags: &self.flags,
+        }
+    }
 }
 
 /// Manages the invocation of a component model function with the canonical ABI.
@@ -312,6 +1287,8 @@ struct FuncBindgen<'a, C: AsContextMut> {
     pub results: &'a mut [Value],
     /// The resource tables to use.
     pub resource_tables: &'a Mutex<Vec<HandleTable>>,
+    /// The state of the instance, which holds its future and stream handles.
+    pub state_table: &'a StateTable,
     /// The types to use.
     pub types: &'a [crate::types::ValueType],
     /// The handles to drop at the call's end.
@@ -322,31 +1299,76 @@ struct FuncBindgen<'a, C: AsContextMut> {
     pub instance_id: u64,
     /// The store ID to use.
     pub store_id: u64,
+    /// The limits on values lifted from guest memory.
+    pub lift_limits: LiftLimits,
+    /// The number of bytes lifted from guest memory so far.
+    pub lifted_bytes: usize,
+    /// The flags of the component instance whose memory is used.
+    pub flags: &'a InstanceFlags,
 }
 
 impl<'a, C: AsContextMut> FuncBindgen<'a, C> {
+    /// Computes the guest memory address at the given offset from a guest pointer.
+    fn address(ptr: i32, offset: i32) -> Result<usize> {
+        (ptr as u32 as usize)
+            .checked_add(offset as u32 as usize)
+            .filter(|x| *x <= u32::MAX as usize)
+            .context("Guest memory address overflowed.")
+    }
+
+    /// Gets the guest memory, failing if the canonical options did not provide one.
+    fn memory(&self) -> Result<&'a Memory> {
+        self.memory.as_ref().context(
+            "Function accessed guest memory, but its canonical options did not provide one.",
+        )
+    }
+
+    /// Ensures that a range of `size` bytes at `ptr` is aligned and lies within guest memory.
+    fn validate_range(&self, ptr: usize, size: usize, align: usize) -> Result<()> {
+        validate_range(&self.ctx, self.memory()?, ptr, size, align)
+    }
+
+    /// Records that `size` bytes are being lifted from guest memory, failing if this exceeds
+    /// the per-call limit.
+    fn account_lifted(&mut self, size: usize) -> Result<()> {
+        self.lift_limits.account(&mut self.lifted_bytes, size)
+    }
+
+    /// Validates a list of `element` values in guest memory against the lift limits and memory
+    /// bounds, returning its pointer and length.
+    fn validate_list(&mut self, ptr: i32, len: i32, element: &Type) -> Result<(usize, usize)> {
+        let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+        self.lift_limits.check_list(len)?;
+
+        let size = self.component.size_align.size(element).size_wasm32();
+        let align = self.component.size_align.align(element).align_wasm32();
+        let byte_len = len
+            .checked_mul(size)
+            .context("List byte length overflowed.")?;
+        self.validate_range(ptr, byte_len, align)?;
+        self.account_lifted(byte_len)?;
+        Ok((ptr, len))
+    }
+
     /// Loads a type from the given offset in guest memory.
     fn load<B: Blittable>(&self, offset: usize) -> Result<B> {
         Ok(B::from_bytes(<B::Array as ByteArray>::load(
             &self.ctx,
-            self.memory.as_ref().expect("No memory."),
+            self.memory()?,
             offset,
         )?))
     }
 
     /// Stores a type to the given offset in guest memory.
     fn store<B: Blittable>(&mut self, offset: usize, value: B) -> Result<()> {
-        value.to_bytes().store(
-            &mut self.ctx,
-            self.memory.as_ref().expect("No memory."),
-            offset,
-        )
+        let memory = self.memory()?;
+        value.to_bytes().store(&mut self.ctx, memory, offset)
     }
 
     /// Loads a list of types from the given offset in guest memory.
     fn load_array<B: Blittable>(&self, offset: usize, len: usize) -> Result<Arc<[B]>> {
         let mut raw_memory = B::zeroed_array(len);
-        self.memory.as_ref().expect("No memory").read(
+        self.memory()?.read(
             self.ctx.as_context().inner,
             offset,
             B::to_le_slice_mut(
@@ -358,12 +1380,151 @@ impl<'a, C: AsContextMut> FuncBindgen<'a, C> {
 
     /// Stores a list of types to the given offset in guest memory.
     fn store_array<B: Blittable>(&mut self, offset: usize, value: &[B]) -> Result<()> {
-        self.memory.as_ref().expect("No memory.").write(
+        self.memory()?.write(
             self.ctx.as_context_mut().inner,
             offset,
             B::to_le_slice(value),
         )
     }
+
+    /// Calls the guest's reallocation function, failing if the new allocation is misaligned.
+    fn call_realloc(
+        &mut self,
+        old_ptr: usize,
+        old_size: usize,
+        align: usize,
+        new_size: usize,
+    ) -> Result<usize> {
+        let args = [
+            wasm_runtime_layer::Value::I32(old_ptr as i32),
+            wasm_runtime_layer::Value::I32(old_size as i32),
+            wasm_runtime_layer::Value::I32(align as i32),
+            wasm_runtime_layer::Value::I32(new_size as i32),
+        ];
+        let mut res = [wasm_runtime_layer::Value::I32(0)];
+        self.flags.call_without_leave(
+            &mut self.ctx,
+            self.realloc
+                .as_ref()
+                .context("Guest did not export a reallocation function.")?,
+            &args,
+            &mut res,
+        )?;
+
+        let ptr = require_matches!(res[0], wasm_runtime_layer::Value::I32(x), x) as u32 as usize;
+        ensure!(
+            ptr.is_multiple_of(align),
+            "Reallocated pointer was not aligned."
+        );
+        Ok(ptr)
+    }
+
+    /// Stores a string in guest memory using the function's string encoding, returning its pointer
+    /// and tagged length in code units. Allocations follow the canonical ABI: space is reserved for
+    /// the worst case, and then shrunk to fit the encoded string.
+    fn lower_string(&mut self, value: &str) -> Result<(usize, usize)> {
+        use crate::transcode::*;
+
+        ensure!(
+            value.len() <= MAX_STRING_BYTE_LENGTH,
+            "String was too long to lower."
+        );
+
+        match self.encoding {
+            StringEncoding::Utf8 => {
+                let ptr = self.call_realloc(0, 0, 1, value.len())?;
+                self.store_array(ptr, value.as_bytes())?;
+                Ok((ptr, value.len()))
+            }
+            StringEncoding::Utf16 => {
+                let worst_case_size = byte_len(value.len())?;
+                ensure!(
+                    worst_case_size <= MAX_STRING_BYTE_LENGTH,
+                    "String was too long to lower."
+                );
+                let ptr = self.call_realloc(0, 0, 2, worst_case_size)?;
+                self.store_utf16(ptr, worst_case_size, value)
+            }
+            StringEncoding::CompactUtf16 => {
+                let ptr = self.call_realloc(0, 0, 2, value.len())?;
+                let latin1 = value
+                    .chars()
+                    .map_while(|ch| u8::try_from(ch).ok())
+                    .collect::<Vec<_>>();
+                self.store_array(ptr, &latin1)?;
+
+                if latin1.len() < value.chars().count() {
+                    // The string contains a character outside of Latin-1, so it must be stored as UTF-16.
+                    let worst_case_size = byte_len(value.len())?;
+                    ensure!(
+                        worst_case_size <= MAX_STRING_BYTE_LENGTH,
+                        "String was too long to lower."
+                    );
+                    let ptr = self.call_realloc(ptr, value.len(), 2, worst_case_size)?;
+                    let (ptr, code_units) = self.store_utf16(ptr, worst_case_size, value)?;
+                    Ok((ptr, code_units | UTF16_TAG))
+                } else {
+                    // Multi-byte UTF-8 characters occupy one byte each in Latin-1, so the allocation may shrink.
+                    let ptr = if latin1.len() < value.len() {
+                        self.call_realloc(ptr, value.len(), 2, latin1.len())?
+                    } else {
+                        ptr
+                    };
+                    Ok((ptr, latin1.len()))
+                }
+            }
+        }
+    }
+
+    /// Writes a string as UTF-16 to an allocation of the given size, shrinking the allocation if the
+    /// encoded string is smaller. Returns the final pointer and the number of code units written.
+    fn store_utf16(&mut self, ptr: usize, size: usize, value: &str) -> Result<(usize, usize)> {
+        let encoded = value.encode_utf16().collect::<Vec<_>>();
+        self.store_array(ptr, &encoded)?;
+
+        let encoded_size = crate::transcode::byte_len(encoded.len())?;
+        let ptr = if encoded_size < size {
+            self.call_realloc(ptr, size, 2, encoded_size)?
+        } else {
+            ptr
+        };
+        Ok((ptr, encoded.len()))
+    }
+
+    /// Acquires the instance's table of future and stream handles.
+    fn waitables(&self) -> Result<MutexGuard<'a, crate::concurrent::Waitables>> {
+        self.state_table
+            .waitables
+            .try_lock()
+            .map_err(|_| crate::Error::resource("Could not lock future and stream handles.").into())
+    }
+
+    /// Gets the value type which corresponds to the given component type.
+    fn value_type(&self, ty: &Type) -> Result<ValueType> {
+        value_type(self.types, ty)
+    }
+}
+
+/// Gets the value type which corresponds to the given component type, resolving type
+/// definitions with `types`.
+fn value_type(types: &[ValueType], ty: &Type) -> Result<ValueType> {
+    Ok(match ty {
+        Type::Bool => ValueType::Bool,
+        Type::U8 => ValueType::U8,
+        Type::U16 => ValueType::U16,
+        Type::U32 => ValueType::U32,
+        Type::U64 => ValueType::U64,
+        Type::S8 => ValueType::S8,
+        Type::S16 => ValueType::S16,
+        Type::S32 => ValueType::S32,
+        Type::S64 => ValueType::S64,
+        Type::F32 => ValueType::F32,
+        Type::F64 => ValueType::F64,
+        Type::Char => ValueType::Char,
+        Type::String => ValueType::String,
+        Type::Id(x) => types[x.index()].clone(),
+        Type::ErrorContext => ValueType::ErrorContext,
+    })
 }
 
 impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
@@ -439,50 +1600,50 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
             Instruction::I32Load { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
-                results.push(Value::S32(self.load((x as usize) + (*offset as usize))?))
+                results.push(Value::S32(self.load(Self::address(x, *offset)?)?))
             ),
             Instruction::I32Load8U { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
                 results.push(Value::S32(
-                    self.load::<u8>((x as usize) + (*offset as usize))? as i32
+                    self.load::<u8>(Self::address(x, *offset)?)? as i32
                 ))
             ),
             Instruction::I32Load8S { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
                 results.push(Value::S32(
-                    self.load::<i8>((x as usize) + (*offset as usize))? as i32
+                    self.load::<i8>(Self::address(x, *offset)?)? as i32
                 ))
             ),
             Instruction::I32Load16U { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
                 results.push(Value::S32(
-                    self.load::<u16>((x as usize) + (*offset as usize))? as i32
+                    self.load::<u16>(Self::address(x, *offset)?)? as i32
                 ))
             ),
             Instruction::I32Load16S { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
                 results.push(Value::S32(
-                    self.load::<i16>((x as usize) + (*offset as usize))? as i32
+                    self.load::<i16>(Self::address(x, *offset)?)? as i32
                 ))
             ),
             Instruction::I64Load { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
-                results.push(Value::S64(self.load((x as usize) + (*offset as usize))?))
+                results.push(Value::S64(self.load(Self::address(x, *offset)?)?))
             ),
             Instruction::F32Load { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
-                results.push(Value::F32(self.load((x as usize) + (*offset as usize))?))
+                results.push(Value::F32(self.load(Self::address(x, *offset)?)?))
             ),
             Instruction::F64Load { offset } => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
-                results.push(Value::F64(self.load((x as usize) + (*offset as usize))?))
+                results.push(Value::F64(self.load(Self::address(x, *offset)?)?))
             ),
             Instruction::I32Store { offset } => require_matches!(
                 operands.pop(),
@@ -490,7 +1651,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::S32(x)),
-                    self.store((address as usize) + (*offset as usize), x)?
+                    self.store(Self::address(address, *offset)?, x)?
                 )
             ),
             Instruction::I32Store8 { offset } => require_matches!(
@@ -499,7 +1660,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::S32(x)),
-                    self.store((address as usize) + (*offset as usize), x as u8)?
+                    self.store(Self::address(address, *offset)?, x as u8)?
                 )
             ),
             Instruction::I32Store16 { offset } => require_matches!(
@@ -508,7 +1669,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::S32(x)),
-                    self.store((address as usize) + (*offset as usize), x as u16)?
+                    self.store(Self::address(address, *offset)?, x as u16)?
                 )
             ),
             Instruction::I64Store { offset } => require_matches!(
@@ -517,7 +1678,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::S64(x)),
-                    self.store((address as usize) + (*offset as usize), x)?
+                    self.store(Self::address(address, *offset)?, x)?
                 )
             ),
             Instruction::F32Store { offset } => require_matches!(
@@ -526,7 +1687,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::F32(x)),
-                    self.store((address as usize) + (*offset as usize), x)?
+                    self.store(Self::address(address, *offset)?, x)?
                 )
             ),
             Instruction::F64Store { offset } => require_matches!(
@@ -535,7 +1696,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 require_matches!(
                     operands.pop(),
                     Some(Value::F64(x)),
-                    self.store((address as usize) + (*offset as usize), x)?
+                    self.store(Self::address(address, *offset)?, x)?
                 )
             ),
             Instruction::I32FromChar => require_matches!(
@@ -637,7 +1798,10 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 operands.pop(),
                 Some(Value::S32(x)),
                 results.push(Value::Char(char::from_u32(x as u32).ok_or_else(|| {
-                    Error::msg("Could not convert integer to char.")
+                    anyhow!(
+                        "Value {:#x} was not a valid Unicode scalar value.",
+                        x as u32
+                    )
                 })?))
             ),
             Instruction::Float32FromF32 => require_matches!(
@@ -653,7 +1817,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
             Instruction::BoolFromI32 => require_matches!(
                 operands.pop(),
                 Some(Value::S32(x)),
-                results.push(Value::Bool(x > 0))
+                results.push(Value::Bool(x != 0))
             ),
             Instruction::I32FromBool => require_matches!(
                 operands.pop(),
@@ -661,32 +1825,10 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 results.push(Value::S32(x as i32))
             ),
             Instruction::StringLower { realloc: _ } => {
-                let encoded = require_matches!(
-                    operands.pop(),
-                    Some(Value::String(x)),
-                    match self.encoding {
-                        StringEncoding::Utf8 => Vec::from_iter(x.bytes()),
-                        StringEncoding::Utf16 | StringEncoding::CompactUtf16 =>
-                            x.encode_utf16().flat_map(|a| a.to_le_bytes()).collect(),
-                    }
-                );
-
-                let realloc = self.realloc.as_ref().expect("No realloc.");
-                let args = [
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(1),
-                    wasm_runtime_layer::Value::I32(encoded.len() as i32),
-                ];
-                let mut res = [wasm_runtime_layer::Value::I32(0)];
-                realloc.call(&mut self.ctx.as_context_mut().inner, &args, &mut res)?;
-                let ptr = require_matches!(&res[0], wasm_runtime_layer::Value::I32(x), *x);
-
-                let memory = self.memory.as_ref().expect("No memory.");
-                memory.write(&mut self.ctx.as_context_mut().inner, ptr as usize, &encoded)?;
-
-                results.push(Value::S32(ptr));
-                results.push(Value::S32(encoded.len() as i32));
+                let value = require_matches!(operands.pop(), Some(Value::String(x)), x);
+                let (ptr, tagged_code_units) = self.lower_string(&value)?;
+                results.push(Value::S32(ptr as i32));
+                results.push(Value::S32(tagged_code_units as i32));
             }
             Instruction::ListCanonLower {
                 element,
@@ -696,16 +1838,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 let align = self.component.size_align.align(element).align_wasm32();
                 let size = self.component.size_align.size(element).size_wasm32();
 
-                let realloc = self.realloc.as_ref().expect("No realloc.");
-                let args = [
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(align as i32),
-                    wasm_runtime_layer::Value::I32((list.len() * size) as i32),
-                ];
-                let mut res = [wasm_runtime_layer::Value::I32(0)];
-                realloc.call(&mut self.ctx.as_context_mut().inner, &args, &mut res)?;
-                let ptr = require_matches!(res[0], wasm_runtime_layer::Value::I32(x), x);
+                let ptr = self.call_realloc(0, 0, align, list.len() * size)? as i32;
 
                 match element {
                     Type::U8 => self.store_array(ptr as usize, list.typed::<u8>()?)?,
@@ -733,16 +1866,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 let align = self.component.size_align.align(element).align_wasm32();
                 let size = self.component.size_align.size(element).size_wasm32();
 
-                let realloc = self.realloc.as_ref().expect("No realloc.");
-                let args = [
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(align as i32),
-                    wasm_runtime_layer::Value::I32((list.len() * size) as i32),
-                ];
-                let mut res = [wasm_runtime_layer::Value::I32(0)];
-                realloc.call(&mut self.ctx.as_context_mut().inner, &args, &mut res)?;
-                let ptr = require_matches!(res[0], wasm_runtime_layer::Value::I32(x), x);
+                let ptr = self.call_realloc(0, 0, align, list.len() * size)? as i32;
 
                 len.set(list.len() as i32);
 
@@ -756,52 +1880,39 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 results.push(Value::S32(ptr));
             }
             Instruction::StringLift => {
-                let memory = self.memory.as_ref().expect("No memory.");
-                let len = require_matches!(operands.pop(), Some(Value::S32(len)), len) as usize;
-                let mut result = vec![0; len];
-                require_matches!(
-                    operands.pop(),
-                    Some(Value::S32(ptr)),
-                    memory.read(&self.ctx.as_context().inner, ptr as usize, &mut result)?
-                );
-
-                match self.encoding {
-                    StringEncoding::Utf8 => {
-                        results.push(Value::String(String::from_utf8(result)?.into()))
-                    }
-                    StringEncoding::Utf16 | StringEncoding::CompactUtf16 => {
-                        ensure!(result.len() & 0b1 == 0, "Invalid string length");
-                        results.push(Value::String(
-                            String::from_utf16(
-                                &result
-                                    .chunks_exact(2)
-                                    .map(|e| {
-                                        u16::from_be_bytes(
-                                            e.try_into().expect("All chunks must have size 2."),
-                                        )
-                                    })
-                                    .collect::<Vec<_>>(),
-                            )?
-                            .into(),
-                        ));
-                    }
-                }
+                let tagged_code_units =
+                    require_matches!(operands.pop(), Some(Value::S32(len)), len) as u32 as usize;
+                let ptr =
+                    require_matches!(operands.pop(), Some(Value::S32(ptr)), ptr) as u32 as usize;
+                let (align, byte_len, encoding) =
+                    crate::transcode::string_layout(*self.encoding, tagged_code_units)?;
+                self.lift_limits.check_string(byte_len)?;
+                self.validate_range(ptr, byte_len, align)?;
+                self.account_lifted(byte_len)?;
+
+                let mut bytes = vec![0; byte_len];
+                self.memory()?
+                    .read(&self.ctx.as_context().inner, ptr, &mut bytes)?;
+                results.push(Value::String(
+                    crate::transcode::decode_string(encoding, &bytes)?.into(),
+                ));
             }
             Instruction::ListCanonLift { element, ty: _ } => {
                 let len = require_matches!(operands.pop(), Some(Value::S32(x)), x);
                 let ptr = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                let (ptr, len) = self.validate_list(ptr, len, element)?;
 
                 results.push(Value::List(match element {
-                    Type::U8 => self.load_array::<u8>(ptr as usize, len as usize)?.into(),
-                    Type::U16 => self.load_array::<u16>(ptr as usize, len as usize)?.into(),
-                    Type::U32 => self.load_array::<u32>(ptr as usize, len as usize)?.into(),
-                    Type::U64 => self.load_array::<u64>(ptr as usize, len as usize)?.into(),
-                    Type::S8 => self.load_array::<i8>(ptr as usize, len as usize)?.into(),
-                    Type::S16 => self.load_array::<i16>(ptr as usize, len as usize)?.into(),
-                    Type::S32 => self.load_array::<i32>(ptr as usize, len as usize)?.into(),
-                    Type::S64 => self.load_array::<i64>(ptr as usize, len as usize)?.into(),
-                    Type::F32 => self.load_array::<f32>(ptr as usize, len as usize)?.into(),
-                    Type::F64 => self.load_array::<f64>(ptr as usize, len as usize)?.into(),
+                    Type::U8 => self.load_array::<u8>(ptr, len)?.into(),
+                    Type::U16 => self.load_array::<u16>(ptr, len)?.into(),
+                    Type::U32 => self.load_array::<u32>(ptr, len)?.into(),
+                    Type::U64 => self.load_array::<u64>(ptr, len)?.into(),
+                    Type::S8 => self.load_array::<i8>(ptr, len)?.into(),
+                    Type::S16 => self.load_array::<i16>(ptr, len)?.into(),
+                    Type::S32 => self.load_array::<i32>(ptr, len)?.into(),
+                    Type::S64 => self.load_array::<i64>(ptr, len)?.into(),
+                    Type::F32 => self.load_array::<f32>(ptr, len)?.into(),
+                    Type::F64 => self.load_array::<f64>(ptr, len)?.into(),
                     _ => unreachable!(),
                 }));
             }
@@ -819,6 +1930,18 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
             Instruction::ReadI32 { value } => {
                 value.set(require_matches!(operands.pop(), Some(Value::S32(x)), x))
             }
+            Instruction::ValidateList { element, len } => {
+                let length = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                let ptr = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                let _ = self.validate_list(ptr, length, element)?;
+                len.set(length);
+                results.push(Value::S32(ptr));
+            }
+            Instruction::ValidatePointer { size, align } => {
+                let ptr = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                self.validate_range(ptr as u32 as usize, *size, *align)?;
+                results.push(Value::S32(ptr));
+            }
             Instruction::RecordLower { record: _, ty } => {
                 let official_ty =
                     require_matches!(&self.types[ty.index()], ValueType::Record(x), x);
@@ -866,7 +1989,7 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                     let mut tables = self
                         .resource_tables
                         .try_lock()
-                        .expect("Could not acquire table access.");
+                        .map_err(|_| crate::Error::resource("Could not acquire table access."))?;
                     results.push(Value::S32(
                         tables[self.component.resource_map[def.index()].as_u32() as usize].add(
                             HandleElement {
@@ -888,10 +2011,9 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                     if val.ty().is_owned_by_instance(self.instance_id) {
                         results.push(Value::S32(rep));
                     } else {
-                        let mut tables = self
-                            .resource_tables
-                            .try_lock()
-                            .expect("Could not acquire table access.");
+                        let mut tables = self.resource_tables.try_lock().map_err(|_| {
+                            crate::Error::resource("Could not acquire table access.")
+                        })?;
                         let res = self.component.resource_map[def.index()].as_u32();
                         let idx = tables[res as usize].add(HandleElement {
                             rep,
@@ -904,71 +2026,86 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 }
                 _ => unreachable!(),
             },
-            Instruction::HandleLift { handle, ty } => match &self.types[ty.index()] {
-                ValueType::Own(ty) => {
-                    let def = match handle {
-                        Handle::Own(x) => x,
-                        Handle::Borrow(x) => x,
-                    };
-                    let val = require_matches!(operands.pop(), Some(Value::S32(x)), x);
-
-                    let mut tables = self
-                        .resource_tables
-                        .try_lock()
-                        .expect("Could not acquire table access.");
-                    let table =
-                        &mut tables[self.component.resource_map[def.index()].as_u32() as usize];
-                    let elem = table.remove(val)?;
-                    ensure!(
-                        elem.lend_count == 0,
-                        "Attempted to transfer ownership while handle was lent."
-                    );
-                    ensure!(
-                        elem.own,
-                        "Attempted to transfer ownership of non-owned handle."
-                    );
+            Instruction::HandleLift { handle, ty } => {
+                match &self.types[ty.index()] {
+                    ValueType::Own(ty) => {
+                        let def = match handle {
+                            Handle::Own(x) => x,
+                            Handle::Borrow(x) => x,
+                        };
+                        let val = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+
+                        let mut tables = self.resource_tables.try_lock().map_err(|_| {
+                            crate::Error::resource("Could not acquire table access.")
+                        })?;
+                        let table =
+                            &mut tables[self.component.resource_map[def.index()].as_u32() as usize];
+                        let elem = table.remove(val)?;
+                        ensure!(
+                            elem.lend_count == 0,
+                            "Attempted to transfer ownership while handle was lent."
+                        );
+                        ensure!(
+                            elem.own,
+                            "Attempted to transfer ownership of non-owned handle."
+                        );
+
+                        results.push(Value::Own(ResourceOwn::new_guest(
+                            elem.rep,
+                            ty.clone(),
+                            self.store_id,
+                            table.destructor().cloned(),
+                        )));
+                    }
+                    ValueType::Borrow(ty) => {
+                        let def = match handle {
+                            Handle::Own(x) => x,
+                            Handle::Borrow(x) => x,
+                        };
+                        let val = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+
+                        let mut tables = self.resource_tables.try_lock().map_err(|_| {
+                            crate::Error::resource("Could not acquire table access.")
+                        })?;
+                        let res = self.component.resource_map[def.index()].as_u32();
+                        let table = &mut tables[res as usize];
+                        let mut elem = *table.get(val)?;
 
-                    results.push(Value::Own(ResourceOwn::new_guest(
-                        elem.rep,
-                        ty.clone(),
-                        self.store_id,
-                        table.destructor().cloned(),
-                    )));
-                }
-                ValueType::Borrow(ty) => {
-                    let def = match handle {
-                        Handle::Own(x) => x,
-                        Handle::Borrow(x) => x,
-                    };
-                    let val = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                        if elem.own {
+                            elem.lend_count += 1;
+                            table.set(val, elem);
+                        }
 
-                    let mut tables = self
-                        .resource_tables
-                        .try_lock()
-                        .expect("Could not acquire table access.");
-                    let res = self.component.resource_map[def.index()].as_u32();
-                    let table = &mut tables[res as usize];
-                    let mut elem = *table.get(val)?;
-
-                    if elem.own {
-                        elem.lend_count += 1;
-                        table.set(val, elem);
+                        let borrow = ResourceBorrow::new(elem.rep, self.store_id, ty.clone());
+                        self.required_dropped
+                            .push((elem.own, res, val, borrow.dead_ref()));
+                        results.push(Value::Borrow(borrow));
                     }
-
-                    let borrow = ResourceBorrow::new(elem.rep, self.store_id, ty.clone());
-                    self.required_dropped
-                        .push((elem.own, res, val, borrow.dead_ref()));
-                    results.push(Value::Borrow(borrow));
+                    _ => unreachable!(),
                 }
-                _ => unreachable!(),
-            },
+            }
             Instruction::TupleLower { tuple: _, ty: _ } => {
                 let tuple = require_matches!(operands.pop(), Some(Value::Tuple(x)), x);
                 results.extend(tuple.iter().cloned());
             }
-            Instruction::TupleLift { tuple: _, ty } => {
-                results.push(Value::Tuple(crate::values::Tuple::new_unchecked(
-                    require_matches!(&self.types[ty.index()], ValueType::Tuple(x), x.clone()),
+            Instruction::TupleLift { tuple: _, ty } => {
+                results.push(Value::Tuple(crate::values::Tuple::new_unchecked(
+                    require_matches!(&self.types[ty.index()], ValueType::Tuple(x), x.clone()),
+                    operands.drain(..),
+                )));
+            }
+            Instruction::FixedListLower { size, ty: _ } => {
+                let list = require_matches!(operands.pop(), Some(Value::FixedList(x)), x);
+                ensure!(
+                    list.len() == *size as usize,
+                    "Expected {size} list items, but got {}.",
+                    list.len()
+                );
+                results.extend(list.iter().cloned());
+            }
+            Instruction::FixedListLift { size: _, ty } => {
+                results.push(Value::FixedList(crate::values::FixedList::new_unchecked(
+                    require_matches!(&self.types[ty.index()], ValueType::FixedList(x), x.clone()),
                     operands.drain(..),
                 )));
             }
@@ -1097,12 +2234,94 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                     &args,
                     &mut self.flat_results,
                 )?;
-                results.extend(
-                    self.flat_results
-                        .iter()
-                        .map(TryFrom::try_from)
-                        .collect::<Result<Vec<_>>>()?,
+                for value in &self.flat_results {
+                    results.push(value.try_into()?);
+                }
+            }
+            Instruction::AsyncCallWasm { name: _, sig } => {
+                let args = operands
+                    .iter()
+                    .map(TryFrom::try_from)
+                    .collect::<Result<Vec<_>>>()?;
+                // Holds the first callback code, if the function has a callback
+                self.flat_results = vec![wasm_runtime_layer::Value::I32(0); sig.results.len()];
+                self.callee_wasm.expect("No available WASM callee.").call(
+                    &mut self.ctx.as_context_mut().inner,
+                    &args,
+                    &mut self.flat_results,
+                )?;
+            }
+            Instruction::FutureLower { ty } => {
+                let official_ty =
+                    require_matches!(&self.types[ty.index()], ValueType::Future(x), x);
+                let future = require_matches!(operands.pop(), Some(Value::Future(x)), x);
+                ensure!(
+                    &future.ty() == official_ty,
+                    crate::Error::TypeMismatch {
+                        expected: ValueType::Future(official_ty.clone()),
+                        actual: ValueType::Future(future.ty()),
+                    }
+                );
+                let channel = future.lower(&mut self.ctx)?;
+                results.push(Value::S32(
+                    self.waitables()?
+                        .lower(channel, crate::concurrent::ChannelKind::Future),
+                ));
+            }
+            Instruction::FutureLift { ty } => {
+                let official_ty =
+                    require_matches!(&self.types[ty.index()], ValueType::Future(x), x);
+                let idx = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                results.push(Value::Future(crate::concurrent::lift_future(
+                    &mut self.ctx,
+                    &self.state_table.waitables,
+                    idx,
+                    official_ty.clone(),
+                )?));
+            }
+            Instruction::StreamLower { ty } => {
+                let official_ty =
+                    require_matches!(&self.types[ty.index()], ValueType::Stream(x), x);
+                let stream = require_matches!(operands.pop(), Some(Value::Stream(x)), x);
+                ensure!(
+                    &stream.ty() == official_ty,
+                    crate::Error::TypeMismatch {
+                        expected: ValueType::Stream(official_ty.clone()),
+                        actual: ValueType::Stream(stream.ty()),
+                    }
                 );
+                let channel = stream.lower(&mut self.ctx)?;
+                results.push(Value::S32(
+                    self.waitables()?
+                        .lower(channel, crate::concurrent::ChannelKind::Stream),
+                ));
+            }
+            Instruction::StreamLift { ty } => {
+                let official_ty =
+                    require_matches!(&self.types[ty.index()], ValueType::Stream(x), x);
+                let idx = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                results.push(Value::Stream(crate::concurrent::lift_stream(
+                    &mut self.ctx,
+                    &self.state_table.waitables,
+                    idx,
+                    official_ty.clone(),
+                )?));
+            }
+            Instruction::ErrorContextLower => {
+                let context = require_matches!(operands.pop(), Some(Value::ErrorContext(x)), x);
+                results.push(Value::S32(
+                    crate::concurrent::lock_error_contexts(&self.state_table.error_contexts)?
+                        .add(context),
+                ));
+            }
+            Instruction::ErrorContextLift => {
+                // The guest keeps its handle, which it must drop separately.
+                let idx = require_matches!(operands.pop(), Some(Value::S32(x)), x);
+                results.push(Value::ErrorContext(
+                    crate::concurrent::lock_error_contexts(&self.state_table.error_contexts)?
+                        .get(idx)?
+                        .clone(),
+                ));
             }
             Instruction::CallInterface { func } => {
                 for _i in 0..func.result.iter().count() {
@@ -1111,41 +2330,28 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
 
                 self.callee_interface
                     .expect("No available interface callee.")
-                    .call(self.ctx.as_context_mut(), operands, &mut results[..])?;
+                    .call_inner(self.ctx.as_context_mut(), operands, &mut results[..])?;
+
+                // The callee may return a subtype of the results that the guest expects
+                for (result, ty) in results.iter_mut().zip(&func.result) {
+                    *result = result.coerce(&self.value_type(ty)?)?;
+                }
             }
             Instruction::Return { amt: _, func: _ } => {
                 if let Some(post) = &self.post_return {
-                    post.call(
-                        &mut self.ctx.as_context_mut().inner,
+                    self.flags.call_without_leave(
+                        &mut self.ctx,
+                        post,
                         &self.flat_results,
                         &mut [],
                     )?;
                 }
 
-                let mut tables = self
-                    .resource_tables
-                    .try_lock()
-                    .expect("Could not lock resource table.");
-                for (res, idx) in &self.handles_to_drop {
-                    tables[*res as usize]
-                        .remove(*idx)
-                        .expect("Could not find handle to drop.");
-                }
-
-                for (own, res, idx, ptr) in &self.required_dropped {
-                    ensure!(
-                        Arc::strong_count(ptr) == 1,
-                        "Borrow was not dropped at the end of method."
-                    );
-
-                    if *own {
-                        let table = &mut tables[*res as usize];
-                        let mut elem = *table.get(*idx)?;
-
-                        elem.lend_count -= 1;
-                        table.set(*idx, elem);
-                    }
-                }
+                release_handles(
+                    self.resource_tables,
+                    &self.handles_to_drop,
+                    &self.required_dropped,
+                )?;
 
                 for (i, val) in operands.drain(..).enumerate() {
                     *self
@@ -1159,52 +2365,99 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
                 size,
                 align,
             } => {
-                let realloc = self.realloc.as_ref().expect("No realloc.");
-                let args = [
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(0),
-                    wasm_runtime_layer::Value::I32(*align as i32),
-                    wasm_runtime_layer::Value::I32(*size as i32),
-                ];
-                let mut res = [wasm_runtime_layer::Value::I32(0)];
-                realloc.call(&mut self.ctx.as_context_mut().inner, &args, &mut res)?;
-                require_matches!(
-                    &res[0],
-                    wasm_runtime_layer::Value::I32(x),
-                    results.push(Value::S32(*x))
-                );
+                let ptr = self.call_realloc(0, 0, *align, *size)?;
+                results.push(Value::S32(ptr as i32));
             }
         }
 
         Ok(())
     }
 
-    fn sizes(&self) -> &SizeAlign {
+    fn sizes(&self) -> &SizeAlignTable {
         &self.component.size_align
     }
 
     fn is_list_canonical(&self, element: &Type) -> bool {
-        /// Whether this is a little-endian machine.
-        const LITTLE_ENDIAN: bool = cfg!(target_endian = "little");
-
-        match element {
-            Type::Bool => false,
-            Type::U8 => true,
-            Type::U16 => LITTLE_ENDIAN,
-            Type::U32 => LITTLE_ENDIAN,
-            Type::U64 => LITTLE_ENDIAN,
-            Type::S8 => true,
-            Type::S16 => LITTLE_ENDIAN,
-            Type::S32 => LITTLE_ENDIAN,
-            Type::S64 => LITTLE_ENDIAN,
-            Type::F32 => LITTLE_ENDIAN,
-            Type::F64 => LITTLE_ENDIAN,
-            Type::Char => false,
-            Type::String => false,
-            Type::Id(_) => false,
-            Type::ErrorContext => false,
+        is_list_canonical(element)
+    }
+}
+
+/// Drops the handles that a call's arguments moved, and returns the handles that it lent,
+/// once the call ends.
+fn release_handles(
+    resource_tables: &Mutex<Vec<HandleTable>>,
+    handles_to_drop: &[(u32, i32)],
+    required_dropped: &[(bool, u32, i32, Arc<AtomicBool>)],
+) -> Result<()> {
+    let mut tables = resource_tables
+        .try_lock()
+        .map_err(|_| crate::Error::resource("Could not lock resource table."))?;
+    for (res, idx) in handles_to_drop {
+        tables[*res as usize]
+            .remove(*idx)
+            .context("Could not find handle to drop.")?;
+    }
+
+    for (own, res, idx, ptr) in required_dropped {
+        ensure!(
+            Arc::strong_count(ptr) == 1,
+            "Borrow was not dropped at the end of method."
+        );
+
+        if *own {
+            let table = &mut tables[*res as usize];
+            let mut elem = *table.get(*idx)?;
+
+            elem.lend_count -= 1;
+            table.set(*idx, elem);
         }
     }
+    Ok(())
+}
+
+/// Ensures that a range of `size` bytes at `ptr` is aligned and lies within guest memory.
+pub(crate) fn validate_range(
+    ctx: impl AsContext,
+    memory: &Memory,
+    ptr: usize,
+    size: usize,
+    align: usize,
+) -> Result<()> {
+    ensure!(
+        ptr.is_multiple_of(align),
+        "Pointer {ptr:#x} was not aligned to {align} bytes."
+    );
+    let memory_size = memory.current_pages(ctx.as_context().inner) as usize * WASM_PAGE_SIZE;
+    ensure!(
+        ptr.checked_add(size).is_some_and(|end| end <= memory_size),
+        "Range of {size} bytes at {ptr:#x} was outside of guest memory ({memory_size} bytes)."
+    );
+    Ok(())
+}
+
+/// Determines whether a list of the given element type has the same representation in host
+/// memory as in guest memory, so that it may be copied directly.
+pub(crate) fn is_list_canonical(element: &Type) -> bool {
+    /// Whether this is a little-endian machine.
+    const LITTLE_ENDIAN: bool = cfg!(target_endian = "little");
+
+    match element {
+        Type::Bool => false,
+        Type::U8 => true,
+        Type::U16 => LITTLE_ENDIAN,
+        Type::U32 => LITTLE_ENDIAN,
+        Type::U64 => LITTLE_ENDIAN,
+        Type::S8 => true,
+        Type::S16 => LITTLE_ENDIAN,
+        Type::S32 => LITTLE_ENDIAN,
+        Type::S64 => LITTLE_ENDIAN,
+        Type::F32 => LITTLE_ENDIAN,
+        Type::F64 => LITTLE_ENDIAN,
+        Type::Char => false,
+        Type::String => false,
+        Type::Id(_) => false,
+        Type::ErrorContext => false,
+    }
 }
 
 /// A strongly-typed component model function that can be called to interact with [`Instance`]s.
@@ -1212,6 +2465,8 @@ impl<'a, C: AsContextMut> Bindgen for FuncBindgen<'a, C> {
 pub struct TypedFunc<P: ComponentList, R: ComponentList> {
     /// The inner function to call.
     inner: Func,
+    /// How to call the inner function with core values directly, if possible.
+    flat: Option<FlatCall>,
     /// A marker to prevent compiler errors.
     data: PhantomData<fn(P) -> R>,
 }
@@ -1239,6 +2494,7 @@ impl<P: ComponentList, R: ComponentList> TypedFunc<P, R> {
                     r.into_values(res)
                 },
             ),
+            flat: None,
             data: PhantomData,
         }
     }
@@ -1247,7 +2503,11 @@ impl<P: ComponentList, R: ComponentList> TypedFunc<P, R> {
     ///
     /// - The store did not match the original.
     /// - A trap occurred.
-    pub fn call(&self, ctx: impl AsContextMut, params: P) -> Result<R> {
+    pub fn call(&self, mut ctx: impl AsContextMut, params: P) -> Result<R> {
+        if let Some(flat) = self.flat {
+            return Func::called_by_host(&mut ctx, |ctx| self.inner.call_flat(ctx, flat, &params));
+        }
+
         let mut params_results = vec![Value::Bool(false); P::LEN + R::LEN];
         params.into_values(&mut params_results[0..P::LEN])?;
         let (params, results) = params_results.split_at_mut(P::LEN);
@@ -1255,6 +2515,23 @@ impl<P: ComponentList, R: ComponentList> TypedFunc<P, R> {
         R::from_values(results)
     }
 
+    /// Calls this function asynchronously, returning an error under the same conditions as
+    /// [`TypedFunc::call`]. See [`Func::call_async`] for details.
+    pub async fn call_async(&self, mut ctx: impl AsContextMut, params: P) -> Result<R> {
+        // Only guest functions lifted synchronously are called directly
+        if let Some(flat) = self.flat {
+            return Func::called_by_executor(ctx.as_context_mut(), |ctx| {
+                self.inner.call_flat(ctx, flat, &params)
+            });
+        }
+
+        let mut params_results = vec![Value::Bool(false); P::LEN + R::LEN];
+        params.into_values(&mut params_results[0..P::LEN])?;
+        let (params, results) = params_results.split_at_mut(P::LEN);
+        self.inner.call_async(ctx, params, results).await?;
+        R::from_values(results)
+    }
+
     /// Gets the underlying, untyped function.
     pub fn func(&self) -> Func {
         self.inner.clone()
@@ -1266,6 +2543,526 @@ impl<P: ComponentList, R: ComponentList> TypedFunc<P, R> {
     }
 }
 
+/// A call to a host function created with [`Func::new_borrowed`], through which the function
+/// accesses the store, reads its parameters, and writes its result.
+pub struct HostCall<'a, T: 'static, E: backend::WasmEngine> {
+    /// The type of the function.
+    ty: &'a FuncType,
+    /// Whether the result has been written.
+    result_set: bool,
+    /// The parameters that were lifted from the guest, if they have been read.
+    params: Option<Arc<[BorrowedParam]>>,
+    /// The arguments and results of the call.
+    inner: HostCallInner<'a, T, E>,
+}
+
+impl<'a, T: 'static, E: backend::WasmEngine> HostCall<'a, T, E> {
+    /// Calls a borrowing host function with component model values.
+    fn call_values(
+        ctx: StoreContextMut<'a, T, E>,
+        ty: &'a FuncType,
+        arguments: &'a [Value],
+        results: &'a mut [Value],
+        f: &BorrowedFunctionBacking<T, E>,
+    ) -> Result<()> {
+        let mut call = Self {
+            ty,
+            result_set: false,
+            params: None,
+            inner: HostCallInner::Values {
+                ctx,
+                arguments,
+                results,
+            },
+        };
+        f(&mut call)?;
+        call.finish()
+    }
+
+    /// Calls a borrowing host function from a guest, with the core values of the call.
+    fn call_flat(
+        ctx: StoreContextMut<'a, T, E>,
+        ty: &'a FuncType,
+        options: &'a GuestInvokeOptions,
+        flat: FlatCall,
+        arguments: &[wasm_runtime_layer::Value],
+        results: &'a mut [wasm_runtime_layer::Value],
+        f: &BorrowedFunctionBacking<T, E>,
+    ) -> Result<()> {
+        let retptr = if flat.retptr {
+            let ptr = require_matches!(
+                arguments.last(),
+                Some(wasm_runtime_layer::Value::I32(x)),
+                *x
+            );
+            Some(ptr as u32 as usize)
+        } else {
+            None
+        };
+
+        let mut call = Self {
+            ty,
+            result_set: false,
+            params: None,
+            inner: HostCallInner::Flat {
+                cx: FlatContext::new(
+                    ctx,
+                    options.memory.as_ref(),
+                    options.realloc.as_ref(),
+                    &options.flags,
+                    arguments.to_vec(),
+                ),
+                indirect_params: flat.indirect_params,
+                retptr,
+                results,
+            },
+        };
+        f(&mut call)?;
+        call.finish()
+    }
+
+    /// Gets the store context.
+    pub fn ctx(&mut self) -> StoreContextMut<'_, T, E> {
+        match &mut self.inner {
+            HostCallInner::Values { ctx, .. } => ctx.as_context_mut(),
+            HostCallInner::Flat { cx, .. } => cx.ctx_mut().as_context_mut(),
+        }
+    }
+
+    /// Gets the type of the function.
+    pub fn ty(&self) -> &FuncType {
+        self.ty
+    }
+
+    /// Reads the parameters of the call. When a guest passes strings or byte lists directly,
+    /// each is copied from guest memory into a buffer the first time that the parameters are
+    /// read. Later reads share the same buffers, so each call copies its parameters at most once.
+    pub fn params(&mut self) -> Result<HostParams<'a>> {
+        if let Some(params) = &self.params {
+            return Ok(HostParams {
+                inner: HostParamsInner::Flat(params.clone()),
+            });
+        }
+
+        let ty = self.ty;
+        let params = match &mut self.inner {
+            HostCallInner::Values { arguments, .. } => HostParamsInner::Values(arguments),
+            HostCallInner::Flat {
+                cx,
+                indirect_params,
+                ..
+            } => {
+                cx.rewind();
+                let mut fields = if *indirect_params {
+                    let ptr = cx.pop_u32()?;
+                    let layout = FlatLayout::record(
+                        &ty.params().iter().map(borrowed_layout).collect::<Vec<_>>(),
+                    )
+                    .context("Parameters could not be loaded from guest memory.")?;
+                    cx.validate_range(ptr, layout.size(), layout.align())?;
+                    Some(FlatFields::new(ptr))
+                } else {
+                    None
+                };
+
+                let mut params = Vec::with_capacity(ty.params().len());
+                for param in ty.params() {
+                    let offset = match &mut fields {
+                        Some(fields) => Some(fields.next(
+                            borrowed_layout(param).context("Parameter could not be lifted.")?,
+                        )),
+                        None => None,
+                    };
+                    params.push(BorrowedParam::lift(cx, param, offset)?);
+                }
+                HostParamsInner::Flat(self.params.insert(params.into()).clone())
+            }
+        };
+
+        Ok(HostParams { inner: params })
+    }
+
+    /// Sets the result of the call.
+    pub fn set_result<R: ComponentType>(&mut self, value: R) -> Result<()> {
+        self.write_result(R::ty(), BorrowedResult::Value(value.into_value()?))
+    }
+
+    /// Sets the result of the call to a `list<u8>`. When the call comes from a guest, the bytes
+    /// are written directly into memory allocated with the guest's `realloc`.
+    pub fn set_result_bytes(&mut self, value: &[u8]) -> Result<()> {
+        self.write_result(
+            ValueType::List(ListType::new(ValueType::U8)),
+            BorrowedResult::Bytes(value),
+        )
+    }
+
+    /// Sets the result of the call to a `string`. When the call comes from a guest, the string
+    /// is written directly into memory allocated with the guest's `realloc`.
+    pub fn set_result_str(&mut self, value: &str) -> Result<()> {
+        self.write_result(ValueType::String, BorrowedResult::Str(value))
+    }
+
+    /// Writes the result of the call, after checking it against the function's type.
+    fn write_result(&mut self, actual: ValueType, value: BorrowedResult<'_>) -> Result<()> {
+        let expected = self
+            .ty
+            .results()
+            .first()
+            .context("Function does not have a result.")?;
+        ensure!(
+            actual.is_subtype_of(expected),
+            crate::Error::TypeMismatch {
+                expected: expected.clone(),
+                actual
+            }
+        );
+        ensure!(!self.result_set, "Result was already set.");
+
+        match &mut self.inner {
+            HostCallInner::Values { results, .. } => results[0] = value.into_value(),
+            HostCallInner::Flat {
+                cx,
+                retptr: Some(ptr),
+                ..
+            } => {
+                let layout =
+                    borrowed_layout(expected).context("Result could not be stored directly.")?;
+                cx.validate_range(*ptr, layout.size(), layout.align())?;
+                value.lower(cx, Some(*ptr))?;
+            }
+            HostCallInner::Flat {
+                cx,
+                retptr: None,
+                results,
+                ..
+            } => cx.lower_into(results, |cx| value.lower(cx, None))?,
+        }
+
+        self.result_set = true;
+        Ok(())
+    }
+
+    /// Ensures that the function set its result before returning.
+    fn finish(self) -> Result<()> {
+        ensure!(
+            self.result_set || self.ty.results().is_empty(),
+            "Host function did not set its result."
+        );
+        Ok(())
+    }
+}
+
+/// Holds the arguments and results of a [`HostCall`].
+enum HostCallInner<'a, T: 'static, E: backend::WasmEngine> {
+    /// The call passes component model values.
+    Values {
+        /// The store context.
+        ctx: StoreContextMut<'a, T, E>,
+        /// The arguments of the call.
+        arguments: &'a [Value],
+        /// The results of the call.
+        results: &'a mut [Value],
+    },
+    /// The call passes core values and guest memory directly.
+    Flat {
+        /// The context which holds the core arguments, and accesses guest memory.
+        cx: FlatContext<'a, StoreContextMut<'a, T, E>>,
+        /// Whether the parameters are passed through a pointer into guest memory.
+        indirect_params: bool,
+        /// The pointer at which the results are stored, if any.
+        retptr: Option<usize>,
+        /// The core results of the call.
+        results: &'a mut [wasm_runtime_layer::Value],
+    },
+}
+
+/// The parameters of a [`HostCall`], from which strings and byte lists may be borrowed.
+pub struct HostParams<'a> {
+    /// The parameter values.
+    inner: HostParamsInner<'a>,
+}
+
+impl<'a> HostParams<'a> {
+    /// Gets the number of parameters.
+    pub fn len(&self) -> usize {
+        match &self.inner {
+            HostParamsInner::Values(x) => x.len(),
+            HostParamsInner::Flat(x) => x.len(),
+        }
+    }
+
+    /// Whether there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Converts the parameter at the given index to a Rust type.
+    pub fn get<P: ComponentType>(&self, index: usize) -> Result<P> {
+        match &self.inner {
+            HostParamsInner::Values(x) => P::from_value(Self::index(x, index)?),
+            HostParamsInner::Flat(x) => P::from_value(&Self::index(x, index)?.to_value()),
+        }
+    }
+
+    /// Borrows the `list<u8>` parameter at the given index.
+    pub fn bytes(&self, index: usize) -> Result<&[u8]> {
+        match &self.inner {
+            HostParamsInner::Values(x) => match Self::index(x, index)? {
+                Value::List(list) => list.typed(),
+                _ => bail!("Parameter {index} was not a list<u8>."),
+            },
+            HostParamsInner::Flat(x) => match Self::index(x, index)? {
+                BorrowedParam::Bytes(bytes) => std::result::Result::Ok(bytes),
+                _ => bail!("Parameter {index} was not a list<u8>."),
+            },
+        }
+    }
+
+    /// Borrows the `string` parameter at the given index.
+    pub fn str(&self, index: usize) -> Result<&str> {
+        match &self.inner {
+            HostParamsInner::Values(x) => match Self::index(x, index)? {
+                Value::String(value) => std::result::Result::Ok(value),
+                _ => bail!("Parameter {index} was not a string."),
+            },
+            HostParamsInner::Flat(x) => match Self::index(x, index)? {
+                BorrowedParam::String(value) => std::result::Result::Ok(value),
+                _ => bail!("Parameter {index} was not a string."),
+            },
+        }
+    }
+
+    /// Gets the parameter at the given index.
+    fn index<V>(params: &[V], index: usize) -> Result<&V> {
+        params
+            .get(index)
+            .with_context(|| format!("Parameter index {index} was out of bounds."))
+    }
+}
+
+/// Stores the parameters of a [`HostCall`].
+enum HostParamsInner<'a> {
+    /// The parameters are borrowed component model values.
+    Values(&'a [Value]),
+    /// The parameters were lifted directly from the guest, and are shared with the [`HostCall`].
+    Flat(Arc<[BorrowedParam]>),
+}
+
+/// A parameter lifted directly from the guest by a [`HostCall`].
+enum BorrowedParam {
+    /// A primitive value.
+    Value(Value),
+    /// A `list<u8>`, copied from guest memory.
+    Bytes(Vec<u8>),
+    /// A string, copied from guest memory.
+    String(String),
+}
+
+impl BorrowedParam {
+    /// Lifts a parameter of the given type from the next core values, or from guest memory
+    /// at `offset`.
+    fn lift<C: AsContextMut>(
+        cx: &mut FlatContext<'_, C>,
+        ty: &ValueType,
+        offset: Option<usize>,
+    ) -> Result<Self> {
+        /// Lifts a value of type `T` from the next core values, or from guest memory at `offset`.
+        fn lift<T: ComponentType, C: AsContextMut>(
+            cx: &mut FlatContext<'_, C>,
+            offset: Option<usize>,
+        ) -> Result<T> {
+            match offset {
+                Some(offset) => T::load_flat(cx, offset),
+                None => T::lift_flat(cx),
+            }
+        }
+
+        Ok(match ty {
+            ValueType::Bool => Self::Value(Value::Bool(lift(cx, offset)?)),
+            ValueType::S8 => Self::Value(Value::S8(lift(cx, offset)?)),
+            ValueType::U8 => Self::Value(Value::U8(lift(cx, offset)?)),
+            ValueType::S16 => Self::Value(Value::S16(lift(cx, offset)?)),
+            ValueType::U16 => Self::Value(Value::U16(lift(cx, offset)?)),
+            ValueType::S32 => Self::Value(Value::S32(lift(cx, offset)?)),
+            ValueType::U32 => Self::Value(Value::U32(lift(cx, offset)?)),
+            ValueType::S64 => Self::Value(Value::S64(lift(cx, offset)?)),
+            ValueType::U64 => Self::Value(Value::U64(lift(cx, offset)?)),
+            ValueType::F32 => Self::Value(Value::F32(lift(cx, offset)?)),
+            ValueType::F64 => Self::Value(Value::F64(lift(cx, offset)?)),
+            ValueType::Char => Self::Value(Value::Char(lift(cx, offset)?)),
+            ValueType::String => Self::String(lift(cx, offset)?),
+            ValueType::List(_) => Self::Bytes(lift(cx, offset)?),
+            _ => bail!("Parameter of type {ty:?} could not be lifted directly."),
+        })
+    }
+
+    /// Converts this parameter to a component model value.
+    fn to_value(&self) -> Value {
+        match self {
+            Self::Value(x) => x.clone(),
+            Self::Bytes(x) => Value::List(List::from(&x[..])),
+            Self::String(x) => Value::String(x.as_str().into()),
+        }
+    }
+}
+
+/// A result written by a [`HostCall`].
+enum BorrowedResult<'a> {
+    /// A component model value.
+    Value(Value),
+    /// A borrowed `list<u8>`.
+    Bytes(&'a [u8]),
+    /// A borrowed string.
+    Str(&'a str),
+}
+
+impl BorrowedResult<'_> {
+    /// Converts this result to a component model value.
+    fn into_value(self) -> Value {
+        match self {
+            Self::Value(x) => x,
+            Self::Bytes(x) => Value::List(List::from(x)),
+            Self::Str(x) => Value::String(x.into()),
+        }
+    }
+
+    /// Lowers this result to the next core values, or stores it in guest memory at `offset`.
+    fn lower<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: Option<usize>,
+    ) -> Result<()> {
+        /// Lowers a value of type `T` to the next core values, or stores it at `offset`.
+        fn lower<T: ComponentType, C: AsContextMut>(
+            cx: &mut FlatContext<'_, C>,
+            value: &T,
+            offset: Option<usize>,
+        ) -> Result<()> {
+            match offset {
+                Some(offset) => value.store_flat(cx, offset),
+                None => value.lower_flat(cx),
+            }
+        }
+
+        match self {
+            Self::Value(Value::Bool(x)) => lower(cx, x, offset),
+            Self::Value(Value::S8(x)) => lower(cx, x, offset),
+            Self::Value(Value::U8(x)) => lower(cx, x, offset),
+            Self::Value(Value::S16(x)) => lower(cx, x, offset),
+            Self::Value(Value::U16(x)) => lower(cx, x, offset),
+            Self::Value(Value::S32(x)) => lower(cx, x, offset),
+            Self::Value(Value::U32(x)) => lower(cx, x, offset),
+            Self::Value(Value::S64(x)) => lower(cx, x, offset),
+            Self::Value(Value::U64(x)) => lower(cx, x, offset),
+            Self::Value(Value::F32(x)) => lower(cx, x, offset),
+            Self::Value(Value::F64(x)) => lower(cx, x, offset),
+            Self::Value(Value::Char(x)) => lower(cx, x, offset),
+            Self::Value(Value::String(x)) => BorrowedResult::Str(x).lower(cx, offset),
+            Self::Value(Value::List(x)) => BorrowedResult::Bytes(x.typed()?).lower(cx, offset),
+            Self::Bytes(x) => match offset {
+                Some(offset) => cx.store_list(x, offset),
+                None => cx.lower_list(x),
+            },
+            Self::Str(x) => match offset {
+                Some(offset) => cx.store_string(x, offset),
+                None => cx.lower_string(x),
+            },
+            Self::Value(x) => bail!("Result of type {:?} could not be lowered directly.", x.ty()),
+        }
+    }
+}
+
+/// Gets the layout of a type which borrowing host functions pass directly to and from core
+/// values, or `None` if the type is passed as a [`Value`].
+fn borrowed_layout(ty: &ValueType) -> Option<FlatLayout> {
+    /// Gets the layout of the Rust type `T`.
+    fn layout<T: ComponentType>() -> Option<FlatLayout> {
+        T::LAYOUT
+    }
+
+    match ty {
+        ValueType::Bool => layout::<bool>(),
+        ValueType::S8 => layout::<i8>(),
+        ValueType::U8 => layout::<u8>(),
+        ValueType::S16 => layout::<i16>(),
+        ValueType::U16 => layout::<u16>(),
+        ValueType::S32 => layout::<i32>(),
+        ValueType::U32 => layout::<u32>(),
+        ValueType::S64 => layout::<i64>(),
+        ValueType::U64 => layout::<u64>(),
+        ValueType::F32 => layout::<f32>(),
+        ValueType::F64 => layout::<f64>(),
+        ValueType::Char => layout::<char>(),
+        ValueType::String => layout::<String>(),
+        ValueType::List(x) if x.element_ty() == ValueType::U8 => layout::<Vec<u8>>(),
+        _ => None,
+    }
+}
+
+/// The flags of a component instance, which the canonical ABI uses to prevent the instance
+/// from being reentered, and from calling imports while the host is lowering values into it
+/// or running its post-return functions.
+#[derive(Clone, Debug)]
+pub(crate) struct InstanceFlags(pub Global);
+
+impl InstanceFlags {
+    /// Gets the current flags.
+    fn get(&self, mut ctx: impl AsContextMut) -> Result<i32> {
+        Ok(require_matches!(
+            self.0.get(ctx.as_context_mut().inner),
+            wasm_runtime_layer::Value::I32(x),
+            x
+        ))
+    }
+
+    /// Replaces the current flags.
+    fn set(&self, mut ctx: impl AsContextMut, flags: i32) -> Result<()> {
+        self.0.set(
+            ctx.as_context_mut().inner,
+            wasm_runtime_layer::Value::I32(flags),
+        )
+    }
+
+    /// Whether the instance may currently call its imports.
+    pub(crate) fn may_leave(&self, ctx: impl AsContextMut) -> Result<bool> {
+        Ok(self.get(ctx)? & FLAG_MAY_LEAVE != 0)
+    }
+
+    /// Marks the instance as running one of its exports, failing if the instance is
+    /// already on the call stack.
+    fn enter(&self, mut ctx: impl AsContextMut) -> Result<()> {
+        let flags = self.get(&mut ctx)?;
+        ensure!(
+            flags & FLAG_MAY_ENTER != 0,
+            "Cannot reenter a component instance which is already on the call stack."
+        );
+        self.set(ctx, flags & !FLAG_MAY_ENTER)
+    }
+
+    /// Marks the instance as no longer running an export, once its post-return function
+    /// has completed.
+    fn exit(&self, mut ctx: impl AsContextMut) -> Result<()> {
+        let flags = self.get(&mut ctx)?;
+        self.set(ctx, flags | FLAG_MAY_ENTER)
+    }
+
+    /// Calls a core function of the instance, such as its reallocation or post-return
+    /// function, during which the instance may not call its imports.
+    pub(crate) fn call_without_leave(
+        &self,
+        mut ctx: impl AsContextMut,
+        func: &wasm_runtime_layer::Func,
+        arguments: &[wasm_runtime_layer::Value],
+        results: &mut [wasm_runtime_layer::Value],
+    ) -> Result<()> {
+        let flags = self.get(&mut ctx)?;
+        self.set(&mut ctx, flags & !FLAG_MAY_LEAVE)?;
+        let result = func.call(&mut ctx.as_context_mut().inner, arguments, results);
+        self.set(ctx, flags)?;
+        result
+    }
+}
+
 /// Details the function name and instance in which an error occurred.
 pub struct FuncError {
     /// The name of the function.
@@ -1396,8 +3193,116 @@ impl<const N: usize> ByteArray for [u8; N] {
 type FunctionBacking<T, E> =
     dyn 'static + Send + Sync + Fn(StoreContextMut<T, E>, &[Value], &mut [Value]) -> Result<()>;
 
+/// The type of a dynamic asynchronous host function.
+type AsyncFunctionBacking<T, E> = dyn 'static
+    + Send
+    + Sync
+    + for<'a> Fn(StoreContextMut<'a, T, E>, &'a [Value], &'a mut [Value]) -> HostFuture<'a, ()>;
+
+/// The type of a host function which borrows its parameters.
+type BorrowedFunctionBacking<T, E> =
+    dyn 'static + Send + Sync + for<'a> Fn(&mut HostCall<'a, T, E>) -> Result<()>;
+
 /// The type of the key used in the vector of host functions.
-type FunctionBackingKeyPair<T, E> = (Arc<AtomicUsize>, Arc<FunctionBacking<T, E>>);
+type FunctionBackingKeyPair<T, E> = (Arc<AtomicUsize>, HostFunction<T, E>);
+
+/// A future returned by an asynchronous host function, which may borrow the store until it completes.
+pub type HostFuture<'a, R> = Pin<Box<dyn 'a + Send + Future<Output = Result<R>>>>;
+
+/// A host function, which is either synchronous or asynchronous.
+pub(crate) enum HostFunction<T: 'static, E: backend::WasmEngine> {
+    /// A function which runs to completion when called.
+    Sync(Arc<FunctionBacking<T, E>>),
+    /// A function which returns a future.
+    Async(Arc<AsyncFunctionBacking<T, E>>),
+    /// A function which reads its parameters and writes its results through a [`HostCall`].
+    Borrowed(Arc<BorrowedFunctionBacking<T, E>>),
+}
+
+impl<T: 'static, E: backend::WasmEngine> HostFunction<T, E> {
+    /// Calls this function, blocking the current thread until it completes if it is asynchronous.
+    /// Within [`Func::call_async`], an asynchronous function which does not complete immediately
+    /// fails instead.
+    fn call_blocking(
+        &self,
+        mut ctx: StoreContextMut<T, E>,
+        ty: &FuncType,
+        arguments: &[Value],
+        results: &mut [Value],
+    ) -> Result<()> {
+        match self {
+            Self::Sync(f) => f(ctx, arguments, results),
+            Self::Async(f) => {
+                let within_async = within_async_call(&ctx);
+                run_blocking(within_async, f(ctx.as_context_mut(), arguments, results))
+            }
+            Self::Borrowed(f) => {
+                HostCall::call_values(ctx.as_context_mut(), ty, arguments, results, &**f)
+            }
+        }
+    }
+}
+
+impl<T: 'static, E: backend::WasmEngine> Clone for HostFunction<T, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Sync(f) => Self::Sync(f.clone()),
+            Self::Async(f) => Self::Async(f.clone()),
+            Self::Borrowed(f) => Self::Borrowed(f.clone()),
+        }
+    }
+}
+
+/// Determines whether a call to [`Func::call_async`] is in progress, so that the thread must not
+/// be parked while asynchronous host functions complete.
+pub(crate) fn within_async_call(ctx: impl AsContext) -> bool {
+    ctx.as_context().inner.data().async_calls > 0
+}
+
+/// Drives a future to completion from synchronous code. Within [`Func::call_async`], the future
+/// is polled once, and fails if it is pending, since parking the thread would block the
+/// executor which awaits the call.
+pub(crate) fn run_blocking<R>(
+    within_async: bool,
+    future: impl Future<Output = Result<R>>,
+) -> Result<R> {
+    if !within_async {
+        return block_on(future);
+    }
+
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut std::task::Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => bail!(
+            "Guest code waited synchronously for an asynchronous host function, which would block the executor that called `Func::call_async`. Import the function with the async ABI instead."
+        ),
+    }
+}
+
+/// Drives a future to completion on the current thread, parking the thread whenever the future is pending.
+fn block_on<F: Future>(future: F) -> F::Output {
+    /// Wakes a thread that is parked within [`block_on`].
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
 
 /// A vector for functions that automatically drops items when the references are dropped.
 pub(crate) struct FuncVec<T: 'static, E: backend::WasmEngine> {
@@ -1407,32 +3312,34 @@ pub(crate) struct FuncVec<T: 'static, E: backend::WasmEngine> {
 
 impl<T: 'static, E: backend::WasmEngine> FuncVec<T, E> {
     /// Pushes a new function into the vector.
-    pub fn push(
-        &mut self,
-        f: impl 'static + Send + Sync + Fn(StoreContextMut<T, E>, &[Value], &mut [Value]) -> Result<()>,
-    ) -> Arc<AtomicUsize> {
+    pub fn push(&mut self, f: HostFunction<T, E>) -> Arc<AtomicUsize> {
         if self.functions.capacity() == self.functions.len() {
             self.clear_dead_functions();
+            self.functions.reserve(self.functions.len().max(1));
         }
         let idx = Arc::new(AtomicUsize::new(self.functions.len()));
-        self.functions.push((idx.clone(), Arc::new(f)));
+        self.functions.push((idx.clone(), f));
         idx
     }
 
     /// Gets a function from the vector.
-    pub fn get(&self, value: &AtomicUsize) -> Arc<FunctionBacking<T, E>> {
+    pub fn get(&self, value: &AtomicUsize) -> HostFunction<T, E> {
         self.functions[value.load(Ordering::Acquire)].1.clone()
     }
 
-    /// Clears all dead functions from the vector, and doubles its capacity.
-    fn clear_dead_functions(&mut self) {
-        let new_len = 2 * self.functions.len();
-        let old = replace(&mut self.functions, Vec::with_capacity(new_len));
-        for (idx, val) in old {
-            if Arc::strong_count(&idx) > 1 {
-                idx.store(self.functions.len(), Ordering::Release);
-                self.functions.push((idx, val));
-            }
+    /// Gets the number of functions which are still referenced.
+    pub fn live_count(&self) -> usize {
+        self.functions
+            .iter()
+            .filter(|(idx, _)| Arc::strong_count(idx) > 1)
+            .count()
+    }
+
+    /// Clears all dead functions from the vector, updating the indices of the remaining ones.
+    pub fn clear_dead_functions(&mut self) {
+        self.functions.retain(|(idx, _)| Arc::strong_count(idx) > 1);
+        for (i, (idx, _)) in self.functions.iter().enumerate() {
+            idx.store(i, Ordering::Release);
         }
     }
 }
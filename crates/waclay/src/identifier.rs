@@ -226,6 +226,47 @@ impl InterfaceIdentifier {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Determines whether this interface and `other` are interchangeable under Cargo-style semver rules.
+    /// They must have the same package and interface names, and versions which share a major version,
+    /// or a minor version for `0.x` releases. Unversioned and pre-release interfaces only match exactly.
+    pub fn is_semver_compatible(&self, other: &Self) -> bool {
+        if self.name != other.name || self.package.name != other.package.name {
+            return false;
+        }
+
+        match (self.package.version(), other.package.version()) {
+            (Some(a), Some(b)) if a.pre.is_empty() && b.pre.is_empty() => {
+                match (a.major, a.minor) {
+                    (0, 0) => b.major == 0 && b.minor == 0 && a.patch == b.patch,
+                    (0, minor) => b.major == 0 && b.minor == minor,
+                    (major, _) => b.major == major,
+                }
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Selects the item whose identifier matches this one exactly, or otherwise the one with the newest
+    /// semver-compatible version.
+    pub(crate) fn select_compatible<'a, T>(
+        &self,
+        items: impl IntoIterator<Item = (&'a Self, T)>,
+    ) -> Option<(&'a Self, T)> {
+        let mut best = None::<(&'a Self, T)>;
+        for (id, item) in items {
+            if id == self {
+                return Some((id, item));
+            } else if self.is_semver_compatible(id)
+                && best
+                    .as_ref()
+                    .is_none_or(|(x, _)| x.package.version() < id.package.version())
+            {
+                best = Some((id, item));
+            }
+        }
+        best
+    }
 }
 
 impl TryFrom<&str> for InterfaceIdentifier {
@@ -292,3 +333,49 @@ impl std::fmt::Display for InterfaceIdentifier {
         }
     }
 }
+
+/// Identifies an item that a component imports, either from the root of its world or from an interface.
+#[derive(Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImportIdentifier {
+    /// The name of the item.
+    name: Arc<str>,
+    /// The interface from which the item is imported, if any.
+    interface: Option<InterfaceIdentifier>,
+}
+
+impl ImportIdentifier {
+    /// Creates a new import identifier for the given name and interface.
+    pub fn new(name: impl Into<Arc<str>>, interface: Option<InterfaceIdentifier>) -> Self {
+        Self {
+            name: name.into(),
+            interface,
+        }
+    }
+
+    /// Gets the name of the item.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the interface from which the item is imported, if any.
+    pub fn interface(&self) -> Option<&InterfaceIdentifier> {
+        self.interface.as_ref()
+    }
+}
+
+impl std::fmt::Debug for ImportIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::fmt::Display for ImportIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(inter) = &self.interface {
+            f.write_fmt(format_args!("{}.{}", inter, self.name()))
+        } else {
+            f.write_fmt(format_args!("{}", self.name()))
+        }
+    }
+}
@@ -75,27 +75,68 @@
 //! - Runtime generation of component interface types
 //! - Specialized list types for faster lifting/lowering
 //! - Structural equality of component interface types, as mandated by the spec
-//! - Support for guest resources
+//! - Support for guest resources, including handles that the host forwards from one instance to another
 //! - Support for strongly-typed host resources with destructors
+//! - Typed [`Resource`] and [`ResourceRef`] handles, which may appear in typed function signatures
+//! - Live handle counts per resource type, and reports of handles leaked by dropped instances
+//! - Reclamation of the host functions and resources that dropped instances held, though not of their core instances
+//! - Access to the calling instance from host functions through [`StoreContextMut::caller`]
+//! - Subtyping of records, variants, enums, and flags when linking and calling functions
+//! - Asynchronous host functions, which async exports await through imports lowered with the async ABI
+//! - Structured [`Error`]s for traps, type mismatches, and resource misuse, which callers may match upon
+//! - Guests using UTF-8, UTF-16, or Latin-1+UTF-16 strings, including fused adapters between them
+//! - Validation of values lifted from guest memory, with configurable [`LiftLimits`] per store
+//! - Argument and result conversions which are compiled once per function and reused across calls
+//! - Typed calls which convert primitives, strings, lists, tuples, and records directly to and from core values
+//! - Host functions which borrow string and byte list parameters, and write results directly into guest memory
+//! - Traps on reentrance into an instance, and on imports called while lowering values or running post-return functions
+//! - Futures and streams, with host readers and writers, and exports lifted with the async ABI's callback form
+//! - Waitable sets, on which async exports wait for blocked copies and subtasks to complete
+//! - Error contexts, whose debug messages may be read and created by the host
+//! - Fixed-size lists between the host and host functions, which correspond to Rust arrays
+//! - Serializing parsed components for fast reloading, with the `serde` feature
 //!
 //! The following features have yet to be implemented:
 //!
 //! - A macro for generating host bindings
 //! - More comprehensive tests
-//! - Subtyping
+//! - Guest components whose interfaces use fixed-size lists, which the `wasmtime-environ` translator does not yet accept
+//! - Reclaiming the core instances of dropped instances, which the backend store cannot remove
+//! - Components which export core modules, or import them from within instances
+//! - The `context`, `backpressure`, and `task.cancel` built-ins
+//! - Suspending guests that import asynchronous host functions synchronously, rather than with the async ABI
 
 /// Implements the Canonical ABI conventions for converting between guest and host types.
 mod abi;
 
+/// Implements futures, streams, and the subset of the async ABI's canonical built-ins which `waclay` supports.
+mod concurrent;
+
+/// Defines the structured error type raised by component operations.
+mod error;
+
+/// Converts Rust types directly to and from the flattened canonical ABI representation.
+mod flat;
+
 /// Provides the ability to create and call component model functions.
 mod func;
 
 /// Defines identifiers for component packages and interfaces.
 mod identifier;
 
+/// Compiles the canonical ABI instructions for calling functions into reusable plans.
+mod plan;
+
 /// Defines a macro that will either pattern-match results or throw an error.
 mod require_matches;
 
+/// Implements the serialization and deserialization of parsed components.
+#[cfg(feature = "serde")]
+mod serialize;
+
+/// Implements the string transcoding operations required by fused adapters.
+mod transcode;
+
 /// Defines all types related to the component model.
 mod types;
 
@@ -118,15 +159,21 @@ use wasmtime_environ::ModuleTranslation as WasmtimeModuleTranslation;
 use wit_component::*;
 use wit_parser::*;
 
+use crate::abi::SizeAlignTable;
+use crate::concurrent::*;
+pub use crate::concurrent::{ErrorContext, FutureReader, FutureWriter, StreamReader, StreamWriter};
+pub use crate::error::Error;
+pub use crate::flat::{FlatContext, FlatFields, FlatLayout};
 pub use crate::func::Func;
 pub use crate::func::*;
 pub use crate::identifier::PackageName;
 pub use crate::identifier::*;
+use crate::plan::*;
 use crate::require_matches::*;
 pub use crate::types::*;
 pub use crate::types::{FuncType, ValueType, VariantCase};
 pub use crate::values::*;
-pub use crate::values::{Enum, Flags, Record, Tuple, Value, Variant};
+pub use crate::values::{Enum, Flags, Record, Resource, ResourceRef, Tuple, Value, Variant};
 
 /// A parsed and validated WebAssembly component, which may be used to instantiate [`Instance`]s.
 #[derive(Clone, Debug)]
@@ -134,11 +181,11 @@ pub struct Component(Arc<ComponentInner>);
 
 impl Component {
     /// Creates a new component with the given engine and binary data.
+    /// Fails with [`Error::DecodeError`] if the bytes are not a valid component.
     pub fn new<E: backend::WasmEngine>(engine: &Engine<E>, bytes: &[u8]) -> Result<Self> {
-        let (inner, types) = Self::generate_component(engine, bytes)?;
-        Ok(Self(Arc::new(Self::generate_resources(
-            Self::load_exports(Self::extract_initializers(inner, &types)?, &types)?,
-        )?)))
+        Self::generate_component(engine, bytes)
+            .and_then(|(inner, types)| Self::initialize(inner, types))
+            .map_err(|x| Error::DecodeError(x).into())
     }
 
     /// The types and interfaces exported by this component.
@@ -157,42 +204,75 @@ impl Component {
         &self.0.package
     }
 
+    /// Fills in the initializers, exports, and resources of a component backing.
+    fn initialize(
+        inner: ComponentInner,
+        types: wasmtime_environ::component::ComponentTypes,
+    ) -> Result<Self> {
+        #[allow(unused_mut)]
+        let mut inner = Self::generate_resources(Self::load_exports(
+            Self::extract_initializers(inner, &types)?,
+            &types,
+        )?)?;
+
+        #[cfg(feature = "serde")]
+        {
+            inner.component_types = types;
+        }
+
+        Ok(Self(Arc::new(inner)))
+    }
+
     /// Parses the given bytes into a component, and creates an uninitialized component backing.
     fn generate_component<E: backend::WasmEngine>(
         engine: &Engine<E>,
         bytes: &[u8],
     ) -> Result<(ComponentInner, wasmtime_environ::component::ComponentTypes)> {
-        /// A counter that uniquely identifies components.
-        static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+        let adapter_vec = wasmtime_environ::ScopeVec::new();
+        let (translation, module_data, component_types, owned_resource_tables) =
+            Self::translate_modules(bytes, &adapter_vec)?;
+
+        // Check core modules first, since the WIT decoder rejects them with less helpful errors
+        Self::check_core_modules(&translation.component, &component_types)?;
 
         let decoded = wit_component::decode(bytes)
             .context("Could not decode component information from bytes.")?;
 
-        let (mut resolve, world_id) = match decoded {
+        let (resolve, world_id) = match decoded {
             DecodedWasm::WitPackage(..) => bail!("Cannot instantiate WIT package as module."),
             DecodedWasm::Component(resolve, id) => (resolve, id),
         };
 
-        let adapter_vec = wasmtime_environ::ScopeVec::new();
-        let (translation, module_data, component_types) =
-            Self::translate_modules(bytes, &adapter_vec)?;
+        let metadata = Self::generate_metadata(
+            resolve,
+            world_id,
+            module_data.values().map(|module| &module.module),
+        )?;
 
-        let export_mapping = Self::generate_export_mapping(&module_data);
-        let mut modules =
-            FxHashMap::with_capacity_and_hasher(module_data.len(), Default::default());
+        Ok((
+            Self::create_inner(
+                engine,
+                metadata,
+                translation,
+                module_data
+                    .into_iter()
+                    .map(|(_, module)| (module.wasm, module.module)),
+                owned_resource_tables,
+            )?,
+            component_types,
+        ))
+    }
 
-        for (id, module) in module_data {
-            modules.insert(
-                id,
-                ModuleTranslation {
-                    module: Module::new(engine, module.wasm)?,
-                    translation: module.module,
-                },
-            );
-        }
+    /// Derives the identifiers, type layouts, and export mapping of a component from its
+    /// resolved world and core modules.
+    fn generate_metadata<'a>(
+        mut resolve: Resolve,
+        world_id: WorldId,
+        modules: impl IntoIterator<Item = &'a wasmtime_environ::Module>,
+    ) -> Result<ComponentMetadata> {
+        let export_mapping = Self::generate_export_mapping(modules);
 
-        let mut size_align = SizeAlign::default();
-        size_align.fill(&resolve);
+        let size_align = SizeAlignTable::new(&resolve);
 
         let package = (&resolve.packages[resolve.worlds[world_id]
             .package
@@ -207,34 +287,81 @@ impl Component {
         let type_identifiers =
             Self::generate_type_identifiers(&mut resolve, &interface_identifiers);
 
-        Ok((
-            ComponentInner {
-                export_mapping,
-                export_names: FxHashMap::default(),
-                import_types: ComponentTypes::new(),
-                export_types: ComponentTypes::new(),
-                export_info: ExportTypes::default(),
-                extracted_memories: FxHashMap::default(),
-                extracted_reallocs: FxHashMap::default(),
-                extracted_post_returns: FxHashMap::default(),
-                id: ID_COUNTER.fetch_add(1, Ordering::AcqRel),
-                generated_trampolines: FxHashMap::default(),
-                instance_modules: wasmtime_environ::PrimaryMap::default(),
-                interface_identifiers,
-                type_identifiers,
-                modules,
-                resource_map: vec![
-                    TypeResourceTableIndex::from_u32(u32::MAX - 1);
-                    resolve.types.len()
-                ],
-                resolve,
-                size_align,
-                translation,
-                world_id,
-                package,
-            },
-            component_types,
-        ))
+        Ok(ComponentMetadata {
+            resolve,
+            world_id,
+            package,
+            interface_identifiers,
+            type_identifiers,
+            export_mapping,
+            size_align,
+        })
+    }
+
+    /// Creates an uninitialized component backing from the component's metadata, a component
+    /// translation, and the component's core modules.
+    fn create_inner<E: backend::WasmEngine>(
+        engine: &Engine<E>,
+        metadata: ComponentMetadata,
+        translation: ComponentTranslation,
+        module_data: impl IntoIterator<Item = (impl AsRef<[u8]>, wasmtime_environ::Module)>,
+        owned_resource_tables: Vec<bool>,
+    ) -> Result<ComponentInner> {
+        /// A counter that uniquely identifies components.
+        static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let mut modules = FxHashMap::default();
+        for (id, (wasm, module)) in module_data.into_iter().enumerate() {
+            modules.insert(
+                StaticModuleIndex::from_u32(id as u32),
+                ModuleTranslation {
+                    module: Module::new(engine, wasm.as_ref())?,
+                    translation: module,
+                    #[cfg(feature = "serde")]
+                    bytes: wasm.as_ref().to_vec(),
+                },
+            );
+        }
+
+        let ComponentMetadata {
+            resolve,
+            world_id,
+            package,
+            interface_identifiers,
+            type_identifiers,
+            export_mapping,
+            size_align,
+        } = metadata;
+
+        Ok(ComponentInner {
+            export_mapping,
+            export_names: FxHashMap::default(),
+            import_types: ComponentTypes::new(),
+            export_types: ComponentTypes::new(),
+            export_info: ExportTypes::default(),
+            extracted_memories: FxHashMap::default(),
+            extracted_reallocs: FxHashMap::default(),
+            extracted_post_returns: FxHashMap::default(),
+            extracted_callbacks: FxHashMap::default(),
+            channel_types: FxHashMap::default(),
+            id: ID_COUNTER.fetch_add(1, Ordering::AcqRel),
+            generated_trampolines: FxHashMap::default(),
+            instance_modules: wasmtime_environ::PrimaryMap::default(),
+            interface_identifiers,
+            type_identifiers,
+            modules,
+            resource_destructors: vec![None; owned_resource_tables.len()],
+            owned_resource_tables,
+            resource_map: vec![TypeResourceTableIndex::from_u32(u32::MAX - 1); resolve.types.len()],
+            resource_tables_by_type: FxHashMap::default(),
+            resolve,
+            size_align,
+            translation,
+            world_id,
+            package,
+            #[cfg(feature = "serde")]
+            component_types: wasmtime_environ::component::ComponentTypes::default(),
+        })
     }
 
     /// Generates type identifiers for all types in the resolve.
@@ -262,16 +389,16 @@ impl Component {
     }
 
     /// Creates a mapping from module index to entities, used to resolve component exports at link-time.
-    fn generate_export_mapping(
-        module_data: &wasmtime_environ::PrimaryMap<StaticModuleIndex, WasmtimeModuleTranslation>,
+    fn generate_export_mapping<'a>(
+        modules: impl IntoIterator<Item = &'a wasmtime_environ::Module>,
     ) -> FxHashMap<StaticModuleIndex, FxHashMap<wasmtime_environ::EntityIndex, String>> {
-        let mut export_mapping =
-            FxHashMap::with_capacity_and_hasher(module_data.len(), Default::default());
+        let mut export_mapping = FxHashMap::default();
 
-        for (idx, module) in module_data {
-            let entry: &mut FxHashMap<wasmtime_environ::EntityIndex, String> =
-                export_mapping.entry(idx).or_default();
-            for (name, index) in module.module.exports.clone() {
+        for (idx, module) in modules.into_iter().enumerate() {
+            let entry: &mut FxHashMap<wasmtime_environ::EntityIndex, String> = export_mapping
+                .entry(StaticModuleIndex::from_u32(idx as u32))
+                .or_default();
+            for (name, index) in module.exports.clone() {
                 entry.insert(index, name);
             }
         }
@@ -283,27 +410,25 @@ impl Component {
     fn generate_resources(mut inner: ComponentInner) -> Result<ComponentInner> {
         for (_key, item) in &inner.resolve.worlds[inner.world_id].imports {
             match item {
-                WorldItem::Type(x) => {
-                    if inner.resolve.types[*x].kind == TypeDefKind::Resource {
-                        if let Some(name) = &inner.resolve.types[*x].name {
-                            ensure!(
-                                inner
-                                    .import_types
-                                    .root
-                                    .resources
-                                    .insert(
-                                        name.as_str().into(),
-                                        ResourceType::from_resolve(
-                                            inner.type_identifiers[x.index()].clone(),
-                                            *x,
-                                            &inner,
-                                            None
-                                        )?
-                                    )
-                                    .is_none(),
-                                "Duplicate resource import."
-                            );
-                        }
+                WorldItem::Type(x) if inner.resolve.types[*x].kind == TypeDefKind::Resource => {
+                    if let Some(name) = &inner.resolve.types[*x].name {
+                        ensure!(
+                            inner
+                                .import_types
+                                .root
+                                .resources
+                                .insert(
+                                    name.as_str().into(),
+                                    ResourceType::from_resolve(
+                                        inner.type_identifiers[x.index()].clone(),
+                                        *x,
+                                        &inner,
+                                        None
+                                    )?
+                                )
+                                .is_none(),
+                            "Duplicate resource import."
+                        );
                     }
                 }
                 WorldItem::Interface { id: x, .. } => {
@@ -333,27 +458,25 @@ impl Component {
 
         for (_key, item) in &inner.resolve.worlds[inner.world_id].exports {
             match item {
-                WorldItem::Type(x) => {
-                    if inner.resolve.types[*x].kind == TypeDefKind::Resource {
-                        if let Some(name) = &inner.resolve.types[*x].name {
-                            ensure!(
-                                inner
-                                    .export_types
-                                    .root
-                                    .resources
-                                    .insert(
-                                        name.as_str().into(),
-                                        ResourceType::from_resolve(
-                                            inner.type_identifiers[x.index()].clone(),
-                                            *x,
-                                            &inner,
-                                            None
-                                        )?
-                                    )
-                                    .is_none(),
-                                "Duplicate resource export."
-                            );
-                        }
+                WorldItem::Type(x) if inner.resolve.types[*x].kind == TypeDefKind::Resource => {
+                    if let Some(name) = &inner.resolve.types[*x].name {
+                        ensure!(
+                            inner
+                                .export_types
+                                .root
+                                .resources
+                                .insert(
+                                    name.as_str().into(),
+                                    ResourceType::from_resolve(
+                                        inner.type_identifiers[x.index()].clone(),
+                                        *x,
+                                        &inner,
+                                        None
+                                    )?
+                                )
+                                .is_none(),
+                            "Duplicate resource export."
+                        );
                     }
                 }
                 WorldItem::Interface { id: x, .. } => {
@@ -381,6 +504,19 @@ impl Component {
             }
         }
 
+        let mut resource_tables_by_type = FxHashMap::default();
+        for (id, _) in &inner.resolve.types {
+            let table = inner.resource_map[id.index()];
+            if table.as_u32() < u32::MAX - 1 {
+                if let std::result::Result::Ok(ty) =
+                    ResourceType::from_resolve(None, id, &inner, None)
+                {
+                    resource_tables_by_type.entry(ty).or_insert(table);
+                }
+            }
+        }
+        inner.resource_tables_by_type = resource_tables_by_type;
+
         Ok(inner)
     }
 
@@ -440,7 +576,10 @@ impl Component {
         for initializer in &inner.translation.component.initializers {
             match initializer {
                 GlobalInitializer::InstantiateModule(InstantiateModule::Static(idx, _def)) => {
-                    inner.instance_modules.push(*idx);
+                    inner.instance_modules.push(Some(*idx));
+                }
+                GlobalInitializer::InstantiateModule(InstantiateModule::Import(..)) => {
+                    inner.instance_modules.push(None);
                 }
                 GlobalInitializer::ExtractMemory(ExtractMemory { index, export }) => {
                     ensure!(
@@ -488,7 +627,7 @@ impl Component {
                             assert_eq!(path.len(), 0);
                             ComponentImport {
                                 instance: None,
-                                name: import_name.as_str().into(),
+                                name: Self::function_name(import_name).into(),
                                 func: func.clone(),
                                 options: lowering_opts.clone(),
                             }
@@ -500,7 +639,7 @@ impl Component {
 
                             ComponentImport {
                                 instance: Some(inner.interface_identifiers[i.index()].clone()),
-                                name: path[0].as_str().into(),
+                                name: Self::function_name(&path[0]).into(),
                                 func: func.clone(),
                                 options: lowering_opts.clone(),
                             }
@@ -525,6 +664,7 @@ impl Component {
                         &imp.func,
                         index_ty,
                         &mut inner.resource_map,
+                        &mut inner.channel_types,
                     );
 
                     ensure!(
@@ -548,20 +688,32 @@ impl Component {
                         );
                     }
                 }
-                _ => bail!("Not yet implemented {initializer:?}."),
+                GlobalInitializer::ExtractCallback(ExtractCallback { index, def }) => {
+                    if let CoreDef::Export(export) = def {
+                        ensure!(
+                            inner
+                                .extracted_callbacks
+                                .insert(*index, export.clone())
+                                .is_none(),
+                            "Extracted the same callback more than once."
+                        );
+                    } else {
+                        bail!("Unexpected callback definition type.");
+                    }
+                }
+                // Tables are only used by `thread.spawn_indirect`, which has no trampoline that can be generated.
+                GlobalInitializer::ExtractTable(_) => {}
             }
         }
 
-        for trampoline in inner.generated_trampolines.values_mut() {
-            if let GeneratedTrampoline::ResourceDrop(x, destructor) = trampoline {
-                let resource = &types[*x];
-                if let Some(resource_idx) = inner
-                    .translation
-                    .component
-                    .defined_resource_index(resource.ty)
-                {
-                    *destructor = destructors.remove(&resource_idx);
-                }
+        for (i, destructor) in inner.resource_destructors.iter_mut().enumerate() {
+            let resource = &types[TypeResourceTableIndex::from_u32(i as u32)];
+            if let Some(resource_idx) = inner
+                .translation
+                .component
+                .defined_resource_index(resource.ty)
+            {
+                *destructor = destructors.get(&resource_idx).cloned();
             }
         }
 
@@ -586,11 +738,13 @@ impl Component {
                     index,
                     lower_ty,
                     options,
-                } => assert!(
-                    lowers.push((idx, &translation.component.options[*options], *lower_ty))
-                        == *index,
-                    "Indices did not match."
-                ),
+                } => {
+                    let options = &translation.component.options[*options];
+                    assert!(
+                        lowers.push((idx, options, *lower_ty)) == *index,
+                        "Indices did not match."
+                    );
+                }
                 Trampoline::ResourceNew(x) => {
                     output_trampolines.insert(idx, GeneratedTrampoline::ResourceNew(*x));
                 }
@@ -598,15 +752,174 @@ impl Component {
                     output_trampolines.insert(idx, GeneratedTrampoline::ResourceRep(*x));
                 }
                 Trampoline::ResourceDrop(x) => {
-                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceDrop(*x, None));
+                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceDrop(*x));
+                }
+                Trampoline::Transcoder {
+                    op,
+                    from,
+                    from64,
+                    to,
+                    to64,
+                } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::Transcoder {
+                            op: *op,
+                            from: *from,
+                            from64: *from64,
+                            to: *to,
+                            to64: *to64,
+                        },
+                    );
+                }
+                Trampoline::AlwaysTrap => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::AlwaysTrap);
+                }
+                Trampoline::ResourceTransferOwn => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceTransferOwn);
+                }
+                Trampoline::ResourceTransferBorrow => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceTransferBorrow);
+                }
+                Trampoline::ResourceEnterCall => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceEnterCall);
+                }
+                Trampoline::ResourceExitCall => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::ResourceExitCall);
+                }
+                Trampoline::TaskReturn { .. } => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::TaskReturn);
+                }
+                Trampoline::FutureTransfer
+                | Trampoline::StreamTransfer
+                | Trampoline::ErrorContextTransfer => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::TableTransfer);
+                }
+                Trampoline::ErrorContextNew { options, .. } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::ErrorContext(ErrorContextOp::New(
+                            translation.component.options[*options].clone(),
+                        )),
+                    );
+                }
+                Trampoline::ErrorContextDebugMessage { options, .. } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::ErrorContext(ErrorContextOp::DebugMessage(
+                            translation.component.options[*options].clone(),
+                        )),
+                    );
+                }
+                Trampoline::ErrorContextDrop { .. } => {
+                    output_trampolines
+                        .insert(idx, GeneratedTrampoline::ErrorContext(ErrorContextOp::Drop));
+                }
+                Trampoline::WaitableSetNew { .. } => {
+                    output_trampolines
+                        .insert(idx, GeneratedTrampoline::Waitable(WaitableOp::SetNew));
+                }
+                Trampoline::WaitableSetWait { options } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::Waitable(WaitableOp::SetWait(
+                            translation.component.options[*options].clone(),
+                        )),
+                    );
+                }
+                Trampoline::WaitableSetPoll { options } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::Waitable(WaitableOp::SetPoll(
+                            translation.component.options[*options].clone(),
+                        )),
+                    );
+                }
+                Trampoline::WaitableSetDrop { .. } => {
+                    output_trampolines
+                        .insert(idx, GeneratedTrampoline::Waitable(WaitableOp::SetDrop));
+                }
+                Trampoline::WaitableJoin { .. } => {
+                    output_trampolines.insert(idx, GeneratedTrampoline::Waitable(WaitableOp::Join));
+                }
+                Trampoline::ThreadYield { .. } => {
+                    output_trampolines
+                        .insert(idx, GeneratedTrampoline::Waitable(WaitableOp::Yield));
+                }
+                Trampoline::SubtaskDrop { .. } => {
+                    output_trampolines
+                        .insert(idx, GeneratedTrampoline::Waitable(WaitableOp::SubtaskDrop));
+                }
+                Trampoline::SubtaskCancel { .. } => {
+                    output_trampolines.insert(
+                        idx,
+                        GeneratedTrampoline::Waitable(WaitableOp::SubtaskCancel),
+                    );
+                }
+                _ => {
+                    let Some((table, op)) = Self::channel_trampoline(translation, trampoline)
+                    else {
+                        bail!("Trampoline not implemented.");
+                    };
+                    output_trampolines.insert(idx, GeneratedTrampoline::Channel(table, op));
                 }
-                _ => bail!("Trampoline not implemented."),
             }
         }
         Ok(lowers)
     }
 
+    /// Gets the table and operation of a canonical built-in for futures or streams.
+    fn channel_trampoline(
+        translation: &ComponentTranslation,
+        trampoline: &Trampoline,
+    ) -> Option<(ChannelTable, ChannelOp)> {
+        let options = |x: &OptionsIndex| translation.component.options[*x].clone();
+        Some(match trampoline {
+            Trampoline::StreamNew { ty } => (ChannelTable::Stream(*ty), ChannelOp::New),
+            Trampoline::StreamRead { ty, options: x } => {
+                (ChannelTable::Stream(*ty), ChannelOp::Read(options(x)))
+            }
+            Trampoline::StreamWrite { ty, options: x } => {
+                (ChannelTable::Stream(*ty), ChannelOp::Write(options(x)))
+            }
+            Trampoline::StreamCancelRead { ty, .. } => {
+                (ChannelTable::Stream(*ty), ChannelOp::CancelRead)
+            }
+            Trampoline::StreamCancelWrite { ty, .. } => {
+                (ChannelTable::Stream(*ty), ChannelOp::CancelWrite)
+            }
+            Trampoline::StreamDropReadable { ty } => {
+                (ChannelTable::Stream(*ty), ChannelOp::DropReadable)
+            }
+            Trampoline::StreamDropWritable { ty } => {
+                (ChannelTable::Stream(*ty), ChannelOp::DropWritable)
+            }
+            Trampoline::FutureNew { ty } => (ChannelTable::Future(*ty), ChannelOp::New),
+            Trampoline::FutureRead { ty, options: x } => {
+                (ChannelTable::Future(*ty), ChannelOp::Read(options(x)))
+            }
+            Trampoline::FutureWrite { ty, options: x } => {
+                (ChannelTable::Future(*ty), ChannelOp::Write(options(x)))
+            }
+            Trampoline::FutureCancelRead { ty, .. } => {
+                (ChannelTable::Future(*ty), ChannelOp::CancelRead)
+            }
+            Trampoline::FutureCancelWrite { ty, .. } => {
+                (ChannelTable::Future(*ty), ChannelOp::CancelWrite)
+            }
+            Trampoline::FutureDropReadable { ty } => {
+                (ChannelTable::Future(*ty), ChannelOp::DropReadable)
+            }
+            Trampoline::FutureDropWritable { ty } => {
+                (ChannelTable::Future(*ty), ChannelOp::DropWritable)
+            }
+            _ => return None,
+        })
+    }
+
     /// Translates the given bytes into component data and a set of core modules.
+    /// Also determines which resource tables belong to the instance defining their resource.
+    #[allow(clippy::type_complexity)]
     fn translate_modules<'a>(
         bytes: &'a [u8],
         scope: &'a wasmtime_environ::ScopeVec<u8>,
@@ -614,6 +927,7 @@ impl Component {
         ComponentTranslation,
         wasmtime_environ::PrimaryMap<StaticModuleIndex, WasmtimeModuleTranslation<'a>>,
         wasmtime_environ::component::ComponentTypes,
+        Vec<bool>,
     )> {
         let tunables = wasmtime_environ::Tunables::default_u32();
         let mut validator = Self::create_component_validator();
@@ -623,9 +937,83 @@ impl Component {
             .translate(bytes)
             .context("Could not translate input component to core WASM.")?;
 
+        let num_resource_tables = types.num_resource_tables();
         let component_types = types.finish(&translation.component).0;
 
-        Ok((translation, modules, component_types))
+        let owned_resource_tables = (0..num_resource_tables)
+            .map(|i| {
+                let table = &component_types[TypeResourceTableIndex::from_u32(i as u32)];
+                translation
+                    .component
+                    .defined_resource_index(table.ty)
+                    .is_some_and(|x| {
+                        translation.component.defined_resource_instances[x] == table.instance
+                    })
+            })
+            .collect();
+
+        Ok((translation, modules, component_types, owned_resource_tables))
+    }
+
+    /// Ensures that a component only imports core modules at its top level, and never exports them.
+    /// Core modules have no WIT representation, so other uses of them cannot be linked or exposed.
+    fn check_core_modules(
+        component: &wasmtime_environ::component::Component,
+        types: &wasmtime_environ::component::ComponentTypes,
+    ) -> Result<()> {
+        for (name, ty) in component.import_types.values() {
+            if let wasmtime_environ::component::TypeDef::ComponentInstance(idx) = ty {
+                if let Some(path) = Self::find_module_type(types, *idx, name) {
+                    bail!("Core module import `{path}` is nested within an instance, but only core modules imported at the top level of a component are supported.");
+                }
+            }
+        }
+
+        for (name, idx) in component.exports.raw_iter() {
+            if let Some(path) = Self::find_module_export(component, *idx, name) {
+                bail!("Core module export `{path}` is not supported.");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds a core module among the exports of the given instance type, or their own nested
+    /// exports, returning its dot-separated path.
+    fn find_module_type(
+        types: &wasmtime_environ::component::ComponentTypes,
+        instance: TypeComponentInstanceIndex,
+        path: &str,
+    ) -> Option<String> {
+        types[instance]
+            .exports
+            .iter()
+            .find_map(|(name, ty)| match ty {
+                wasmtime_environ::component::TypeDef::Module(_) => Some(format!("{path}.{name}")),
+                wasmtime_environ::component::TypeDef::ComponentInstance(idx) => {
+                    Self::find_module_type(types, *idx, &format!("{path}.{name}"))
+                }
+                _ => None,
+            })
+    }
+
+    /// Determines whether the given export is a core module or an instance which exports one,
+    /// returning the module's dot-separated path.
+    fn find_module_export(
+        component: &wasmtime_environ::component::Component,
+        index: ExportIndex,
+        path: &str,
+    ) -> Option<String> {
+        match &component.export_items[index] {
+            wasmtime_environ::component::Export::ModuleStatic { .. }
+            | wasmtime_environ::component::Export::ModuleImport { .. } => Some(path.to_string()),
+            wasmtime_environ::component::Export::Instance { exports, .. } => {
+                exports.raw_iter().find_map(|(name, idx)| {
+                    Self::find_module_export(component, *idx, &format!("{path}.{name}"))
+                })
+            }
+            _ => None,
+        }
     }
 
     /// Fills in all of the exports for a component.
@@ -636,9 +1024,20 @@ impl Component {
         Self::export_names(&mut inner);
 
         for (export_name, export_idx) in inner.translation.component.exports.raw_iter() {
-            let world_key = &inner.export_names[export_name];
-            let item = &inner.resolve.worlds[inner.world_id].exports[world_key];
             let export = &inner.translation.component.export_items[*export_idx];
+            let Some(world_key) = inner.export_names.get(export_name) else {
+                // Core modules and types have no associated world item
+                ensure!(
+                    !matches!(
+                        export,
+                        wasmtime_environ::component::Export::LiftedFunction { .. }
+                            | wasmtime_environ::component::Export::Instance { .. }
+                    ),
+                    "Export {export_name} was not present in component world."
+                );
+                continue;
+            };
+            let item = &inner.resolve.worlds[inner.world_id].exports[world_key];
             match export {
                 wasmtime_environ::component::Export::LiftedFunction { ty, func, options } => {
                     let f = match item {
@@ -652,9 +1051,10 @@ impl Component {
                         f,
                         *ty,
                         &mut inner.resource_map,
+                        &mut inner.channel_types,
                     );
 
-                    let export_name = Arc::<str>::from(export_name.as_str());
+                    let export_name = Arc::<str>::from(Self::function_name(export_name));
                     let ty = crate::types::FuncType::from_component(f, &inner, None)?;
 
                     ensure!(
@@ -702,6 +1102,10 @@ impl Component {
                                 ty,
                             } => (func, options, ty),
                             wasmtime_environ::component::Export::Type(_) => continue, // ignored
+                            wasmtime_environ::component::Export::ModuleStatic { .. }
+                            | wasmtime_environ::component::Export::ModuleImport { .. } => {
+                                bail!("Core module exports are not supported.")
+                            }
                             _ => unreachable!(),
                         };
 
@@ -713,6 +1117,7 @@ impl Component {
                             f,
                             *ty,
                             &mut inner.resource_map,
+                            &mut inner.channel_types,
                         );
                         let exp = ComponentExport {
                             options: inner.translation.component.options[*options].clone(),
@@ -723,7 +1128,7 @@ impl Component {
                             func: f.clone(),
                             ty: crate::types::FuncType::from_component(f, &inner, None)?,
                         };
-                        let func_name = Arc::<str>::from(func_name.as_str());
+                        let func_name = Arc::<str>::from(Self::function_name(func_name));
                         ensure!(
                             inner
                                 .export_types
@@ -752,12 +1157,10 @@ impl Component {
                 // ignore type exports for now
                 wasmtime_environ::component::Export::Type(_) => {}
 
-                // This can't be tested at this time so leave it unimplemented
-                wasmtime_environ::component::Export::ModuleStatic { .. } => {
-                    bail!("Not yet implemented.")
-                }
-                wasmtime_environ::component::Export::ModuleImport { .. } => {
-                    bail!("Not yet implemented.")
+                // Core modules have no WIT representation, so they cannot be exposed to the host
+                wasmtime_environ::component::Export::ModuleStatic { .. }
+                | wasmtime_environ::component::Export::ModuleImport { .. } => {
+                    bail!("Core module exports are not supported.")
                 }
             }
         }
@@ -776,33 +1179,42 @@ impl Component {
         inner.export_names = exports;
     }
 
+    /// Gets the name under which an exported or imported function is found. Async functions are
+    /// named with an `[async]` prefix, which callers need not spell out.
+    fn function_name(name: &str) -> &str {
+        name.strip_prefix("[async]").unwrap_or(name)
+    }
+
     /// Updates the mapping from type IDs to table indices based upon the resources
-    /// referenced by the provided function.
+    /// referenced by the provided function, and records the types of its futures and streams.
     fn update_resource_map(
         resolve: &Resolve,
         types: &wasmtime_environ::component::ComponentTypes,
         func: &Function,
         ty_func_idx: TypeFuncIndex,
         map: &mut Vec<TypeResourceTableIndex>,
+        channels: &mut FxHashMap<ChannelTable, wit_parser::TypeId>,
     ) {
         let params_ty = &types[types[ty_func_idx].params];
         for ((_, ty), iface_ty) in func.params.iter().zip(params_ty.types.iter()) {
-            Self::connect_resources(resolve, types, ty, iface_ty, map);
+            Self::connect_resources(resolve, types, ty, iface_ty, map, channels);
         }
         let results_ty = &types[types[ty_func_idx].results];
         for (ty, iface_ty) in func.result.iter().zip(results_ty.types.iter()) {
-            Self::connect_resources(resolve, types, ty, iface_ty, map);
+            Self::connect_resources(resolve, types, ty, iface_ty, map, channels);
         }
     }
 
     /// Inspects the given type (and any referenced subtypes) for resources,
     /// and records the table indices of those resources in the map.
+    /// The tables of any futures and streams are recorded along with their types.
     fn connect_resources(
         resolve: &Resolve,
         types: &wasmtime_environ::component::ComponentTypes,
         ty: &Type,
         iface_ty: &InterfaceType,
         map: &mut Vec<TypeResourceTableIndex>,
+        channels: &mut FxHashMap<ChannelTable, wit_parser::TypeId>,
     ) {
         let Type::Id(id) = ty else { return };
         match (&resolve.types[*id].kind, iface_ty) {
@@ -811,7 +1223,7 @@ impl Component {
             (TypeDefKind::Record(t1), InterfaceType::Record(t2)) => {
                 let t2 = &types[*t2];
                 for (f1, f2) in t1.fields.iter().zip(t2.fields.iter()) {
-                    Self::connect_resources(resolve, types, &f1.ty, &f2.ty, map);
+                    Self::connect_resources(resolve, types, &f1.ty, &f2.ty, map, channels);
                 }
             }
             (
@@ -823,7 +1235,7 @@ impl Component {
             (TypeDefKind::Tuple(t1), InterfaceType::Tuple(t2)) => {
                 let t2 = &types[*t2];
                 for (f1, f2) in t1.types.iter().zip(t2.types.iter()) {
-                    Self::connect_resources(resolve, types, f1, f2, map);
+                    Self::connect_resources(resolve, types, f1, f2, map, channels);
                 }
             }
             (TypeDefKind::Variant(t1), InterfaceType::Variant(t2)) => {
@@ -831,30 +1243,44 @@ impl Component {
                 for (f1, (_, f2_ty)) in t1.cases.iter().zip(t2.cases.iter()) {
                     if let Some(t1) = &f1.ty {
                         if let Some(f2) = f2_ty {
-                            Self::connect_resources(resolve, types, t1, f2, map);
+                            Self::connect_resources(resolve, types, t1, f2, map, channels);
                         }
                     }
                 }
             }
             (TypeDefKind::Option(t1), InterfaceType::Option(t2)) => {
                 let t2 = &types[*t2];
-                Self::connect_resources(resolve, types, t1, &t2.ty, map);
+                Self::connect_resources(resolve, types, t1, &t2.ty, map, channels);
             }
             (TypeDefKind::Result(t1), InterfaceType::Result(t2)) => {
                 let t2 = &types[*t2];
                 if let Some(t1) = &t1.ok {
-                    Self::connect_resources(resolve, types, t1, &t2.ok.unwrap(), map);
+                    Self::connect_resources(resolve, types, t1, &t2.ok.unwrap(), map, channels);
                 }
                 if let Some(t1) = &t1.err {
-                    Self::connect_resources(resolve, types, t1, &t2.err.unwrap(), map);
+                    Self::connect_resources(resolve, types, t1, &t2.err.unwrap(), map, channels);
                 }
             }
             (TypeDefKind::List(t1), InterfaceType::List(t2)) => {
                 let t2 = &types[*t2];
-                Self::connect_resources(resolve, types, t1, &t2.element, map);
+                Self::connect_resources(resolve, types, t1, &t2.element, map, channels);
+            }
+            (TypeDefKind::Future(t1), InterfaceType::Future(t2)) => {
+                channels.insert(ChannelTable::Future(*t2), *id);
+                let t2 = &types[types[*t2].ty];
+                if let (Some(t1), Some(t2)) = (t1, &t2.payload) {
+                    Self::connect_resources(resolve, types, t1, t2, map, channels);
+                }
+            }
+            (TypeDefKind::Stream(t1), InterfaceType::Stream(t2)) => {
+                channels.insert(ChannelTable::Stream(*t2), *id);
+                let t2 = &types[types[*t2].ty];
+                if let (Some(t1), Some(t2)) = (t1, &t2.payload) {
+                    Self::connect_resources(resolve, types, t1, t2, map, channels);
+                }
             }
             (TypeDefKind::Type(ty), _) => {
-                Self::connect_resources(resolve, types, ty, iface_ty, map);
+                Self::connect_resources(resolve, types, ty, iface_ty, map, channels);
             }
             (_, _) => unreachable!(),
         }
@@ -868,6 +1294,26 @@ impl Component {
     }
 }
 
+/// The metadata of a component which is derived from its WIT and core modules, and which is
+/// serialized along with the component so that it need not be derived again.
+struct ComponentMetadata {
+    /// The resolved WIT of the component, with type names moved into the type identifiers.
+    pub resolve: Resolve,
+    /// The ID of the primary exported world.
+    pub world_id: WorldId,
+    /// The package identifier for the component.
+    pub package: PackageIdentifier,
+    /// A mapping from interface ID to parsed identifier.
+    pub interface_identifiers: Vec<InterfaceIdentifier>,
+    /// A mapping from type ID to parsed identifier.
+    pub type_identifiers: Vec<Option<TypeIdentifier>>,
+    /// Maps from module indices to export indices for linking.
+    pub export_mapping:
+        FxHashMap<StaticModuleIndex, FxHashMap<wasmtime_environ::EntityIndex, String>>,
+    /// The size and alignment of component types.
+    pub size_align: SizeAlignTable,
+}
+
 /// Holds the inner, immutable state of an instantiated component.
 struct ComponentInner {
     /// Maps from module indices to export indices for linking.
@@ -887,16 +1333,28 @@ struct ComponentInner {
     /// The post-return functions that this component instantiates and references.
     pub extracted_post_returns:
         FxHashMap<RuntimePostReturnIndex, CoreExport<wasmtime_environ::EntityIndex>>,
+    /// The callbacks of async lifted functions that this component instantiates and references.
+    pub extracted_callbacks:
+        FxHashMap<RuntimeCallbackIndex, CoreExport<wasmtime_environ::EntityIndex>>,
+    /// A mapping from future and stream tables to the types that they hold.
+    pub channel_types: FxHashMap<ChannelTable, wit_parser::TypeId>,
+    /// Whether each resource table belongs to the component instance which defines its resource.
+    pub owned_resource_tables: Vec<bool>,
+    /// The destructor of each resource table's resource, if the resource is defined by this component.
+    pub resource_destructors: Vec<Option<CoreDef>>,
     /// A mapping from type indices to resource table indices.
     pub resource_map: Vec<TypeResourceTableIndex>,
+    /// A mapping from each resource to the first table which holds it, used to find guest destructors.
+    pub resource_tables_by_type: FxHashMap<ResourceType, TypeResourceTableIndex>,
     /// The set of trampolines required to use this resource.
     pub generated_trampolines: FxHashMap<TrampolineIndex, GeneratedTrampoline>,
     /// The component's globally-unique ID.
     pub id: u64,
     /// The imports of the component.
     pub import_types: ComponentTypes,
-    /// A mapping from runtime module indices to static indices.
-    pub instance_modules: wasmtime_environ::PrimaryMap<RuntimeInstanceIndex, StaticModuleIndex>,
+    /// A mapping from runtime module indices to static indices, or `None` for imported modules.
+    pub instance_modules:
+        wasmtime_environ::PrimaryMap<RuntimeInstanceIndex, Option<StaticModuleIndex>>,
     /// A mapping from interface ID to parsed identifier.
     pub interface_identifiers: Vec<InterfaceIdentifier>,
     /// A mapping from type ID to parsed identifier.
@@ -906,13 +1364,16 @@ struct ComponentInner {
     /// The resolved WIT of the component.
     pub resolve: Resolve,
     /// The size and alignment of component types.
-    pub size_align: SizeAlign,
+    pub size_align: SizeAlignTable,
     /// The translated component data.
     pub translation: ComponentTranslation,
     /// The ID of the primary exported world.
     pub world_id: Id<World>,
     /// The package identifier for the component.
     pub package: PackageIdentifier,
+    /// The type information of the translated component, retained so that it may be serialized.
+    #[cfg(feature = "serde")]
+    pub component_types: wasmtime_environ::component::ComponentTypes,
 }
 
 impl std::fmt::Debug for ComponentInner {
@@ -927,6 +1388,9 @@ struct ModuleTranslation {
     pub module: Module,
     /// The translation data for the module.
     pub translation: wasmtime_environ::Module,
+    /// The binary data of the module, retained so that the component may be serialized.
+    #[cfg(feature = "serde")]
+    pub bytes: Vec<u8>,
 }
 
 /// Details the set of types and functions exported by a component.
@@ -1027,6 +1491,8 @@ pub struct Linker {
     root: LinkerInstance,
     /// The set of interfaces against which to link.
     instances: FxHashMap<InterfaceIdentifier, LinkerInstance>,
+    /// Whether interfaces must match exactly, rather than by semver compatibility.
+    exact_versions: bool,
 }
 
 impl Linker {
@@ -1043,21 +1509,48 @@ impl Linker {
     /// Creates a new instance in the linker with the provided name. Returns an
     /// error if an instance with that name already exists.
     pub fn define_instance(&mut self, name: InterfaceIdentifier) -> Result<&mut LinkerInstance> {
-        if self.instance(&name).is_none() {
-            Ok(self.instances.entry(name).or_default())
-        } else {
+        if self.instances.contains_key(&name) {
             bail!("Duplicate instance definition.");
+        } else {
+            Ok(self.instances.entry(name).or_default())
         }
     }
 
-    /// Immutably obtains the instance with the given name, if any.
+    /// Immutably obtains the instance with the given name, if any. Unless exact
+    /// versions are required, an instance with the newest semver-compatible version
+    /// is returned when there is no exact match.
     pub fn instance(&self, name: &InterfaceIdentifier) -> Option<&LinkerInstance> {
-        self.instances.get(name)
+        let exact = self.instances.get(name);
+        if exact.is_some() || self.exact_versions {
+            exact
+        } else {
+            name.select_compatible(&self.instances).map(|(_, x)| x)
+        }
     }
 
-    /// Mutably obtains the instance with the given name, if any.
+    /// Mutably obtains the instance with the given name, if any. Unless exact
+    /// versions are required, an instance with the newest semver-compatible version
+    /// is returned when there is no exact match.
     pub fn instance_mut(&mut self, name: &InterfaceIdentifier) -> Option<&mut LinkerInstance> {
-        self.instances.get_mut(name)
+        let name = if self.exact_versions || self.instances.contains_key(name) {
+            name.clone()
+        } else {
+            name.select_compatible(&self.instances)?.0.clone()
+        };
+        self.instances.get_mut(&name)
+    }
+
+    /// Whether imported interfaces must match defined interfaces exactly. If `false`, which is the default,
+    /// an import is satisfied by the newest defined interface whose version is semver-compatible with it,
+    /// as determined by [`InterfaceIdentifier::is_semver_compatible`].
+    pub fn exact_versions(&self) -> bool {
+        self.exact_versions
+    }
+
+    /// Sets whether imported interfaces must match defined interfaces exactly,
+    /// rather than by semver compatibility.
+    pub fn set_exact_versions(&mut self, exact: bool) {
+        self.exact_versions = exact;
     }
 
     /// Gets an immutable iterator over all instances defined in this linker.
@@ -1074,60 +1567,569 @@ impl Linker {
         self.instances.iter_mut()
     }
 
+    /// Creates a new instance in the linker with the provided name, populated with the functions
+    /// and resources that `instance` exports under `interface`. This allows the exports of one
+    /// component instance to directly satisfy the imports of another. Returns an error if an
+    /// instance with that name already exists, or if the interface was not exported.
+    pub fn define_instance_from_exports(
+        &mut self,
+        name: InterfaceIdentifier,
+        instance: &Instance,
+        interface: &InterfaceIdentifier,
+    ) -> Result<&mut LinkerInstance> {
+        let exports = instance
+            .exports()
+            .instance(interface)
+            .with_context(|| format!("Instance did not export interface {interface}."))?;
+
+        let linker_instance = self.define_instance(name)?;
+        for (name, resource) in exports.resources() {
+            linker_instance.define_resource(name, resource)?;
+        }
+
+        for (name, func) in exports.funcs() {
+            linker_instance.define_func(name, func)?;
+        }
+
+        Ok(linker_instance)
+    }
+
     /// Instantiates a component for the provided store, filling in its imports with externals
     /// defined in this linker. All imports must be defined for instantiation to succeed.
     pub fn instantiate(&self, ctx: impl AsContextMut, component: &Component) -> Result<Instance> {
-        Instance::new(ctx, component, self)
+        self.instantiate_pre(component)?.instantiate(ctx)
     }
-}
 
-/// Describes a concrete interface which components may import.
-#[derive(Clone, Debug, Default)]
-pub struct LinkerInstance {
-    /// The functions in the interface.
-    functions: FxHashMap<Arc<str>, crate::func::Func>,
-    /// The resource types in the interface.
-    resources: FxHashMap<Arc<str>, ResourceType>,
-}
+    /// Resolves and type-checks the imports of a component against this linker, producing an
+    /// [`InstancePre`] that may be instantiated repeatedly without repeating this work.
+    /// Fails with [`Error::LinkError`] if the component cannot be linked.
+    pub fn instantiate_pre(&self, component: &Component) -> Result<InstancePre> {
+        InstancePre::new(component, self)
+    }
 
-impl LinkerInstance {
-    /// Defines a new function for this interface with the provided name.
-    /// Fails if the function already exists.
-    pub fn define_func(
+    /// Compares every import of a component against the definitions in this linker.
+    /// Fails with a [`LinkReport`] listing all missing functions, resources, and modules,
+    /// along with any functions whose types differ from those the component expects.
+    pub fn check(&self, component: &Component) -> Result<(), LinkReport> {
+        ResolvedImports::new(component, self).map(|_| ())
+    }
+
+    /// Defines every function and resource that the component imports, but which this linker lacks.
+    /// The functions fail with an error whenever they are called, and the resources are placeholder
+    /// host resources of which no values exist. This allows components to be instantiated when
+    /// only some of their imports will be used.
+    pub fn define_unknown_imports_as_traps(
         &mut self,
-        name: impl Into<Arc<str>>,
-        func: crate::func::Func,
+        ctx: impl AsContextMut,
+        component: &Component,
     ) -> Result<()> {
-        let n = Into::<Arc<str>>::into(name);
-        if self.functions.contains_key(&n) {
-            bail!("Duplicate function definition.");
-        }
-
-        self.functions.insert(n, func);
-        Ok(())
+        self.define_unknown_imports(ctx, component, |ctx, import, ty| {
+            Func::new(ctx, ty, move |_, _, _| {
+                bail!("Called unknown import {import}.")
+            })
+        })
     }
 
-    /// Gets the function in this interface with the given name, if any.
-    pub fn func(&self, name: impl AsRef<str>) -> Option<crate::func::Func> {
-        self.functions.get(name.as_ref()).cloned()
+    /// Defines every function and resource that the component imports, but which this linker lacks.
+    /// The functions do nothing and return the default value of each result type, such as zero, an
+    /// empty string, or `none`, and fail only if a result is a resource handle. The resources are
+    /// placeholder host resources of which no values exist.
+    pub fn define_unknown_imports_as_default_values(
+        &mut self,
+        ctx: impl AsContextMut,
+        component: &Component,
+    ) -> Result<()> {
+        self.define_unknown_imports(ctx, component, |ctx, import, ty| {
+            let result_tys = ty.results().to_vec();
+            Func::new(ctx, ty, move |_, _, results| {
+                for (result, ty) in results.iter_mut().zip(&result_tys) {
+                    *result = crate::values::Value::default_for(ty).with_context(|| {
+                        format!("Could not return from unknown import {import}.")
+                    })?;
+                }
+                Ok(())
+            })
+        })
     }
 
-    /// Defines a new resource type for this interface with the provided name.
-    /// Fails if the resource type already exists, or if the resource is abstract.
-    pub fn define_resource(
+    /// Defines every function and resource that the component imports, but which this linker lacks.
+    /// Resources are given placeholder host types, and functions are created with `define_func`.
+    fn define_unknown_imports<C: AsContextMut>(
         &mut self,
-        name: impl Into<Arc<str>>,
-        resource_ty: ResourceType,
+        mut ctx: C,
+        component: &Component,
+        mut define_func: impl FnMut(&mut C, ImportIdentifier, crate::types::FuncType) -> Func,
     ) -> Result<()> {
-        ensure!(
-            resource_ty.is_instantiated(),
-            "Cannot link with abstract resource type."
-        );
-
-        let n = Into::<Arc<str>>::into(name);
-        if self.resources.contains_key(&n) {
-            bail!("Duplicate resource definition.");
-        }
+        let mut resource_map = FxHashMap::default();
+        for (id, interface) in component
+            .imports()
+            .instances()
+            .map(|(id, x)| (Some(id), x))
+            .chain([(None, component.imports().root())])
+        {
+            let linker_instance = self.import_instance_mut(id)?;
+            for (name, resource) in interface.resources() {
+                let instantiated = match linker_instance.resource(name) {
+                    Some(instantiated) => instantiated,
+                    None => {
+                        let placeholder =
+                            ResourceType::new::<UnknownResource>(resource.name().cloned());
+                        linker_instance.define_resource(name, placeholder.clone())?;
+                        placeholder
+                    }
+                };
+                resource_map.insert(resource, instantiated);
+            }
+        }
+
+        for trampoline in component.0.generated_trampolines.values() {
+            let GeneratedTrampoline::ImportedFunction(import) = trampoline else {
+                continue;
+            };
+
+            let linker_instance = self.import_instance_mut(import.instance.as_ref())?;
+            if linker_instance.func(&import.name).is_none() {
+                let ty = crate::types::FuncType::from_component(
+                    &import.func,
+                    &component.0,
+                    Some(&resource_map),
+                )?;
+                let func = define_func(
+                    &mut ctx,
+                    ImportIdentifier::new(import.name.clone(), import.instance.clone()),
+                    ty,
+                );
+                linker_instance.define_func(import.name.clone(), func)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the linker instance which satisfies imports from the given interface, or the root
+    /// instance if none is specified. The instance is defined if it does not yet exist.
+    fn import_instance_mut(
+        &mut self,
+        id: Option<&InterfaceIdentifier>,
+    ) -> Result<&mut LinkerInstance> {
+        match id {
+            Some(id) if self.instance(id).is_some() => self
+                .instance_mut(id)
+                .context("Could not get linker instance."),
+            Some(id) => self.define_instance(id.clone()),
+            None => Ok(self.root_mut()),
+        }
+    }
+}
+
+/// The placeholder type of resources defined by [`Linker::define_unknown_imports_as_traps`]
+/// and [`Linker::define_unknown_imports_as_default_values`].
+struct UnknownResource;
+
+/// A component whose imports have been resolved against a [`Linker`], which may be
+/// used to efficiently create many [`Instance`]s.
+#[derive(Clone, Debug)]
+pub struct InstancePre(Arc<InstancePreInner>);
+
+impl InstancePre {
+    /// Resolves all imports of the given component with the specified linker.
+    fn new(component: &Component, linker: &Linker) -> Result<Self> {
+        let imports = ResolvedImports::new(component, linker).map_err(Error::LinkError)?;
+
+        let exported_resources = component
+            .exports()
+            .instances()
+            .flat_map(|(_, x)| x.resources())
+            .chain(component.exports().root().resources())
+            .map(|(_, x)| x)
+            .collect::<Vec<_>>();
+
+        // Without exported resources, the types are identical for every instance
+        let types = if exported_resources.is_empty() {
+            Some(Instance::generate_types(component, &imports.resource_map)?)
+        } else {
+            None
+        };
+
+        Ok(Self(Arc::new(InstancePreInner {
+            component: component.clone(),
+            exported_resources,
+            imports,
+            types,
+        })))
+    }
+
+    /// Gets the component associated with this pre-instance.
+    pub fn component(&self) -> &Component {
+        &self.0.component
+    }
+
+    /// Creates a new instance of the component for the provided store.
+    pub fn instantiate(&self, ctx: impl AsContextMut) -> Result<Instance> {
+        Instance::new(ctx, &self.0)
+    }
+}
+
+/// Holds the resolved imports of a component.
+#[derive(Debug)]
+struct InstancePreInner {
+    /// The component to instantiate.
+    component: Component,
+    /// The resource types which each instance exports, in abstract form.
+    exported_resources: Vec<ResourceType>,
+    /// The linker items which satisfy the component's imports.
+    imports: ResolvedImports,
+    /// The concrete types of every instance, if they do not depend upon exported resources.
+    types: Option<Arc<[crate::types::ValueType]>>,
+}
+
+/// The linker items which satisfy each import of a component.
+#[derive(Debug)]
+struct ResolvedImports {
+    /// The linker functions which satisfy each imported function trampoline.
+    functions: FxHashMap<TrampolineIndex, crate::func::Func>,
+    /// The linker modules which satisfy each imported core module.
+    modules: FxHashMap<RuntimeImportIndex, Module>,
+    /// A mapping from imported component resources to linker resources.
+    resource_map: FxHashMap<ResourceType, ResourceType>,
+}
+
+impl ResolvedImports {
+    /// Looks up every import of the component in the linker, recording any that are
+    /// missing or have the wrong type.
+    fn new(component: &Component, linker: &Linker) -> Result<Self, LinkReport> {
+        let mut report = LinkReport::default();
+
+        let mut resource_map = FxHashMap::default();
+        let mut missing_resources = FxHashSet::default();
+        for (id, interface) in component
+            .imports()
+            .instances()
+            .map(|(id, x)| (Some(id), x))
+            .chain([(None, component.imports().root())])
+        {
+            let linker_instance = match id {
+                Some(id) => linker.instance(id),
+                None => Some(linker.root()),
+            };
+
+            for (name, resource) in interface.resources() {
+                match linker_instance.and_then(|x| x.resource(name)) {
+                    Some(instantiated) => {
+                        resource_map.insert(resource, instantiated);
+                    }
+                    None => {
+                        missing_resources.insert(resource);
+                        report
+                            .missing_resources
+                            .push(ImportIdentifier::new(name, id.cloned()));
+                    }
+                }
+            }
+        }
+
+        let mut functions = FxHashMap::default();
+        for (index, trampoline) in &component.0.generated_trampolines {
+            let GeneratedTrampoline::ImportedFunction(import) = trampoline else {
+                continue;
+            };
+
+            let linker_instance = match &import.instance {
+                Some(id) => linker.instance(id),
+                None => Some(linker.root()),
+            };
+
+            let name = ImportIdentifier::new(import.name.clone(), import.instance.clone());
+            let Some(func) = linker_instance.and_then(|x| x.func(&import.name)) else {
+                report.missing_functions.push(name);
+                continue;
+            };
+
+            let expected = match crate::types::FuncType::from_component(
+                &import.func,
+                &component.0,
+                Some(&resource_map),
+            ) {
+                Result::Ok(x) => x,
+                // The expected type cannot be determined if a resource was missing, but that is reported separately
+                Err(error)
+                    if error
+                        .downcast_ref::<crate::types::UnmappedResource>()
+                        .is_some_and(|x| missing_resources.contains(&x.0)) =>
+                {
+                    continue;
+                }
+                Err(error) => {
+                    report.invalid_functions.push((name, format!("{error:#}")));
+                    continue;
+                }
+            };
+
+            if !func.ty().is_subtype_of(&expected) {
+                report.mismatched_functions.push(FuncTypeMismatch {
+                    import: name,
+                    expected,
+                    actual: func.ty(),
+                });
+                continue;
+            }
+
+            functions.insert(*index, func);
+        }
+
+        let mut modules = FxHashMap::default();
+        for initializer in &component.0.translation.component.initializers {
+            if let GlobalInitializer::InstantiateModule(InstantiateModule::Import(index, _)) =
+                initializer
+            {
+                let (name, module) = Self::resolve_module(&component.0, *index, linker);
+                match module {
+                    Some(module) => {
+                        modules.insert(*index, module);
+                    }
+                    None => report.missing_modules.push(name),
+                }
+            }
+        }
+
+        if report.is_empty() {
+            Result::Ok(Self {
+                functions,
+                modules,
+                resource_map,
+            })
+        } else {
+            report.sort();
+            Err(report)
+        }
+    }
+
+    /// Finds the linker module which satisfies an imported core module, returning the module's name
+    /// alongside it.
+    fn resolve_module(
+        component: &ComponentInner,
+        index: RuntimeImportIndex,
+        linker: &Linker,
+    ) -> (ImportIdentifier, Option<Module>) {
+        let (import_index, path) = &component.translation.component.imports[index];
+        let (import_name, _) = &component.translation.component.import_types[*import_index];
+
+        match &path[..] {
+            [] => (
+                ImportIdentifier::new(import_name.as_str(), None),
+                linker.root().module(import_name),
+            ),
+            // Core modules imported from instances are rejected when the component is loaded
+            _ => (
+                ImportIdentifier::new(format!("{import_name}.{}", path.join(".")), None),
+                None,
+            ),
+        }
+    }
+}
+
+/// Lists every import of a component which a [`Linker`] could not satisfy.
+#[derive(Clone, Debug, Default)]
+pub struct LinkReport {
+    /// The imported functions which were not defined.
+    missing_functions: Vec<ImportIdentifier>,
+    /// The imported resources which were not defined.
+    missing_resources: Vec<ImportIdentifier>,
+    /// The imported core modules which were not defined.
+    missing_modules: Vec<ImportIdentifier>,
+    /// The imported functions which were defined with the wrong type.
+    mismatched_functions: Vec<FuncTypeMismatch>,
+    /// The imported functions whose expected type could not be determined, and the reason why.
+    invalid_functions: Vec<(ImportIdentifier, String)>,
+}
+
+impl LinkReport {
+    /// Whether every import was satisfied.
+    pub fn is_empty(&self) -> bool {
+        self.missing_functions.is_empty()
+            && self.missing_resources.is_empty()
+            && self.missing_modules.is_empty()
+            && self.mismatched_functions.is_empty()
+            && self.invalid_functions.is_empty()
+    }
+
+    /// The imported functions which were not defined.
+    pub fn missing_functions(&self) -> &[ImportIdentifier] {
+        &self.missing_functions
+    }
+
+    /// The imported resources which were not defined.
+    pub fn missing_resources(&self) -> &[ImportIdentifier] {
+        &self.missing_resources
+    }
+
+    /// The imported core modules which were not defined.
+    pub fn missing_modules(&self) -> &[ImportIdentifier] {
+        &self.missing_modules
+    }
+
+    /// The imported functions which were defined with the wrong type.
+    pub fn mismatched_functions(&self) -> &[FuncTypeMismatch] {
+        &self.mismatched_functions
+    }
+
+    /// The imported functions whose expected type could not be determined, and the reason why.
+    pub fn invalid_functions(&self) -> &[(ImportIdentifier, String)] {
+        &self.invalid_functions
+    }
+
+    /// Sorts the entries of the report by name, so that it is deterministic.
+    fn sort(&mut self) {
+        self.missing_functions
+            .sort_by_cached_key(ToString::to_string);
+        self.missing_resources
+            .sort_by_cached_key(ToString::to_string);
+        self.missing_modules.sort_by_cached_key(ToString::to_string);
+        self.mismatched_functions
+            .sort_by_cached_key(|x| x.import.to_string());
+        self.invalid_functions
+            .sort_by_cached_key(|(x, _)| x.to_string());
+    }
+}
+
+impl std::fmt::Display for LinkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Could not link component:")?;
+        for name in &self.missing_functions {
+            write!(f, "\n  missing function {name}")?;
+        }
+        for name in &self.missing_resources {
+            write!(f, "\n  missing resource {name}")?;
+        }
+        for name in &self.missing_modules {
+            write!(f, "\n  missing module {name}")?;
+        }
+        for mismatch in &self.mismatched_functions {
+            write!(f, "\n  {mismatch}")?;
+        }
+        for (name, reason) in &self.invalid_functions {
+            write!(f, "\n  invalid function {name}: {reason}")?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+impl std::error::Error for LinkReport {}
+
+/// Describes an imported function which was defined with a type that is not a subtype of what the component expected.
+#[derive(Clone, Debug)]
+pub struct FuncTypeMismatch {
+    /// The name of the function.
+    import: ImportIdentifier,
+    /// The type that the component imports.
+    expected: crate::types::FuncType,
+    /// The type that the linker defined.
+    actual: crate::types::FuncType,
+}
+
+impl FuncTypeMismatch {
+    /// Gets the name of the function.
+    pub fn import(&self) -> &ImportIdentifier {
+        &self.import
+    }
+
+    /// Gets the type that the component imports.
+    pub fn expected(&self) -> &crate::types::FuncType {
+        &self.expected
+    }
+
+    /// Gets the type that the linker defined.
+    pub fn actual(&self) -> &crate::types::FuncType {
+        &self.actual
+    }
+}
+
+impl std::fmt::Display for FuncTypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "function {} had type {}, but expected {}",
+            self.import, self.actual, self.expected
+        )
+    }
+}
+
+/// Describes a concrete interface which components may import.
+#[derive(Clone, Debug, Default)]
+pub struct LinkerInstance {
+    /// The functions in the interface.
+    functions: FxHashMap<Arc<str>, crate::func::Func>,
+    /// The resource types in the interface.
+    resources: FxHashMap<Arc<str>, ResourceType>,
+    /// The core modules in the interface.
+    modules: FxHashMap<Arc<str>, Module>,
+}
+
+impl LinkerInstance {
+    /// Defines a new function for this interface with the provided name.
+    /// Fails if the function already exists.
+    pub fn define_func(
+        &mut self,
+        name: impl Into<Arc<str>>,
+        func: crate::func::Func,
+    ) -> Result<()> {
+        let n = Into::<Arc<str>>::into(name);
+        if self.functions.contains_key(&n) {
+            bail!("Duplicate function definition.");
+        }
+
+        self.functions.insert(n, func);
+        Ok(())
+    }
+
+    /// Defines a new function for this interface with the provided name, wrapping a closure
+    /// whose parameters and results are Rust types. See [`Func::wrap`] for details.
+    /// Fails if the function already exists.
+    pub fn func_wrap<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        &mut self,
+        ctx: C,
+        name: impl Into<Arc<str>>,
+        f: impl 'static + Send + Sync + Fn(StoreContextMut<C::UserState, C::Engine>, P) -> Result<R>,
+    ) -> Result<()> {
+        self.define_func(name, Func::wrap(ctx, f))
+    }
+
+    /// Defines a new asynchronous function for this interface with the provided name, wrapping a closure
+    /// whose parameters and results are Rust types. See [`Func::wrap_async`] for details.
+    /// Fails if the function already exists.
+    pub fn func_wrap_async<C: AsContextMut, P: ComponentList, R: ComponentList>(
+        &mut self,
+        ctx: C,
+        name: impl Into<Arc<str>>,
+        f: impl 'static
+            + Send
+            + Sync
+            + for<'a> Fn(StoreContextMut<'a, C::UserState, C::Engine>, P) -> HostFuture<'a, R>,
+    ) -> Result<()> {
+        self.define_func(name, Func::wrap_async(ctx, f))
+    }
+
+    /// Gets the function in this interface with the given name, if any.
+    pub fn func(&self, name: impl AsRef<str>) -> Option<crate::func::Func> {
+        self.functions.get(name.as_ref()).cloned()
+    }
+
+    /// Defines a new resource type for this interface with the provided name.
+    /// Fails if the resource type already exists, or if the resource is abstract.
+    pub fn define_resource(
+        &mut self,
+        name: impl Into<Arc<str>>,
+        resource_ty: ResourceType,
+    ) -> Result<()> {
+        ensure!(
+            resource_ty.is_instantiated(),
+            "Cannot link with abstract resource type."
+        );
+
+        let n = Into::<Arc<str>>::into(name);
+        if self.resources.contains_key(&n) {
+            bail!("Duplicate resource definition.");
+        }
 
         self.resources.insert(n, resource_ty);
         Ok(())
@@ -1138,6 +2140,25 @@ impl LinkerInstance {
         self.resources.get(name.as_ref()).cloned()
     }
 
+    /// Defines a new core module for this interface with the provided name, which
+    /// components that import a core module may instantiate. Components may only import
+    /// core modules at their top level, so only modules defined on [`Linker::root_mut`]
+    /// satisfy them. Fails if the module already exists.
+    pub fn define_module(&mut self, name: impl Into<Arc<str>>, module: Module) -> Result<()> {
+        let n = Into::<Arc<str>>::into(name);
+        if self.modules.contains_key(&n) {
+            bail!("Duplicate module definition.");
+        }
+
+        self.modules.insert(n, module);
+        Ok(())
+    }
+
+    /// Gets the core module in this interface with the given name, if any.
+    pub fn module(&self, name: impl AsRef<str>) -> Option<Module> {
+        self.modules.get(name.as_ref()).cloned()
+    }
+
     /// Iterates over all associated functions by name.
     pub fn funcs(&self) -> impl Iterator<Item = (&'_ str, crate::func::Func)> {
         self.functions.iter().map(|(k, v)| (&**k, v.clone()))
@@ -1147,24 +2168,39 @@ impl LinkerInstance {
     pub fn resources(&self) -> impl Iterator<Item = (&'_ str, ResourceType)> {
         self.resources.iter().map(|(k, v)| (&**k, v.clone()))
     }
+
+    /// Iterates over all associated core modules by name.
+    pub fn modules(&self) -> impl Iterator<Item = (&'_ str, Module)> {
+        self.modules.iter().map(|(k, v)| (&**k, v.clone()))
+    }
 }
 
 /// An instantiated WebAssembly component.
 #[derive(Clone, Debug)]
 pub struct Instance(Arc<InstanceInner>);
 
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Instance {}
+
 impl Instance {
-    /// Creates a new instance for the given component with the specified linker.
-    pub(crate) fn new(
-        mut ctx: impl AsContextMut,
-        component: &Component,
-        linker: &Linker,
-    ) -> Result<Self> {
+    /// Creates a new instance for the given component with the specified resolved imports.
+    fn new(mut ctx: impl AsContextMut, pre: &InstancePreInner) -> Result<Self> {
         /// A counter that uniquely identifies instances.
         static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+        let component = &pre.component;
         let mut instance_flags = wasmtime_environ::PrimaryMap::default();
-        for _i in 0..component.0.instance_modules.len() {
+        for _i in 0..component
+            .0
+            .translation
+            .component
+            .num_runtime_component_instances
+        {
             instance_flags.push(Global::new(
                 ctx.as_context_mut().inner,
                 wasm_runtime_layer::Value::I32(
@@ -1176,12 +2212,14 @@ impl Instance {
         }
 
         let id = ID_COUNTER.fetch_add(1, Ordering::AcqRel);
-        let map = Self::create_resource_instantiation_map(id, component, linker)?;
-        let types = Self::generate_types(component, &map)?;
+        let map = Self::create_resource_instantiation_map(id, pre)?;
+        let types = match &pre.types {
+            Some(types) => types.clone(),
+            None => Self::generate_types(component, &map)?,
+        };
         let resource_tables = Mutex::new(vec![
             HandleTable::default();
-            component.0.translation.component.num_resources
-                as usize
+            component.0.owned_resource_tables.len()
         ]);
 
         let instance = InstanceInner {
@@ -1190,14 +2228,20 @@ impl Instance {
             id,
             instances: Default::default(),
             instance_flags,
+            trampoline_states: TrampolineStates::default(),
             state_table: Arc::new(StateTable {
+                instance: OnceLock::new(),
                 dropped: AtomicBool::new(false),
                 resource_tables,
+                call_scopes: Mutex::default(),
+                waitables: Mutex::default(),
+                error_contexts: Mutex::default(),
+                task: Mutex::default(),
             }),
             types,
             store_id: ctx.as_context().inner.data().id,
         };
-        let initialized = Self::global_initialize(instance, &mut ctx, linker, &map)?;
+        let initialized = Self::global_initialize(instance, &mut ctx, pre, &map)?;
         let exported = Self::load_exports(initialized, &ctx, &map)?;
 
         Ok(Self(Arc::new_cyclic(|w| {
@@ -1215,36 +2259,118 @@ impl Instance {
         &self.0.exports
     }
 
+    /// Counts the resource handles that this instance currently holds, grouped by resource type.
+    /// Every resource type for which the instance has a handle table is listed, even if no
+    /// handles of that type are alive.
+    pub fn resource_stats(&self) -> Result<Vec<ResourceStats>> {
+        let tables = self
+            .0
+            .state_table
+            .resource_tables
+            .try_lock()
+            .map_err(|_| Error::resource("Could not lock resource tables."))?;
+
+        let mut stats = Vec::<ResourceStats>::new();
+        for table in &*tables {
+            let Some(ty) = table.ty() else {
+                continue;
+            };
+
+            let index = match stats.iter().position(|x| &x.ty == ty) {
+                Some(index) => index,
+                None => {
+                    stats.push(ResourceStats::new(ty.clone()));
+                    stats.len() - 1
+                }
+            };
+
+            for (_, element) in table.iter() {
+                stats[index].record(element.own, element.lend_count as usize);
+            }
+        }
+
+        Ok(stats)
+    }
+
     /// Drops the instance and all of its owned resources, removing its data from the given store.
     /// Returns the list of errors that occurred while dropping owned resources, but continues
-    /// until all resources have been dropped.
-    pub fn drop<T, E: backend::WasmEngine>(&self, ctx: &mut Store<T, E>) -> Result<Vec<Error>> {
-        ensure!(self.0.store_id == ctx.inner.data().id, "Incorrect store.");
-        self.0.state_table.dropped.store(true, Ordering::Release);
-
-        let mut errors = Vec::new();
+    /// until all resources have been dropped. Each error may be downcast to an [`Error::Trap`].
+    ///
+    /// Afterward, the instance's functions can no longer be called, and the host functions that it
+    /// imported are released. This does not reclaim everything that the instance allocated: its core
+    /// instances, memories, and trampolines remain in the backend store, which cannot remove them, so
+    /// a [`Store`] that repeatedly instantiates components still grows by that amount each time. See
+    /// [`Store::backend_core_instance_count`] and [`Store::backend_trampoline_count`].
+    pub fn drop<T, E: backend::WasmEngine>(
+        &self,
+        ctx: &mut Store<T, E>,
+    ) -> Result<Vec<anyhow::Error>> {
+        let report = self.drop_with_leak_report(ctx)?;
+        Ok(report.errors.into_iter().map(anyhow::Error::from).collect())
+    }
 
-        let mut tables = self
-            .0
-            .state_table
+    /// Drops the instance like [`Instance::drop`], but additionally lists the resource handles
+    /// which the instance still held beforehand. A non-empty list usually means that the guest,
+    /// or the host on its behalf, forgot to drop some resources.
+    pub fn drop_with_leak_report<T, E: backend::WasmEngine>(
+        &self,
+        ctx: &mut Store<T, E>,
+    ) -> Result<LeakReport> {
+        ensure!(self.0.store_id == ctx.inner.data().id, Error::StoreMismatch);
+
+        // Acquire every table before changing any of them, so that a failure leaves the instance intact
+        let state_table = &self.0.state_table;
+        let mut waitables = state_table
+            .waitables
+            .try_lock()
+            .map_err(|_| Error::resource("Could not lock future and stream handles."))?;
+        let mut tables = state_table
             .resource_tables
             .try_lock()
-            .expect("Could not lock resource tables.");
+            .map_err(|_| Error::resource("Could not lock resource tables."))?;
+        let mut error_contexts = state_table
+            .error_contexts
+            .try_lock()
+            .map_err(|_| Error::resource("Could not lock error contexts."))?;
+
+        waitables.drop_all(&mut ctx.inner.data_mut().channels);
+
+        let mut errors = Vec::new();
+        let mut handles = Vec::new();
         for table in &mut *tables {
+            if let Some(ty) = table.ty() {
+                handles.extend(table.iter().map(|(index, element)| LiveHandle {
+                    ty: ty.clone(),
+                    index: index as u32,
+                    own: element.own,
+                    lend_count: element.lend_count as u32,
+                }));
+            }
+
             if let Some(destructor) = table.destructor.as_ref() {
-                for (_, val) in table.array.iter() {
+                for (_, val) in table.array.iter().filter(|(_, x)| x.own) {
                     if let Err(x) = destructor.call(
                         &mut ctx.inner,
                         &[wasm_runtime_layer::Value::I32(val.rep)],
                         &mut [],
                     ) {
-                        errors.push(x);
+                        errors.push(Error::Trap(x));
                     }
                 }
             }
+            table.array.clear();
         }
 
-        Ok(errors)
+        error_contexts.clear();
+        drop((waitables, tables, error_contexts));
+        state_table.dropped.store(true, Ordering::Release);
+
+        // Release the imported functions and other state that the instance's trampolines use,
+        // so that host functions which are no longer referenced elsewhere may be reclaimed.
+        self.0.trampoline_states.clear();
+        ctx.clear_dead_host_functions();
+
+        Ok(LeakReport { errors, handles })
     }
 
     /// Fills the export tables with pointers to the final instance.
@@ -1253,7 +2379,12 @@ impl Instance {
             inst.instance = final_ptr.clone();
         }
 
-        inner.exports.root.instance = final_ptr;
+        inner.exports.root.instance = final_ptr.clone();
+        inner
+            .state_table
+            .instance
+            .set(final_ptr)
+            .expect("Instance pointer was already set.");
         inner
     }
 
@@ -1291,41 +2422,12 @@ impl Instance {
     /// since resource types are unique per instantiation.
     fn create_resource_instantiation_map(
         instance_id: u64,
-        component: &Component,
-        linker: &Linker,
+        pre: &InstancePreInner,
     ) -> Result<FxHashMap<ResourceType, ResourceType>> {
-        let mut types = FxHashMap::default();
-
-        for (name, resource) in component.imports().root().resources() {
-            let instantiated = linker
-                .root()
-                .resource(name)
-                .ok_or_else(|| Error::msg(format!("Could not find resource {name} in linker.")))?;
-            types.insert(resource, instantiated);
-        }
+        let mut types = pre.imports.resource_map.clone();
 
-        for (id, interface) in component.imports().instances() {
-            for (name, resource) in interface.resources() {
-                let instantiated = linker
-                    .instance(id)
-                    .and_then(|x| x.resource(name))
-                    .ok_or_else(|| {
-                        Error::msg(format!(
-                            "Could not find resource {name} from interface {id:?} in linker."
-                        ))
-                    })?;
-                types.insert(resource, instantiated);
-            }
-        }
-
-        for (_, resource) in component
-            .exports()
-            .instances()
-            .flat_map(|(_, x)| x.resources())
-            .chain(component.exports().root().resources())
-        {
-            let instantiated = resource.instantiate(instance_id)?;
-            types.insert(resource, instantiated);
+        for resource in &pre.exported_resources {
+            types.insert(resource.clone(), resource.instantiate(instance_id)?);
         }
 
         Ok(types)
@@ -1352,11 +2454,12 @@ impl Instance {
             );
         }
         for (name, res) in &inner.component.0.export_types.root.resources {
+            let instantiated = Self::instantiate_exported_resource(&inner, res)?;
             inner
                 .exports
                 .root
                 .resources
-                .insert(name.clone(), res.instantiate(inner.id)?);
+                .insert(name.clone(), instantiated);
         }
 
         let mut generated_functions = Vec::new();
@@ -1377,13 +2480,14 @@ impl Instance {
 
         for (inst_name, inst) in &inner.component.0.export_types.instances {
             for (name, res) in &inst.resources {
+                let instantiated = Self::instantiate_exported_resource(&inner, res)?;
                 inner
                     .exports
                     .instances
                     .entry(inst_name.clone())
                     .or_insert_with(ExportInstance::new)
                     .resources
-                    .insert(name.clone(), res.instantiate(inner.id)?);
+                    .insert(name.clone(), instantiated);
             }
         }
 
@@ -1399,27 +2503,58 @@ impl Instance {
         Ok(inner)
     }
 
-    /// Creates the options used to invoke an imported function.
-    fn import_function(
+    /// Instantiates an exported resource type for this instance, attaching the destructor
+    /// that this instance uses so that importers of the resource can drop their handles.
+    fn instantiate_exported_resource(
+        inner: &InstanceInner,
+        resource: &ResourceType,
+    ) -> Result<ResourceType> {
+        let tables = inner
+            .state_table
+            .resource_tables
+            .try_lock()
+            .map_err(|_| Error::resource("Could not get access to resource tables."))?;
+
+        let destructor = inner
+            .component
+            .0
+            .resource_tables_by_type
+            .get(resource)
+            .and_then(|idx| tables[idx.as_u32() as usize].destructor().cloned());
+
+        resource
+            .instantiate(inner.id)?
+            .with_guest_destructor(destructor)
+    }
+
+    /// Gets the memory, realloc, and post-return core exports used by a lifted or lowered function.
+    fn canonical_exports(
         inner: &InstanceInner,
         ctx: impl AsContext,
         options: &CanonicalOptions,
-        func: &Function,
-    ) -> GuestInvokeOptions {
+    ) -> Result<(
+        Option<Memory>,
+        Option<wasm_runtime_layer::Func>,
+        Option<wasm_runtime_layer::Func>,
+    )> {
         let (memory, realloc) = match &options.data_model {
             wasmtime_environ::component::CanonicalOptionsDataModel::LinearMemory(lm) => {
-                let memory = lm.memory.map(|idx| {
-                    Self::core_export(inner, &ctx, &inner.component.0.extracted_memories[&idx])
-                        .expect("Could not get runtime memory export.")
-                        .into_memory()
-                        .expect("Export was not of memory type.")
-                });
-                let realloc = lm.realloc.map(|idx| {
-                    Self::core_export(inner, &ctx, &inner.component.0.extracted_reallocs[&idx])
-                        .expect("Could not get runtime realloc export.")
-                        .into_func()
-                        .expect("Export was not of func type.")
-                });
+                let memory = lm
+                    .memory
+                    .map(|idx| {
+                        Self::core_export(inner, &ctx, &inner.component.0.extracted_memories[&idx])
+                            .and_then(Extern::into_memory)
+                            .context("Could not get runtime memory export.")
+                    })
+                    .transpose()?;
+                let realloc = lm
+                    .realloc
+                    .map(|idx| {
+                        Self::core_export(inner, &ctx, &inner.component.0.extracted_reallocs[&idx])
+                            .and_then(Extern::into_func)
+                            .context("Could not get runtime realloc export.")
+                    })
+                    .transpose()?;
                 (memory, realloc)
             }
             wasmtime_environ::component::CanonicalOptionsDataModel::Gc {} => {
@@ -1427,14 +2562,34 @@ impl Instance {
                 (None, None)
             }
         };
-        let post_return = options.post_return.map(|idx| {
-            Self::core_export(inner, &ctx, &inner.component.0.extracted_post_returns[&idx])
-                .expect("Could not get runtime post return export.")
-                .into_func()
-                .expect("Export was not of func type.")
-        });
-
-        GuestInvokeOptions {
+        let post_return = options
+            .post_return
+            .map(|idx| {
+                Self::core_export(inner, &ctx, &inner.component.0.extracted_post_returns[&idx])
+                    .and_then(Extern::into_func)
+                    .context("Could not get runtime post return export.")
+            })
+            .transpose()?;
+
+        Ok((memory, realloc, post_return))
+    }
+
+    /// Creates the options used to invoke an imported function.
+    fn import_function(
+        inner: &InstanceInner,
+        ctx: impl AsContext,
+        import: &ComponentImport,
+    ) -> Result<GuestInvokeOptions> {
+        let ComponentImport { options, func, .. } = import;
+        let (memory, realloc, post_return) = Self::canonical_exports(inner, &ctx, options)?;
+
+        Ok(GuestInvokeOptions {
+            site: Arc::new(ImportSite {
+                state_table: inner.state_table.clone(),
+                interface: import.instance.clone(),
+                name: import.name.clone(),
+                memory: memory.clone(),
+            }),
             component: inner.component.0.clone(),
             encoding: options.string_encoding,
             function: func.clone(),
@@ -1445,7 +2600,11 @@ impl Instance {
             types: inner.types.clone(),
             instance_id: inner.id,
             store_id: ctx.as_context().inner.data().id,
-        }
+            plan: CachedPlan::default(),
+            flat: OnceLock::new(),
+            flags: InstanceFlags(inner.instance_flags[options.instance].clone()),
+            async_: options.async_,
+        })
     }
 
     /// Creates an exported function from the provided definitions.
@@ -1459,36 +2618,17 @@ impl Instance {
         interface_id: Option<InterfaceIdentifier>,
     ) -> Result<crate::func::Func> {
         let callee = Self::core_export(inner, &ctx, def)
-            .expect("Could not get callee export.")
-            .into_func()
-            .expect("Export was not of func type.");
-        let (memory, realloc) = match &options.data_model {
-            wasmtime_environ::component::CanonicalOptionsDataModel::LinearMemory(lm) => {
-                let memory = lm.memory.map(|idx| {
-                    Self::core_export(inner, &ctx, &inner.component.0.extracted_memories[&idx])
-                        .expect("Could not get runtime memory export.")
-                        .into_memory()
-                        .expect("Export was not of memory type.")
-                });
-                let realloc = lm.realloc.map(|idx| {
-                    Self::core_export(inner, &ctx, &inner.component.0.extracted_reallocs[&idx])
-                        .expect("Could not get runtime realloc export.")
-                        .into_func()
-                        .expect("Export was not of func type.")
-                });
-                (memory, realloc)
-            }
-            wasmtime_environ::component::CanonicalOptionsDataModel::Gc {} => {
-                // GC data model doesn't use memory or realloc
-                (None, None)
-            }
-        };
-        let post_return = options.post_return.map(|idx| {
-            Self::core_export(inner, &ctx, &inner.component.0.extracted_post_returns[&idx])
-                .expect("Could not get runtime post return export.")
-                .into_func()
-                .expect("Export was not of func type.")
-        });
+            .and_then(Extern::into_func)
+            .context("Could not get callee export.")?;
+        let (memory, realloc, post_return) = Self::canonical_exports(inner, &ctx, options)?;
+        let callback = options
+            .callback
+            .map(|idx| {
+                Self::core_export(inner, &ctx, &inner.component.0.extracted_callbacks[&idx])
+                    .and_then(Extern::into_func)
+                    .context("Could not get runtime callback export.")
+            })
+            .transpose()?;
 
         Ok(crate::func::Func {
             store_id: ctx.as_context().inner.data().id,
@@ -1507,6 +2647,10 @@ impl Instance {
                     types: inner.types.clone(),
                     instance_id: inner.id,
                     interface_id,
+                    plan: CachedPlan::default(),
+                    flags: InstanceFlags(inner.instance_flags[options.instance].clone()),
+                    callback,
+                    is_async: options.async_,
                 }),
             ),
         })
@@ -1517,10 +2661,8 @@ impl Instance {
         inner: &InstanceInner,
         mut ctx: impl AsContextMut,
         def: &CoreDef,
-        linker: &Linker,
+        pre: &InstancePreInner,
         ty: ExternType,
-        destructors: &mut Vec<TrampolineIndex>,
-        resource_map: &FxHashMap<ResourceType, ResourceType>,
     ) -> Result<Extern> {
         match def {
             CoreDef::Export(x) => {
@@ -1532,6 +2674,7 @@ impl Instance {
                 } else {
                     bail!("Incorrect extern type.")
                 };
+                ctx.as_context_mut().inner.data_mut().trampolines += 1;
                 match inner
                     .component
                     .0
@@ -1540,35 +2683,31 @@ impl Instance {
                     .context("Could not find exported trampoline.")?
                 {
                     GeneratedTrampoline::ImportedFunction(component_import) => {
-                        let expected = crate::types::FuncType::from_component(
-                            &component_import.func,
-                            &inner.component.0,
-                            Some(resource_map),
-                        )?;
-                        let func = Self::get_component_import(component_import, linker)?;
-                        ensure!(
-                            func.ty() == expected,
-                            "Function import {} had type {}, but expected {expected}",
-                            component_import.name,
-                            func.ty()
-                        );
-                        let guest_options = Self::import_function(
-                            inner,
-                            &ctx,
-                            &component_import.options,
-                            &component_import.func,
-                        );
+                        let func = pre
+                            .imports
+                            .functions
+                            .get(x)
+                            .context("Could not find resolved function import.")?
+                            .clone();
+                        let guest_options = Self::import_function(inner, &ctx, component_import)?;
 
                         // Improve the name
                         // Due to indirect calls in wasm this function has the name `"0"`, `"1"`, etc, which is only their index in the indirect call table across modules
                         let ty = ty.with_name(component_import.name.clone());
+                        let state = inner.trampoline_states.register((func, guest_options));
 
                         Ok(Extern::Func(wasm_runtime_layer::Func::new(
                             ctx.as_context_mut().inner,
                             ty,
                             move |ctx, args, results| {
+                                let import = trampoline_state(&state)?;
+                                let (func, guest_options) = &*import;
                                 let ctx = StoreContextMut { inner: ctx };
-                                func.call_from_guest(ctx, &guest_options, args, results)
+                                if guest_options.async_ {
+                                    func.start_from_guest(ctx, &state, guest_options, args, results)
+                                } else {
+                                    func.call_from_guest(ctx, guest_options, args, results)
+                                }
                             },
                         )))
                     }
@@ -1582,10 +2721,10 @@ impl Instance {
                             move |_ctx, args, results| {
                                 let rep =
                                     require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
-                                let mut table_array = tables
-                                    .resource_tables
-                                    .try_lock()
-                                    .expect("Could not get mutual reference to table.");
+                                let mut table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
                                 results[0] = wasm_runtime_layer::Value::I32(
                                     table_array[x as usize].add(HandleElement {
                                         rep,
@@ -1607,10 +2746,10 @@ impl Instance {
                             move |_ctx, args, results| {
                                 let idx =
                                     require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
-                                let table_array = tables
-                                    .resource_tables
-                                    .try_lock()
-                                    .expect("Could not get mutual reference to table.");
+                                let table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
                                 results[0] = wasm_runtime_layer::Value::I32(
                                     table_array[x as usize].get(idx)?.rep,
                                 );
@@ -1618,9 +2757,8 @@ impl Instance {
                             },
                         )))
                     }
-                    GeneratedTrampoline::ResourceDrop(y, _) => {
-                        destructors.push(*x);
-                        let x = y.as_u32();
+                    GeneratedTrampoline::ResourceDrop(x) => {
+                        let x = x.as_u32();
                         let tables = inner.state_table.clone();
                         let ty = ty.with_name(format!("resource-drop-{}", x));
                         Ok(Extern::Func(wasm_runtime_layer::Func::new(
@@ -1629,10 +2767,10 @@ impl Instance {
                             move |ctx, args, _results| {
                                 let idx =
                                     require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
-                                let mut table_array = tables
-                                    .resource_tables
-                                    .try_lock()
-                                    .expect("Could not get mutual reference to table.");
+                                let mut table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
                                 let current_table = &mut table_array[x as usize];
 
                                 let elem_borrow = current_table.get(idx)?;
@@ -1658,12 +2796,358 @@ impl Instance {
                             },
                         )))
                     }
+                    GeneratedTrampoline::Transcoder {
+                        op,
+                        from,
+                        from64,
+                        to,
+                        to64,
+                    } => {
+                        let transcoder = crate::transcode::Transcoder {
+                            op: *op,
+                            from: Self::extracted_memory(inner, &ctx, from)?,
+                            from64: *from64,
+                            to: Self::extracted_memory(inner, &ctx, to)?,
+                            to64: *to64,
+                        };
+                        let ty = ty.with_name(format!("transcode-{}", op.symbol_fragment()));
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |ctx, args, results| transcoder.call(ctx, args, results),
+                        )))
+                    }
+                    GeneratedTrampoline::AlwaysTrap => {
+                        let ty = ty.with_name("always-trap");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            |_ctx, _args, _results| bail!("Degenerate component adapter called."),
+                        )))
+                    }
+                    GeneratedTrampoline::ResourceTransferOwn => {
+                        let tables = inner.state_table.clone();
+                        let ty = ty.with_name("resource-transfer-own");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |_ctx, args, results| {
+                                let idx =
+                                    require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+                                let src =
+                                    require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x);
+                                let dst =
+                                    require_matches!(args[2], wasm_runtime_layer::Value::I32(x), x);
+                                let mut table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
+
+                                let elem = *table_array[src as usize].get(idx)?;
+                                ensure!(
+                                    elem.own,
+                                    "Attempted to transfer ownership of non-owned handle."
+                                );
+                                ensure!(
+                                    elem.lend_count == 0,
+                                    "Attempted to transfer ownership while handle was lent."
+                                );
+                                table_array[src as usize].remove(idx)?;
+
+                                results[0] = wasm_runtime_layer::Value::I32(
+                                    table_array[dst as usize].add(HandleElement {
+                                        rep: elem.rep,
+                                        own: true,
+                                        lend_count: 0,
+                                    }),
+                                );
+                                Ok(())
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::ResourceTransferBorrow => {
+                        let tables = inner.state_table.clone();
+                        let owned_resource_tables = inner.component.0.owned_resource_tables.clone();
+                        let ty = ty.with_name("resource-transfer-borrow");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |_ctx, args, results| {
+                                let idx =
+                                    require_matches!(args[0], wasm_runtime_layer::Value::I32(x), x);
+                                let src =
+                                    require_matches!(args[1], wasm_runtime_layer::Value::I32(x), x);
+                                let dst =
+                                    require_matches!(args[2], wasm_runtime_layer::Value::I32(x), x);
+                                let mut table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
+                                let mut scopes = tables.call_scopes.try_lock().map_err(|_| {
+                                    Error::resource(
+                                        "Could not get mutual reference to call scopes.",
+                                    )
+                                })?;
+                                let scope = scopes
+                                    .last_mut()
+                                    .context("Attempted to lend handle outside of call.")?;
+
+                                let mut elem = *table_array[src as usize].get(idx)?;
+                                if elem.own {
+                                    elem.lend_count += 1;
+                                    table_array[src as usize].set(idx, elem);
+                                    scope.lends.push((src as u32, idx));
+                                }
+
+                                // The instance which defines a resource receives its representation directly
+                                results[0] = if owned_resource_tables[dst as usize] {
+                                    wasm_runtime_layer::Value::I32(elem.rep)
+                                } else {
+                                    let borrow = table_array[dst as usize].add(HandleElement {
+                                        rep: elem.rep,
+                                        own: false,
+                                        lend_count: 0,
+                                    });
+                                    scope.borrows.push((dst as u32, borrow));
+                                    wasm_runtime_layer::Value::I32(borrow)
+                                };
+                                Ok(())
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::ResourceEnterCall => {
+                        let tables = inner.state_table.clone();
+                        let ty = ty.with_name("resource-enter-call");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |_ctx, _args, _results| {
+                                tables
+                                    .call_scopes
+                                    .try_lock()
+                                    .map_err(|_| {
+                                        Error::resource(
+                                            "Could not get mutual reference to call scopes.",
+                                        )
+                                    })?
+                                    .push(CallScope::default());
+                                Ok(())
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::ResourceExitCall => {
+                        let tables = inner.state_table.clone();
+                        let ty = ty.with_name("resource-exit-call");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |_ctx, _args, _results| {
+                                let scope = tables
+                                    .call_scopes
+                                    .try_lock()
+                                    .map_err(|_| {
+                                        Error::resource(
+                                            "Could not get mutual reference to call scopes.",
+                                        )
+                                    })?
+                                    .pop()
+                                    .context("Attempted to exit call that was never entered.")?;
+                                let mut table_array =
+                                    tables.resource_tables.try_lock().map_err(|_| {
+                                        Error::resource("Could not get mutual reference to table.")
+                                    })?;
+
+                                for (table, idx) in scope.borrows {
+                                    table_array[table as usize].remove(idx)?;
+                                }
+
+                                for (table, idx) in scope.lends {
+                                    let mut elem = *table_array[table as usize].get(idx)?;
+                                    elem.lend_count -= 1;
+                                    table_array[table as usize].set(idx, elem);
+                                }
+                                Ok(())
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::Channel(table, op) => {
+                        let builtin = Self::channel_builtin(inner, &ctx, table, op)?;
+                        let ty = ty.with_name(format!("{}-{}", builtin.kind.name(), op.name()));
+                        let builtin = inner.trampoline_states.register(builtin);
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |ctx, args, results| {
+                                trampoline_state(&builtin)?.call(
+                                    StoreContextMut { inner: ctx },
+                                    args,
+                                    results,
+                                )
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::ErrorContext(op) => {
+                        let copy = match op {
+                            ErrorContextOp::New(options)
+                            | ErrorContextOp::DebugMessage(options) => {
+                                Some(Self::copy_options(inner, &ctx, options)?)
+                            }
+                            ErrorContextOp::Drop => None,
+                        };
+                        let builtin = ErrorContextBuiltin {
+                            op: op.clone(),
+                            state_table: inner.state_table.clone(),
+                            copy,
+                        };
+                        let ty = ty.with_name(op.name());
+                        let builtin = inner.trampoline_states.register(builtin);
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |ctx, args, results| {
+                                trampoline_state(&builtin)?.call(
+                                    StoreContextMut { inner: ctx },
+                                    args,
+                                    results,
+                                )
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::Waitable(op) => {
+                        let copy = match op {
+                            WaitableOp::SetWait(options) | WaitableOp::SetPoll(options) => {
+                                Some(Self::copy_options(inner, &ctx, options)?)
+                            }
+                            _ => None,
+                        };
+                        let builtin = WaitableBuiltin {
+                            op: op.clone(),
+                            state_table: inner.state_table.clone(),
+                            copy,
+                        };
+                        let ty = ty.with_name(op.name());
+                        let builtin = inner.trampoline_states.register(builtin);
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |ctx, args, results| {
+                                trampoline_state(&builtin)?.call(
+                                    StoreContextMut { inner: ctx },
+                                    args,
+                                    results,
+                                )
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::TableTransfer => {
+                        let ty = ty.with_name("table-transfer");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            |_ctx, args, results| {
+                                // Each instance has a single table of future, stream, and error context
+                                // handles, so a transfer leaves the handle unchanged.
+                                results[0] = args[0].clone();
+                                Ok(())
+                            },
+                        )))
+                    }
+                    GeneratedTrampoline::TaskReturn => {
+                        let tables = inner.state_table.clone();
+                        let ty = ty.with_name("task-return");
+                        Ok(Extern::Func(wasm_runtime_layer::Func::new(
+                            ctx.as_context_mut().inner,
+                            ty,
+                            move |ctx, args, _results| {
+                                task_return(StoreContextMut { inner: ctx }, &tables.task, args)
+                            },
+                        )))
+                    }
                 }
             }
             CoreDef::InstanceFlags(i) => Ok(Extern::Global(inner.instance_flags[*i].clone())),
         }
     }
 
+    /// Creates the options with which a canonical built-in copies values to and from guest memory.
+    fn copy_options(
+        inner: &InstanceInner,
+        ctx: impl AsContext,
+        options: &CanonicalOptions,
+    ) -> Result<CopyOptions> {
+        let (memory, realloc, _) = Self::canonical_exports(inner, &ctx, options)?;
+        Ok(CopyOptions {
+            component: inner.component.0.clone(),
+            encoding: options.string_encoding,
+            memory,
+            realloc,
+            state_table: inner.state_table.clone(),
+            types: inner.types.clone(),
+            instance_id: inner.id,
+            store_id: ctx.as_context().inner.data().id,
+            flags: InstanceFlags(inner.instance_flags[options.instance].clone()),
+        })
+    }
+
+    /// Creates the implementation of a canonical built-in for futures or streams.
+    fn channel_builtin(
+        inner: &InstanceInner,
+        ctx: impl AsContext,
+        table: &ChannelTable,
+        op: &ChannelOp,
+    ) -> Result<ChannelBuiltin> {
+        let id = *inner
+            .component
+            .0
+            .channel_types
+            .get(table)
+            .context("Could not find future or stream type.")?;
+        let payload = match &inner.component.0.resolve.types[id].kind {
+            TypeDefKind::Future(x) | TypeDefKind::Stream(x) => *x,
+            _ => bail!("Incorrect future or stream type."),
+        };
+        let payload_ty = match &inner.types[id.index()] {
+            crate::types::ValueType::Future(x) => x.payload_ty(),
+            crate::types::ValueType::Stream(x) => x.payload_ty(),
+            _ => bail!("Incorrect future or stream type."),
+        };
+        let copy = match op {
+            ChannelOp::Read(options) | ChannelOp::Write(options) => {
+                Some(Self::copy_options(inner, &ctx, options)?)
+            }
+            _ => None,
+        };
+
+        Ok(ChannelBuiltin {
+            op: op.clone(),
+            kind: table.kind(),
+            payload,
+            payload_ty,
+            state_table: inner.state_table.clone(),
+            copy,
+        })
+    }
+
+    /// Gets a memory that was extracted for use by canonical options or adapters.
+    fn extracted_memory(
+        inner: &InstanceInner,
+        ctx: impl AsContext,
+        index: &RuntimeMemoryIndex,
+    ) -> Result<Memory> {
+        Self::core_export(
+            inner,
+            ctx,
+            inner
+                .component
+                .0
+                .extracted_memories
+                .get(index)
+                .context("Could not find extracted memory.")?,
+        )
+        .and_then(Extern::into_memory)
+        .context("Could not get runtime memory export.")
+    }
+
     /// Gets the core WASM export associated with the provided definition.
     fn core_export<T: Copy + Into<wasmtime_environ::EntityIndex>>(
         inner: &InstanceInner,
@@ -1673,7 +3157,7 @@ impl Instance {
         let name = match &export.item {
             ExportItem::Index(idx) => {
                 &inner.component.0.export_mapping
-                    [&inner.component.0.instance_modules[export.instance]][&(*idx).into()]
+                    [inner.component.0.instance_modules[export.instance].as_ref()?][&(*idx).into()]
             }
             ExportItem::Name(s) => s,
         };
@@ -1685,10 +3169,9 @@ impl Instance {
     fn global_initialize(
         mut inner: InstanceInner,
         mut ctx: impl AsContextMut,
-        linker: &Linker,
+        pre: &InstancePreInner,
         resource_map: &FxHashMap<ResourceType, ResourceType>,
     ) -> Result<InstanceInner> {
-        let mut destructors = Vec::new();
         for initializer in &inner.component.0.translation.component.initializers {
             match initializer {
                 GlobalInitializer::InstantiateModule(InstantiateModule::Static(idx, def)) => {
@@ -1696,56 +3179,78 @@ impl Instance {
                     let imports = Self::generate_imports(
                         &inner,
                         &mut ctx,
-                        linker,
-                        module,
-                        def,
-                        &mut destructors,
-                        resource_map,
+                        pre,
+                        &module.module,
+                        module
+                            .translation
+                            .imports()
+                            .zip(def)
+                            .map(|((module, name, _), arg)| (module, name, arg)),
                     )?;
                     let instance = wasm_runtime_layer::Instance::new(
                         &mut ctx.as_context_mut().inner,
                         &module.module,
                         &imports,
                     )?;
+                    ctx.as_context_mut().inner.data_mut().core_instances += 1;
+                    inner.instances.push(instance);
+                }
+                GlobalInitializer::InstantiateModule(InstantiateModule::Import(idx, args)) => {
+                    let module = pre
+                        .imports
+                        .modules
+                        .get(idx)
+                        .context("Could not find resolved module import.")?;
+                    let imports = Self::generate_imports(
+                        &inner,
+                        &mut ctx,
+                        pre,
+                        module,
+                        args.iter().flat_map(|(module, names)| {
+                            names
+                                .iter()
+                                .map(move |(name, arg)| (module.as_str(), name.as_str(), arg))
+                        }),
+                    )?;
+                    let instance = wasm_runtime_layer::Instance::new(
+                        &mut ctx.as_context_mut().inner,
+                        module,
+                        &imports,
+                    )?;
+                    ctx.as_context_mut().inner.data_mut().core_instances += 1;
                     inner.instances.push(instance);
                 }
                 GlobalInitializer::ExtractMemory(_) => {}
                 GlobalInitializer::ExtractRealloc(_) => {}
+                GlobalInitializer::ExtractCallback(_) => {}
                 GlobalInitializer::ExtractPostReturn(_) => {}
+                GlobalInitializer::ExtractTable(_) => {}
                 GlobalInitializer::LowerImport { .. } => {}
                 GlobalInitializer::Resource(_) => {}
-                _ => bail!("Not yet implemented {initializer:?}."),
             }
         }
 
-        Self::fill_destructors(inner, ctx, destructors, resource_map)
+        Self::fill_destructors(inner, ctx, resource_map)
     }
 
-    /// Generates the set of core WASM imports for this component.
-    fn generate_imports(
+    /// Generates the set of core WASM imports for a module of this component.
+    fn generate_imports<'a>(
         inner: &InstanceInner,
         mut store: impl AsContextMut,
-        linker: &Linker,
-        module: &ModuleTranslation,
-        defs: &[CoreDef],
-        destructors: &mut Vec<TrampolineIndex>,
-        resource_map: &FxHashMap<ResourceType, ResourceType>,
+        pre: &InstancePreInner,
+        module: &Module,
+        defs: impl IntoIterator<Item = (&'a str, &'a str, &'a CoreDef)>,
     ) -> Result<Imports> {
         let mut import_ty_map = FxHashMap::default();
 
         let engine = store.as_context().engine().clone();
-        for import in module.module.imports(&engine) {
+        for import in module.imports(&engine) {
             import_ty_map.insert((import.module, import.name), import.ty.clone());
         }
 
         let mut imports = Imports::default();
 
-        for (host, name, def) in module
-            .translation
-            .imports()
-            .zip(defs)
-            .map(|((module, name, _), arg)| (module, name, arg))
-        {
+        for (host, name, def) in defs {
             let ty = import_ty_map
                 .get(&(host, name))
                 .context("Unrecognized import.")?
@@ -1753,60 +3258,32 @@ impl Instance {
             imports.define(
                 host,
                 name,
-                Self::core_import(
-                    inner,
-                    &mut store,
-                    def,
-                    linker,
-                    ty,
-                    destructors,
-                    resource_map,
-                )?,
+                Self::core_import(inner, &mut store, def, pre, ty)?,
             );
         }
 
         Ok(imports)
     }
 
-    /// Gets an import from the linker for this component.
-    fn get_component_import(
-        import: &ComponentImport,
-        linker: &Linker,
-    ) -> Result<crate::func::Func> {
-        let inst = if let Some(name) = &import.instance {
-            linker
-                .instance(name)
-                .ok_or_else(|| Error::msg(format!("Could not find imported interface {name:?}")))?
-        } else {
-            linker.root()
-        };
-
-        inst.func(&import.name)
-            .ok_or_else(|| Error::msg(format!("Could not find function import {}", import.name)))
-    }
-
     /// Fills the resource tables with all resource destructors.
     fn fill_destructors(
         inner: InstanceInner,
         ctx: impl AsContext,
-        destructors: Vec<TrampolineIndex>,
         resource_map: &FxHashMap<ResourceType, ResourceType>,
     ) -> Result<InstanceInner> {
         let mut tables = inner
             .state_table
             .resource_tables
             .try_lock()
-            .expect("Could not get access to resource tables.");
+            .map_err(|_| Error::resource("Could not get access to resource tables."))?;
 
-        for index in destructors {
-            let (x, def) = require_matches!(
-                &inner.component.0.generated_trampolines[&index],
-                GeneratedTrampoline::ResourceDrop(x, def),
-                (x, def)
-            );
+        for (table, def) in tables
+            .iter_mut()
+            .zip(&inner.component.0.resource_destructors)
+        {
             if let Some(def) = def {
                 let export = require_matches!(def, CoreDef::Export(x), x);
-                tables[x.as_u32() as usize].set_destructor(Some(require_matches!(
+                table.set_destructor(Some(require_matches!(
                     Self::core_export(&inner, &ctx, export),
                     Some(Extern::Func(x)),
                     x
@@ -1824,11 +3301,19 @@ impl Instance {
                 &inner.component.0,
                 Some(resource_map),
             )?;
+            let table = &mut tables[idx.as_u32() as usize];
+            if table.ty().is_none() {
+                table.set_ty(res.clone());
+            }
             match res.host_destructor() {
                 Some(Some(func)) => tables[idx.as_u32() as usize].set_destructor(Some(func)),
                 Some(None) => tables[idx.as_u32() as usize]
                     .set_destructor(ctx.as_context().inner.data().drop_host_resource.clone()),
-                _ => {}
+                None => {
+                    if let Some(func) = res.guest_destructor().flatten() {
+                        tables[idx.as_u32() as usize].set_destructor(Some(func));
+                    }
+                }
             }
         }
 
@@ -1861,8 +3346,16 @@ impl Exports {
         &self.root
     }
 
-    /// Gets the instance with the specified name, if any.
+    /// Gets the instance with the specified name, if any. If there is no exact match,
+    /// the instance with the newest semver-compatible version is returned.
     pub fn instance(&self, name: &InterfaceIdentifier) -> Option<&ExportInstance> {
+        self.instances
+            .get(name)
+            .or_else(|| name.select_compatible(&self.instances).map(|(_, x)| x))
+    }
+
+    /// Gets the instance whose name matches the specified one exactly, including its version, if any.
+    pub fn instance_exact(&self, name: &InterfaceIdentifier) -> Option<&ExportInstance> {
         self.instances.get(name)
     }
 
@@ -1895,19 +3388,30 @@ impl ExportInstance {
 
     /// Gets the associated function by name, if any.
     pub fn func(&self, name: impl AsRef<str>) -> Option<crate::func::Func> {
-        self.functions.get(name.as_ref()).map(|x| {
-            x.instantiate(Instance(
-                self.instance.upgrade().expect("Instance did not exist."),
-            ))
-        })
+        self.functions
+            .get(name.as_ref())
+            .map(|x| self.instantiate(x, self.instance.upgrade()))
     }
 
     /// Iterates over all associated functions by name.
     pub fn funcs(&self) -> impl Iterator<Item = (&'_ str, crate::func::Func)> {
-        let inst = self.instance.upgrade().expect("Instance did not exist.");
+        let inst = self.instance.upgrade();
         self.functions
             .iter()
-            .map(move |(k, v)| (&**k, v.instantiate(Instance(inst.clone()))))
+            .map(move |(k, v)| (&**k, self.instantiate(v, inst.clone())))
+    }
+
+    /// Ties a function to the instance that owns these exports. If the instance no longer exists,
+    /// the function is returned untied, and calling it fails with [`Error::InstanceDropped`].
+    fn instantiate(
+        &self,
+        func: &crate::func::Func,
+        inst: Option<Arc<InstanceInner>>,
+    ) -> crate::func::Func {
+        match inst {
+            Some(inst) => func.instantiate(Instance(inst)),
+            None => func.clone(),
+        }
     }
 
     /// Gets the associated abstract resource by name, if any.
@@ -1936,19 +3440,85 @@ struct InstanceInner {
     pub instances: wasmtime_environ::PrimaryMap<RuntimeInstanceIndex, wasm_runtime_layer::Instance>,
     /// Stores the instance-specific state.
     pub state_table: Arc<StateTable>,
+    /// The state captured by this instance's trampolines.
+    pub trampoline_states: TrampolineStates,
     /// The list of types for this instance.
     pub types: Arc<[crate::types::ValueType]>,
     /// The store ID associated with this instance.
     pub store_id: u64,
 }
 
+/// Owns the state that an instance's trampolines use, such as imported functions and canonical
+/// built-ins. The backend store keeps trampolines alive for as long as the store itself, so
+/// trampolines only hold weak references to this state, which is released with the instance.
+#[derive(Debug, Default)]
+struct TrampolineStates(Mutex<Vec<Arc<dyn Any + Send + Sync>>>);
+
+impl TrampolineStates {
+    /// Takes ownership of the given state, returning a weak reference for a trampoline to hold.
+    pub fn register<T: Any + Send + Sync>(&self, state: T) -> Weak<T> {
+        let state = Arc::new(state);
+        let weak = Arc::downgrade(&state);
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(state);
+        weak
+    }
+
+    /// Releases all state, so that the trampolines which use it fail if they are called.
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Upgrades the weak reference held by a trampoline, failing if the instance was dropped.
+fn trampoline_state<T>(state: &Weak<T>) -> Result<Arc<T>> {
+    Ok(state.upgrade().ok_or(Error::InstanceDropped)?)
+}
+
 /// Stores the instance-specific state for a component.
 #[derive(Debug)]
 struct StateTable {
+    /// The instance which owns this state.
+    pub instance: OnceLock<Weak<InstanceInner>>,
     /// Whether this instance has been dropped.
     pub dropped: AtomicBool,
     /// The set of resource tables and destructors.
     pub resource_tables: Mutex<Vec<HandleTable>>,
+    /// The handles lent by each active call between the instance's nested components.
+    pub call_scopes: Mutex<Vec<CallScope>>,
+    /// The future and stream handles and waitable sets held by the instance.
+    pub waitables: Mutex<Waitables>,
+    /// The error contexts held by the instance.
+    pub error_contexts: Mutex<ErrorContextTable>,
+    /// The state of the async export which the instance is running, if any.
+    pub task: Mutex<TaskState>,
+}
+
+/// Describes an imported function of an instance, so that host functions may identify their caller.
+#[derive(Debug)]
+struct ImportSite {
+    /// The state of the instance which imports the function.
+    pub state_table: Arc<StateTable>,
+    /// The interface from which the function is imported.
+    pub interface: Option<InterfaceIdentifier>,
+    /// The name of the import.
+    pub name: Arc<str>,
+    /// The memory of the importing instance, if the import uses one.
+    pub memory: Option<Memory>,
+}
+
+/// Records the handles that were lent for the duration of a call between nested components.
+#[derive(Debug, Default)]
+struct CallScope {
+    /// The owned handles, by table and index, whose lend counts were incremented.
+    pub lends: Vec<(u32, i32)>,
+    /// The borrowed handles, by table and index, which were created for the callee.
+    pub borrows: Vec<(u32, i32)>,
 }
 
 /// Details an import for a component.
@@ -2005,6 +3575,12 @@ impl<T: 'static, E: backend::WasmEngine> Store<T, E> {
                 host_functions: FuncVec::default(),
                 host_resources: Slab::default(),
                 drop_host_resource: None,
+                lift_limits: LiftLimits::default(),
+                channels: Slab::default(),
+                callers: Vec::new(),
+                core_instances: 0,
+                trampolines: 0,
+                async_calls: 0,
             },
         );
 
@@ -2044,6 +3620,336 @@ impl<T: 'static, E: backend::WasmEngine> Store<T, E> {
     pub fn into_data(self) -> T {
         self.inner.into_data().data
     }
+
+    /// Returns the limits on values that guests may pass to the host through this [`Store`].
+    pub fn lift_limits(&self) -> LiftLimits {
+        self.inner.data().lift_limits
+    }
+
+    /// Sets the limits on values that guests may pass to the host through this [`Store`].
+    /// Calls which exceed these limits fail with an error instead of allocating host memory.
+    pub fn set_lift_limits(&mut self, limits: LiftLimits) {
+        self.inner.data_mut().lift_limits = limits;
+    }
+
+    /// Gets the number of host functions in this [`Store`] which are still referenced by a [`Func`],
+    /// a [`Linker`], or an instance that imports them.
+    pub fn host_function_count(&self) -> usize {
+        self.inner.data().host_functions.live_count()
+    }
+
+    /// Gets the number of core instances that components have created in the backend store of this
+    /// [`Store`], for diagnostics only. The backend cannot remove core instances, or the memories and
+    /// tables that they define, so this count grows with every instantiation, even after instances
+    /// are dropped, and no method of [`Store`] lowers it.
+    pub fn backend_core_instance_count(&self) -> usize {
+        self.inner.data().core_instances
+    }
+
+    /// Gets the number of trampolines, through which guests call imports and built-ins, that components
+    /// have created in the backend store of this [`Store`], for diagnostics only. Like core instances,
+    /// trampolines are never removed, but the state that they reference is freed when their instance
+    /// is dropped.
+    pub fn backend_trampoline_count(&self) -> usize {
+        self.inner.data().trampolines
+    }
+
+    /// Frees the host functions which are no longer referenced. This happens automatically as new
+    /// functions are created and when instances are dropped, but may be called to release
+    /// memory immediately. Nothing else is freed: core instances and trampolines remain in the
+    /// backend store.
+    pub fn clear_dead_host_functions(&mut self) {
+        self.inner.data_mut().host_functions.clear_dead_functions();
+    }
+
+    /// Counts the host resources that are alive in this [`Store`], grouped by resource type.
+    /// Each value counts as an owned handle, whether it is held by the host or was
+    /// passed to a guest. Borrows created with [`ResourceOwn::borrow`] that were not yet
+    /// dropped count as lent.
+    pub fn host_resource_stats(&self) -> Vec<ResourceStats> {
+        let mut stats = Vec::<ResourceStats>::new();
+        for (_, resource) in &self.inner.data().host_resources {
+            let index = match stats.iter().position(|x| x.ty == resource.ty) {
+                Some(index) => index,
+                None => {
+                    stats.push(ResourceStats::new(resource.ty.clone()));
+                    stats.len() - 1
+                }
+            };
+
+            let lent = match resource.tracker.load(Ordering::Acquire) {
+                usize::MAX => 0,
+                x => x,
+            };
+            stats[index].record(true, lent);
+        }
+        stats
+    }
+}
+
+/// Limits on the size of values that are lifted from guest memory, such as the arguments of
+/// host functions or the results of guest functions.
+///
+/// Guest pointers and lengths are always validated against the bounds of guest memory.
+/// These limits additionally guard the host against guests which return very large values.
+/// By default, no limits are imposed, except that each future or stream buffers at most
+/// [`LiftLimits::DEFAULT_MAX_BUFFERED_VALUES`] values which guests wrote.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LiftLimits {
+    /// The maximum number of bytes in a single string.
+    max_string_bytes: usize,
+    /// The maximum number of elements in a single list.
+    max_list_elements: usize,
+    /// The maximum number of bytes lifted from guest memory during one call.
+    max_bytes_per_call: usize,
+    /// The maximum number of values written by guests which a single future or stream buffers.
+    max_buffered_values: usize,
+}
+
+impl LiftLimits {
+    /// The number of values which a future or stream buffers by default.
+    pub const DEFAULT_MAX_BUFFERED_VALUES: usize = 1024;
+
+    /// Sets the maximum number of bytes that a single string may occupy in guest memory.
+    pub fn with_max_string_bytes(mut self, max: usize) -> Self {
+        self.max_string_bytes = max;
+        self
+    }
+
+    /// Sets the maximum number of elements that a single list may contain.
+    pub fn with_max_list_elements(mut self, max: usize) -> Self {
+        self.max_list_elements = max;
+        self
+    }
+
+    /// Sets the maximum total number of bytes that may be lifted from guest memory
+    /// for the arguments or results of one call.
+    pub fn with_max_bytes_per_call(mut self, max: usize) -> Self {
+        self.max_bytes_per_call = max;
+        self
+    }
+
+    /// Sets the maximum number of values written by guests which a single future or stream
+    /// buffers until they are read. Once a stream's buffer is full, writes return `BLOCKED`
+    /// until values are read. At least one value is always buffered.
+    pub fn with_max_buffered_values(mut self, max: usize) -> Self {
+        self.max_buffered_values = max;
+        self
+    }
+
+    /// The maximum number of bytes that a single string may occupy in guest memory.
+    pub fn max_string_bytes(&self) -> usize {
+        self.max_string_bytes
+    }
+
+    /// The maximum number of elements that a single list may contain.
+    pub fn max_list_elements(&self) -> usize {
+        self.max_list_elements
+    }
+
+    /// The maximum total number of bytes that may be lifted from guest memory during one call.
+    pub fn max_bytes_per_call(&self) -> usize {
+        self.max_bytes_per_call
+    }
+
+    /// The maximum number of values written by guests which a single future or stream buffers.
+    pub fn max_buffered_values(&self) -> usize {
+        self.max_buffered_values
+    }
+
+    /// Ensures that a string of `byte_len` bytes is within the limits.
+    pub(crate) fn check_string(&self, byte_len: usize) -> Result<()> {
+        let max = self.max_string_bytes;
+        ensure!(
+            byte_len <= max,
+            "String of {byte_len} bytes exceeded the limit of {max} bytes."
+        );
+        Ok(())
+    }
+
+    /// Ensures that a list of `len` elements is within the limits.
+    pub(crate) fn check_list(&self, len: usize) -> Result<()> {
+        let max = self.max_list_elements;
+        ensure!(
+            len <= max,
+            "List of {len} elements exceeded the limit of {max} elements."
+        );
+        Ok(())
+    }
+
+    /// Records that `size` more bytes are being lifted from guest memory, failing if the total
+    /// in `lifted_bytes` exceeds the per-call limit.
+    pub(crate) fn account(&self, lifted_bytes: &mut usize, size: usize) -> Result<()> {
+        *lifted_bytes = lifted_bytes.saturating_add(size);
+        let max = self.max_bytes_per_call;
+        ensure!(
+            *lifted_bytes <= max,
+            "Call lifted more than the limit of {max} bytes from guest memory."
+        );
+        Ok(())
+    }
+}
+
+impl Default for LiftLimits {
+    fn default() -> Self {
+        Self {
+            max_string_bytes: usize::MAX,
+            max_list_elements: usize::MAX,
+            max_bytes_per_call: usize::MAX,
+            max_buffered_values: Self::DEFAULT_MAX_BUFFERED_VALUES,
+        }
+    }
+}
+
+/// Counts the live handles of a single resource type, as reported by
+/// [`Instance::resource_stats`] or [`Store::host_resource_stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceStats {
+    /// The type of resource being counted.
+    ty: ResourceType,
+    /// The number of owned handles.
+    own: usize,
+    /// The number of borrowed handles.
+    borrowed: usize,
+    /// The number of borrows that are currently lent out from the owned handles.
+    lent: usize,
+}
+
+impl ResourceStats {
+    /// Creates a new, empty set of statistics for the given type.
+    fn new(ty: ResourceType) -> Self {
+        Self {
+            ty,
+            own: 0,
+            borrowed: 0,
+            lent: 0,
+        }
+    }
+
+    /// Records a single live handle.
+    fn record(&mut self, own: bool, lent: usize) {
+        if own {
+            self.own += 1;
+        } else {
+            self.borrowed += 1;
+        }
+        self.lent += lent;
+    }
+
+    /// The type of resource being counted.
+    pub fn ty(&self) -> &ResourceType {
+        &self.ty
+    }
+
+    /// The number of owned handles that are alive.
+    pub fn own(&self) -> usize {
+        self.own
+    }
+
+    /// The number of borrowed handles that are alive.
+    pub fn borrowed(&self) -> usize {
+        self.borrowed
+    }
+
+    /// The number of borrows that the owned handles have lent out and which were not yet returned.
+    pub fn lent(&self) -> usize {
+        self.lent
+    }
+
+    /// The total number of live handles.
+    pub fn total(&self) -> usize {
+        self.own + self.borrowed
+    }
+}
+
+/// A resource handle which was still held by an instance when it was dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiveHandle {
+    /// The type of the resource.
+    ty: ResourceType,
+    /// The index of the handle in the instance's table.
+    index: u32,
+    /// Whether the handle was owned rather than borrowed.
+    own: bool,
+    /// The number of borrows that the handle had lent out.
+    lend_count: u32,
+}
+
+impl LiveHandle {
+    /// The type of the resource.
+    pub fn ty(&self) -> &ResourceType {
+        &self.ty
+    }
+
+    /// The index of the handle in the instance's handle table.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Whether the handle was owned rather than borrowed.
+    pub fn own(&self) -> bool {
+        self.own
+    }
+
+    /// The number of borrows that the handle had lent out and which were not yet returned.
+    pub fn lend_count(&self) -> u32 {
+        self.lend_count
+    }
+}
+
+impl std::fmt::Display for LiveHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ty = if self.own {
+            ValueType::Own(self.ty.clone())
+        } else {
+            ValueType::Borrow(self.ty.clone())
+        };
+        write!(f, "{ty} handle {}", self.index)?;
+        if self.lend_count > 0 {
+            write!(f, " (lent {} times)", self.lend_count)?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+/// The outcome of [`Instance::drop_with_leak_report`].
+#[derive(Debug)]
+pub struct LeakReport {
+    /// The errors that occurred while running destructors.
+    errors: Vec<Error>,
+    /// The handles which were still alive before the instance was dropped.
+    handles: Vec<LiveHandle>,
+}
+
+impl LeakReport {
+    /// The [`Error::Trap`]s that occurred while dropping owned resources.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// The handles which the instance still held when it was dropped.
+    pub fn handles(&self) -> &[LiveHandle] {
+        &self.handles
+    }
+
+    /// Whether the instance had dropped all of its handles beforehand.
+    pub fn is_clean(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
+
+impl std::fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.handles.is_empty() {
+            return write!(f, "no leaked handles");
+        }
+
+        write!(f, "{} leaked handle(s):", self.handles.len())?;
+        for handle in &self.handles {
+            write!(f, "\n  {handle}")?;
+        }
+        std::fmt::Result::Ok(())
+    }
 }
 
 /// A temporary handle to a [`&Store<T>`][`Store`].
@@ -2061,6 +3967,13 @@ impl<'a, T: 'a + 'static, E: backend::WasmEngine> StoreContext<'a, T, E> {
         self.inner.engine()
     }
 
+    /// Gets the instance whose import is currently being called, if a host function is running
+    /// on behalf of a guest. Host functions that the host calls directly, including from within
+    /// another host function, have no caller.
+    pub fn caller(&self) -> Option<Caller> {
+        Caller::current(self.inner.data())
+    }
+
     /// Access the underlying data owned by this store.
     ///
     /// Same as [`Store::data`].
@@ -2069,6 +3982,62 @@ impl<'a, T: 'a + 'static, E: backend::WasmEngine> StoreContext<'a, T, E> {
     }
 }
 
+/// Identifies the instance that called a host function, along with the import through which
+/// it was called. This allows one set of host functions to be shared by many instances, while
+/// attributing work to the right one.
+#[derive(Clone, Debug)]
+pub struct Caller {
+    /// The calling instance.
+    instance: Instance,
+    /// The import that the instance called.
+    site: Arc<ImportSite>,
+}
+
+impl Caller {
+    /// Gets the caller of the innermost host function that is running in the store, if any.
+    fn current<T, E: backend::WasmEngine>(store: &StoreInner<T, E>) -> Option<Self> {
+        let site = store.callers.last()?.clone()?;
+        let instance = Instance(site.state_table.instance.get()?.upgrade()?);
+        Some(Self { instance, site })
+    }
+
+    /// Gets the calling instance. Its exports may not be called until the host function returns,
+    /// since component instances cannot be reentered.
+    pub fn instance(&self) -> &Instance {
+        &self.instance
+    }
+
+    /// Gets the root package of the calling instance's component.
+    pub fn package(&self) -> &PackageIdentifier {
+        self.instance.component().package()
+    }
+
+    /// Gets the interface through which the caller imported the function, or [`None`] if it was
+    /// imported at the top level of the component's world.
+    pub fn interface(&self) -> Option<&InterfaceIdentifier> {
+        self.site.interface.as_ref()
+    }
+
+    /// Gets the name of the import that the caller invoked.
+    pub fn name(&self) -> &str {
+        &self.site.name
+    }
+
+    /// Reads bytes from the caller's linear memory at the given offset into `buffer`. Fails if
+    /// the import does not use a memory, or if the range is out of bounds.
+    pub fn read_memory(&self, ctx: impl AsContext, offset: usize, buffer: &mut [u8]) -> Result<()> {
+        ensure!(
+            ctx.as_context().inner.data().id == self.instance.0.store_id,
+            Error::StoreMismatch
+        );
+        self.site
+            .memory
+            .as_ref()
+            .context("The import does not have access to the caller's memory.")?
+            .read(ctx.as_context().inner, offset, buffer)
+    }
+}
+
 /// A temporary handle to a [`&mut Store<T>`][`Store`].
 ///
 /// This type is suitable for [`AsContextMut`] or [`AsContext`] trait bounds on methods if desired.
@@ -2084,6 +4053,13 @@ impl<'a, T: 'a + 'static, E: backend::WasmEngine> StoreContextMut<'a, T, E> {
         self.inner.engine()
     }
 
+    /// Gets the instance whose import is currently being called, if a host function is running
+    /// on behalf of a guest. Host functions that the host calls directly, including from within
+    /// another host function, have no caller.
+    pub fn caller(&self) -> Option<Caller> {
+        Caller::current(self.inner.data())
+    }
+
     /// Access the underlying data owned by this store.
     ///
     /// Same as [`Store::data`].
@@ -2210,9 +4186,33 @@ struct StoreInner<T: 'static, E: backend::WasmEngine> {
     /// The table of host functions.
     pub host_functions: FuncVec<T, E>,
     /// The table of host resources.
-    pub host_resources: Slab<Box<dyn Any + Send + Sync>>,
+    pub host_resources: Slab<HostResource>,
     /// A function that drops a host resource from this store.
     pub drop_host_resource: Option<wasm_runtime_layer::Func>,
+    /// The limits on values lifted from guest memory.
+    pub lift_limits: LiftLimits,
+    /// The futures and streams created in this store.
+    pub channels: Slab<Channel>,
+    /// The imports which guests are currently calling, from outermost to innermost. Calls that
+    /// the host started are marked with `None`, since they have no calling instance.
+    pub callers: Vec<Option<Arc<ImportSite>>>,
+    /// The number of core instances that components have created in the backend store.
+    pub core_instances: usize,
+    /// The number of trampolines that components have created in the backend store.
+    pub trampolines: usize,
+    /// The number of calls to [`Func::call_async`] in progress, during which threads must not be
+    /// parked while asynchronous host functions complete.
+    pub async_calls: usize,
+}
+
+/// A value that backs a host resource, along with the information needed to introspect it.
+struct HostResource {
+    /// The type of the resource.
+    pub ty: ResourceType,
+    /// The tracker of the [`ResourceOwn`] which created this resource.
+    pub tracker: Arc<AtomicUsize>,
+    /// The backing value.
+    pub value: Box<dyn Any + Send + Sync>,
 }
 
 /// Denotes a trampoline used by components to interact with the host.
@@ -2226,7 +4226,40 @@ enum GeneratedTrampoline {
     /// The guest would like to obtain the representation of a resource.
     ResourceRep(TypeResourceTableIndex),
     /// The guest would like to drop a resource.
-    ResourceDrop(TypeResourceTableIndex, Option<CoreDef>),
+    ResourceDrop(TypeResourceTableIndex),
+    /// A fused adapter would like to copy a string between two memories.
+    Transcoder {
+        /// The transcoding operation to perform.
+        op: Transcode,
+        /// The memory from which the string is read.
+        from: RuntimeMemoryIndex,
+        /// Whether the source memory uses 64-bit pointers.
+        from64: bool,
+        /// The memory to which the string is written.
+        to: RuntimeMemoryIndex,
+        /// Whether the destination memory uses 64-bit pointers.
+        to64: bool,
+    },
+    /// A fused adapter that cannot be called, because it lifts and lowers within the same instance.
+    AlwaysTrap,
+    /// A fused adapter would like to move an owned handle between two resource tables.
+    ResourceTransferOwn,
+    /// A fused adapter would like to lend a handle from one resource table to another.
+    ResourceTransferBorrow,
+    /// A fused adapter is beginning a call which may lend handles.
+    ResourceEnterCall,
+    /// A fused adapter is ending a call, so any lent handles should be returned.
+    ResourceExitCall,
+    /// The guest would like to create, copy, or drop a future or stream.
+    Channel(ChannelTable, ChannelOp),
+    /// The guest would like to create, read, or drop an error context.
+    ErrorContext(ErrorContextOp),
+    /// The guest would like to use a waitable set, or yield to other tasks.
+    Waitable(WaitableOp),
+    /// A fused adapter would like to move a future, stream, or error context between two tables.
+    TableTransfer,
+    /// A function lifted with the async ABI would like to return its results.
+    TaskReturn,
 }
 
 /// Represents a resource handle owned by a guest instance.
@@ -2247,6 +4280,8 @@ struct HandleTable {
     array: Slab<HandleElement>,
     /// The destructor for this handle type.
     destructor: Option<wasm_runtime_layer::Func>,
+    /// The resource type of the handles in this table, if it is visible to the component.
+    ty: Option<ResourceType>,
 }
 
 impl HandleTable {
@@ -2260,6 +4295,21 @@ impl HandleTable {
         self.destructor = destructor;
     }
 
+    /// Gets the resource type of the handles in this table, if known.
+    pub fn ty(&self) -> Option<&ResourceType> {
+        self.ty.as_ref()
+    }
+
+    /// Sets the resource type of the handles in this table.
+    pub fn set_ty(&mut self, ty: ResourceType) {
+        self.ty = Some(ty);
+    }
+
+    /// Iterates over the handles in this table, along with their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &HandleElement)> {
+        self.array.iter().map(|(i, x)| (i as i32, x))
+    }
+
     /// Gets the element at the specified slot, or fails if it is empty.
     pub fn get(&self, i: i32) -> Result<&HandleElement> {
         self.array.get(i as usize).context("Invalid handle index.")
@@ -2282,7 +4332,9 @@ impl HandleTable {
     /// Removes the handle at the provided index from the table,
     /// or fails if there was no handle present.
     pub fn remove(&mut self, i: i32) -> Result<HandleElement> {
-        Ok(self.array.remove(i as usize))
+        self.array
+            .try_remove(i as usize)
+            .context("Invalid handle index.")
     }
 }
 
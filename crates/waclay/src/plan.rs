@@ -0,0 +1,499 @@
+use std::sync::*;
+
+use anyhow::*;
+use wit_parser::*;
+
+use crate::abi::*;
+
+/// Declares the instructions which may be copied into a plan as-is, because they neither
+/// borrow from the function's types nor depend upon the values being converted.
+macro_rules! simple_ops {
+    ($($variant:ident $({ $($field:ident : $field_ty:ty),* })?,)*) => {
+        /// An instruction which does not borrow from the function's types.
+        #[derive(Copy, Clone, Debug)]
+        enum SimpleOp {
+            $(
+                /// Corresponds to the instruction of the same name.
+                $variant $({ $(
+                    /// Corresponds to the instruction field of the same name.
+                    $field: $field_ty
+                ),* })?,
+            )*
+        }
+
+        impl SimpleOp {
+            /// Gets the operation for the given instruction, if it is simple.
+            fn from_instruction(inst: &Instruction<'_>) -> Option<Self> {
+                match inst {
+                    $(Instruction::$variant $({ $($field),* })? => Some(Self::$variant $({ $($field: *$field),* })?),)*
+                    _ => None,
+                }
+            }
+
+            /// Gets the instruction for this operation.
+            fn instruction(self) -> Instruction<'static> {
+                match self {
+                    $(Self::$variant $({ $($field),* })? => Instruction::$variant $({ $($field),* })?,)*
+                }
+            }
+        }
+    };
+}
+
+simple_ops! {
+    GetArg { nth: usize },
+    I32Const { val: i32 },
+    I32Load { offset: i32 },
+    I32Load8U { offset: i32 },
+    I32Load8S { offset: i32 },
+    I32Load16U { offset: i32 },
+    I32Load16S { offset: i32 },
+    I64Load { offset: i32 },
+    F32Load { offset: i32 },
+    F64Load { offset: i32 },
+    I32Store { offset: i32 },
+    I32Store8 { offset: i32 },
+    I32Store16 { offset: i32 },
+    I64Store { offset: i32 },
+    F32Store { offset: i32 },
+    F64Store { offset: i32 },
+    I32FromChar,
+    I64FromU64,
+    I64FromS64,
+    I32FromU32,
+    I32FromS32,
+    I32FromU16,
+    I32FromS16,
+    I32FromU8,
+    I32FromS8,
+    F32FromFloat32,
+    F64FromFloat64,
+    S8FromI32,
+    U8FromI32,
+    S16FromI32,
+    U16FromI32,
+    S32FromI32,
+    U32FromI32,
+    S64FromI64,
+    U64FromI64,
+    CharFromI32,
+    Float32FromF32,
+    Float64FromF64,
+    BoolFromI32,
+    I32FromBool,
+    StringLower { realloc: Option<&'static str> },
+    StringLift,
+    ValidatePointer { size: usize, align: usize },
+    Malloc { realloc: &'static str, size: usize, align: usize },
+    FutureLower { ty: TypeId },
+    FutureLift { ty: TypeId },
+    StreamLower { ty: TypeId },
+    StreamLift { ty: TypeId },
+    ErrorContextLower,
+    ErrorContextLift,
+    FixedListLower { size: u32, ty: TypeId },
+    FixedListLift { size: u32, ty: TypeId },
+}
+
+/// An instruction recorded in a plan, which owns any type information that it refers to.
+#[derive(Clone, Debug)]
+enum PlanOp {
+    /// An instruction which does not refer to any types.
+    Simple(SimpleOp),
+    /// Lowers a list whose representation matches the canonical ABI.
+    ListCanonLower {
+        /// The element type.
+        element: Type,
+        /// The reallocation function to use.
+        realloc: Option<&'static str>,
+    },
+    /// Lifts a list whose representation matches the canonical ABI.
+    ListCanonLift {
+        /// The element type.
+        element: Type,
+        /// The list type.
+        ty: TypeId,
+    },
+    /// Lowers a record into its fields.
+    RecordLower {
+        /// The record definition.
+        record: Record,
+        /// The record type.
+        ty: TypeId,
+    },
+    /// Lifts a record from its fields.
+    RecordLift {
+        /// The record definition.
+        record: Record,
+        /// The record type.
+        ty: TypeId,
+    },
+    /// Lowers a resource handle.
+    HandleLower {
+        /// The handle definition.
+        handle: Handle,
+        /// The handle type.
+        ty: TypeId,
+    },
+    /// Lifts a resource handle.
+    HandleLift {
+        /// The handle definition.
+        handle: Handle,
+        /// The handle type.
+        ty: TypeId,
+    },
+    /// Lowers a tuple into its elements.
+    TupleLower {
+        /// The tuple definition.
+        tuple: Tuple,
+        /// The tuple type.
+        ty: TypeId,
+    },
+    /// Lifts a tuple from its elements.
+    TupleLift {
+        /// The tuple definition.
+        tuple: Tuple,
+        /// The tuple type.
+        ty: TypeId,
+    },
+    /// Lowers a set of flags.
+    FlagsLower {
+        /// The flags definition.
+        flags: Flags,
+        /// The flags type.
+        ty: TypeId,
+    },
+    /// Lifts a set of flags.
+    FlagsLift {
+        /// The flags definition.
+        flags: Flags,
+        /// The flags type.
+        ty: TypeId,
+    },
+    /// Lowers an enum to its discriminant.
+    EnumLower {
+        /// The enum definition.
+        enum_: Enum,
+        /// The enum type.
+        ty: TypeId,
+    },
+    /// Calls the core WASM function.
+    CallWasm,
+    /// Calls the core WASM function, which is lifted with the async ABI.
+    AsyncCallWasm,
+    /// Calls the interface function.
+    CallInterface,
+    /// Returns the given number of values from the function.
+    Return {
+        /// The number of values returned.
+        amt: usize,
+    },
+}
+
+impl PlanOp {
+    /// Records the given instruction, failing if it depends upon the values being converted.
+    fn from_instruction(inst: &Instruction<'_>) -> Result<Self> {
+        if let Some(op) = SimpleOp::from_instruction(inst) {
+            return Ok(Self::Simple(op));
+        }
+
+        Ok(match inst {
+            Instruction::ListCanonLower { element, realloc } => Self::ListCanonLower {
+                element: **element,
+                realloc: *realloc,
+            },
+            Instruction::ListCanonLift { element, ty } => Self::ListCanonLift {
+                element: **element,
+                ty: *ty,
+            },
+            Instruction::RecordLower { record, ty } => Self::RecordLower {
+                record: (*record).clone(),
+                ty: *ty,
+            },
+            Instruction::RecordLift { record, ty } => Self::RecordLift {
+                record: (*record).clone(),
+                ty: *ty,
+            },
+            Instruction::HandleLower { handle, ty } => Self::HandleLower {
+                handle: **handle,
+                ty: *ty,
+            },
+            Instruction::HandleLift { handle, ty } => Self::HandleLift {
+                handle: **handle,
+                ty: *ty,
+            },
+            Instruction::TupleLower { tuple, ty } => Self::TupleLower {
+                tuple: (*tuple).clone(),
+                ty: *ty,
+            },
+            Instruction::TupleLift { tuple, ty } => Self::TupleLift {
+                tuple: (*tuple).clone(),
+                ty: *ty,
+            },
+            Instruction::FlagsLower { flags, ty } => Self::FlagsLower {
+                flags: (*flags).clone(),
+                ty: *ty,
+            },
+            Instruction::FlagsLift { flags, ty } => Self::FlagsLift {
+                flags: (*flags).clone(),
+                ty: *ty,
+            },
+            Instruction::EnumLower { enum_, ty } => Self::EnumLower {
+                enum_: (*enum_).clone(),
+                ty: *ty,
+            },
+            Instruction::CallWasm { .. } => Self::CallWasm,
+            Instruction::AsyncCallWasm { .. } => Self::AsyncCallWasm,
+            Instruction::CallInterface { .. } => Self::CallInterface,
+            Instruction::Return { amt, .. } => Self::Return { amt: *amt },
+            _ => bail!("Instruction {inst:?} depends upon runtime values."),
+        })
+    }
+
+    /// Gets the instruction for this operation.
+    fn instruction<'a>(&'a self, func: &'a Function, sig: &'a WasmSignature) -> Instruction<'a> {
+        match self {
+            Self::Simple(op) => op.instruction(),
+            Self::ListCanonLower { element, realloc } => Instruction::ListCanonLower {
+                element,
+                realloc: *realloc,
+            },
+            Self::ListCanonLift { element, ty } => Instruction::ListCanonLift { element, ty: *ty },
+            Self::RecordLower { record, ty } => Instruction::RecordLower { record, ty: *ty },
+            Self::RecordLift { record, ty } => Instruction::RecordLift { record, ty: *ty },
+            Self::HandleLower { handle, ty } => Instruction::HandleLower { handle, ty: *ty },
+            Self::HandleLift { handle, ty } => Instruction::HandleLift { handle, ty: *ty },
+            Self::TupleLower { tuple, ty } => Instruction::TupleLower { tuple, ty: *ty },
+            Self::TupleLift { tuple, ty } => Instruction::TupleLift { tuple, ty: *ty },
+            Self::FlagsLower { flags, ty } => Instruction::FlagsLower { flags, ty: *ty },
+            Self::FlagsLift { flags, ty } => Instruction::FlagsLift { flags, ty: *ty },
+            Self::EnumLower { enum_, ty } => Instruction::EnumLower { enum_, ty: *ty },
+            Self::CallWasm => Instruction::CallWasm {
+                name: &func.name,
+                sig,
+            },
+            Self::AsyncCallWasm => Instruction::AsyncCallWasm {
+                name: &func.name,
+                sig,
+            },
+            Self::CallInterface => Instruction::CallInterface { func },
+            Self::Return { amt } => Instruction::Return { amt: *amt, func },
+        }
+    }
+}
+
+/// An operand consumed by a step of a plan.
+#[derive(Copy, Clone, Debug)]
+struct PlanInput {
+    /// The slot holding the operand.
+    slot: usize,
+    /// Whether no later step reads this slot, so that the operand may be moved out of it.
+    last_use: bool,
+}
+
+/// A single instruction of a plan, along with the slots that it reads and writes.
+#[derive(Clone, Debug)]
+struct PlanStep {
+    /// The instruction to emit.
+    op: PlanOp,
+    /// The operands of the instruction.
+    inputs: Vec<PlanInput>,
+    /// The first slot into which results are written. Results occupy consecutive slots.
+    first_output: usize,
+    /// The number of results that the instruction produces.
+    outputs: usize,
+}
+
+/// A compiled sequence of canonical ABI instructions for calling a function.
+///
+/// Running the [`Generator`] walks the function's signature on every call. For signatures whose
+/// instructions do not depend upon the values being passed, such as those made of scalars,
+/// strings, lists of scalars, records, tuples, and handles, the instructions are recorded once and
+/// then replayed against a [`Bindgen`]. Operands are kept in numbered slots rather than on a stack,
+/// so replaying a plan performs no type traversal. Signatures containing variants, options,
+/// results, or lists of non-scalar elements branch on runtime values, so they are not planned.
+#[derive(Clone, Debug)]
+pub(crate) struct FuncPlan {
+    /// The core signature of the function.
+    signature: WasmSignature,
+    /// The instructions to emit.
+    steps: Vec<PlanStep>,
+    /// The number of operand slots used by the plan.
+    slots: usize,
+    /// The largest number of operands consumed by any step.
+    max_inputs: usize,
+    /// The largest number of results produced by any step.
+    max_outputs: usize,
+}
+
+impl FuncPlan {
+    /// Compiles a plan for calling the given function, or returns `None` if the function's
+    /// instructions depend upon runtime values.
+    pub fn new(
+        resolve: &Resolve,
+        sizes: &SizeAlignTable,
+        variant: AbiVariant,
+        lift_lower: LiftLower,
+        func: &Function,
+    ) -> Option<Self> {
+        let mut recorder = PlanRecorder {
+            sizes,
+            steps: Vec::new(),
+            slots: 0,
+        };
+
+        Generator::new(resolve, variant, lift_lower, &mut recorder)
+            .call(func)
+            .ok()?;
+
+        // Walk the steps in reverse to find the final read of each slot.
+        let mut read = vec![false; recorder.slots];
+        for step in recorder.steps.iter_mut().rev() {
+            for input in step.inputs.iter_mut().rev() {
+                input.last_use = !read[input.slot];
+                read[input.slot] = true;
+            }
+        }
+
+        Some(Self {
+            signature: resolve.wasm_signature(variant, func),
+            max_inputs: recorder
+                .steps
+                .iter()
+                .map(|x| x.inputs.len())
+                .max()
+                .unwrap_or(0),
+            max_outputs: recorder.steps.iter().map(|x| x.outputs).max().unwrap_or(0),
+            steps: recorder.steps,
+            slots: recorder.slots,
+        })
+    }
+
+    /// Emits the plan's instructions to the provided bindgen, as if the generator had been called.
+    pub fn call<B: Bindgen>(
+        &self,
+        resolve: &Resolve,
+        func: &Function,
+        bindgen: &mut B,
+    ) -> Result<()> {
+        let mut slots = vec![None; self.slots];
+        let mut operands = Vec::with_capacity(self.max_inputs);
+        let mut results = Vec::with_capacity(self.max_outputs);
+
+        for step in &self.steps {
+            operands.clear();
+            results.clear();
+
+            for input in &step.inputs {
+                let operand = if input.last_use {
+                    slots[input.slot].take()
+                } else {
+                    slots[input.slot].clone()
+                };
+                operands.push(operand.context("Plan read an empty slot.")?);
+            }
+
+            bindgen.emit(
+                resolve,
+                &step.op.instruction(func, &self.signature),
+                &mut operands,
+                &mut results,
+            )?;
+
+            ensure!(
+                results.len() == step.outputs,
+                "{:?} expected {} results, got {}",
+                step.op,
+                step.outputs,
+                results.len()
+            );
+            for (slot, result) in slots[step.first_output..].iter_mut().zip(results.drain(..)) {
+                *slot = Some(result);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A plan for calling a function which is compiled upon first use.
+#[derive(Debug, Default)]
+pub(crate) struct CachedPlan(OnceLock<Option<FuncPlan>>);
+
+impl CachedPlan {
+    /// Emits the instructions for calling the given function to the provided bindgen. The
+    /// instructions are compiled into a plan on the first call, and functions which cannot be
+    /// planned use the generator instead.
+    pub fn call<B: Bindgen>(
+        &self,
+        component: &crate::ComponentInner,
+        variant: AbiVariant,
+        lift_lower: LiftLower,
+        func: &Function,
+        bindgen: &mut B,
+    ) -> Result<()> {
+        let plan = self.0.get_or_init(|| {
+            FuncPlan::new(
+                &component.resolve,
+                &component.size_align,
+                variant,
+                lift_lower,
+                func,
+            )
+        });
+
+        match plan {
+            Some(plan) => plan.call(&component.resolve, func, bindgen),
+            None => Generator::new(&component.resolve, variant, lift_lower, bindgen).call(func),
+        }
+    }
+}
+
+/// Records the instructions emitted by a generator, treating each operand as a numbered slot.
+struct PlanRecorder<'a> {
+    /// The sizes of the component's types.
+    sizes: &'a SizeAlignTable,
+    /// The instructions recorded so far.
+    steps: Vec<PlanStep>,
+    /// The number of slots allocated so far.
+    slots: usize,
+}
+
+impl Bindgen for PlanRecorder<'_> {
+    type Operand = usize;
+
+    fn emit(
+        &mut self,
+        _resolve: &Resolve,
+        inst: &Instruction<'_>,
+        operands: &mut Vec<Self::Operand>,
+        results: &mut Vec<Self::Operand>,
+    ) -> Result<()> {
+        let op = PlanOp::from_instruction(inst)?;
+        let outputs = inst.results_len();
+        self.steps.push(PlanStep {
+            op,
+            inputs: operands
+                .drain(..)
+                .map(|slot| PlanInput {
+                    slot,
+                    last_use: false,
+                })
+                .collect(),
+            first_output: self.slots,
+            outputs,
+        });
+
+        results.extend(self.slots..self.slots + outputs);
+        self.slots += outputs;
+        Ok(())
+    }
+
+    fn sizes(&self) -> &SizeAlignTable {
+        self.sizes
+    }
+
+    fn is_list_canonical(&self, element: &Type) -> bool {
+        crate::func::is_list_canonical(element)
+    }
+}
@@ -0,0 +1,1250 @@
+use std::marker::PhantomData;
+
+use anyhow::*;
+use fxhash::*;
+use id_arena::*;
+use serde::{Deserialize, Serialize};
+use wasm_runtime_layer::*;
+use wasmtime_environ::component::{
+    ComponentTranslation, FixedEncoding, LoweredIndex, OptionsIndex, RuntimeMemoryIndex,
+    StaticModuleIndex, Trampoline, Transcode, TypeComponentLocalErrorContextTableIndex,
+    TypeFuncIndex, TypeFutureTableIndex, TypeResourceTableIndex, TypeStreamTableIndex,
+    TypeTupleIndex,
+};
+use wit_parser::*;
+
+use crate::abi::SizeAlignTable;
+use crate::{Component, ComponentMetadata, InterfaceIdentifier, PackageIdentifier, TypeIdentifier};
+
+/// The bytes with which every serialized component begins.
+const MAGIC: &[u8] = b"\0waclay-component";
+
+/// Every transcoding operation, indexed by its serialized representation.
+const TRANSCODES: [Transcode; 12] = [
+    Transcode::Copy(FixedEncoding::Utf8),
+    Transcode::Copy(FixedEncoding::Utf16),
+    Transcode::Copy(FixedEncoding::Latin1),
+    Transcode::Latin1ToUtf16,
+    Transcode::Latin1ToUtf8,
+    Transcode::Utf16ToCompactProbablyUtf16,
+    Transcode::Utf16ToCompactUtf16,
+    Transcode::Utf16ToLatin1,
+    Transcode::Utf16ToUtf8,
+    Transcode::Utf8ToCompactUtf16,
+    Transcode::Utf8ToLatin1,
+    Transcode::Utf8ToUtf16,
+];
+
+/// The versions of this crate and of every dependency whose types are serialized with their own
+/// encoding. A component may only be reloaded if all of these match.
+const VERSIONS: [(&str, &str); 2] = [
+    ("waclay", env!("CARGO_PKG_VERSION")),
+    ("wasmtime-environ", wasmtime_environ::VERSION),
+];
+
+impl Component {
+    /// Serializes the parsed metadata and core modules of this component into bytes.
+    /// The result may be reloaded with [`Component::deserialize`], which skips decoding
+    /// and translating the original component binary.
+    ///
+    /// The output is only guaranteed to be readable by the same version of this crate,
+    /// built against the same version of `wasmtime-environ`.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let inner = &self.0;
+
+        let mut modules = inner.modules.iter().collect::<Vec<_>>();
+        modules.sort_unstable_by_key(|(idx, _)| **idx);
+
+        let payload = postcard::to_allocvec(&SerializedComponentRef {
+            resolve: SerializedResolve::new(&inner.resolve),
+            world: inner.world_id.index(),
+            package: &inner.package,
+            interface_identifiers: &inner.interface_identifiers,
+            type_identifiers: &inner.type_identifiers,
+            export_mapping: &inner.export_mapping,
+            size_align: &inner.size_align,
+            component: &inner.translation.component,
+            trampolines: inner
+                .translation
+                .trampolines
+                .values()
+                .map(SerializedTrampoline::new)
+                .collect::<Result<_>>()?,
+            types: &inner.component_types,
+            owned_resource_tables: &inner.owned_resource_tables,
+            modules: modules
+                .into_iter()
+                .map(|(_, module)| SerializedModuleRef {
+                    bytes: &module.bytes,
+                    translation: &module.translation,
+                })
+                .collect(),
+        })
+        .context("Could not serialize component.")?;
+
+        postcard::to_extend(
+            &SerializedHeader {
+                versions: VERSIONS
+                    .iter()
+                    .map(|(name, version)| (name.to_string(), version.to_string()))
+                    .collect(),
+                checksum: fxhash::hash64(&payload),
+                payload,
+            },
+            MAGIC.to_vec(),
+        )
+        .context("Could not serialize component.")
+    }
+
+    /// Reloads a component from bytes that were produced by [`Component::serialize`].
+    /// Only the core modules are compiled by the engine. The WIT, identifiers, type layouts,
+    /// and export mapping are used as-is; only the resource and function types, which are
+    /// unique to each component, are created anew.
+    ///
+    /// Fails with [`crate::Error::DecodeError`] if the bytes were produced by a different version
+    /// of this crate or its dependencies, or were corrupted.
+    pub fn deserialize<E: backend::WasmEngine>(engine: &Engine<E>, bytes: &[u8]) -> Result<Self> {
+        Self::read_serialized(engine, bytes).map_err(|x| crate::Error::DecodeError(x).into())
+    }
+
+    /// Decodes the contents of a serialized component and compiles its core modules.
+    fn read_serialized<E: backend::WasmEngine>(engine: &Engine<E>, bytes: &[u8]) -> Result<Self> {
+        let header = bytes
+            .strip_prefix(MAGIC)
+            .context("Bytes do not contain a serialized component.")?;
+        let header = postcard::from_bytes::<SerializedHeader>(header)
+            .context("Could not read serialized component header.")?;
+
+        for (name, version) in VERSIONS {
+            let serialized = header
+                .versions
+                .iter()
+                .find(|(x, _)| x == name)
+                .map_or("none", |(_, x)| x.as_str());
+            ensure!(
+                serialized == version,
+                "Component was serialized with version {serialized} of {name}, but version {version} is in use."
+            );
+        }
+        ensure!(
+            header.checksum == fxhash::hash64(&header.payload),
+            "Serialized component checksum did not match."
+        );
+
+        let serialized = postcard::from_bytes::<SerializedComponent>(&header.payload)
+            .context("Could not deserialize component.")?;
+
+        let resolve = serialized.resolve.into_resolve()?;
+        let world_id = resolve
+            .worlds
+            .iter()
+            .nth(serialized.world)
+            .map(|(id, _)| id)
+            .context("Serialized component world was missing.")?;
+        ensure!(
+            serialized.type_identifiers.len() == resolve.types.len()
+                && serialized.interface_identifiers.len() == resolve.interfaces.len(),
+            "Serialized component identifiers did not match its WIT."
+        );
+
+        let translation = ComponentTranslation {
+            component: serialized.component,
+            trampolines: serialized
+                .trampolines
+                .into_iter()
+                .map(SerializedTrampoline::into_trampoline)
+                .collect::<Result<_>>()?,
+        };
+
+        let inner = Self::create_inner(
+            engine,
+            ComponentMetadata {
+                resolve,
+                world_id,
+                package: serialized.package,
+                interface_identifiers: serialized.interface_identifiers,
+                type_identifiers: serialized.type_identifiers,
+                export_mapping: serialized.export_mapping,
+                size_align: serialized.size_align,
+            },
+            translation,
+            serialized
+                .modules
+                .into_iter()
+                .map(|x| (x.bytes, x.translation)),
+            serialized.owned_resource_tables,
+        )?;
+
+        Self::initialize(inner, serialized.types)
+    }
+}
+
+/// Maps from module indices to the names of their exported entities.
+type ExportMapping = FxHashMap<StaticModuleIndex, FxHashMap<wasmtime_environ::EntityIndex, String>>;
+
+/// Guards a serialized component against being loaded by an incompatible version of this crate.
+#[derive(Serialize, Deserialize)]
+struct SerializedHeader {
+    /// The names and versions of this crate and its dependencies which serialized the component.
+    versions: Vec<(String, String)>,
+    /// A hash of the payload, used to detect corruption.
+    checksum: u64,
+    /// The serialized component.
+    payload: Vec<u8>,
+}
+
+/// The serialized form of a component, borrowed from its backing.
+#[derive(Serialize)]
+struct SerializedComponentRef<'a> {
+    /// The resolved WIT of the component.
+    resolve: SerializedResolve,
+    /// The index of the component's world.
+    world: usize,
+    /// The package identifier for the component.
+    package: &'a PackageIdentifier,
+    /// A mapping from interface index to parsed identifier.
+    interface_identifiers: &'a [InterfaceIdentifier],
+    /// A mapping from type index to parsed identifier.
+    type_identifiers: &'a [Option<TypeIdentifier>],
+    /// Maps from module indices to export indices for linking.
+    export_mapping: &'a ExportMapping,
+    /// The size and alignment of component types.
+    size_align: &'a SizeAlignTable,
+    /// The translated component.
+    component: &'a wasmtime_environ::component::Component,
+    /// The trampolines required by the component.
+    trampolines: Vec<SerializedTrampoline>,
+    /// The type information of the translated component.
+    types: &'a wasmtime_environ::component::ComponentTypes,
+    /// Whether each resource table belongs to the component instance which defines its resource.
+    owned_resource_tables: &'a [bool],
+    /// The core modules of the component, in index order.
+    modules: Vec<SerializedModuleRef<'a>>,
+}
+
+/// The serialized form of a component, as it is reloaded.
+#[derive(Deserialize)]
+struct SerializedComponent {
+    /// The resolved WIT of the component.
+    resolve: SerializedResolve,
+    /// The index of the component's world.
+    world: usize,
+    /// The package identifier for the component.
+    package: PackageIdentifier,
+    /// A mapping from interface index to parsed identifier.
+    interface_identifiers: Vec<InterfaceIdentifier>,
+    /// A mapping from type index to parsed identifier.
+    type_identifiers: Vec<Option<TypeIdentifier>>,
+    /// Maps from module indices to export indices for linking.
+    export_mapping: ExportMapping,
+    /// The size and alignment of component types.
+    size_align: SizeAlignTable,
+    /// The translated component.
+    component: wasmtime_environ::component::Component,
+    /// The trampolines required by the component.
+    trampolines: Vec<SerializedTrampoline>,
+    /// The type information of the translated component.
+    types: wasmtime_environ::component::ComponentTypes,
+    /// Whether each resource table belongs to the component instance which defines its resource.
+    owned_resource_tables: Vec<bool>,
+    /// The core modules of the component, in index order.
+    modules: Vec<SerializedModule>,
+}
+
+/// The serialized form of a core module, borrowed from its backing.
+#[derive(Serialize)]
+struct SerializedModuleRef<'a> {
+    /// The binary data of the module.
+    bytes: &'a [u8],
+    /// The translation data for the module.
+    translation: &'a wasmtime_environ::Module,
+}
+
+/// The serialized form of a core module, as it is reloaded.
+#[derive(Deserialize)]
+struct SerializedModule {
+    /// The binary data of the module.
+    bytes: Vec<u8>,
+    /// The translation data for the module.
+    translation: wasmtime_environ::Module,
+}
+
+/// A serializable mirror of the trampolines which this crate supports.
+#[derive(Serialize, Deserialize)]
+enum SerializedTrampoline {
+    /// A lowered import.
+    LowerImport {
+        /// The runtime lowering state that this trampoline will access.
+        index: LoweredIndex,
+        /// The type of the function that is being lowered.
+        lower_ty: TypeFuncIndex,
+        /// The canonical ABI options used when lowering this function.
+        options: OptionsIndex,
+    },
+    /// A string transcoder required by a fused adapter.
+    Transcoder {
+        /// The index of the transcoding operation in [`TRANSCODES`].
+        op: u8,
+        /// The memory from which the string is read.
+        from: RuntimeMemoryIndex,
+        /// Whether the source memory uses 64-bit pointers.
+        from64: bool,
+        /// The memory to which the string is written.
+        to: RuntimeMemoryIndex,
+        /// Whether the destination memory uses 64-bit pointers.
+        to64: bool,
+    },
+    /// An adapter which always traps.
+    AlwaysTrap,
+    /// A `resource.new` intrinsic.
+    ResourceNew(TypeResourceTableIndex),
+    /// A `resource.rep` intrinsic.
+    ResourceRep(TypeResourceTableIndex),
+    /// A `resource.drop` intrinsic.
+    ResourceDrop(TypeResourceTableIndex),
+    /// Moves an owned handle between two resource tables.
+    ResourceTransferOwn,
+    /// Lends a handle from one resource table to another.
+    ResourceTransferBorrow,
+    /// Begins a call which may lend handles.
+    ResourceEnterCall,
+    /// Ends a call which may have lent handles.
+    ResourceExitCall,
+    /// A `task.return` intrinsic.
+    TaskReturn {
+        /// The types of the values which are returned.
+        results: TypeTupleIndex,
+        /// The canonical ABI options used when lifting the returned values.
+        options: OptionsIndex,
+    },
+    /// A `stream.new` intrinsic.
+    StreamNew {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+    },
+    /// A `stream.read` intrinsic.
+    StreamRead {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+        /// The canonical ABI options used when copying values.
+        options: OptionsIndex,
+    },
+    /// A `stream.write` intrinsic.
+    StreamWrite {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+        /// The canonical ABI options used when copying values.
+        options: OptionsIndex,
+    },
+    /// A `stream.cancel-read` intrinsic.
+    StreamCancelRead {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+        /// Whether the intrinsic may return before the copy is cancelled.
+        async_: bool,
+    },
+    /// A `stream.cancel-write` intrinsic.
+    StreamCancelWrite {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+        /// Whether the intrinsic may return before the copy is cancelled.
+        async_: bool,
+    },
+    /// A `stream.drop-readable` intrinsic.
+    StreamDropReadable {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+    },
+    /// A `stream.drop-writable` intrinsic.
+    StreamDropWritable {
+        /// The table of the stream type.
+        ty: TypeStreamTableIndex,
+    },
+    /// Moves a stream handle between two tables.
+    StreamTransfer,
+    /// A `future.new` intrinsic.
+    FutureNew {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+    },
+    /// A `future.read` intrinsic.
+    FutureRead {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+        /// The canonical ABI options used when copying values.
+        options: OptionsIndex,
+    },
+    /// A `future.write` intrinsic.
+    FutureWrite {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+        /// The canonical ABI options used when copying values.
+        options: OptionsIndex,
+    },
+    /// A `future.cancel-read` intrinsic.
+    FutureCancelRead {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+        /// Whether the intrinsic may return before the copy is cancelled.
+        async_: bool,
+    },
+    /// A `future.cancel-write` intrinsic.
+    FutureCancelWrite {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+        /// Whether the intrinsic may return before the copy is cancelled.
+        async_: bool,
+    },
+    /// A `future.drop-readable` intrinsic.
+    FutureDropReadable {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+    },
+    /// A `future.drop-writable` intrinsic.
+    FutureDropWritable {
+        /// The table of the future type.
+        ty: TypeFutureTableIndex,
+    },
+    /// Moves a future handle between two tables.
+    FutureTransfer,
+    /// An `error-context.new` intrinsic.
+    ErrorContextNew {
+        /// The table of error contexts.
+        ty: TypeComponentLocalErrorContextTableIndex,
+        /// The canonical ABI options used when reading the debug message.
+        options: OptionsIndex,
+    },
+    /// An `error-context.debug-message` intrinsic.
+    ErrorContextDebugMessage {
+        /// The table of error contexts.
+        ty: TypeComponentLocalErrorContextTableIndex,
+        /// The canonical ABI options used when writing the debug message.
+        options: OptionsIndex,
+    },
+    /// An `error-context.drop` intrinsic.
+    ErrorContextDrop {
+        /// The table of error contexts.
+        ty: TypeComponentLocalErrorContextTableIndex,
+    },
+    /// Moves an error context handle between two tables.
+    ErrorContextTransfer,
+}
+
+impl SerializedTrampoline {
+    /// Creates the serialized form of a trampoline.
+    fn new(trampoline: &Trampoline) -> Result<Self> {
+        Ok(match trampoline {
+            Trampoline::LowerImport {
+                index,
+                lower_ty,
+                options,
+            } => Self::LowerImport {
+                index: *index,
+                lower_ty: *lower_ty,
+                options: *options,
+            },
+            Trampoline::Transcoder {
+                op,
+                from,
+                from64,
+                to,
+                to64,
+            } => Self::Transcoder {
+                op: TRANSCODES
+                    .iter()
+                    .position(|x| x == op)
+                    .context("Unknown transcoding operation.")? as u8,
+                from: *from,
+                from64: *from64,
+                to: *to,
+                to64: *to64,
+            },
+            Trampoline::AlwaysTrap => Self::AlwaysTrap,
+            Trampoline::ResourceNew(x) => Self::ResourceNew(*x),
+            Trampoline::ResourceRep(x) => Self::ResourceRep(*x),
+            Trampoline::ResourceDrop(x) => Self::ResourceDrop(*x),
+            Trampoline::ResourceTransferOwn => Self::ResourceTransferOwn,
+            Trampoline::ResourceTransferBorrow => Self::ResourceTransferBorrow,
+            Trampoline::ResourceEnterCall => Self::ResourceEnterCall,
+            Trampoline::ResourceExitCall => Self::ResourceExitCall,
+            Trampoline::TaskReturn { results, options } => Self::TaskReturn {
+                results: *results,
+                options: *options,
+            },
+            Trampoline::StreamNew { ty } => Self::StreamNew { ty: *ty },
+            Trampoline::StreamRead { ty, options } => Self::StreamRead {
+                ty: *ty,
+                options: *options,
+            },
+            Trampoline::StreamWrite { ty, options } => Self::StreamWrite {
+                ty: *ty,
+                options: *options,
+            },
+            Trampoline::StreamCancelRead { ty, async_ } => Self::StreamCancelRead {
+                ty: *ty,
+                async_: *async_,
+            },
+            Trampoline::StreamCancelWrite { ty, async_ } => Self::StreamCancelWrite {
+                ty: *ty,
+                async_: *async_,
+            },
+            Trampoline::StreamDropReadable { ty } => Self::StreamDropReadable { ty: *ty },
+            Trampoline::StreamDropWritable { ty } => Self::StreamDropWritable { ty: *ty },
+            Trampoline::StreamTransfer => Self::StreamTransfer,
+            Trampoline::FutureNew { ty } => Self::FutureNew { ty: *ty },
+            Trampoline::FutureRead { ty, options } => Self::FutureRead {
+                ty: *ty,
+                options: *options,
+            },
+            Trampoline::FutureWrite { ty, options } => Self::FutureWrite {
+                ty: *ty,
+                options: *options,
+            },
+            Trampoline::FutureCancelRead { ty, async_ } => Self::FutureCancelRead {
+                ty: *ty,
+                async_: *async_,
+            },
+            Trampoline::FutureCancelWrite { ty, async_ } => Self::FutureCancelWrite {
+                ty: *ty,
+                async_: *async_,
+            },
+            Trampoline::FutureDropReadable { ty } => Self::FutureDropReadable { ty: *ty },
+            Trampoline::FutureDropWritable { ty } => Self::FutureDropWritable { ty: *ty },
+            Trampoline::FutureTransfer => Self::FutureTransfer,
+            Trampoline::ErrorContextNew { ty, options } => Self::ErrorContextNew {
+                ty: *ty,
+                options: *options,
+            },
+            Trampoline::ErrorContextDebugMessage { ty, options } => {
+                Self::ErrorContextDebugMessage {
+                    ty: *ty,
+                    options: *options,
+                }
+            }
+            Trampoline::ErrorContextDrop { ty } => Self::ErrorContextDrop { ty: *ty },
+            Trampoline::ErrorContextTransfer => Self::ErrorContextTransfer,
+            _ => bail!("Trampoline not implemented."),
+        })
+    }
+
+    /// Converts this back into a trampoline.
+    fn into_trampoline(self) -> Result<Trampoline> {
+        Ok(match self {
+            Self::LowerImport {
+                index,
+                lower_ty,
+                options,
+            } => Trampoline::LowerImport {
+                index,
+                lower_ty,
+                options,
+            },
+            Self::Transcoder {
+                op,
+                from,
+                from64,
+                to,
+                to64,
+            } => Trampoline::Transcoder {
+                op: *TRANSCODES
+                    .get(op as usize)
+                    .context("Unknown transcoding operation.")?,
+                from,
+                from64,
+                to,
+                to64,
+            },
+            Self::AlwaysTrap => Trampoline::AlwaysTrap,
+            Self::ResourceNew(x) => Trampoline::ResourceNew(x),
+            Self::ResourceRep(x) => Trampoline::ResourceRep(x),
+            Self::ResourceDrop(x) => Trampoline::ResourceDrop(x),
+            Self::ResourceTransferOwn => Trampoline::ResourceTransferOwn,
+            Self::ResourceTransferBorrow => Trampoline::ResourceTransferBorrow,
+            Self::ResourceEnterCall => Trampoline::ResourceEnterCall,
+            Self::ResourceExitCall => Trampoline::ResourceExitCall,
+            Self::TaskReturn { results, options } => Trampoline::TaskReturn { results, options },
+            Self::StreamNew { ty } => Trampoline::StreamNew { ty },
+            Self::StreamRead { ty, options } => Trampoline::StreamRead { ty, options },
+            Self::StreamWrite { ty, options } => Trampoline::StreamWrite { ty, options },
+            Self::StreamCancelRead { ty, async_ } => Trampoline::StreamCancelRead { ty, async_ },
+            Self::StreamCancelWrite { ty, async_ } => Trampoline::StreamCancelWrite { ty, async_ },
+            Self::StreamDropReadable { ty } => Trampoline::StreamDropReadable { ty },
+            Self::StreamDropWritable { ty } => Trampoline::StreamDropWritable { ty },
+            Self::StreamTransfer => Trampoline::StreamTransfer,
+            Self::FutureNew { ty } => Trampoline::FutureNew { ty },
+            Self::FutureRead { ty, options } => Trampoline::FutureRead { ty, options },
+            Self::FutureWrite { ty, options } => Trampoline::FutureWrite { ty, options },
+            Self::FutureCancelRead { ty, async_ } => Trampoline::FutureCancelRead { ty, async_ },
+            Self::FutureCancelWrite { ty, async_ } => Trampoline::FutureCancelWrite { ty, async_ },
+            Self::FutureDropReadable { ty } => Trampoline::FutureDropReadable { ty },
+            Self::FutureDropWritable { ty } => Trampoline::FutureDropWritable { ty },
+            Self::FutureTransfer => Trampoline::FutureTransfer,
+            Self::ErrorContextNew { ty, options } => Trampoline::ErrorContextNew { ty, options },
+            Self::ErrorContextDebugMessage { ty, options } => {
+                Trampoline::ErrorContextDebugMessage { ty, options }
+            }
+            Self::ErrorContextDrop { ty } => Trampoline::ErrorContextDrop { ty },
+            Self::ErrorContextTransfer => Trampoline::ErrorContextTransfer,
+        })
+    }
+}
+
+/// A serializable mirror of a [`Resolve`]. Documentation and stability attributes are not
+/// retained, since they are not needed once a component has been parsed.
+#[derive(Serialize, Deserialize)]
+struct SerializedResolve {
+    /// All worlds within the resolve, in index order.
+    worlds: Vec<SerializedWorld>,
+    /// All interfaces within the resolve, in index order.
+    interfaces: Vec<SerializedInterface>,
+    /// All types within the resolve, in index order.
+    types: Vec<SerializedTypeDef>,
+    /// All packages within the resolve, in index order.
+    packages: Vec<SerializedPackage>,
+}
+
+impl SerializedResolve {
+    /// Creates the serialized form of a resolve.
+    fn new(resolve: &Resolve) -> Self {
+        Self {
+            worlds: resolve
+                .worlds
+                .iter()
+                .map(|(_, x)| SerializedWorld {
+                    name: x.name.clone(),
+                    imports: x
+                        .imports
+                        .iter()
+                        .map(|(k, v)| (k.into(), v.into()))
+                        .collect(),
+                    exports: x
+                        .exports
+                        .iter()
+                        .map(|(k, v)| (k.into(), v.into()))
+                        .collect(),
+                    package: x.package.map(|x| x.index()),
+                })
+                .collect(),
+            interfaces: resolve
+                .interfaces
+                .iter()
+                .map(|(_, x)| SerializedInterface {
+                    name: x.name.clone(),
+                    types: x
+                        .types
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.index()))
+                        .collect(),
+                    functions: x.functions.values().map(Into::into).collect(),
+                    package: x.package.map(|x| x.index()),
+                })
+                .collect(),
+            types: resolve
+                .types
+                .iter()
+                .map(|(_, x)| SerializedTypeDef {
+                    name: x.name.clone(),
+                    kind: (&x.kind).into(),
+                    owner: match x.owner {
+                        TypeOwner::World(x) => SerializedTypeOwner::World(x.index()),
+                        TypeOwner::Interface(x) => SerializedTypeOwner::Interface(x.index()),
+                        TypeOwner::None => SerializedTypeOwner::None,
+                    },
+                })
+                .collect(),
+            packages: resolve
+                .packages
+                .iter()
+                .map(|(_, x)| SerializedPackage {
+                    namespace: x.name.namespace.clone(),
+                    name: x.name.name.clone(),
+                    version: x.name.version.clone(),
+                    interfaces: x
+                        .interfaces
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.index()))
+                        .collect(),
+                    worlds: x
+                        .worlds
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.index()))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Converts this back into a resolve, checking that every reference is in bounds.
+    fn into_resolve(self) -> Result<Resolve> {
+        let mut resolve = Resolve::default();
+        let ids = ResolveIds {
+            worlds: ArenaIds::new(&resolve.worlds, self.worlds.len()),
+            interfaces: ArenaIds::new(&resolve.interfaces, self.interfaces.len()),
+            types: ArenaIds::new(&resolve.types, self.types.len()),
+            packages: ArenaIds::new(&resolve.packages, self.packages.len()),
+        };
+
+        for world in self.worlds {
+            resolve.worlds.alloc(World {
+                name: world.name,
+                imports: world
+                    .imports
+                    .into_iter()
+                    .map(|(k, v)| Ok((k.into_world_key(&ids)?, v.into_world_item(&ids)?)))
+                    .collect::<Result<_>>()?,
+                exports: world
+                    .exports
+                    .into_iter()
+                    .map(|(k, v)| Ok((k.into_world_key(&ids)?, v.into_world_item(&ids)?)))
+                    .collect::<Result<_>>()?,
+                package: world.package.map(|x| ids.packages.get(x)).transpose()?,
+                docs: Docs::default(),
+                stability: Stability::Unknown,
+                includes: Vec::new(),
+                include_names: Vec::new(),
+            });
+        }
+
+        for interface in self.interfaces {
+            resolve.interfaces.alloc(Interface {
+                name: interface.name,
+                types: interface
+                    .types
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, ids.types.get(v)?)))
+                    .collect::<Result<_>>()?,
+                functions: interface
+                    .functions
+                    .into_iter()
+                    .map(|x| {
+                        let func = x.into_function(&ids)?;
+                        Ok((func.name.clone(), func))
+                    })
+                    .collect::<Result<_>>()?,
+                docs: Docs::default(),
+                stability: Stability::Unknown,
+                package: interface.package.map(|x| ids.packages.get(x)).transpose()?,
+            });
+        }
+
+        for ty in self.types {
+            resolve.types.alloc(TypeDef {
+                name: ty.name,
+                kind: ty.kind.into_kind(&ids)?,
+                owner: match ty.owner {
+                    SerializedTypeOwner::World(x) => TypeOwner::World(ids.worlds.get(x)?),
+                    SerializedTypeOwner::Interface(x) => {
+                        TypeOwner::Interface(ids.interfaces.get(x)?)
+                    }
+                    SerializedTypeOwner::None => TypeOwner::None,
+                },
+                docs: Docs::default(),
+                stability: Stability::Unknown,
+            });
+        }
+
+        for package in self.packages {
+            let name = PackageName {
+                namespace: package.namespace,
+                name: package.name,
+                version: package.version,
+            };
+
+            let id = resolve.packages.alloc(Package {
+                name: name.clone(),
+                docs: Docs::default(),
+                interfaces: package
+                    .interfaces
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, ids.interfaces.get(v)?)))
+                    .collect::<Result<_>>()?,
+                worlds: package
+                    .worlds
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, ids.worlds.get(v)?)))
+                    .collect::<Result<_>>()?,
+            });
+            resolve.package_names.insert(name, id);
+        }
+
+        Ok(resolve)
+    }
+}
+
+/// The IDs which will be assigned to each arena of a resolve as it is rebuilt.
+struct ResolveIds {
+    /// The IDs of worlds.
+    worlds: ArenaIds<World>,
+    /// The IDs of interfaces.
+    interfaces: ArenaIds<Interface>,
+    /// The IDs of types.
+    types: ArenaIds<TypeDef>,
+    /// The IDs of packages.
+    packages: ArenaIds<Package>,
+}
+
+/// Creates IDs for an arena that has not yet been filled.
+struct ArenaIds<T> {
+    /// The unique identifier of the arena.
+    arena_id: u32,
+    /// The number of items that the arena will hold.
+    len: usize,
+    /// Marks the type of item in the arena.
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ArenaIds<T> {
+    /// Prepares to create IDs for the given empty arena, which will hold `len` items.
+    fn new(arena: &Arena<T>, len: usize) -> Self {
+        Self {
+            arena_id: DefaultArenaBehavior::<T>::arena_id(arena.next_id()),
+            len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets the ID that the item at `index` will have, if it is in bounds.
+    fn get(&self, index: usize) -> Result<Id<T>> {
+        ensure!(
+            index < self.len,
+            "Serialized component WIT referenced a missing item."
+        );
+        Ok(DefaultArenaBehavior::<T>::new_id(self.arena_id, index))
+    }
+}
+
+/// The serialized form of a world.
+#[derive(Serialize, Deserialize)]
+struct SerializedWorld {
+    /// The name of the world.
+    name: String,
+    /// The items imported by the world.
+    imports: Vec<(SerializedWorldKey, SerializedWorldItem)>,
+    /// The items exported by the world.
+    exports: Vec<(SerializedWorldKey, SerializedWorldItem)>,
+    /// The index of the package which owns the world.
+    package: Option<usize>,
+}
+
+/// The serialized form of a world key.
+#[derive(Serialize, Deserialize)]
+enum SerializedWorldKey {
+    /// A kebab-name.
+    Name(String),
+    /// The index of an interface which is assigned no name.
+    Interface(usize),
+}
+
+impl From<&WorldKey> for SerializedWorldKey {
+    fn from(value: &WorldKey) -> Self {
+        match value {
+            WorldKey::Name(x) => Self::Name(x.clone()),
+            WorldKey::Interface(x) => Self::Interface(x.index()),
+        }
+    }
+}
+
+impl SerializedWorldKey {
+    /// Converts this back into a world key.
+    fn into_world_key(self, ids: &ResolveIds) -> Result<WorldKey> {
+        Ok(match self {
+            Self::Name(x) => WorldKey::Name(x),
+            Self::Interface(x) => WorldKey::Interface(ids.interfaces.get(x)?),
+        })
+    }
+}
+
+/// The serialized form of an item imported or exported by a world.
+#[derive(Serialize, Deserialize)]
+enum SerializedWorldItem {
+    /// The index of an interface.
+    Interface(usize),
+    /// A freestanding function.
+    Function(SerializedFunction),
+    /// The index of a type.
+    Type(usize),
+}
+
+impl From<&WorldItem> for SerializedWorldItem {
+    fn from(value: &WorldItem) -> Self {
+        match value {
+            WorldItem::Interface { id, .. } => Self::Interface(id.index()),
+            WorldItem::Function(x) => Self::Function(x.into()),
+            WorldItem::Type(x) => Self::Type(x.index()),
+        }
+    }
+}
+
+impl SerializedWorldItem {
+    /// Converts this back into a world item.
+    fn into_world_item(self, ids: &ResolveIds) -> Result<WorldItem> {
+        Ok(match self {
+            Self::Interface(x) => WorldItem::Interface {
+                id: ids.interfaces.get(x)?,
+                stability: Stability::Unknown,
+            },
+            Self::Function(x) => WorldItem::Function(x.into_function(ids)?),
+            Self::Type(x) => WorldItem::Type(ids.types.get(x)?),
+        })
+    }
+}
+
+/// The serialized form of an interface.
+#[derive(Serialize, Deserialize)]
+struct SerializedInterface {
+    /// The name of the interface, if any.
+    name: Option<String>,
+    /// The names and indices of the types exported by the interface.
+    types: Vec<(String, usize)>,
+    /// The functions exported by the interface.
+    functions: Vec<SerializedFunction>,
+    /// The index of the package which owns the interface.
+    package: Option<usize>,
+}
+
+/// The serialized form of a package.
+#[derive(Serialize, Deserialize)]
+struct SerializedPackage {
+    /// The namespace of the package.
+    namespace: String,
+    /// The name of the package.
+    name: String,
+    /// The version of the package.
+    version: Option<semver::Version>,
+    /// The names and indices of the interfaces within the package.
+    interfaces: Vec<(String, usize)>,
+    /// The names and indices of the worlds within the package.
+    worlds: Vec<(String, usize)>,
+}
+
+/// The serialized form of a function.
+#[derive(Serialize, Deserialize)]
+struct SerializedFunction {
+    /// The name of the function.
+    name: String,
+    /// The kind of the function.
+    kind: SerializedFunctionKind,
+    /// The names and types of the parameters.
+    params: Vec<(String, SerializedType)>,
+    /// The result type, if any.
+    result: Option<SerializedType>,
+}
+
+impl From<&Function> for SerializedFunction {
+    fn from(value: &Function) -> Self {
+        Self {
+            name: value.name.clone(),
+            kind: match value.kind {
+                FunctionKind::Freestanding => SerializedFunctionKind::Freestanding,
+                FunctionKind::AsyncFreestanding => SerializedFunctionKind::AsyncFreestanding,
+                FunctionKind::Method(x) => SerializedFunctionKind::Method(x.index()),
+                FunctionKind::AsyncMethod(x) => SerializedFunctionKind::AsyncMethod(x.index()),
+                FunctionKind::Static(x) => SerializedFunctionKind::Static(x.index()),
+                FunctionKind::AsyncStatic(x) => SerializedFunctionKind::AsyncStatic(x.index()),
+                FunctionKind::Constructor(x) => SerializedFunctionKind::Constructor(x.index()),
+            },
+            params: value
+                .params
+                .iter()
+                .map(|(name, ty)| (name.clone(), ty.into()))
+                .collect(),
+            result: value.result.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl SerializedFunction {
+    /// Converts this back into a function.
+    fn into_function(self, ids: &ResolveIds) -> Result<Function> {
+        Ok(Function {
+            name: self.name,
+            kind: match self.kind {
+                SerializedFunctionKind::Freestanding => FunctionKind::Freestanding,
+                SerializedFunctionKind::AsyncFreestanding => FunctionKind::AsyncFreestanding,
+                SerializedFunctionKind::Method(x) => FunctionKind::Method(ids.types.get(x)?),
+                SerializedFunctionKind::AsyncMethod(x) => {
+                    FunctionKind::AsyncMethod(ids.types.get(x)?)
+                }
+                SerializedFunctionKind::Static(x) => FunctionKind::Static(ids.types.get(x)?),
+                SerializedFunctionKind::AsyncStatic(x) => {
+                    FunctionKind::AsyncStatic(ids.types.get(x)?)
+                }
+                SerializedFunctionKind::Constructor(x) => {
+                    FunctionKind::Constructor(ids.types.get(x)?)
+                }
+            },
+            params: self
+                .params
+                .into_iter()
+                .map(|(name, ty)| Ok((name, ty.into_type(ids)?)))
+                .collect::<Result<_>>()?,
+            result: self.result.map(|x| x.into_type(ids)).transpose()?,
+            docs: Docs::default(),
+            stability: Stability::Unknown,
+        })
+    }
+}
+
+/// The serialized form of a function kind. Resources are referred to by type index.
+#[derive(Serialize, Deserialize)]
+enum SerializedFunctionKind {
+    /// A freestanding function.
+    Freestanding,
+    /// An async freestanding function.
+    AsyncFreestanding,
+    /// A resource method.
+    Method(usize),
+    /// An async resource method.
+    AsyncMethod(usize),
+    /// A static resource function.
+    Static(usize),
+    /// An async static resource function.
+    AsyncStatic(usize),
+    /// A resource constructor.
+    Constructor(usize),
+}
+
+/// The serialized form of a type definition.
+#[derive(Serialize, Deserialize)]
+struct SerializedTypeDef {
+    /// The name of the type, if it has not been moved into the type identifiers.
+    name: Option<String>,
+    /// The kind of the type.
+    kind: SerializedTypeDefKind,
+    /// The item which defined the type.
+    owner: SerializedTypeOwner,
+}
+
+/// The serialized form of a type owner.
+#[derive(Serialize, Deserialize)]
+enum SerializedTypeOwner {
+    /// The index of a world.
+    World(usize),
+    /// The index of an interface.
+    Interface(usize),
+    /// The type has no owner.
+    None,
+}
+
+/// The serialized form of a type definition kind.
+#[derive(Serialize, Deserialize)]
+enum SerializedTypeDefKind {
+    /// A record with named fields.
+    Record(Vec<(String, SerializedType)>),
+    /// A resource.
+    Resource,
+    /// An owned handle to the resource with the given type index.
+    Own(usize),
+    /// A borrowed handle to the resource with the given type index.
+    Borrow(usize),
+    /// A set of named flags.
+    Flags(Vec<String>),
+    /// A tuple.
+    Tuple(Vec<SerializedType>),
+    /// A variant with named cases.
+    Variant(Vec<(String, Option<SerializedType>)>),
+    /// An enum with named cases.
+    Enum(Vec<String>),
+    /// An option.
+    Option(SerializedType),
+    /// A result.
+    Result(Option<SerializedType>, Option<SerializedType>),
+    /// A list.
+    List(SerializedType),
+    /// A list with a fixed size.
+    FixedSizeList(SerializedType, u32),
+    /// A future.
+    Future(Option<SerializedType>),
+    /// A stream.
+    Stream(Option<SerializedType>),
+    /// An alias of another type.
+    Type(SerializedType),
+    /// A type of unknown structure.
+    Unknown,
+}
+
+impl From<&TypeDefKind> for SerializedTypeDefKind {
+    fn from(value: &TypeDefKind) -> Self {
+        match value {
+            TypeDefKind::Record(x) => Self::Record(
+                x.fields
+                    .iter()
+                    .map(|x| (x.name.clone(), (&x.ty).into()))
+                    .collect(),
+            ),
+            TypeDefKind::Resource => Self::Resource,
+            TypeDefKind::Handle(Handle::Own(x)) => Self::Own(x.index()),
+            TypeDefKind::Handle(Handle::Borrow(x)) => Self::Borrow(x.index()),
+            TypeDefKind::Flags(x) => Self::Flags(x.flags.iter().map(|x| x.name.clone()).collect()),
+            TypeDefKind::Tuple(x) => Self::Tuple(x.types.iter().map(Into::into).collect()),
+            TypeDefKind::Variant(x) => Self::Variant(
+                x.cases
+                    .iter()
+                    .map(|x| (x.name.clone(), x.ty.as_ref().map(Into::into)))
+                    .collect(),
+            ),
+            TypeDefKind::Enum(x) => Self::Enum(x.cases.iter().map(|x| x.name.clone()).collect()),
+            TypeDefKind::Option(x) => Self::Option(x.into()),
+            TypeDefKind::Result(x) => Self::Result(
+                x.ok.as_ref().map(Into::into),
+                x.err.as_ref().map(Into::into),
+            ),
+            TypeDefKind::List(x) => Self::List(x.into()),
+            TypeDefKind::FixedSizeList(x, len) => Self::FixedSizeList(x.into(), *len),
+            TypeDefKind::Future(x) => Self::Future(x.as_ref().map(Into::into)),
+            TypeDefKind::Stream(x) => Self::Stream(x.as_ref().map(Into::into)),
+            TypeDefKind::Type(x) => Self::Type(x.into()),
+            TypeDefKind::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl SerializedTypeDefKind {
+    /// Converts this back into a type definition kind.
+    fn into_kind(self, ids: &ResolveIds) -> Result<TypeDefKind> {
+        Ok(match self {
+            Self::Record(x) => TypeDefKind::Record(Record {
+                fields: x
+                    .into_iter()
+                    .map(|(name, ty)| {
+                        Ok(Field {
+                            name,
+                            ty: ty.into_type(ids)?,
+                            docs: Docs::default(),
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            }),
+            Self::Resource => TypeDefKind::Resource,
+            Self::Own(x) => TypeDefKind::Handle(Handle::Own(ids.types.get(x)?)),
+            Self::Borrow(x) => TypeDefKind::Handle(Handle::Borrow(ids.types.get(x)?)),
+            Self::Flags(x) => TypeDefKind::Flags(Flags {
+                flags: x
+                    .into_iter()
+                    .map(|name| Flag {
+                        name,
+                        docs: Docs::default(),
+                    })
+                    .collect(),
+            }),
+            Self::Tuple(x) => TypeDefKind::Tuple(Tuple {
+                types: x
+                    .into_iter()
+                    .map(|x| x.into_type(ids))
+                    .collect::<Result<_>>()?,
+            }),
+            Self::Variant(x) => TypeDefKind::Variant(Variant {
+                cases: x
+                    .into_iter()
+                    .map(|(name, ty)| {
+                        Ok(Case {
+                            name,
+                            ty: ty.map(|x| x.into_type(ids)).transpose()?,
+                            docs: Docs::default(),
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            }),
+            Self::Enum(x) => TypeDefKind::Enum(Enum {
+                cases: x
+                    .into_iter()
+                    .map(|name| EnumCase {
+                        name,
+                        docs: Docs::default(),
+                    })
+                    .collect(),
+            }),
+            Self::Option(x) => TypeDefKind::Option(x.into_type(ids)?),
+            Self::Result(ok, err) => TypeDefKind::Result(Result_ {
+                ok: ok.map(|x| x.into_type(ids)).transpose()?,
+                err: err.map(|x| x.into_type(ids)).transpose()?,
+            }),
+            Self::List(x) => TypeDefKind::List(x.into_type(ids)?),
+            Self::FixedSizeList(x, len) => TypeDefKind::FixedSizeList(x.into_type(ids)?, len),
+            Self::Future(x) => TypeDefKind::Future(x.map(|x| x.into_type(ids)).transpose()?),
+            Self::Stream(x) => TypeDefKind::Stream(x.map(|x| x.into_type(ids)).transpose()?),
+            Self::Type(x) => TypeDefKind::Type(x.into_type(ids)?),
+            Self::Unknown => TypeDefKind::Unknown,
+        })
+    }
+}
+
+/// The serialized form of a type reference.
+#[derive(Serialize, Deserialize)]
+enum SerializedType {
+    /// A boolean.
+    Bool,
+    /// An unsigned 8-bit integer.
+    U8,
+    /// An unsigned 16-bit integer.
+    U16,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 8-bit integer.
+    S8,
+    /// A signed 16-bit integer.
+    S16,
+    /// A signed 32-bit integer.
+    S32,
+    /// A signed 64-bit integer.
+    S64,
+    /// A 32-bit float.
+    F32,
+    /// A 64-bit float.
+    F64,
+    /// A character.
+    Char,
+    /// A string.
+    String,
+    /// An error context.
+    ErrorContext,
+    /// The index of a type definition.
+    Id(usize),
+}
+
+impl From<&Type> for SerializedType {
+    fn from(value: &Type) -> Self {
+        match value {
+            Type::Bool => Self::Bool,
+            Type::U8 => Self::U8,
+            Type::U16 => Self::U16,
+            Type::U32 => Self::U32,
+            Type::U64 => Self::U64,
+            Type::S8 => Self::S8,
+            Type::S16 => Self::S16,
+            Type::S32 => Self::S32,
+            Type::S64 => Self::S64,
+            Type::F32 => Self::F32,
+            Type::F64 => Self::F64,
+            Type::Char => Self::Char,
+            Type::String => Self::String,
+            Type::ErrorContext => Self::ErrorContext,
+            Type::Id(x) => Self::Id(x.index()),
+        }
+    }
+}
+
+impl SerializedType {
+    /// Converts this back into a type reference.
+    fn into_type(self, ids: &ResolveIds) -> Result<Type> {
+        Ok(match self {
+            Self::Bool => Type::Bool,
+            Self::U8 => Type::U8,
+            Self::U16 => Type::U16,
+            Self::U32 => Type::U32,
+            Self::U64 => Type::U64,
+            Self::S8 => Type::S8,
+            Self::S16 => Type::S16,
+            Self::S32 => Type::S32,
+            Self::S64 => Type::S64,
+            Self::F32 => Type::F32,
+            Self::F64 => Type::F64,
+            Self::Char => Type::Char,
+            Self::String => Type::String,
+            Self::ErrorContext => Type::ErrorContext,
+            Self::Id(x) => Type::Id(ids.types.get(x)?),
+        })
+    }
+}
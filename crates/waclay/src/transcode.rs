@@ -0,0 +1,285 @@
+use anyhow::*;
+use wasm_runtime_layer::*;
+use wasmtime_environ::component::{FixedEncoding, StringEncoding, Transcode};
+
+/// The bit which is set in a compact string length when the string is encoded as UTF-16.
+pub const UTF16_TAG: usize = 1 << 31;
+
+/// The largest number of bytes that a string may occupy in guest memory.
+pub const MAX_STRING_BYTE_LENGTH: usize = UTF16_TAG - 1;
+
+/// Copies strings between the linear memories of two component instances on behalf
+/// of a fused adapter, changing their encoding if required.
+pub struct Transcoder {
+    /// The transcoding operation to perform.
+    pub op: Transcode,
+    /// The memory from which strings are read.
+    pub from: Memory,
+    /// Whether the source memory uses 64-bit pointers.
+    pub from64: bool,
+    /// The memory to which strings are written.
+    pub to: Memory,
+    /// Whether the destination memory uses 64-bit pointers.
+    pub to64: bool,
+}
+
+impl Transcoder {
+    /// Performs the transcoding operation. The arguments and results follow the
+    /// signatures that `wasmtime_environ` generates for each [`Transcode`].
+    pub fn call(
+        &self,
+        mut ctx: impl AsContextMut,
+        args: &[Value],
+        results: &mut [Value],
+    ) -> Result<()> {
+        let args = args.iter().map(pointer).collect::<Result<Vec<_>>>()?;
+        ensure!(args.len() >= 3, "Incorrect number of transcoder arguments.");
+
+        let (src, src_len, dst) = (args[0], args[1], args[2]);
+        let (src_bytes, dst_bytes) = match self.op {
+            Transcode::Copy(FixedEncoding::Utf8 | FixedEncoding::Latin1)
+            | Transcode::Utf8ToLatin1 => (src_len, src_len),
+            Transcode::Copy(FixedEncoding::Utf16) | Transcode::Utf16ToCompactProbablyUtf16 => {
+                (byte_len(src_len)?, byte_len(src_len)?)
+            }
+            Transcode::Latin1ToUtf16 | Transcode::Utf8ToUtf16 => (src_len, byte_len(src_len)?),
+            Transcode::Utf16ToLatin1 => (byte_len(src_len)?, src_len),
+            Transcode::Latin1ToUtf8 => (src_len, argument(&args, 3)?),
+            Transcode::Utf16ToUtf8 => (byte_len(src_len)?, argument(&args, 3)?),
+            Transcode::Utf8ToCompactUtf16 => (src_len, byte_len(argument(&args, 3)?)?),
+            Transcode::Utf16ToCompactUtf16 => (byte_len(src_len)?, byte_len(argument(&args, 3)?)?),
+        };
+
+        let mut src_buffer = vec![0; src_bytes];
+        self.from.read(ctx.as_context(), src, &mut src_buffer)?;
+        let mut dst_buffer = vec![0; dst_bytes];
+        self.to.read(ctx.as_context(), dst, &mut dst_buffer)?;
+
+        let output = match self.op {
+            Transcode::Copy(FixedEncoding::Utf8) => {
+                std::str::from_utf8(&src_buffer).context("Invalid UTF-8 string.")?;
+                dst_buffer.copy_from_slice(&src_buffer);
+                vec![]
+            }
+            Transcode::Copy(FixedEncoding::Utf16) => {
+                validate_utf16(&src_buffer)?;
+                dst_buffer.copy_from_slice(&src_buffer);
+                vec![]
+            }
+            Transcode::Copy(FixedEncoding::Latin1) => {
+                dst_buffer.copy_from_slice(&src_buffer);
+                vec![]
+            }
+            Transcode::Latin1ToUtf16 => {
+                for (byte, unit) in src_buffer.iter().zip(dst_buffer.chunks_exact_mut(2)) {
+                    unit.copy_from_slice(&u16::from(*byte).to_le_bytes());
+                }
+                vec![]
+            }
+            Transcode::Utf8ToUtf16 => vec![utf8_to_utf16(&src_buffer, &mut dst_buffer)?],
+            Transcode::Utf16ToUtf8 => {
+                let mut read = 0;
+                let mut written = 0;
+                for ch in utf16_chars(&src_buffer) {
+                    let ch = ch?;
+                    if dst_buffer.len() - written < ch.len_utf8() {
+                        break;
+                    }
+                    read += ch.len_utf16();
+                    written += ch.encode_utf8(&mut dst_buffer[written..]).len();
+                }
+                vec![read, written]
+            }
+            Transcode::Latin1ToUtf8 => {
+                let mut read = 0;
+                let mut written = 0;
+                for ch in src_buffer.iter().copied().map(char::from) {
+                    if dst_buffer.len() - written < ch.len_utf8() {
+                        break;
+                    }
+                    read += 1;
+                    written += ch.encode_utf8(&mut dst_buffer[written..]).len();
+                }
+                vec![read, written]
+            }
+            Transcode::Utf16ToCompactProbablyUtf16 => {
+                validate_utf16(&src_buffer)?;
+                if src_buffer.chunks_exact(2).all(|unit| unit[1] == 0) {
+                    for (i, unit) in src_buffer.chunks_exact(2).enumerate() {
+                        dst_buffer[i] = unit[0];
+                    }
+                    vec![src_len]
+                } else {
+                    dst_buffer.copy_from_slice(&src_buffer);
+                    vec![src_len | UTF16_TAG]
+                }
+            }
+            Transcode::Utf8ToLatin1 => {
+                let mut read = 0;
+                let mut written = 0;
+                for ch in std::str::from_utf8(&src_buffer)
+                    .context("Invalid UTF-8 string.")?
+                    .chars()
+                {
+                    let Result::Ok(byte) = u8::try_from(ch) else {
+                        break;
+                    };
+                    dst_buffer[written] = byte;
+                    read += ch.len_utf8();
+                    written += 1;
+                }
+                vec![read, written]
+            }
+            Transcode::Utf16ToLatin1 => {
+                let mut written = 0;
+                for unit in src_buffer.chunks_exact(2) {
+                    if unit[1] != 0 {
+                        break;
+                    }
+                    dst_buffer[written] = unit[0];
+                    written += 1;
+                }
+                vec![written, written]
+            }
+            Transcode::Utf8ToCompactUtf16 => {
+                let latin1_bytes = argument(&args, 4)?;
+                let rest = inflate_latin1_bytes(&mut dst_buffer, latin1_bytes)?;
+                vec![latin1_bytes + utf8_to_utf16(&src_buffer, rest)?]
+            }
+            Transcode::Utf16ToCompactUtf16 => {
+                let latin1_bytes = argument(&args, 4)?;
+                let rest = inflate_latin1_bytes(&mut dst_buffer, latin1_bytes)?;
+                validate_utf16(&src_buffer)?;
+                ensure!(
+                    src_buffer.len() <= rest.len(),
+                    "Transcoding destination was too small."
+                );
+                rest[..src_buffer.len()].copy_from_slice(&src_buffer);
+                vec![latin1_bytes + src_len]
+            }
+        };
+
+        self.to.write(ctx.as_context_mut(), dst, &dst_buffer)?;
+
+        ensure!(
+            output.len() == results.len(),
+            "Incorrect number of transcoder results."
+        );
+        // When two results are returned, the first refers to the source and the second to the destination.
+        let pointer_sizes = &[self.from64, self.to64][2 - output.len()..];
+        for ((result, value), is64) in results.iter_mut().zip(output).zip(pointer_sizes) {
+            *result = if *is64 {
+                Value::I64(value as i64)
+            } else {
+                Value::I32(value as i32)
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Determines the alignment, byte length, and encoding of a string in guest memory
+/// from its encoding options and its tagged length in code units.
+pub fn string_layout(
+    encoding: StringEncoding,
+    tagged_code_units: usize,
+) -> Result<(usize, usize, FixedEncoding)> {
+    Ok(match encoding {
+        StringEncoding::Utf8 => (1, tagged_code_units, FixedEncoding::Utf8),
+        StringEncoding::Utf16 => (2, byte_len(tagged_code_units)?, FixedEncoding::Utf16),
+        StringEncoding::CompactUtf16 if tagged_code_units & UTF16_TAG != 0 => (
+            2,
+            byte_len(tagged_code_units ^ UTF16_TAG)?,
+            FixedEncoding::Utf16,
+        ),
+        StringEncoding::CompactUtf16 => (2, tagged_code_units, FixedEncoding::Latin1),
+    })
+}
+
+/// Decodes a string from bytes in the given encoding, failing if they contain invalid code units.
+pub fn decode_string(encoding: FixedEncoding, bytes: &[u8]) -> Result<String> {
+    match encoding {
+        FixedEncoding::Utf8 => std::str::from_utf8(bytes)
+            .map(str::to_owned)
+            .context("Invalid UTF-8 string."),
+        FixedEncoding::Utf16 => {
+            ensure!(
+                bytes.len().is_multiple_of(2),
+                "Invalid UTF-16 string length."
+            );
+            utf16_chars(bytes).collect()
+        }
+        FixedEncoding::Latin1 => Ok(bytes.iter().copied().map(char::from).collect()),
+    }
+}
+
+/// Converts a pointer or length argument into a `usize`.
+fn pointer(value: &Value) -> Result<usize> {
+    match value {
+        Value::I32(x) => Ok(*x as u32 as usize),
+        Value::I64(x) => Ok(*x as u64 as usize),
+        _ => bail!("Incorrect type."),
+    }
+}
+
+/// Gets the argument at the given position, or fails if it was not provided.
+fn argument(args: &[usize], index: usize) -> Result<usize> {
+    args.get(index)
+        .copied()
+        .context("Incorrect number of transcoder arguments.")
+}
+
+/// Gets the number of bytes occupied by the given number of UTF-16 code units.
+pub fn byte_len(units: usize) -> Result<usize> {
+    units.checked_mul(2).context("String length overflowed.")
+}
+
+/// Decodes the characters of a little-endian UTF-16 byte buffer.
+fn utf16_chars(bytes: &[u8]) -> impl '_ + Iterator<Item = Result<char>> {
+    char::decode_utf16(
+        bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]])),
+    )
+    .map(|ch| ch.context("Invalid UTF-16 string."))
+}
+
+/// Ensures that the given little-endian UTF-16 byte buffer is valid.
+fn validate_utf16(bytes: &[u8]) -> Result<()> {
+    utf16_chars(bytes).try_for_each(|ch| ch.map(|_| ()))
+}
+
+/// Encodes UTF-8 bytes into a little-endian UTF-16 byte buffer, returning the number of
+/// code units written.
+fn utf8_to_utf16(src: &[u8], dst: &mut [u8]) -> Result<usize> {
+    let mut written = 0;
+    for unit in std::str::from_utf8(src)
+        .context("Invalid UTF-8 string.")?
+        .encode_utf16()
+    {
+        dst.get_mut(2 * written..2 * written + 2)
+            .context("Transcoding destination was too small.")?
+            .copy_from_slice(&unit.to_le_bytes());
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Widens the Latin-1 bytes at the start of the buffer into UTF-16 code units, returning
+/// the remainder of the buffer after the widened units.
+fn inflate_latin1_bytes(dst: &mut [u8], latin1_bytes: usize) -> Result<&mut [u8]> {
+    ensure!(
+        byte_len(latin1_bytes)? <= dst.len(),
+        "Transcoding destination was too small."
+    );
+
+    // Widen in reverse so that no byte is overwritten before it has been read.
+    for i in (0..latin1_bytes).rev() {
+        let byte = dst[i];
+        dst[2 * i] = byte;
+        dst[2 * i + 1] = 0;
+    }
+
+    Ok(&mut dst[2 * latin1_bytes..])
+}
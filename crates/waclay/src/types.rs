@@ -1,4 +1,5 @@
 use std::any::*;
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::hash::*;
 use std::sync::atomic::*;
@@ -10,11 +11,27 @@ use id_arena::*;
 #[cfg(feature = "serde")]
 use serde::*;
 
+use crate::flat::*;
 use crate::values::ComponentType;
 use crate::TypeIdentifier;
 use crate::{require_matches, UnaryComponentType};
 use crate::{AsContextMut, ComponentInner, StoreContextMut};
 
+/// Indicates that a type referred to an abstract resource which had no entry in the resource map.
+#[derive(Clone, Debug)]
+pub(crate) struct UnmappedResource(pub ResourceType);
+
+impl Display for UnmappedResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.name() {
+            Some(name) => write!(f, "Resource {name} was not defined."),
+            None => write!(f, "Resource was not defined."),
+        }
+    }
+}
+
+impl std::error::Error for UnmappedResource {}
+
 /// Represents a component model interface type.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -65,6 +82,14 @@ pub enum ValueType {
     Own(ResourceType),
     /// The borrowed resource handle type.
     Borrow(ResourceType),
+    /// The readable end of a future type.
+    Future(FutureType),
+    /// The readable end of a stream type.
+    Stream(StreamType),
+    /// The diagnostic error context type.
+    ErrorContext,
+    /// A list of values with a fixed length.
+    FixedList(FixedListType),
 }
 
 impl Display for ValueType {
@@ -180,6 +205,19 @@ impl Display for ValueType {
                 "borrow<{}>",
                 x.name().map(ToString::to_string).unwrap_or("".to_string())
             ),
+            // future<type> or future
+            ValueType::Future(x) => match x.payload_ty() {
+                Some(ty) => write!(f, "future<{}>", ty),
+                None => write!(f, "future"),
+            },
+            // stream<type> or stream
+            ValueType::Stream(x) => match x.payload_ty() {
+                Some(ty) => write!(f, "stream<{}>", ty),
+                None => write!(f, "stream"),
+            },
+            ValueType::ErrorContext => write!(f, "error-context"),
+            // list<type, len>
+            ValueType::FixedList(x) => write!(f, "list<{}, {}>", x.element_ty(), x.len()),
         }
     }
 }
@@ -206,7 +244,7 @@ impl ValueType {
             wit_parser::Type::Char => Self::Char,
             wit_parser::Type::String => Self::String,
             wit_parser::Type::Id(x) => Self::from_component_typedef(*x, component, resource_map)?,
-            &wit_parser::Type::ErrorContext => bail!("ErrorContext not yet supported"),
+            wit_parser::Type::ErrorContext => Self::ErrorContext,
         })
     }
 
@@ -272,15 +310,126 @@ impl ValueType {
                 component,
                 resource_map,
             )?)),
-            wit_parser::TypeDefKind::Future(_) => bail!("Unimplemented."),
-            wit_parser::TypeDefKind::Stream(_) => bail!("Unimplemented."),
+            wit_parser::TypeDefKind::Future(x) => Self::Future(FutureType::new(
+                x.as_ref()
+                    .map(|t| Self::from_component(t, component, resource_map))
+                    .transpose()?,
+            )),
+            wit_parser::TypeDefKind::Stream(x) => Self::Stream(StreamType::new(
+                x.as_ref()
+                    .map(|t| Self::from_component(t, component, resource_map))
+                    .transpose()?,
+            )),
             wit_parser::TypeDefKind::Type(x) => Self::from_component(x, component, resource_map)?,
             wit_parser::TypeDefKind::Unknown => unreachable!(),
-            wit_parser::TypeDefKind::FixedSizeList(_, _) => {
-                bail!("FixedSizeList not yet supported")
-            }
+            wit_parser::TypeDefKind::FixedSizeList(x, len) => Self::FixedList(FixedListType::new(
+                Self::from_component(x, component, resource_map)?,
+                *len,
+            )),
         })
     }
+
+    /// Whether values of this type may be used where values of `other` are expected.
+    /// In addition to identical types, the following component model subtyping rules are supported:
+    ///
+    /// - Records may have extra fields, and may omit fields whose supertype is an `option`.
+    /// - Variants and enums may have fewer cases, and flags may have fewer names.
+    /// - Lists, tuples, options, and results are subtypes if their contents are.
+    /// - Handles of a host resource type and of the default [`crate::Resource::resource_type`] for the
+    ///   same representation may be used in place of each other. Handles of any type may be used where
+    ///   the untyped handles of a Rust signature are expected, but not the other way around.
+    pub fn is_subtype_of(&self, other: &ValueType) -> bool {
+        match (self, other) {
+            (Self::List(a), Self::List(b)) => a.element.is_subtype_of(&b.element),
+            (Self::FixedList(a), Self::FixedList(b)) => {
+                a.len == b.len && a.element.is_subtype_of(&b.element)
+            }
+            (Self::Record(a), Self::Record(b)) => b.fields.iter().all(|(_, name, ty)| {
+                match a.fields.iter().find(|(_, x, _)| x == name) {
+                    Some((_, _, field)) => field.is_subtype_of(ty),
+                    None => matches!(ty, Self::Option(_)),
+                }
+            }),
+            (Self::Tuple(a), Self::Tuple(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields
+                        .iter()
+                        .zip(b.fields())
+                        .all(|(x, y)| x.is_subtype_of(y))
+            }
+            (Self::Variant(a), Self::Variant(b)) => a.cases.iter().all(|case| {
+                b.cases
+                    .iter()
+                    .find(|x| x.name == case.name)
+                    .is_some_and(|x| Self::is_payload_subtype(&case.ty, &x.ty))
+            }),
+            (Self::Enum(a), Self::Enum(b)) => a.cases.iter().all(|x| b.cases.contains(x)),
+            (Self::Option(a), Self::Option(b)) => a.ty.is_subtype_of(&b.ty),
+            (Self::Result(a), Self::Result(b)) => {
+                Self::is_payload_subtype(&a.ok_err.0, &b.ok_err.0)
+                    && Self::is_payload_subtype(&a.ok_err.1, &b.ok_err.1)
+            }
+            (Self::Flags(a), Self::Flags(b)) => a.names.iter().all(|x| b.indices.contains_key(x)),
+            (Self::Own(a), Self::Own(b)) | (Self::Borrow(a), Self::Borrow(b)) => a.is_subtype_of(b),
+            _ => self == other,
+        }
+    }
+
+    /// Whether this type, which was declared by a Rust type, describes the same values as `actual`.
+    /// The types must be equal, which ignores their names, except that placeholder resource types
+    /// stand for the types that they accept.
+    pub(crate) fn describes(&self, actual: &ValueType) -> bool {
+        match (self, actual) {
+            (Self::List(a), Self::List(b)) => a.element.describes(&b.element),
+            (Self::FixedList(a), Self::FixedList(b)) => {
+                a.len == b.len && a.element.describes(&b.element)
+            }
+            (Self::Record(a), Self::Record(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields.iter().zip(b.fields.iter()).all(
+                        |((i, name, ty), (j, other_name, other_ty))| {
+                            i == j && name == other_name && ty.describes(other_ty)
+                        },
+                    )
+            }
+            (Self::Tuple(a), Self::Tuple(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields.iter().zip(b.fields()).all(|(x, y)| x.describes(y))
+            }
+            (Self::Variant(a), Self::Variant(b)) => {
+                a.cases.len() == b.cases.len()
+                    && a.cases
+                        .iter()
+                        .zip(b.cases.iter())
+                        .all(|(x, y)| x.name == y.name && Self::payload_describes(&x.ty, &y.ty))
+            }
+            (Self::Option(a), Self::Option(b)) => a.ty.describes(&b.ty),
+            (Self::Result(a), Self::Result(b)) => {
+                Self::payload_describes(&a.ok_err.0, &b.ok_err.0)
+                    && Self::payload_describes(&a.ok_err.1, &b.ok_err.1)
+            }
+            (Self::Own(a), Self::Own(b)) | (Self::Borrow(a), Self::Borrow(b)) => a.stands_for(b),
+            _ => self == actual,
+        }
+    }
+
+    /// Whether an optional payload, which was declared by a Rust type, describes another.
+    fn payload_describes(declared: &Option<ValueType>, actual: &Option<ValueType>) -> bool {
+        match (declared, actual) {
+            (Some(declared), Some(actual)) => declared.describes(actual),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the optional payload of a variant case or result is a subtype of another.
+    fn is_payload_subtype(sub: &Option<ValueType>, sup: &Option<ValueType>) -> bool {
+        match (sub, sup) {
+            (Some(sub), Some(sup)) => sub.is_subtype_of(sup),
+            (None, None) => true,
+            _ => false,
+        }
+    }
 }
 
 /// Describes the type of a list of values, all of the same type.
@@ -305,6 +454,40 @@ impl ListType {
     }
 }
 
+/// Describes the type of a list with a fixed number of values, all of the same type.
+///
+/// Guest components cannot use fixed-size lists yet, because the `wasmtime-environ` translator
+/// rejects them, so these types only appear in the signatures of host functions.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FixedListType {
+    /// The element of the list.
+    element: Arc<ValueType>,
+    /// The number of elements in the list.
+    len: u32,
+}
+
+impl FixedListType {
+    /// Creates a new fixed-size list type for the given element type and length.
+    pub fn new(element_ty: ValueType, len: u32) -> Self {
+        Self {
+            element: Arc::new(element_ty),
+            len,
+        }
+    }
+
+    /// Gets the element type for this list.
+    pub fn element_ty(&self) -> ValueType {
+        (*self.element).clone()
+    }
+
+    /// Gets the number of elements in this list.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+}
+
 /// Describes the type of an unordered collection of named fields, each associated with the values.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -689,6 +872,50 @@ impl OptionType {
     }
 }
 
+/// The type of the readable end of a future, which eventually produces a single value.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FutureType {
+    /// The type of value that the future produces, if any.
+    payload: Option<Arc<ValueType>>,
+}
+
+impl FutureType {
+    /// Creates a new future type which produces a value of the given type, or no value.
+    pub fn new(payload: Option<ValueType>) -> Self {
+        Self {
+            payload: payload.map(Arc::new),
+        }
+    }
+
+    /// Gets the type of value that the future produces, if any.
+    pub fn payload_ty(&self) -> Option<ValueType> {
+        self.payload.as_deref().cloned()
+    }
+}
+
+/// The type of the readable end of a stream, which produces a sequence of values.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StreamType {
+    /// The type of the stream's elements, if any.
+    payload: Option<Arc<ValueType>>,
+}
+
+impl StreamType {
+    /// Creates a new stream type whose elements have the given type, or carry no value.
+    pub fn new(payload: Option<ValueType>) -> Self {
+        Self {
+            payload: payload.map(Arc::new),
+        }
+    }
+
+    /// Gets the type of the stream's elements, if any.
+    pub fn payload_ty(&self) -> Option<ValueType> {
+        self.payload.as_deref().cloned()
+    }
+}
+
 /// A type that denotes successful or unsuccessful operation.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -822,10 +1049,76 @@ impl ResourceType {
                 resource_id: RESOURCE_ID_COUNTER.fetch_add(1, Ordering::AcqRel),
                 type_id: TypeId::of::<T>(),
                 associated_store: None,
+                placeholder: false,
             },
         }
     }
 
+    /// Gets the placeholder type for representations of type `T`, which is shared by all callers.
+    /// In the signatures of Rust functions, a placeholder stands for every host resource type whose
+    /// representation is `T`. The placeholder for [`AnyResource`] stands for every resource type.
+    pub(crate) fn placeholder<T: 'static>() -> Self {
+        /// The placeholders that have been created for each representation type.
+        static TYPES: Mutex<Vec<(TypeId, ResourceType)>> = Mutex::new(Vec::new());
+
+        let mut types = TYPES.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, ty)) = types.iter().find(|(id, _)| *id == TypeId::of::<T>()) {
+            return ty.clone();
+        }
+
+        let ty = Self {
+            name: Some(TypeIdentifier::new(type_name::<T>(), None)),
+            kind: ResourceKindValue::Host {
+                resource_id: RESOURCE_ID_COUNTER.fetch_add(1, Ordering::AcqRel),
+                type_id: TypeId::of::<T>(),
+                associated_store: None,
+                placeholder: true,
+            },
+        };
+        types.push((TypeId::of::<T>(), ty.clone()));
+        ty
+    }
+
+    /// Whether this type, when it appears in the signature of a Rust function, accepts handles of type `other`.
+    pub(crate) fn stands_for(&self, other: &ResourceType) -> bool {
+        match (&self.kind, &other.kind) {
+            _ if self == other => true,
+            (
+                ResourceKindValue::Host {
+                    type_id,
+                    placeholder: true,
+                    ..
+                },
+                _,
+            ) if *type_id == TypeId::of::<AnyResource>() => true,
+            (
+                ResourceKindValue::Host {
+                    type_id: a,
+                    placeholder: true,
+                    ..
+                },
+                ResourceKindValue::Host { type_id: b, .. },
+            ) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Whether handles of this type may be used where handles of type `other` are expected. Besides
+    /// equal types, the placeholder for a representation `T` and the host resource types whose
+    /// representation is `T` may be used in place of each other. Handles of every type may be used in
+    /// place of the placeholder for untyped handles, but it may not be used in place of any other type.
+    pub(crate) fn is_subtype_of(&self, other: &ResourceType) -> bool {
+        other.stands_for(self) || (!self.is_any() && self.stands_for(other))
+    }
+
+    /// Whether this is the placeholder type for untyped handles, which stands for every resource type.
+    pub(crate) fn is_any(&self) -> bool {
+        matches!(
+            &self.kind,
+            ResourceKindValue::Host { type_id, placeholder: true, .. } if *type_id == TypeId::of::<AnyResource>()
+        )
+    }
+
     /// Creates a new host resource for storing values of the given type. Additionally,
     /// adds a destructor that is called when the resource is dropped. Note that multiple
     /// resource types may be created for the same `T`, and they will be distinct.
@@ -846,14 +1139,16 @@ impl ResourceType {
                 let resource = wasm_runtime_layer::AsContextMut::as_context_mut(&mut ctx)
                     .data_mut()
                     .host_resources
-                    .remove(
+                    .try_remove(
                         require_matches!(val[0], wasm_runtime_layer::Value::I32(x), x) as usize,
-                    );
+                    )
+                    .with_context(|| crate::Error::resource("Resource was not present."))?;
                 destructor(
                     StoreContextMut { inner: ctx },
                     *resource
+                        .value
                         .downcast()
-                        .expect("Resource was of incorrect type."),
+                        .map_err(|_| crate::Error::resource("Resource was of incorrect type."))?,
                 )
             },
         );
@@ -864,6 +1159,7 @@ impl ResourceType {
                 resource_id: RESOURCE_ID_COUNTER.fetch_add(1, Ordering::AcqRel),
                 type_id: TypeId::of::<T>(),
                 associated_store: Some((store_id, destructor)),
+                placeholder: false,
             },
         })
     }
@@ -917,7 +1213,9 @@ impl ResourceType {
                 },
             };
             if let Some(map) = resource_map {
-                Ok(map[&ab].clone())
+                map.get(&ab)
+                    .cloned()
+                    .ok_or_else(|| UnmappedResource(ab).into())
             } else {
                 Ok(ab)
             }
@@ -945,10 +1243,44 @@ impl ResourceType {
         if let ResourceKindValue::Abstract { id, component: _ } = &self.kind {
             Ok(Self {
                 name: self.name.clone(),
-                kind: ResourceKindValue::Instantiated { id: *id, instance },
+                kind: ResourceKindValue::Instantiated {
+                    id: *id,
+                    instance,
+                    destructor: None,
+                },
             })
         } else {
-            bail!("Resource was not abstract.");
+            bail!(crate::Error::resource("Resource was not abstract."));
+        }
+    }
+
+    /// Attaches the destructor of the defining instance to this instantiated guest resource,
+    /// so that instances which import the resource can drop their handles.
+    pub(crate) fn with_guest_destructor(
+        &self,
+        destructor: Option<wasm_runtime_layer::Func>,
+    ) -> Result<Self> {
+        if let ResourceKindValue::Instantiated { id, instance, .. } = &self.kind {
+            Ok(Self {
+                name: self.name.clone(),
+                kind: ResourceKindValue::Instantiated {
+                    id: *id,
+                    instance: *instance,
+                    destructor,
+                },
+            })
+        } else {
+            bail!(crate::Error::resource("Resource was not instantiated."));
+        }
+    }
+
+    /// Determines whether this is an instantiated guest resource, and if so, returns the
+    /// destructor of the defining instance.
+    pub(crate) fn guest_destructor(&self) -> Option<Option<wasm_runtime_layer::Func>> {
+        if let ResourceKindValue::Instantiated { destructor, .. } = &self.kind {
+            Some(destructor.clone())
+        } else {
+            None
         }
     }
 
@@ -1004,6 +1336,8 @@ enum ResourceKindValue {
         id: usize,
         /// The ID of the instance.
         instance: u64,
+        /// The destructor of the defining instance, if it has been resolved.
+        destructor: Option<wasm_runtime_layer::Func>,
     },
     /// A resource associated with the host.
     Host {
@@ -1013,9 +1347,16 @@ enum ResourceKindValue {
         type_id: TypeId,
         /// The associated store and destructor, if any.
         associated_store: Option<(u64, wasm_runtime_layer::Func)>,
+        /// Whether this type is shared by all callers for its representation, and stands for other
+        /// types in the signatures of Rust functions.
+        placeholder: bool,
     },
 }
 
+/// The representation type whose placeholder stands for every resource type, which is used by
+/// untyped resource handles in the signatures of Rust functions.
+pub(crate) struct AnyResource;
+
 impl PartialEq for ResourceKindValue {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -1030,8 +1371,12 @@ impl PartialEq for ResourceKindValue {
                 },
             ) => a == x && b == y,
             (
-                ResourceKindValue::Instantiated { id: a, instance: b },
-                ResourceKindValue::Instantiated { id: x, instance: y },
+                ResourceKindValue::Instantiated {
+                    id: a, instance: b, ..
+                },
+                ResourceKindValue::Instantiated {
+                    id: x, instance: y, ..
+                },
             ) => a == x && b == y,
             (
                 ResourceKindValue::Host { resource_id: a, .. },
@@ -1052,7 +1397,7 @@ impl Hash for ResourceKindValue {
                 id.hash(state);
                 component.hash(state);
             }
-            ResourceKindValue::Instantiated { id, instance } => {
+            ResourceKindValue::Instantiated { id, instance, .. } => {
                 id.hash(state);
                 instance.hash(state);
             }
@@ -1181,8 +1526,30 @@ impl FuncType {
         &self.params_results[self.len_params..]
     }
 
-    /// Returns `Ok` if the number and types of items in `params` matches as expected by the [`FuncType`].
-    pub(crate) fn match_params(&self, params: &[crate::values::Value]) -> Result<()> {
+    /// Whether a function of this type may be used where a function of type `other` is expected.
+    /// This holds when each parameter of `other` is a subtype of the corresponding parameter
+    /// of this function, and each result of this function is a subtype of the corresponding result of `other`.
+    pub fn is_subtype_of(&self, other: &FuncType) -> bool {
+        self.params().len() == other.params().len()
+            && self.results().len() == other.results().len()
+            && other
+                .params()
+                .iter()
+                .zip(self.params())
+                .all(|(a, b)| a.is_subtype_of(b))
+            && self
+                .results()
+                .iter()
+                .zip(other.results())
+                .all(|(a, b)| a.is_subtype_of(b))
+    }
+
+    /// Returns `Ok` if the number of items in `params` matches as expected by the [`FuncType`], and each
+    /// item's type is a subtype of its parameter. Items of a subtype are converted to the parameter types.
+    pub(crate) fn match_params<'a>(
+        &self,
+        params: &'a [crate::values::Value],
+    ) -> Result<Cow<'a, [crate::values::Value]>> {
         if self.params().len() != params.len() {
             bail!("Incorrect parameter length.");
         }
@@ -1190,25 +1557,47 @@ impl FuncType {
             .params()
             .iter()
             .cloned()
-            .ne(params.iter().map(crate::values::Value::ty))
+            .eq(params.iter().map(crate::values::Value::ty))
         {
-            bail!("Incorrect parameter types.");
+            return Ok(Cow::Borrowed(params));
         }
-        Ok(())
+
+        params
+            .iter()
+            .zip(self.params())
+            .map(|(value, ty)| {
+                let actual = value.ty();
+                ensure!(
+                    actual.is_subtype_of(ty),
+                    crate::Error::TypeMismatch {
+                        expected: ty.clone(),
+                        actual
+                    }
+                );
+                value.coerce(ty)
+            })
+            .collect::<Result<_>>()
+            .map(Cow::Owned)
     }
 
-    /// Returns `Ok` if the number and types of items in `results` matches as expected by the [`FuncType`].
-    pub(crate) fn match_results(&self, results: &[crate::values::Value]) -> Result<()> {
+    /// Returns `Ok` if the number of items in `results` matches as expected by the [`FuncType`], and each
+    /// item's type is a subtype of its result. Items of a subtype are converted to the result types.
+    pub(crate) fn match_results(&self, results: &mut [crate::values::Value]) -> Result<()> {
         if self.results().len() != results.len() {
             bail!("Incorrect result length.");
         }
-        if self
-            .results()
-            .iter()
-            .cloned()
-            .ne(results.iter().map(crate::values::Value::ty))
-        {
-            bail!("Incorrect result types.");
+        for (value, ty) in results.iter_mut().zip(self.results()) {
+            let value_ty = value.ty();
+            if value_ty != *ty {
+                ensure!(
+                    value_ty.is_subtype_of(ty),
+                    crate::Error::TypeMismatch {
+                        expected: ty.clone(),
+                        actual: value_ty
+                    }
+                );
+                *value = value.coerce(ty)?;
+            }
         }
         Ok(())
     }
@@ -1229,6 +1618,26 @@ pub trait ComponentList: 'static + Sized {
 
     /// Attempts to convert a list of values into a component list of this type.
     fn from_values(values: &[crate::values::Value]) -> Result<Self>;
+
+    /// The canonical ABI layout of this list, laid out as a record, or `None` if it may
+    /// only be converted through values.
+    const RECORD_LAYOUT: Option<FlatLayout> = None;
+
+    /// Lowers this component list to core values, appending them to the context.
+    fn lower_flats<C: AsContextMut>(&self, _cx: &mut FlatContext<'_, C>) -> Result<()> {
+        bail!("List cannot be converted directly to core values.")
+    }
+
+    /// Lifts a component list of this type from the next core values of the context.
+    fn lift_flats<C: AsContextMut>(_cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        bail!("List cannot be converted directly from core values.")
+    }
+
+    /// Loads a component list of this type, laid out as a record, from guest memory at the
+    /// given offset, which has been checked to lie within guest memory.
+    fn load_flats<C: AsContextMut>(_cx: &mut FlatContext<'_, C>, _offset: usize) -> Result<Self> {
+        bail!("List cannot be converted directly from guest memory.")
+    }
 }
 
 impl ComponentList for () {
@@ -1243,6 +1652,20 @@ impl ComponentList for () {
     fn into_values(self, _values: &mut [crate::values::Value]) -> Result<()> {
         Ok(())
     }
+
+    const RECORD_LAYOUT: Option<FlatLayout> = FlatLayout::record(&[]);
+
+    fn lower_flats<C: AsContextMut>(&self, _cx: &mut FlatContext<'_, C>) -> Result<()> {
+        Ok(())
+    }
+
+    fn lift_flats<C: AsContextMut>(_cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        Ok(())
+    }
+
+    fn load_flats<C: AsContextMut>(_cx: &mut FlatContext<'_, C>, _offset: usize) -> Result<Self> {
+        Ok(())
+    }
 }
 
 impl<T: UnaryComponentType> ComponentList for T {
@@ -1263,6 +1686,20 @@ impl<T: UnaryComponentType> ComponentList for T {
         assert!(values.len() == 1);
         T::from_value(&values[0])
     }
+
+    const RECORD_LAYOUT: Option<FlatLayout> = T::LAYOUT;
+
+    fn lower_flats<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        T::lower_flat(self, cx)
+    }
+
+    fn lift_flats<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        T::lift_flat(cx)
+    }
+
+    fn load_flats<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        T::load_flat(cx, offset)
+    }
 }
 
 /// A function that returns a single result, and eats a macro parameter in the process.
@@ -1298,6 +1735,23 @@ macro_rules! impl_component_list {
                 let mut counter = 0;
                 Ok(($($name::from_value(&values[{ let res = counter; counter += 1; res }])?, )+))
             }
+
+            const RECORD_LAYOUT: Option<FlatLayout> = FlatLayout::record(&[$($name::LAYOUT,)+]);
+
+            fn lower_flats<S: AsContextMut>(&self, cx: &mut FlatContext<'_, S>) -> Result<()> {
+                let ($($extra,)+) = self;
+                $($extra.lower_flat(cx)?;)+
+                Ok(())
+            }
+
+            fn lift_flats<S: AsContextMut>(cx: &mut FlatContext<'_, S>) -> Result<Self> {
+                Ok(($($name::lift_flat(cx)?,)+))
+            }
+
+            fn load_flats<S: AsContextMut>(cx: &mut FlatContext<'_, S>, offset: usize) -> Result<Self> {
+                let mut fields = FlatFields::new(offset);
+                Ok(($($name::load_flat(cx, fields.field::<$name>()?)?,)+))
+            }
         }
     };
 }
@@ -13,6 +13,8 @@ use private::*;
 #[cfg(feature = "serde")]
 use serde::*;
 
+use crate::concurrent::{ErrorContext, FutureReader, StreamReader};
+use crate::flat::*;
 use crate::require_matches::require_matches;
 use crate::types::*;
 use crate::AsContext;
@@ -69,6 +71,14 @@ pub enum Value {
     Own(ResourceOwn),
     /// A borrowed resource handle.
     Borrow(ResourceBorrow),
+    /// The readable end of a future.
+    Future(FutureReader),
+    /// The readable end of a stream.
+    Stream(StreamReader),
+    /// A diagnostic error context.
+    ErrorContext(ErrorContext),
+    /// A list of values with a fixed length.
+    FixedList(FixedList),
 }
 
 impl Value {
@@ -98,8 +108,203 @@ impl Value {
             Value::Flags(x) => ValueType::Flags(x.ty()),
             Value::Own(x) => ValueType::Own(x.ty()),
             Value::Borrow(x) => ValueType::Borrow(x.ty()),
+            Value::Future(x) => ValueType::Future(x.ty()),
+            Value::Stream(x) => ValueType::Stream(x.ty()),
+            Value::ErrorContext(_) => ValueType::ErrorContext,
+            Value::FixedList(x) => ValueType::FixedList(x.ty()),
         }
     }
+
+    /// Converts this value into the representation of `ty`, which must be a supertype of this value's type.
+    /// Extra record fields are discarded, missing optional fields are set to `none`, and cases and
+    /// flags are matched by name.
+    pub(crate) fn coerce(&self, ty: &ValueType) -> Result<Self> {
+        if self.ty() == *ty {
+            return Ok(self.clone());
+        }
+
+        Ok(match (self, ty) {
+            (Value::List(x), ValueType::List(y)) => Value::List(List::new(
+                y.clone(),
+                x.iter()
+                    .map(|value| value.coerce(&y.element_ty()))
+                    .collect::<Result<Vec<_>>>()?,
+            )?),
+            (Value::FixedList(x), ValueType::FixedList(y)) => Value::FixedList(FixedList::new(
+                y.clone(),
+                x.iter()
+                    .map(|value| value.coerce(&y.element_ty()))
+                    .collect::<Result<Vec<_>>>()?,
+            )?),
+            (Value::Record(x), ValueType::Record(y)) => Value::Record(Record::from_sorted(
+                y.clone(),
+                y.fields
+                    .iter()
+                    .map(|(_, name, field_ty)| {
+                        let value = match (x.field(name), field_ty) {
+                            (Some(value), _) => value.coerce(field_ty)?,
+                            (None, ValueType::Option(option_ty)) => {
+                                Value::Option(OptionValue::new(option_ty.clone(), None)?)
+                            }
+                            (None, _) => bail!("Record was missing field {name}."),
+                        };
+                        Ok((name.clone(), value))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            (Value::Tuple(x), ValueType::Tuple(y)) => {
+                ensure!(x.len() == y.fields().len(), "Tuple lengths did not match.");
+                Value::Tuple(Tuple::new_unchecked(
+                    y.clone(),
+                    x.iter()
+                        .zip(y.fields())
+                        .map(|(value, field_ty)| value.coerce(field_ty))
+                        .collect::<Result<Vec<_>>>()?,
+                ))
+            }
+            (Value::Variant(x), ValueType::Variant(y)) => {
+                let name = x.ty().cases()[x.discriminant()].name().to_string();
+                let discriminant = y
+                    .cases()
+                    .iter()
+                    .position(|case| case.name() == name)
+                    .with_context(|| format!("Variant did not have case {name}."))?;
+                let value = match (x.value(), y.cases()[discriminant].ty()) {
+                    (Some(value), Some(case_ty)) => Some(value.coerce(&case_ty)?),
+                    (None, None) => None,
+                    _ => bail!("Variant case {name} payloads did not match."),
+                };
+                Value::Variant(Variant::new(y.clone(), discriminant, value)?)
+            }
+            (Value::Enum(x), ValueType::Enum(y)) => {
+                let name = x
+                    .ty()
+                    .cases()
+                    .nth(x.discriminant())
+                    .context("Discriminant out-of-range.")?
+                    .to_string();
+                let discriminant = y
+                    .cases()
+                    .position(|case| case == name)
+                    .with_context(|| format!("Enum did not have case {name}."))?;
+                Value::Enum(Enum::new(y.clone(), discriminant)?)
+            }
+            (Value::Option(x), ValueType::Option(y)) => Value::Option(OptionValue::new(
+                y.clone(),
+                x.as_ref()
+                    .map(|value| value.coerce(&y.some_ty()))
+                    .transpose()?,
+            )?),
+            (Value::Result(x), ValueType::Result(y)) => {
+                /// Converts the payload of a result case into the given type.
+                fn coerce_payload(
+                    value: &Option<Value>,
+                    ty: Option<ValueType>,
+                ) -> Result<Option<Value>> {
+                    match (value, ty) {
+                        (Some(value), Some(ty)) => Ok(Some(value.coerce(&ty)?)),
+                        (None, None) => Ok(None),
+                        _ => bail!("Result payloads did not match."),
+                    }
+                }
+
+                Value::Result(ResultValue::new(
+                    y.clone(),
+                    match &**x {
+                        std::result::Result::Ok(value) => {
+                            std::result::Result::Ok(coerce_payload(value, y.ok_ty())?)
+                        }
+                        std::result::Result::Err(value) => {
+                            std::result::Result::Err(coerce_payload(value, y.err_ty())?)
+                        }
+                    },
+                )?)
+            }
+            (Value::Flags(x), ValueType::Flags(y)) => {
+                let mut flags = Flags::new(y.clone());
+                for name in x.ty().names() {
+                    if x.get(name)? {
+                        flags.set(name, true)?;
+                    }
+                }
+                Value::Flags(flags)
+            }
+            (Value::Own(x), ValueType::Own(y)) => {
+                ensure!(x.ty.is_subtype_of(y), "Value was not of the expected type.");
+                Value::Own(if y.is_any() { x.clone() } else { x.with_ty(y) })
+            }
+            (Value::Borrow(x), ValueType::Borrow(y)) => {
+                ensure!(x.ty.is_subtype_of(y), "Value was not of the expected type.");
+                Value::Borrow(if y.is_any() { x.clone() } else { x.with_ty(y) })
+            }
+            _ => bail!("Value was not of the expected type."),
+        })
+    }
+
+    /// Creates the default value of the given type: `false`, zero, an empty string or list, `none`, or
+    /// the first case of a variant, enum, or result. Fails for resource handles, futures, and streams,
+    /// which have no default.
+    pub(crate) fn default_for(ty: &ValueType) -> Result<Self> {
+        /// Creates the default value of an optional payload type.
+        fn default_payload(ty: Option<ValueType>) -> Result<Option<Value>> {
+            ty.map(|x| Value::default_for(&x)).transpose()
+        }
+
+        Ok(match ty {
+            ValueType::Bool => Value::Bool(false),
+            ValueType::S8 => Value::S8(0),
+            ValueType::U8 => Value::U8(0),
+            ValueType::S16 => Value::S16(0),
+            ValueType::U16 => Value::U16(0),
+            ValueType::S32 => Value::S32(0),
+            ValueType::U32 => Value::U32(0),
+            ValueType::S64 => Value::S64(0),
+            ValueType::U64 => Value::U64(0),
+            ValueType::F32 => Value::F32(0.0),
+            ValueType::F64 => Value::F64(0.0),
+            ValueType::Char => Value::Char('\0'),
+            ValueType::String => Value::String("".into()),
+            ValueType::List(x) => Value::List(List::new(x.clone(), [])?),
+            ValueType::Record(x) => Value::Record(Record::from_sorted(
+                x.clone(),
+                x.fields
+                    .iter()
+                    .map(|(_, name, ty)| Ok((name.clone(), Value::default_for(ty)?)))
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            ValueType::Tuple(x) => Value::Tuple(Tuple::new_unchecked(
+                x.clone(),
+                x.fields()
+                    .iter()
+                    .map(Value::default_for)
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            ValueType::Variant(x) => {
+                let case = x.cases().first().context("Variant had no cases.")?;
+                Value::Variant(Variant::new(x.clone(), 0, default_payload(case.ty())?)?)
+            }
+            ValueType::Enum(x) => Value::Enum(Enum::new(x.clone(), 0)?),
+            ValueType::Option(x) => Value::Option(OptionValue::new(x.clone(), None)?),
+            ValueType::Result(x) => Value::Result(ResultValue::new(
+                x.clone(),
+                std::result::Result::Ok(default_payload(x.ok_ty())?),
+            )?),
+            ValueType::Flags(x) => Value::Flags(Flags::new(x.clone())),
+            ValueType::Own(_) | ValueType::Borrow(_) => {
+                bail!("Resource handles do not have a default value.")
+            }
+            ValueType::Future(_) | ValueType::Stream(_) => {
+                bail!("Futures and streams do not have a default value.")
+            }
+            ValueType::ErrorContext => Value::ErrorContext(ErrorContext::new("")),
+            ValueType::FixedList(x) => Value::FixedList(FixedList::new_unchecked(
+                x.clone(),
+                (0..x.len())
+                    .map(|_| Value::default_for(&x.element_ty()))
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+        })
+    }
 }
 
 impl TryFrom<&Value> for wasm_runtime_layer::Value {
@@ -473,6 +678,87 @@ impl<'a> IntoIterator for &'a Tuple {
     }
 }
 
+/// A list of values with a fixed length, all of the same type.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FixedList {
+    /// The elements of this list.
+    values: Arc<[Value]>,
+    /// The type of the list.
+    ty: FixedListType,
+}
+
+impl FixedList {
+    /// Creates a new fixed-size list of the given type, from the provided values. Fails if the
+    /// number of values does not match the list's length, or any value has the wrong type.
+    pub fn new(ty: FixedListType, values: impl IntoIterator<Item = Value>) -> Result<Self> {
+        let element_ty = ty.element_ty();
+        let values = values
+            .into_iter()
+            .map(|val| {
+                (val.ty() == element_ty)
+                    .then_some(val)
+                    .ok_or_else(|| Error::msg("List item was not of correct type."))
+            })
+            .collect::<Result<Arc<_>>>()?;
+        ensure!(
+            values.len() == ty.len() as usize,
+            "Expected {} list items, but got {}.",
+            ty.len(),
+            values.len()
+        );
+        Ok(Self { values, ty })
+    }
+
+    /// Gets the type of this value.
+    pub fn ty(&self) -> FixedListType {
+        self.ty.clone()
+    }
+
+    /// Creates a new fixed-size list of the given type without any typechecking.
+    pub(crate) fn new_unchecked(
+        ty: FixedListType,
+        values: impl IntoIterator<Item = Value>,
+    ) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+            ty,
+        }
+    }
+}
+
+impl PartialEq for FixedList {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl Deref for FixedList {
+    type Target = [Value];
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl IntoIterator for FixedList {
+    type IntoIter = std::vec::IntoIter<Value>;
+    type Item = Value;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter().cloned().collect::<Vec<_>>().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FixedList {
+    type IntoIter = std::slice::Iter<'a, Value>;
+    type Item = &'a Value;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
 /// A value that exists in one of multiple possible states. Each state may optionally
 /// have a type associated with it.
 #[derive(Clone, Debug, PartialEq)]
@@ -652,7 +938,7 @@ impl Flags {
     pub fn new(ty: FlagsType) -> Self {
         let names = ty.names().len() as u32;
         Self {
-            flags: if names > usize::BITS {
+            flags: if names > u32::BITS {
                 FlagsList::Multiple(Arc::new(vec![0; (((names - 1) / u32::BITS) + 1) as usize]))
             } else {
                 FlagsList::Single(0)
@@ -661,27 +947,28 @@ impl Flags {
         }
     }
 
-    /// Gets the value of the flag with the given name.
-    pub fn get(&self, name: impl AsRef<str>) -> bool {
-        self.get_index(self.index_of(name))
+    /// Gets the value of the flag with the given name, failing if the type has no such flag.
+    pub fn get(&self, name: impl AsRef<str>) -> Result<bool> {
+        Ok(self.get_index(self.index_of(name)?))
     }
 
     /// Gets the value of the flag with the given index.
     pub fn get_index(&self, index: usize) -> bool {
         let index = index as u32;
         match &self.flags {
-            FlagsList::Single(x) => (*x >> index) == 1,
+            FlagsList::Single(x) => (*x >> index) & 1 == 1,
             FlagsList::Multiple(x) => {
                 let arr_index = index / u32::BITS;
                 let sub_index = index % u32::BITS;
-                (x[arr_index as usize] >> sub_index) == 1
+                (x[arr_index as usize] >> sub_index) & 1 == 1
             }
         }
     }
 
-    /// Sets the value of the flag with the given name.
-    pub fn set(&mut self, name: impl AsRef<str>, value: bool) {
-        self.set_index(self.index_of(name), value)
+    /// Sets the value of the flag with the given name, failing if the type has no such flag.
+    pub fn set(&mut self, name: impl AsRef<str>, value: bool) -> Result<()> {
+        self.set_index(self.index_of(name)?, value);
+        Ok(())
     }
 
     /// Sets the value of the flag with the given index.
@@ -728,12 +1015,13 @@ impl Flags {
     }
 
     /// Gets the flag index associated with the provided name.
-    fn index_of(&self, name: impl AsRef<str>) -> usize {
-        *self
-            .ty
+    fn index_of(&self, name: impl AsRef<str>) -> Result<usize> {
+        let name = name.as_ref();
+        self.ty
             .indices
-            .get(name.as_ref())
-            .expect("Unknown flag name")
+            .get(name)
+            .copied()
+            .with_context(|| format!("Flags did not have name {name}."))
     }
 }
 
@@ -773,16 +1061,21 @@ impl ResourceOwn {
         let store_id = store_ctx.inner.data().id;
         ensure!(
             ty.valid_for::<T>(store_id),
-            "Resource value was of incorrect type."
+            crate::Error::resource("Resource value was of incorrect type.")
         );
+        let tracker = Arc::<AtomicUsize>::default();
         let rep = store_ctx
             .inner
             .data_mut()
             .host_resources
-            .insert(Box::new(value)) as i32;
+            .insert(crate::HostResource {
+                ty: ty.clone(),
+                tracker: tracker.clone(),
+                value: Box::new(value),
+            }) as i32;
 
         Ok(Self {
-            tracker: Arc::default(),
+            tracker,
             rep,
             destructor: match ty
                 .host_destructor()
@@ -796,6 +1089,14 @@ impl ResourceOwn {
         })
     }
 
+    /// Gets a handle to the same resource, which is described by another type with the same representation.
+    pub(crate) fn with_ty(&self, ty: &ResourceType) -> Self {
+        Self {
+            ty: ty.clone(),
+            ..self.clone()
+        }
+    }
+
     /// Creates a new owned resource that is received from a guest.
     pub(crate) fn new_guest(
         rep: i32,
@@ -816,12 +1117,13 @@ impl ResourceOwn {
     pub fn borrow(&self, ctx: impl crate::AsContextMut) -> Result<ResourceBorrow> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             self.tracker.load(Ordering::Acquire) < usize::MAX,
-            "Resource was already destroyed."
+            crate::Error::resource("Resource was already destroyed.")
         );
+        self.tracker.fetch_add(1, Ordering::AcqRel);
         Ok(ResourceBorrow {
             dead: Arc::default(),
             host_tracker: Some(self.tracker.clone()),
@@ -843,11 +1145,11 @@ impl ResourceOwn {
     ) -> Result<&'a T> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             self.tracker.load(Ordering::Acquire) < usize::MAX,
-            "Resource was already destroyed."
+            crate::Error::resource("Resource was already destroyed.")
         );
 
         if self.ty.host_destructor().is_some() {
@@ -855,11 +1157,14 @@ impl ResourceOwn {
                 .data()
                 .host_resources
                 .get(self.rep as usize)
-                .expect("Resource was not present.")
+                .with_context(|| crate::Error::resource("Resource was not present."))?
+                .value
                 .downcast_ref()
-                .context("Resource was not of requested type.")
+                .with_context(|| crate::Error::resource("Resource was not of requested type."))
         } else {
-            bail!("Cannot get the representation for a guest-owned resource.");
+            bail!(crate::Error::resource(
+                "Cannot get the representation for a guest-owned resource."
+            ));
         }
     }
 
@@ -875,11 +1180,11 @@ impl ResourceOwn {
     ) -> Result<&'a mut T> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             self.tracker.load(Ordering::Acquire) < usize::MAX,
-            "Resource was already destroyed."
+            crate::Error::resource("Resource was already destroyed.")
         );
 
         if self.ty.host_destructor().is_some() {
@@ -887,11 +1192,14 @@ impl ResourceOwn {
                 .data_mut()
                 .host_resources
                 .get_mut(self.rep as usize)
-                .expect("Resource was not present.")
+                .with_context(|| crate::Error::resource("Resource was not present."))?
+                .value
                 .downcast_mut()
-                .context("Resource was not of requested type.")
+                .with_context(|| crate::Error::resource("Resource was not of requested type."))
         } else {
-            bail!("Cannot get the representation for a guest-owned resource.");
+            bail!(crate::Error::resource(
+                "Cannot get the representation for a guest-owned resource."
+            ));
         }
     }
 
@@ -902,19 +1210,19 @@ impl ResourceOwn {
 
     /// Removes this resource from the context without invoking the destructor, and returns the value.
     /// Fails if this is not a host resource, or if the resource is borrowed.
-    pub fn take<T: 'static + Send + Sync>(&self, mut ctx: impl crate::AsContextMut) -> Result<()> {
+    pub fn take<T: 'static + Send + Sync>(&self, mut ctx: impl crate::AsContextMut) -> Result<T> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             self.tracker.load(Ordering::Acquire) == 0,
-            "Resource had remaining borrows or was already dropped."
+            crate::Error::resource("Resource had remaining borrows or was already dropped.")
         );
 
         ensure!(
             self.ty.host_destructor().is_some(),
-            "Resource did not originate from host."
+            crate::Error::resource("Resource did not originate from host.")
         );
 
         ensure!(
@@ -923,18 +1231,24 @@ impl ResourceOwn {
                 .data_mut()
                 .host_resources
                 .get(self.rep as usize)
-                .expect("Resource was not present.")
+                .with_context(|| crate::Error::resource("Resource was not present."))?
+                .value
                 .is::<T>(),
-            "Resource was of incorrect type."
+            crate::Error::resource("Resource was of incorrect type.")
         );
 
-        *ctx.as_context_mut()
+        let value = ctx
+            .as_context_mut()
             .inner
             .data_mut()
             .host_resources
             .remove(self.rep as usize)
+            .value
             .downcast()
-            .expect("Could not downcast resource.")
+            .map_err(|_| crate::Error::resource("Resource was of incorrect type."))?;
+
+        self.tracker.store(usize::MAX, Ordering::Release);
+        Ok(*value)
     }
 
     /// Drops this resource and invokes the destructor, removing it from the context.
@@ -942,34 +1256,42 @@ impl ResourceOwn {
     pub fn drop(&self, mut ctx: impl crate::AsContextMut) -> Result<()> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             self.tracker.load(Ordering::Acquire) == 0,
-            "Resource had remaining borrows or was already dropped."
+            crate::Error::resource("Resource had remaining borrows or was already dropped.")
         );
 
         if let Some(destructor) = &self.destructor {
-            destructor.call(
-                ctx.as_context_mut().inner,
-                &[wasm_runtime_layer::Value::I32(self.rep)],
-                &mut [],
-            )?;
+            destructor
+                .call(
+                    ctx.as_context_mut().inner,
+                    &[wasm_runtime_layer::Value::I32(self.rep)],
+                    &mut [],
+                )
+                .map_err(crate::Error::Trap)?;
         }
 
         self.tracker.store(usize::MAX, Ordering::Release);
         Ok(())
     }
 
-    /// Lowers this owned resource into a guest context.
+    /// Lowers this owned resource into a guest context, transferring ownership to the guest.
+    /// Fails if the resource is borrowed, since the borrows would outlive the transfer.
     pub(crate) fn lower(&self, ctx: impl crate::AsContextMut) -> Result<i32> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
+        let tracker = self.tracker.load(Ordering::Acquire);
         ensure!(
-            self.tracker.load(Ordering::Acquire) < usize::MAX,
-            "Resource was already destroyed."
+            tracker < usize::MAX,
+            crate::Error::resource("Resource was already destroyed.")
+        );
+        ensure!(
+            tracker == 0,
+            crate::Error::resource("Attempted to transfer ownership while resource was lent.")
         );
         self.tracker.store(usize::MAX, Ordering::Release);
         Ok(self.rep)
@@ -1039,11 +1361,11 @@ impl ResourceBorrow {
     ) -> Result<&'a T> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             !self.dead.load(Ordering::Acquire),
-            "Borrow was already dropped."
+            crate::Error::resource("Borrow was already dropped.")
         );
 
         if self.ty.host_destructor().is_some() {
@@ -1051,11 +1373,14 @@ impl ResourceBorrow {
                 .data()
                 .host_resources
                 .get(self.rep as usize)
-                .expect("Resource was not present.")
+                .with_context(|| crate::Error::resource("Resource was not present."))?
+                .value
                 .downcast_ref()
-                .context("Resource was not of requested type.")
+                .with_context(|| crate::Error::resource("Resource was not of requested type."))
         } else {
-            bail!("Cannot get the representation for a guest-owned resource.");
+            bail!(crate::Error::resource(
+                "Cannot get the representation for a guest-owned resource."
+            ));
         }
     }
 
@@ -1071,11 +1396,11 @@ impl ResourceBorrow {
     ) -> Result<&'a mut T> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             !self.dead.load(Ordering::Acquire),
-            "Borrow was already dropped."
+            crate::Error::resource("Borrow was already dropped.")
         );
 
         if self.ty.host_destructor().is_some() {
@@ -1083,11 +1408,14 @@ impl ResourceBorrow {
                 .data_mut()
                 .host_resources
                 .get_mut(self.rep as usize)
-                .expect("Resource was not present.")
+                .with_context(|| crate::Error::resource("Resource was not present."))?
+                .value
                 .downcast_mut()
-                .context("Resource was not of requested type.")
+                .with_context(|| crate::Error::resource("Resource was not of requested type."))
         } else {
-            bail!("Cannot get the representation for a guest-owned resource.");
+            bail!(crate::Error::resource(
+                "Cannot get the representation for a guest-owned resource."
+            ));
         }
     }
 
@@ -1096,20 +1424,21 @@ impl ResourceBorrow {
         self.ty.clone()
     }
 
-    /// Drops this borrow. Fails if this was not a manual borrow of a host resource.
+    /// Drops this borrow. Fails if this was not a manual borrow of a host resource, or if
+    /// the borrow (or one of its clones) was already dropped.
     pub fn drop(&self, ctx: impl crate::AsContextMut) -> Result<()> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
-        );
-        ensure!(
-            !self.dead.load(Ordering::Acquire),
-            "Borrow was already dropped."
+            crate::Error::StoreMismatch
         );
         let tracker = self
             .host_tracker
             .as_ref()
-            .context("Only host borrows require dropping.")?;
+            .with_context(|| crate::Error::resource("Only host borrows require dropping."))?;
+        ensure!(
+            !self.dead.swap(true, Ordering::AcqRel),
+            crate::Error::resource("Borrow was already dropped.")
+        );
         tracker.fetch_sub(1, Ordering::AcqRel);
         Ok(())
     }
@@ -1118,11 +1447,11 @@ impl ResourceBorrow {
     pub(crate) fn lower(&self, ctx: impl crate::AsContextMut) -> Result<i32> {
         ensure!(
             self.store_id == ctx.as_context().inner.data().id,
-            "Incorrect store."
+            crate::Error::StoreMismatch
         );
         ensure!(
             !self.dead.load(Ordering::Acquire),
-            "Borrow was already dropped."
+            crate::Error::resource("Borrow was already dropped.")
         );
         Ok(self.rep)
     }
@@ -1131,6 +1460,14 @@ impl ResourceBorrow {
     pub(crate) fn dead_ref(&self) -> Arc<AtomicBool> {
         self.dead.clone()
     }
+
+    /// Gets a handle to the same borrow, which is described by another type with the same representation.
+    pub(crate) fn with_ty(&self, ty: &ResourceType) -> Self {
+        Self {
+            ty: ty.clone(),
+            ..self.clone()
+        }
+    }
 }
 
 impl PartialEq for ResourceBorrow {
@@ -1155,6 +1492,273 @@ impl<'a> Deserialize<'a> for ResourceBorrow {
     }
 }
 
+/// An owned host resource whose representation is a value of type `T`. Unlike [`ResourceOwn`],
+/// this implements [`ComponentType`], so it may appear in the signatures of [`TypedFunc`](crate::TypedFunc)s
+/// and of closures passed to [`Func::wrap`](crate::Func::wrap).
+///
+/// A `Resource<T>` may hold a resource of any host [`ResourceType`] whose representation is `T`.
+/// As a convenience, [`Resource::resource_type`] provides a default type for `T`, which may be used
+/// to define the resource in a [`Linker`](crate::Linker). In the signatures of typed functions, the
+/// default type stands for every host resource type whose representation is `T`. When the resource
+/// is destroyed, the host value is dropped.
+pub struct Resource<T> {
+    /// The untyped resource.
+    inner: ResourceOwn,
+    /// Marks the type of the representation.
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync> Resource<T> {
+    /// Gets the default resource type for representations of type `T`, which is shared by all callers.
+    pub fn resource_type() -> ResourceType {
+        ResourceType::placeholder::<T>()
+    }
+
+    /// Creates a new resource for the given value, with the default resource type.
+    pub fn new(ctx: impl AsContextMut, value: T) -> Result<Self> {
+        Self::with_type(ctx, value, Self::resource_type())
+    }
+
+    /// Creates a new resource for the given value, with a host resource type whose representation is `T`.
+    pub fn with_type(ctx: impl AsContextMut, value: T, ty: ResourceType) -> Result<Self> {
+        Ok(Self {
+            inner: ResourceOwn::new(ctx, value, ty)?,
+            data: PhantomData,
+        })
+    }
+
+    /// Creates a borrow of this resource. The resulting borrow must be manually released via [`ResourceRef::drop`] afterward.
+    pub fn borrow(&self, ctx: impl AsContextMut) -> Result<ResourceRef<T>> {
+        Ok(ResourceRef {
+            inner: self.inner.borrow(ctx)?,
+            data: PhantomData,
+        })
+    }
+
+    /// Gets the value of this resource. Fails if the resource was already dropped.
+    pub fn rep<'a, S: 'static, E: wasm_runtime_layer::backend::WasmEngine>(
+        &self,
+        ctx: &'a crate::StoreContext<S, E>,
+    ) -> Result<&'a T> {
+        self.inner.rep(ctx)
+    }
+
+    /// Gets the mutable value of this resource. Fails if the resource was already dropped.
+    pub fn rep_mut<'a, S: 'static, E: wasm_runtime_layer::backend::WasmEngine>(
+        &self,
+        ctx: &'a mut crate::StoreContextMut<S, E>,
+    ) -> Result<&'a mut T> {
+        self.inner.rep_mut(ctx)
+    }
+
+    /// Removes this resource from the context and returns the value. Fails if the resource is borrowed.
+    pub fn take(&self, ctx: impl AsContextMut) -> Result<T> {
+        self.inner.take(ctx)
+    }
+
+    /// Drops this resource and its value, removing it from the context.
+    /// Fails if the resource is borrowed or already destroyed.
+    pub fn drop(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.inner.drop(ctx)
+    }
+
+    /// Gets the untyped resource.
+    pub fn as_inner(&self) -> &ResourceOwn {
+        &self.inner
+    }
+
+    /// Converts this into an untyped resource.
+    pub fn into_inner(self) -> ResourceOwn {
+        self.inner
+    }
+}
+
+impl<T: 'static + Send + Sync> TryFrom<ResourceOwn> for Resource<T> {
+    type Error = Error;
+
+    fn try_from(value: ResourceOwn) -> Result<Self> {
+        ensure!(
+            value.ty.valid_for::<T>(value.store_id),
+            crate::Error::resource(format!("Resource was not of type {}.", type_name::<T>()))
+        );
+        Ok(Self {
+            inner: value,
+            data: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for Resource<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Resource<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Resource").field(&self.inner).finish()
+    }
+}
+
+impl<T> PartialEq for Resource<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: 'static + Send + Sync> ComponentType for Resource<T> {
+    fn ty() -> ValueType {
+        ValueType::Own(Self::resource_type())
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        require_matches!(value, Value::Own(x), x).clone().try_into()
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Own(self.inner))
+    }
+}
+
+/// A borrowed host resource whose representation is a value of type `T`. This is the borrowed
+/// counterpart of [`Resource`], and may likewise appear in typed function signatures.
+pub struct ResourceRef<T> {
+    /// The untyped borrow.
+    inner: ResourceBorrow,
+    /// Marks the type of the representation.
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static + Send + Sync> ResourceRef<T> {
+    /// Gets the value of this resource. Fails if the borrow was already dropped.
+    pub fn rep<'a, S: 'static, E: wasm_runtime_layer::backend::WasmEngine>(
+        &self,
+        ctx: &'a crate::StoreContext<S, E>,
+    ) -> Result<&'a T> {
+        self.inner.rep(ctx)
+    }
+
+    /// Gets the mutable value of this resource. Fails if the borrow was already dropped.
+    pub fn rep_mut<'a, S: 'static, E: wasm_runtime_layer::backend::WasmEngine>(
+        &self,
+        ctx: &'a mut crate::StoreContextMut<S, E>,
+    ) -> Result<&'a mut T> {
+        self.inner.rep_mut(ctx)
+    }
+
+    /// Drops this borrow. Fails if this was not created by [`Resource::borrow`].
+    pub fn drop(&self, ctx: impl AsContextMut) -> Result<()> {
+        self.inner.drop(ctx)
+    }
+
+    /// Gets the untyped borrow.
+    pub fn as_inner(&self) -> &ResourceBorrow {
+        &self.inner
+    }
+
+    /// Converts this into an untyped borrow.
+    pub fn into_inner(self) -> ResourceBorrow {
+        self.inner
+    }
+}
+
+impl<T: 'static + Send + Sync> TryFrom<ResourceBorrow> for ResourceRef<T> {
+    type Error = Error;
+
+    fn try_from(value: ResourceBorrow) -> Result<Self> {
+        ensure!(
+            value.ty.valid_for::<T>(value.store_id),
+            crate::Error::resource(format!("Resource was not of type {}.", type_name::<T>()))
+        );
+        Ok(Self {
+            inner: value,
+            data: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for ResourceRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ResourceRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ResourceRef").field(&self.inner).finish()
+    }
+}
+
+impl<T> PartialEq for ResourceRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: 'static + Send + Sync> UnaryComponentType for Resource<T> {}
+
+impl<T: 'static + Send + Sync> ComponentType for ResourceRef<T> {
+    fn ty() -> ValueType {
+        ValueType::Borrow(Resource::<T>::resource_type())
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        require_matches!(value, Value::Borrow(x), x)
+            .clone()
+            .try_into()
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Borrow(self.inner))
+    }
+}
+
+impl<T: 'static + Send + Sync> UnaryComponentType for ResourceRef<T> {}
+
+/// Untyped handles may appear in the signatures of typed functions, where they stand for owned
+/// resources of any type, including those exported by guests. Since they may hold resources of
+/// any type, host functions that return them cannot satisfy imports which expect a specific resource type.
+impl ComponentType for ResourceOwn {
+    fn ty() -> ValueType {
+        ValueType::Own(ResourceType::placeholder::<crate::types::AnyResource>())
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        Ok(require_matches!(value, Value::Own(x), x).clone())
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Own(self))
+    }
+}
+
+impl UnaryComponentType for ResourceOwn {}
+
+/// Untyped borrows may appear in the signatures of typed functions, where they stand for borrowed
+/// resources of any type, including those exported by guests. Since they may hold resources of
+/// any type, host functions that return them cannot satisfy imports which expect a specific resource type.
+impl ComponentType for ResourceBorrow {
+    fn ty() -> ValueType {
+        ValueType::Borrow(ResourceType::placeholder::<crate::types::AnyResource>())
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        Ok(require_matches!(value, Value::Borrow(x), x).clone())
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::Borrow(self))
+    }
+}
+
+impl UnaryComponentType for ResourceBorrow {}
+
 /// A type which can convert itself to and from component model values.
 pub trait ComponentType: 'static + Sized {
     /// Gets the component model type for instances of `Self`.
@@ -1165,6 +1769,62 @@ pub trait ComponentType: 'static + Sized {
 
     /// Attempts to convert `Self` into a component model value.
     fn into_value(self) -> Result<Value>;
+
+    /// The canonical ABI layout of this type, or `None` if values of this type are only
+    /// converted through [`Value`]s. When the parameters and results of a [`TypedFunc`](crate::TypedFunc)
+    /// all have layouts, calls convert them directly to and from core values using the methods below.
+    const LAYOUT: Option<FlatLayout> = None;
+
+    /// Lowers `self` to core values, appending them to the context.
+    fn lower_flat<C: AsContextMut>(&self, _cx: &mut FlatContext<'_, C>) -> Result<()> {
+        bail!("Type does not implement lowering to core values.")
+    }
+
+    /// Stores `self` in guest memory at the given offset.
+    fn store_flat<C: AsContextMut>(
+        &self,
+        _cx: &mut FlatContext<'_, C>,
+        _offset: usize,
+    ) -> Result<()> {
+        bail!("Type does not implement storing to guest memory.")
+    }
+
+    /// Lifts an instance of `Self` from the next core values of the context.
+    fn lift_flat<C: AsContextMut>(_cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        bail!("Type does not implement lifting from core values.")
+    }
+
+    /// Loads an instance of `Self` from guest memory at the given offset, which has been
+    /// checked to lie within guest memory.
+    fn load_flat<C: AsContextMut>(_cx: &mut FlatContext<'_, C>, _offset: usize) -> Result<Self> {
+        bail!("Type does not implement loading from guest memory.")
+    }
+
+    /// Stores consecutive instances of `Self` in guest memory at the given offset.
+    fn store_flat_list<C: AsContextMut>(
+        values: &[Self],
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        let size = layout_of::<Self>()?.size();
+        for (i, value) in values.iter().enumerate() {
+            value.store_flat(cx, offset + i * size)?;
+        }
+        Ok(())
+    }
+
+    /// Loads `len` consecutive instances of `Self` from guest memory at the given offset, which
+    /// has been checked to lie within guest memory.
+    fn load_flat_list<C: AsContextMut>(
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<Self>> {
+        let size = layout_of::<Self>()?.size();
+        (0..len)
+            .map(|i| Self::load_flat(cx, offset + i * size))
+            .collect()
+    }
 }
 
 /// Implements the `ComponentType` trait for primitive values.
@@ -1183,6 +1843,33 @@ macro_rules! impl_primitive_component_type {
                 fn into_value(self) -> Result<Value> {
                     Ok(Value::$enum_name(self))
                 }
+
+                const LAYOUT: Option<FlatLayout> =
+                    Some(FlatLayout::new(1, size_of::<Self>(), size_of::<Self>()));
+
+                fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+                    FlatPrimitive::lower(self, cx)
+                }
+
+                fn store_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {
+                    FlatPrimitive::store(self, cx, offset)
+                }
+
+                fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+                    <Self as FlatPrimitive>::lift(cx)
+                }
+
+                fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+                    <Self as FlatPrimitive>::load(cx, offset)
+                }
+
+                fn store_flat_list<C: AsContextMut>(values: &[Self], cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {
+                    <Self as FlatPrimitive>::store_list(values, cx, offset)
+                }
+
+                fn load_flat_list<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize, len: usize) -> Result<Vec<Self>> {
+                    <Self as FlatPrimitive>::load_list(cx, offset, len)
+                }
             }
         )*
     };
@@ -1204,6 +1891,28 @@ impl ComponentType for String {
     fn into_value(self) -> Result<Value> {
         Ok(Value::String(self.into()))
     }
+
+    const LAYOUT: Option<FlatLayout> = Some(FlatLayout::new(2, 8, 4));
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        FlatString::lower(self, cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        FlatString::store(self, cx, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        <Self as FlatString>::lift(cx)
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        <Self as FlatString>::load(cx, offset)
+    }
 }
 
 impl ComponentType for Box<str> {
@@ -1220,6 +1929,28 @@ impl ComponentType for Box<str> {
     fn into_value(self) -> Result<Value> {
         Ok(Value::String(self.into()))
     }
+
+    const LAYOUT: Option<FlatLayout> = Some(FlatLayout::new(2, 8, 4));
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        FlatString::lower(self, cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        FlatString::store(self, cx, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        <Self as FlatString>::lift(cx)
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        <Self as FlatString>::load(cx, offset)
+    }
 }
 
 impl ComponentType for Arc<str> {
@@ -1234,6 +1965,42 @@ impl ComponentType for Arc<str> {
     fn into_value(self) -> Result<Value> {
         Ok(Value::String(self))
     }
+
+    const LAYOUT: Option<FlatLayout> = Some(FlatLayout::new(2, 8, 4));
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        FlatString::lower(self, cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        FlatString::store(self, cx, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        <Self as FlatString>::lift(cx)
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        <Self as FlatString>::load(cx, offset)
+    }
+}
+
+impl ComponentType for ErrorContext {
+    fn ty() -> ValueType {
+        ValueType::ErrorContext
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        Ok(require_matches!(value, Value::ErrorContext(x), x).clone())
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::ErrorContext(self))
+    }
 }
 
 impl<T: ComponentType> ComponentType for Option<T> {
@@ -1277,6 +2044,28 @@ impl<T: ComponentType> ComponentType for Box<T> {
     fn into_value(self) -> Result<Value> {
         Ok(T::into_value(*self)?)
     }
+
+    const LAYOUT: Option<FlatLayout> = T::LAYOUT;
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        T::lower_flat(self, cx)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        T::store_flat(self, cx, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        T::lift_flat(cx).map(Box::new)
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        T::load_flat(cx, offset).map(Box::new)
+    }
 }
 
 impl ComponentType for Result<(), ()> {
@@ -1589,6 +2378,89 @@ impl<T: ComponentType> ComponentType for Vec<T> {
             }
         }))
     }
+
+    const LAYOUT: Option<FlatLayout> = match T::LAYOUT {
+        Some(_) => Some(FlatLayout::new(2, 8, 4)),
+        None => None,
+    };
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        cx.lower_list(self)
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        cx.store_list(self, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        let ptr = cx.pop_u32()?;
+        let len = cx.pop_u32()?;
+        cx.lift_list(ptr, len)
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        let (ptr, len) = cx.load_pointer_pair(offset)?;
+        cx.lift_list(ptr, len)
+    }
+}
+
+impl<T: ComponentType, const N: usize> ComponentType for [T; N] {
+    fn ty() -> ValueType {
+        ValueType::FixedList(FixedListType::new(T::ty(), N as u32))
+    }
+
+    fn from_value(value: &Value) -> Result<Self> {
+        let list = require_matches!(value, Value::FixedList(x), x);
+        list.iter()
+            .map(T::from_value)
+            .collect::<Result<Vec<_>>>()?
+            .try_into()
+            .map_err(|x: Vec<T>| anyhow!("Expected {N} list items, but got {}.", x.len()))
+    }
+
+    fn into_value(self) -> Result<Value> {
+        Ok(Value::FixedList(FixedList::new(
+            FixedListType::new(T::ty(), N as u32),
+            self.into_iter()
+                .map(T::into_value)
+                .collect::<Result<Vec<_>>>()?,
+        )?))
+    }
+
+    const LAYOUT: Option<FlatLayout> = FlatLayout::array(T::LAYOUT, N);
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        for value in self {
+            value.lower_flat(cx)?;
+        }
+        Ok(())
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        T::store_flat_list(self, cx, offset)
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        (0..N)
+            .map(|_| T::lift_flat(cx))
+            .collect::<Result<Vec<_>>>()?
+            .try_into()
+            .map_err(|_| anyhow!("Expected {N} list items."))
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        T::load_flat_list(cx, offset, N)?
+            .try_into()
+            .map_err(|_| anyhow!("Expected {N} list items."))
+    }
 }
 
 /// Implements `ComponentType` for tuples
@@ -1613,6 +2485,29 @@ macro_rules! tuple_impl {
                     [$(<$ty as ComponentType>::into_value(self.$idx)?,)*]
                 )?))
             }
+
+            const LAYOUT: Option<FlatLayout> =
+                FlatLayout::record(&[$(<$ty as ComponentType>::LAYOUT,)*]);
+
+            fn lower_flat<S: AsContextMut>(&self, cx: &mut FlatContext<'_, S>) -> Result<()> {
+                $(self.$idx.lower_flat(cx)?;)*
+                Ok(())
+            }
+
+            fn store_flat<S: AsContextMut>(&self, cx: &mut FlatContext<'_, S>, offset: usize) -> Result<()> {
+                let mut fields = FlatFields::new(offset);
+                $(self.$idx.store_flat(cx, fields.field::<$ty>()?)?;)*
+                Ok(())
+            }
+
+            fn lift_flat<S: AsContextMut>(cx: &mut FlatContext<'_, S>) -> Result<Self> {
+                Ok(($(<$ty as ComponentType>::lift_flat(cx)?,)*))
+            }
+
+            fn load_flat<S: AsContextMut>(cx: &mut FlatContext<'_, S>, offset: usize) -> Result<Self> {
+                let mut fields = FlatFields::new(offset);
+                Ok(($(<$ty as ComponentType>::load_flat(cx, fields.field::<$ty>()?)?,)*))
+            }
         }
     };
 }
@@ -1652,6 +2547,7 @@ impl_unary!(
     [] String,
     [] Box<str>,
     [] Arc<str>,
+    [] ErrorContext,
     [T: ComponentType] Option<T>,
     [T: ComponentType] Box<T>,
     [T: ComponentType] Vec<T>,
@@ -1661,6 +2557,8 @@ impl_unary!(
     [T: ComponentType] Result<T, ()>,
 );
 
+impl<T: ComponentType, const N: usize> UnaryComponentType for [T; N] {}
+
 /// A module used to hide traits that are implementation details.
 mod private {
     use super::*;
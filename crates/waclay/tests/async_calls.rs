@@ -0,0 +1,137 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use waclay::*;
+
+// The component of the `async_host` example, which exports an async `process-message` function
+// that waits for the asynchronous `lookup` and `log` functions which it imports.
+const WASM: &[u8] = include_bytes!("../examples/async_host/component.wasm");
+
+// The component of the `string_host_guest` example, which imports its `host-log` function synchronously.
+const SYNC_WASM: &[u8] = include_bytes!("../examples/string_host_guest/component.wasm");
+
+/// A future which is pending when first polled, and ready when polled again.
+#[derive(Default)]
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Instantiates the component with host functions that take the given time to complete,
+/// returning its `process-message` export.
+fn instantiate(
+    store: &mut Store<(), wasmi_runtime_layer::Engine>,
+    delay: Duration,
+) -> TypedFunc<String, u32> {
+    let component = Component::new(store.engine(), WASM).unwrap();
+    let mut linker = Linker::default();
+    let host = linker
+        .define_instance("test:guest/host".try_into().unwrap())
+        .unwrap();
+    host.func_wrap_async(&mut *store, "lookup", move |_, key: String| {
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            Ok(key)
+        })
+    })
+    .unwrap();
+    host.func_wrap_async(&mut *store, "log", |_, _: String| {
+        Box::pin(async { Ok(()) })
+    })
+    .unwrap();
+
+    linker
+        .instantiate(&mut *store, &component)
+        .unwrap()
+        .exports()
+        .instance(&"test:guest/message".try_into().unwrap())
+        .unwrap()
+        .func("process-message")
+        .unwrap()
+        .typed()
+        .unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn dropped_call_restores_store() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    let process_message = instantiate(&mut store, Duration::from_millis(50));
+
+    // Drop the call while the guest waits for `lookup`
+    let timeout = tokio::time::timeout(
+        Duration::from_millis(5),
+        process_message.call_async(&mut store, "key".to_string()),
+    );
+    assert!(timeout.await.is_err());
+
+    // Host functions called synchronously may block again, since no asynchronous call remains
+    let ready = Func::wrap_async(&mut store, |_, (): ()| {
+        Box::pin(async {
+            YieldOnce::default().await;
+            Ok(1u32)
+        })
+    })
+    .typed::<(), u32>()
+    .unwrap();
+    assert_eq!(ready.call(&mut store, ()).unwrap(), 1);
+
+    // The instance which the dropped call entered may be entered again
+    let length = process_message
+        .call_async(&mut store, "key".to_string())
+        .await
+        .unwrap();
+    assert_eq!(length, 3);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn synchronous_import_of_pending_function_fails() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, SYNC_WASM).unwrap();
+    let mut linker = Linker::default();
+    linker
+        .define_instance("test:guest/host-logger".try_into().unwrap())
+        .unwrap()
+        .func_wrap_async(&mut store, "host-log", |_, _: String| {
+            Box::pin(async {
+                YieldOnce::default().await;
+                Ok(())
+            })
+        })
+        .unwrap();
+    let process_message = linker
+        .instantiate(&mut store, &component)
+        .unwrap()
+        .exports()
+        .instance(&"test:guest/message".try_into().unwrap())
+        .unwrap()
+        .func("process-message")
+        .unwrap()
+        .typed::<String, String>()
+        .unwrap();
+
+    // The guest cannot be suspended, so the call fails rather than park the executor's thread
+    let error = process_message
+        .call_async(&mut store, "hello".to_string())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("waited synchronously"));
+
+    // Outside `call_async`, the thread is parked until the future completes
+    process_message
+        .call(&mut store, "hello".to_string())
+        .unwrap();
+}
@@ -0,0 +1,63 @@
+use waclay::*;
+
+const MODULE_IMPORT: &[u8] = include_bytes!("core_modules/module_import.wasm");
+const ENGINE_MODULE: &[u8] = include_bytes!("core_modules/engine.wasm");
+
+/// Gets the message of the error that occurs when loading the given component.
+fn load_error(bytes: &[u8]) -> String {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    format!("{:#}", Component::new(&engine, bytes).unwrap_err())
+}
+
+#[test]
+fn top_level_module_imports_are_linked() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, MODULE_IMPORT).unwrap();
+
+    let mut linker = Linker::default();
+    let report = linker.check(&component).unwrap_err();
+    assert_eq!(report.missing_modules().len(), 1);
+    assert_eq!(report.missing_modules()[0].to_string(), "engine");
+
+    linker
+        .root_mut()
+        .define_module(
+            "engine",
+            wasm_runtime_layer::Module::new(&engine, ENGINE_MODULE).unwrap(),
+        )
+        .unwrap();
+    let instance = linker.instantiate(&mut store, &component).unwrap();
+    let answer = instance
+        .exports()
+        .root()
+        .func("answer")
+        .unwrap()
+        .typed::<(), u32>()
+        .unwrap();
+    assert_eq!(answer.call(&mut store, ()).unwrap(), 42);
+}
+
+#[test]
+fn nested_module_imports_are_rejected() {
+    let error = load_error(include_bytes!("core_modules/nested_module_import.wasm"));
+    assert!(
+        error.contains("Core module import `test:modules/plugins.engine` is nested"),
+        "{error}"
+    );
+}
+
+#[test]
+fn module_exports_are_rejected() {
+    let error = load_error(include_bytes!("core_modules/module_export.wasm"));
+    assert!(
+        error.contains("Core module export `inner` is not supported"),
+        "{error}"
+    );
+
+    let error = load_error(include_bytes!("core_modules/instance_module_export.wasm"));
+    assert!(
+        error.contains("Core module export `test:modules/api.inner` is not supported"),
+        "{error}"
+    );
+}
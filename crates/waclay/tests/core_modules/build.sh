@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# Assemble the test components and the core module that they import
+for name in module_export instance_module_export nested_module_import module_import engine; do
+    wasm-tools parse $name.wat -o $name.wasm
+done
@@ -0,0 +1,4 @@
+;; The core module which satisfies the import of `module_import.wat`.
+(module
+  (func (export "answer") (result i32) i32.const 42)
+)
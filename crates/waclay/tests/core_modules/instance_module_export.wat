@@ -0,0 +1,10 @@
+;; A component which exports a core module from within an exported interface.
+(component
+  (core module $m
+    (func (export "answer") (result i32) i32.const 42)
+  )
+  (core instance $i (instantiate $m))
+  (func $answer (result u32) (canon lift (core func $i "answer")))
+  (instance $api (export "answer" (func $answer)) (export "inner" (core module $m)))
+  (export "test:modules/api" (instance $api))
+)
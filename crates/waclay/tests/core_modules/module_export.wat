@@ -0,0 +1,10 @@
+;; A component which exports a core module beside a function.
+(component
+  (core module $m
+    (func (export "answer") (result i32) i32.const 42)
+  )
+  (core instance $i (instantiate $m))
+  (func $answer (result u32) (canon lift (core func $i "answer")))
+  (export "answer" (func $answer))
+  (export "inner" (core module $m))
+)
@@ -0,0 +1,9 @@
+;; A component which imports a core module at its top level, and exports a function from it.
+(component
+  (import "engine" (core module $m
+    (export "answer" (func (result i32)))
+  ))
+  (core instance $i (instantiate $m))
+  (func $answer (result u32) (canon lift (core func $i "answer")))
+  (export "answer" (func $answer))
+)
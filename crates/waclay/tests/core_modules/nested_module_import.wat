@@ -0,0 +1,12 @@
+;; A component which imports a core module from within an imported interface.
+(component
+  (import "test:modules/plugins" (instance
+    (export "engine" (core module))
+  ))
+  (core module $m
+    (func (export "answer") (result i32) i32.const 42)
+  )
+  (core instance $i (instantiate $m))
+  (func $answer (result u32) (canon lift (core func $i "answer")))
+  (export "answer" (func $answer))
+)
@@ -0,0 +1,29 @@
+use waclay::*;
+
+const WASM: &[u8] = include_bytes!("../examples/fixed_lists/component.wasm");
+
+#[test]
+fn host_functions_round_trip_arrays() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+
+    let reverse = Func::wrap(&mut store, |_, (mut values,): ([u16; 3],)| {
+        values.reverse();
+        Ok(values)
+    });
+
+    let result = reverse
+        .typed::<([u16; 3],), [u16; 3]>()
+        .unwrap()
+        .call(&mut store, ([1, 2, 3],))
+        .unwrap();
+    assert_eq!(result, [3, 2, 1]);
+
+    assert!(reverse.typed::<([u16; 4],), [u16; 4]>().is_err());
+}
+
+#[test]
+fn guest_components_are_rejected() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    assert!(Component::new(&engine, WASM).is_err());
+}
@@ -0,0 +1,26 @@
+use waclay::*;
+
+#[test]
+fn unknown_flag_names_fail() {
+    let ty = FlagsType::new(None, ["read", "write"]).unwrap();
+    let mut flags = Flags::new(ty);
+
+    flags.set("write", true).unwrap();
+    assert!(flags.get("write").unwrap());
+    assert!(!flags.get("read").unwrap());
+
+    assert!(flags.get("execute").is_err());
+    assert!(flags.set("execute", true).is_err());
+}
+
+#[test]
+fn flags_beyond_one_word_are_stored() {
+    let names = (0..40).map(|i| format!("flag-{i}")).collect::<Vec<_>>();
+    let mut flags = Flags::new(FlagsType::new(None, names).unwrap());
+
+    flags.set("flag-39", true).unwrap();
+    flags.set("flag-1", true).unwrap();
+    assert!(flags.get("flag-39").unwrap());
+    assert!(flags.get("flag-1").unwrap());
+    assert!(!flags.get("flag-32").unwrap());
+}
@@ -0,0 +1,37 @@
+use waclay::*;
+
+const WASM: &[u8] = include_bytes!("../examples/single_component/component.wasm");
+
+#[test]
+fn dropped_instance_reports_errors_instead_of_panicking() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+
+    let interface = instance
+        .exports()
+        .instance(&"test:guest/foo".try_into().unwrap())
+        .unwrap();
+    let select_nth = interface
+        .func("select-nth")
+        .unwrap()
+        .typed::<(Vec<String>, u32), String>()
+        .unwrap();
+
+    assert!(instance.drop(&mut store).unwrap().is_empty());
+    assert!(instance.drop(&mut store).unwrap().is_empty());
+
+    let error = select_nth
+        .call(&mut store, (vec!["a".to_string()], 0))
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::InstanceDropped)
+    ));
+
+    let stale = interface.func("select-nth").unwrap();
+    assert!(stale.typed::<(Vec<String>, u32), String>().is_ok());
+}
@@ -0,0 +1,38 @@
+use waclay::*;
+
+// The component of the `streams` example, whose `squares` export writes its values to a stream
+// synchronously, and ignores how many of them were written.
+const WASM: &[u8] = include_bytes!("../examples/streams/component.wasm");
+
+#[test]
+fn guest_writes_are_bounded() {
+    let engine = Engine::new(wasmi_runtime_layer::Engine::default());
+    let mut store = Store::new(&engine, ());
+    store.set_lift_limits(LiftLimits::default().with_max_buffered_values(3));
+
+    let component = Component::new(&engine, WASM).unwrap();
+    let instance = Linker::default()
+        .instantiate(&mut store, &component)
+        .unwrap();
+    let squares = instance
+        .exports()
+        .instance(&"test:streams/numbers".try_into().unwrap())
+        .unwrap()
+        .func("squares")
+        .unwrap();
+
+    // The stream buffers only as many values as the limit allows, so the write completes partially
+    let mut results = [Value::Bool(false)];
+    squares
+        .call(&mut store, &[Value::U32(5)], &mut results)
+        .unwrap();
+    let Value::Stream(reader) = &results[0] else {
+        panic!("Expected a stream.");
+    };
+
+    let mut values = Vec::new();
+    while let Some(read) = reader.read(&mut store, 8).unwrap() {
+        values.extend(read);
+    }
+    assert_eq!(values, [Value::U32(0), Value::U32(1), Value::U32(4)]);
+}
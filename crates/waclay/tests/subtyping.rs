@@ -0,0 +1,52 @@
+use waclay::*;
+
+/// The representation of the resources in these tests.
+struct Counter;
+
+#[test]
+fn distinct_resources_are_not_subtypes() {
+    let a = ResourceType::new::<Counter>(None);
+    let b = ResourceType::new::<Counter>(None);
+
+    // Resource types are distinct even when they share a representation
+    assert!(ValueType::Own(a.clone()).is_subtype_of(&ValueType::Own(a.clone())));
+    assert!(!ValueType::Own(a.clone()).is_subtype_of(&ValueType::Own(b.clone())));
+    assert!(!ValueType::Own(b.clone()).is_subtype_of(&ValueType::Own(a.clone())));
+    assert!(!ValueType::Borrow(a.clone()).is_subtype_of(&ValueType::Borrow(b.clone())));
+    assert!(!ValueType::Borrow(a.clone()).is_subtype_of(&ValueType::Own(a.clone())));
+
+    // Neither type may be used in place of the other within a function signature
+    let takes_a = FuncType::new([ValueType::Own(a.clone())], []);
+    let takes_b = FuncType::new([ValueType::Own(b.clone())], []);
+    assert!(!takes_a.is_subtype_of(&takes_b));
+    assert!(!takes_b.is_subtype_of(&takes_a));
+}
+
+#[test]
+fn untyped_handles_accept_but_do_not_replace_other_types() {
+    let a = ResourceType::new::<Counter>(None);
+    let any = <ResourceOwn as ComponentType>::ty();
+
+    assert!(ValueType::Own(a.clone()).is_subtype_of(&any));
+    assert!(!any.is_subtype_of(&ValueType::Own(a.clone())));
+
+    // A function returning untyped handles cannot satisfy an import that returns `a`
+    let returns_any = FuncType::new([], [any]);
+    let returns_a = FuncType::new([], [ValueType::Own(a)]);
+    assert!(returns_a.is_subtype_of(&returns_any));
+    assert!(!returns_any.is_subtype_of(&returns_a));
+}
+
+#[test]
+fn default_resource_type_stands_for_its_representation() {
+    let a = ResourceType::new::<Counter>(None);
+    let default = ValueType::Own(Resource::<Counter>::resource_type());
+
+    assert!(ValueType::Own(a.clone()).is_subtype_of(&default));
+    assert!(default.is_subtype_of(&ValueType::Own(a)));
+
+    // The default type of another representation stands for neither
+    let other = ValueType::Own(Resource::<String>::resource_type());
+    assert!(!other.is_subtype_of(&default));
+    assert!(!default.is_subtype_of(&other));
+}
@@ -58,6 +58,43 @@ impl ComponentType for Response {
         )?;
         Ok(Value::Record(record))
     }
+
+    const LAYOUT: Option<FlatLayout> = FlatLayout::record(&[
+        <u32 as ComponentType>::LAYOUT,
+        <String as ComponentType>::LAYOUT,
+    ]);
+
+    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {
+        self.id.lower_flat(cx)?;
+        self.reply.lower_flat(cx)?;
+        Ok(())
+    }
+
+    fn store_flat<C: AsContextMut>(
+        &self,
+        cx: &mut FlatContext<'_, C>,
+        offset: usize,
+    ) -> Result<()> {
+        let mut fields = FlatFields::new(offset);
+        self.id.store_flat(cx, fields.field::<u32>()?)?;
+        self.reply.store_flat(cx, fields.field::<String>()?)?;
+        Ok(())
+    }
+
+    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {
+        Ok(Self {
+            id: <u32 as ComponentType>::lift_flat(cx)?,
+            reply: <String as ComponentType>::lift_flat(cx)?,
+        })
+    }
+
+    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {
+        let mut fields = FlatFields::new(offset);
+        Ok(Self {
+            id: <u32 as ComponentType>::load_flat(cx, fields.field::<u32>()?)?,
+            reply: <String as ComponentType>::load_flat(cx, fields.field::<String>()?)?,
+        })
+    }
 }
 
 impl UnaryComponentType for Response {}
@@ -10,8 +10,8 @@
 //!
 //! ## Limitations
 //! - Resource types: Core waclay supports them, but bindings generation is not yet implemented
-//! - Future types: Not supported (requires async runtime in core waclay)
-//! - Stream types: Not supported (requires async runtime in core waclay)
+//! - Future types: Core waclay supports them through `FutureReader`, but bindings generation is not yet implemented
+//! - Stream types: Core waclay supports them through `StreamReader`, but bindings generation is not yet implemented
 //!
 //! See FEATURES.md in the repository root for detailed feature comparison.
 
@@ -192,6 +192,10 @@ fn generate_record_component_type(
     writeln!(output, "        )?;")?;
     writeln!(output, "        Ok(Value::Record(record))")?;
     writeln!(output, "    }}")?;
+    writeln!(output)?;
+
+    generate_record_flat_conversions(resolve, record, output)?;
+
     writeln!(output, "}}")?;
     writeln!(output)?;
     writeln!(output, "impl UnaryComponentType for {} {{}}", rust_name)?;
@@ -725,14 +729,12 @@ fn sanitize_param_name(name: &str) -> String {
 
 fn parse_resource_function_name(func_name: &str) -> (bool, String) {
     // Check if this is a resource constructor
-    if func_name.starts_with("[constructor]") {
-        let resource_name = &func_name["[constructor]".len()..];
+    if let Some(resource_name) = func_name.strip_prefix("[constructor]") {
         return (true, format!("new_{}", resource_name.to_snake_case()));
     }
     
     // Check if this is a resource method
-    if func_name.starts_with("[method]") {
-        let rest = &func_name["[method]".len()..];
+    if let Some(rest) = func_name.strip_prefix("[method]") {
         if let Some(dot_pos) = rest.find('.') {
             let resource_name = &rest[..dot_pos];
             let method_name = &rest[dot_pos + 1..];
@@ -1240,7 +1242,7 @@ fn type_to_value_type(resolve: &Resolve, ty: &Type) -> String {
                                 .as_ref()
                                 .map(|n| n.to_upper_camel_case())
                                 .unwrap_or_else(|| format!("Resource{:?}", id));
-                            return format!("ValueType::Own({}::resource_type())", resource_name);
+                            format!("ValueType::Own({}::resource_type())", resource_name)
                         }
                         Handle::Borrow(id) => {
                             // For Borrow handles, use ValueType::Borrow
@@ -1250,7 +1252,7 @@ fn type_to_value_type(resolve: &Resolve, ty: &Type) -> String {
                                 .as_ref()
                                 .map(|n| n.to_upper_camel_case())
                                 .unwrap_or_else(|| format!("Resource{:?}", id));
-                            return format!("ValueType::Borrow({}::resource_type())", resource_name);
+                            format!("ValueType::Borrow({}::resource_type())", resource_name)
                         }
                     }
                 }
@@ -1263,6 +1265,98 @@ fn type_to_value_type(resolve: &Resolve, ty: &Type) -> String {
     }
 }
 
+/// Generates the direct conversions between a record and core values, which typed
+/// calls use when every field supports them.
+fn generate_record_flat_conversions(
+    resolve: &Resolve,
+    record: &Record,
+    output: &mut String,
+) -> Result<()> {
+    let fields = record
+        .fields
+        .iter()
+        .map(|field| {
+            (
+                field.name.to_snake_case(),
+                type_to_rust_type(resolve, &field.ty),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // LAYOUT constant
+    writeln!(output, "    const LAYOUT: Option<FlatLayout> = FlatLayout::record(&[")?;
+    for (_, field_type) in &fields {
+        writeln!(output, "        <{} as ComponentType>::LAYOUT,", field_type)?;
+    }
+    writeln!(output, "    ]);")?;
+    writeln!(output)?;
+
+    // lower_flat() method
+    writeln!(
+        output,
+        "    fn lower_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>) -> Result<()> {{"
+    )?;
+    for (field_name, _) in &fields {
+        writeln!(output, "        self.{}.lower_flat(cx)?;", field_name)?;
+    }
+    writeln!(output, "        Ok(())")?;
+    writeln!(output, "    }}")?;
+    writeln!(output)?;
+
+    // store_flat() method
+    writeln!(
+        output,
+        "    fn store_flat<C: AsContextMut>(&self, cx: &mut FlatContext<'_, C>, offset: usize) -> Result<()> {{"
+    )?;
+    writeln!(output, "        let mut fields = FlatFields::new(offset);")?;
+    for (field_name, field_type) in &fields {
+        writeln!(
+            output,
+            "        self.{}.store_flat(cx, fields.field::<{}>()?)?;",
+            field_name, field_type
+        )?;
+    }
+    writeln!(output, "        Ok(())")?;
+    writeln!(output, "    }}")?;
+    writeln!(output)?;
+
+    // lift_flat() method
+    writeln!(
+        output,
+        "    fn lift_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>) -> Result<Self> {{"
+    )?;
+    writeln!(output, "        Ok(Self {{")?;
+    for (field_name, field_type) in &fields {
+        writeln!(
+            output,
+            "            {}: <{} as ComponentType>::lift_flat(cx)?,",
+            field_name, field_type
+        )?;
+    }
+    writeln!(output, "        }})")?;
+    writeln!(output, "    }}")?;
+    writeln!(output)?;
+
+    // load_flat() method
+    writeln!(
+        output,
+        "    fn load_flat<C: AsContextMut>(cx: &mut FlatContext<'_, C>, offset: usize) -> Result<Self> {{"
+    )?;
+    writeln!(output, "        let mut fields = FlatFields::new(offset);")?;
+    writeln!(output, "        Ok(Self {{")?;
+    for (field_name, field_type) in &fields {
+        writeln!(
+            output,
+            "            {}: <{} as ComponentType>::load_flat(cx, fields.field::<{}>()?)?,",
+            field_name, field_type, field_type
+        )?;
+    }
+    writeln!(output, "        }})")?;
+    writeln!(output, "    }}")?;
+
+    Ok(())
+}
+
 fn generate_field_conversion(
     resolve: &Resolve,
     field_name: &str,